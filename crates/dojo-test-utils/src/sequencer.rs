@@ -4,9 +4,7 @@ use jsonrpsee::core::Error;
 pub use katana_core::backend::config::{Environment, StarknetConfig};
 use katana_core::sequencer::KatanaSequencer;
 pub use katana_core::sequencer::SequencerConfig;
-use katana_rpc::api::ApiKind;
-use katana_rpc::config::ServerConfig;
-use katana_rpc::{spawn, NodeHandle};
+use katana_node::{Node, NodeBuilder};
 use starknet::accounts::{ExecutionEncoding, SingleOwnerAccount};
 use starknet::core::chain_id;
 use starknet::core::types::FieldElement;
@@ -20,37 +18,30 @@ pub struct TestAccount {
     pub account_address: FieldElement,
 }
 
-#[allow(unused)]
 pub struct TestSequencer {
     url: Url,
-    handle: NodeHandle,
+    node: Node,
     account: TestAccount,
     pub sequencer: Arc<KatanaSequencer>,
 }
 
 impl TestSequencer {
     pub async fn start(config: SequencerConfig, starknet_config: StarknetConfig) -> Self {
-        let sequencer = Arc::new(KatanaSequencer::new(config, starknet_config).await);
+        let node = NodeBuilder::new()
+            .sequencer_config(config)
+            .starknet_config(starknet_config)
+            .start()
+            .await
+            .expect("Unable to start node");
 
-        let handle = spawn(
-            Arc::clone(&sequencer),
-            ServerConfig {
-                port: 0,
-                host: "127.0.0.1".into(),
-                max_connections: 100,
-                apis: vec![ApiKind::Starknet, ApiKind::Katana],
-            },
-        )
-        .await
-        .expect("Unable to spawn server");
-
-        let url = Url::parse(&format!("http://{}", handle.addr)).expect("Failed to parse URL");
+        let url = node.url();
+        let sequencer = Arc::clone(node.sequencer());
 
-        let account = sequencer.backend.accounts[0].clone();
+        let account = node.accounts()[0].clone();
         let account =
             TestAccount { private_key: account.private_key, account_address: account.address };
 
-        TestSequencer { sequencer, account, handle, url }
+        TestSequencer { sequencer, account, node, url }
     }
 
     pub fn account(&self) -> SingleOwnerAccount<JsonRpcClient<HttpTransport>, LocalWallet> {
@@ -68,7 +59,7 @@ impl TestSequencer {
     }
 
     pub fn stop(self) -> Result<(), Error> {
-        self.handle.handle.stop()
+        self.node.rpc().handle.stop()
     }
 
     pub fn url(&self) -> Url {
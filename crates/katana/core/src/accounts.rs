@@ -1,7 +1,7 @@
 use std::fmt::Display;
 
 use anyhow::Result;
-use katana_primitives::contract::ContractAddress;
+use katana_primitives::contract::{ContractAddress, StorageKey, StorageValue};
 use katana_primitives::FieldElement;
 use katana_provider::traits::state::StateWriter;
 use rand::rngs::SmallRng;
@@ -55,23 +55,32 @@ impl Account {
         // set the class hash at the account address
         state.set_class_hash_of_contract(address, self.class_hash)?;
         // set the public key in the account contract
-        state.set_storage(
-            address,
-            get_storage_var_address("Account_public_key", &[]).unwrap(),
-            self.public_key,
-        )?;
+        let (contract, key, value) = self.public_key_storage();
+        state.set_storage(contract, key, value)?;
         // initialze account nonce
         state.set_nonce(address, 1u128.into())?;
         Ok(())
     }
 
     fn fund(&self, state: &dyn StateWriter) -> Result<()> {
-        state.set_storage(
-            *FEE_TOKEN_ADDRESS,
-            get_storage_var_address("ERC20_balances", &[self.address]).unwrap(),
-            self.balance,
-        )?;
-        Ok(())
+        let (contract, key, value) = self.balance_storage();
+        state.set_storage(contract, key, value)
+    }
+
+    /// Returns the storage entries written by [`Account::deploy_and_fund`], as `(contract, key,
+    /// value)` tuples.
+    pub fn genesis_storage(&self) -> [(ContractAddress, StorageKey, StorageValue); 2] {
+        [self.public_key_storage(), self.balance_storage()]
+    }
+
+    fn public_key_storage(&self) -> (ContractAddress, StorageKey, StorageValue) {
+        let key = get_storage_var_address("Account_public_key", &[]).unwrap();
+        (self.address.into(), key, self.public_key)
+    }
+
+    fn balance_storage(&self) -> (ContractAddress, StorageKey, StorageValue) {
+        let key = get_storage_var_address("ERC20_balances", &[self.address]).unwrap();
+        (*FEE_TOKEN_ADDRESS, key, self.balance)
     }
 }
 
@@ -1,13 +1,18 @@
-use blockifier::block_context::{BlockContext, FeeTokenAddresses, GasPrices};
+use std::path::PathBuf;
+
+use katana_executor::executor::BlockEnv;
+use katana_primitives::block::GasPrices;
+use katana_provider::providers::db::pruner::PruningConfig;
 use starknet_api::block::{BlockNumber, BlockTimestamp};
 use starknet_api::core::ChainId;
 use url::Url;
 
 use crate::constants::{
-    DEFAULT_GAS_PRICE, DEFAULT_INVOKE_MAX_STEPS, DEFAULT_VALIDATE_MAX_STEPS, FEE_TOKEN_ADDRESS,
-    SEQUENCER_ADDRESS,
+    DEFAULT_GAS_PRICE, DEFAULT_INVOKE_MAX_STEPS, DEFAULT_VALIDATE_MAX_STEPS,
+    DETERMINISTIC_BLOCK_TIME, FEE_TOKEN_ADDRESS, SEQUENCER_ADDRESS,
 };
-use crate::env::{get_default_vm_resource_fee_cost, BlockContextGenerator};
+use crate::env::{BlockContextGenerator, TimeMode};
+use crate::utils::get_current_timestamp;
 
 #[derive(Debug, Clone)]
 pub struct StarknetConfig {
@@ -17,29 +22,37 @@ pub struct StarknetConfig {
     pub env: Environment,
     pub fork_rpc_url: Option<Url>,
     pub fork_block_number: Option<u64>,
+    /// The database directory of another Katana instance to fork from, at `fork_block_number` or
+    /// at its latest block.
+    pub fork_db: Option<PathBuf>,
+    /// The state dump to fork from. See [`StateDump`](super::dump::StateDump).
+    pub fork_state: Option<PathBuf>,
+    /// The directory of the database the chain is persisted to, and resumed from on restart. The
+    /// chain is only kept in memory if not set.
+    pub db_dir: Option<PathBuf>,
+    /// How much history is kept in the database at `db_dir`.
+    pub pruning: PruningConfig,
     pub disable_validate: bool,
+    pub parallel_execution: bool,
+    /// The mode in which the timestamps of the blocks advance.
+    pub time_mode: TimeMode,
+    /// Makes the chain independent of the wall clock and of the order in which the transactions
+    /// received together arrive, so that the same transactions always produce the same chain.
+    pub deterministic: bool,
 }
 
 impl StarknetConfig {
-    pub fn block_context(&self) -> BlockContext {
-        BlockContext {
+    pub fn block_context(&self) -> BlockEnv {
+        BlockEnv {
             block_number: BlockNumber::default(),
             chain_id: ChainId(self.env.chain_id.clone()),
             block_timestamp: BlockTimestamp::default(),
-            sequencer_address: (*SEQUENCER_ADDRESS).into(),
-            // As the fee has two currencies, we also have to adjust their addresses.
-            // https://github.com/starkware-libs/blockifier/blob/51b343fe38139a309a69b2482f4b484e8caa5edf/crates/blockifier/src/block_context.rs#L34
-            fee_token_addresses: FeeTokenAddresses {
-                eth_fee_token_address: (*FEE_TOKEN_ADDRESS).into(),
-                strk_fee_token_address: Default::default(),
-            },
-            vm_resource_fee_cost: get_default_vm_resource_fee_cost().into(),
-            // Gas prices are dual too.
-            // https://github.com/starkware-libs/blockifier/blob/51b343fe38139a309a69b2482f4b484e8caa5edf/crates/blockifier/src/block_context.rs#L49
-            gas_prices: GasPrices {
-                eth_l1_gas_price: self.env.gas_price,
-                strk_l1_gas_price: Default::default(),
-            },
+            sequencer_address: *SEQUENCER_ADDRESS,
+            fee_token_address: *FEE_TOKEN_ADDRESS,
+            gas_prices: GasPrices::new(
+                self.env.gas_price.try_into().expect("gas price must fit in a u64"),
+                0,
+            ),
             validate_max_n_steps: self.env.validate_max_steps,
             invoke_tx_max_n_steps: self.env.invoke_max_steps,
             max_recursion_depth: 1000,
@@ -47,7 +60,18 @@ impl StarknetConfig {
     }
 
     pub fn block_context_generator(&self) -> BlockContextGenerator {
-        BlockContextGenerator::default()
+        if self.deterministic {
+            // the timestamps are derived from the block numbers, starting from the genesis one
+            let time_mode = match self.time_mode {
+                TimeMode::Frozen | TimeMode::FixedIncrement(_) => self.time_mode,
+                TimeMode::Realtime | TimeMode::Accelerated(_) => {
+                    TimeMode::FixedIncrement(DETERMINISTIC_BLOCK_TIME)
+                }
+            };
+            BlockContextGenerator::new(time_mode, self.block_context().block_timestamp.0)
+        } else {
+            BlockContextGenerator::new(self.time_mode, get_current_timestamp().as_secs())
+        }
     }
 }
 
@@ -59,8 +83,15 @@ impl Default for StarknetConfig {
             disable_fee: false,
             fork_rpc_url: None,
             fork_block_number: None,
+            fork_db: None,
+            fork_state: None,
+            db_dir: None,
+            pruning: PruningConfig::default(),
             env: Environment::default(),
             disable_validate: false,
+            parallel_execution: false,
+            time_mode: TimeMode::default(),
+            deterministic: false,
         }
     }
 }
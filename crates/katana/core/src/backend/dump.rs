@@ -0,0 +1,101 @@
+//! Dumps of the chain state that new nodes can be forked from.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use katana_db::models::class::StoredContractClass;
+use katana_primitives::block::{BlockHash, Header};
+use katana_primitives::contract::{
+    ClassHash, CompiledClassHash, ContractAddress, FlattenedSierraClass, Nonce, StorageKey,
+    StorageValue,
+};
+use katana_primitives::state::{StateUpdates, StateUpdatesWithDeclaredClasses};
+use serde::{Deserialize, Serialize};
+
+/// The state of a chain at a given block, stored as gzipped JSON.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateDump {
+    /// The hash of the block the state is at.
+    pub block_hash: BlockHash,
+    /// The header of the block the state is at.
+    pub header: Header,
+    /// The state, as the updates to apply on an empty state.
+    pub state_updates: DumpedStateUpdates,
+    /// The sierra classes of the declared classes.
+    pub sierra_classes: BTreeMap<ClassHash, FlattenedSierraClass>,
+    /// The compiled classes of the declared classes.
+    pub compiled_classes: BTreeMap<ClassHash, StoredContractClass>,
+}
+
+/// The [`StateUpdates`] of a [`StateDump`], sorted so that the same state is always dumped the
+/// same way.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DumpedStateUpdates {
+    /// A mapping of contract addresses to their nonces.
+    pub nonce_updates: BTreeMap<ContractAddress, Nonce>,
+    /// A mapping of contract addresses to their storage entries.
+    pub storage_updates: BTreeMap<ContractAddress, BTreeMap<StorageKey, StorageValue>>,
+    /// A mapping of contract addresses to their class hashes.
+    pub contract_updates: BTreeMap<ContractAddress, ClassHash>,
+    /// A mapping of declared class hashes to their compiled class hashes.
+    pub declared_classes: BTreeMap<ClassHash, CompiledClassHash>,
+}
+
+impl From<DumpedStateUpdates> for StateUpdates {
+    fn from(updates: DumpedStateUpdates) -> Self {
+        Self {
+            nonce_updates: updates.nonce_updates.into_iter().collect(),
+            storage_updates: updates
+                .storage_updates
+                .into_iter()
+                .map(|(address, entries)| (address, entries.into_iter().collect()))
+                .collect(),
+            contract_updates: updates.contract_updates.into_iter().collect(),
+            declared_classes: updates.declared_classes.into_iter().collect(),
+        }
+    }
+}
+
+impl StateDump {
+    /// Reads the state dump at the given path.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .with_context(|| format!("Opening state dump at path {}", path.display()))?;
+        serde_json::from_reader(GzDecoder::new(BufReader::new(file)))
+            .with_context(|| format!("Reading state dump at path {}", path.display()))
+    }
+
+    /// Writes the state dump to the given path, replacing the file if it already exists.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let file = File::create(path)
+            .with_context(|| format!("Creating state dump at path {}", path.display()))?;
+
+        let mut encoder = GzEncoder::new(BufWriter::new(file), Compression::default());
+        serde_json::to_writer(&mut encoder, self)
+            .with_context(|| format!("Writing state dump at path {}", path.display()))?;
+        encoder.finish()?.flush()?;
+
+        Ok(())
+    }
+
+    /// Returns the updates that build the dumped state from an empty one.
+    pub fn into_state_updates(self) -> StateUpdatesWithDeclaredClasses {
+        StateUpdatesWithDeclaredClasses {
+            state_updates: self.state_updates.into(),
+            declared_sierra_classes: self.sierra_classes.into_iter().collect(),
+            declared_compiled_classes: self
+                .compiled_classes
+                .into_iter()
+                .map(|(hash, class)| (hash, class.into()))
+                .collect(),
+        }
+    }
+}
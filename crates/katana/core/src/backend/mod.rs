@@ -1,20 +1,29 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
 
-use blockifier::block_context::BlockContext;
+use katana_db::{init_db, open_db_ro};
+#[cfg(feature = "blockifier")]
+use katana_executor::blockifier::BlockifierFactory;
+use katana_executor::executor::{BlockEnv, ExecutionFlags, ExecutorFactory};
 use katana_primitives::block::{
-    Block, FinalityStatus, GasPrices, Header, PartialHeader, SealedBlockWithStatus,
+    Block, FinalityStatus, Header, PartialHeader, SealedBlockWithStatus,
 };
-use katana_primitives::contract::ContractAddress;
+use katana_primitives::contract::{ContractAddress, StorageKey};
 use katana_primitives::receipt::Receipt;
 use katana_primitives::state::StateUpdatesWithDeclaredClasses;
 use katana_primitives::transaction::TxWithHash;
 use katana_primitives::version::CURRENT_STARKNET_VERSION;
 use katana_primitives::FieldElement;
+use katana_provider::providers::db::pruner::{PrunerHandle, PruningConfig};
+use katana_provider::providers::db::DbProvider;
 use katana_provider::providers::fork::ForkedProvider;
 use katana_provider::providers::in_memory::InMemoryProvider;
-use katana_provider::traits::block::{BlockHashProvider, BlockWriter};
+use katana_provider::traits::block::{
+    BlockHashProvider, BlockNumberProvider, BlockStatusProvider, BlockWriter, HeaderProvider,
+};
 use katana_provider::traits::state::{StateFactoryProvider, StateProvider};
-use parking_lot::RwLock;
+use katana_provider::traits::state_update::StateUpdateProvider;
+use parking_lot::{Mutex, RwLock};
 use starknet::core::types::{BlockId, BlockStatus, MaybePendingBlockWithTxHashes};
 use starknet::core::utils::parse_cairo_short_string;
 use starknet::providers::jsonrpc::HttpTransport;
@@ -25,12 +34,14 @@ use tracing::{info, trace};
 
 pub mod config;
 pub mod contract;
+pub mod dump;
 pub mod storage;
 
 use self::config::StarknetConfig;
+use self::dump::StateDump;
 use self::storage::Blockchain;
 use crate::accounts::{Account, DevAccountGenerator};
-use crate::constants::DEFAULT_PREFUNDED_ACCOUNT_BALANCE;
+use crate::constants::{DEFAULT_PREFUNDED_ACCOUNT_BALANCE, PRUNING_INTERVAL};
 use crate::env::{BlockContextGenerator, Env};
 use crate::service::block_producer::MinedBlockOutcome;
 use crate::utils::get_current_timestamp;
@@ -45,10 +56,25 @@ pub struct Backend {
     pub block_context_generator: RwLock<BlockContextGenerator>,
     /// Prefunded dev accounts
     pub accounts: Vec<Account>,
+    /// The factory of the executors used to execute transactions.
+    pub executor_factory: Arc<dyn ExecutorFactory>,
+    /// The pruner of the database the chain is persisted to, if pruning is enabled. The pruner
+    /// is stopped when the backend is dropped. Its handle isn't `Sync`, hence the mutex.
+    pub pruner: Option<Mutex<PrunerHandle>>,
 }
 
 impl Backend {
+    /// Creates a new backend that executes transactions with blockifier.
+    #[cfg(feature = "blockifier")]
     pub async fn new(config: StarknetConfig) -> Self {
+        Self::with_executor_factory(config, Arc::new(BlockifierFactory)).await
+    }
+
+    /// Creates a new backend that executes transactions with the executors of the given factory.
+    pub async fn with_executor_factory(
+        config: StarknetConfig,
+        executor_factory: Arc<dyn ExecutorFactory>,
+    ) -> Self {
         let mut block_context = config.block_context();
         let block_context_generator = config.block_context_generator();
 
@@ -57,6 +83,8 @@ impl Backend {
             .with_balance(*DEFAULT_PREFUNDED_ACCOUNT_BALANCE)
             .generate();
 
+        let mut pruner = None;
+        let mut resumed = false;
         let blockchain: Blockchain = if let Some(forked_url) = &config.fork_rpc_url {
             let provider = Arc::new(JsonRpcClient::new(HttpTransport::new(forked_url.clone())));
             let forked_chain_id = provider.chain_id().await.unwrap();
@@ -78,7 +106,7 @@ impl Backend {
 
             block_context.block_number = BlockNumber(block.block_number);
             block_context.block_timestamp = BlockTimestamp(block.timestamp);
-            block_context.sequencer_address = ContractAddress(block.sequencer_address).into();
+            block_context.sequencer_address = ContractAddress(block.sequencer_address);
             block_context.chain_id = ChainId(parse_cairo_short_string(&forked_chain_id).unwrap());
 
             trace!(
@@ -102,6 +130,102 @@ impl Backend {
                 },
             )
             .expect("able to create forked blockchain")
+        } else if let Some(path) = &config.fork_db {
+            let db = open_db_ro(path).expect("failed to open database to fork");
+            let db = DbProvider::new(db);
+
+            let forked_block_num = if let Some(num) = config.fork_block_number {
+                num
+            } else {
+                db.latest_number().expect("failed to fetch block number from forked database")
+            };
+
+            let header = db
+                .header_by_number(forked_block_num)
+                .unwrap()
+                .expect("block to be forked must exist in the database");
+            let block_hash = db.block_hash_by_num(forked_block_num).unwrap().unwrap();
+            let status = db.block_status(forked_block_num.into()).unwrap().unwrap();
+            let state = db
+                .pinned_state(forked_block_num)
+                .unwrap()
+                .expect("state of the block to be forked must be available");
+
+            block_context.block_number = BlockNumber(header.number);
+            block_context.block_timestamp = BlockTimestamp(header.timestamp);
+            block_context.sequencer_address = header.sequencer_address;
+
+            trace!(
+                target: "backend",
+                "forking database at block {} from {}",
+                header.number,
+                path.display()
+            );
+
+            Blockchain::new_from_forked(
+                ForkedProvider::new_with_state(Box::new(state)),
+                block_hash,
+                header.parent_hash,
+                &block_context,
+                header.state_root,
+                status,
+            )
+            .expect("able to create forked blockchain")
+        } else if let Some(path) = &config.fork_state {
+            let dump = StateDump::load(path).expect("failed to load state dump to fork");
+            let (block_hash, header) = (dump.block_hash, dump.header.clone());
+
+            block_context.block_number = BlockNumber(header.number);
+            block_context.block_timestamp = BlockTimestamp(header.timestamp);
+            block_context.sequencer_address = header.sequencer_address;
+
+            trace!(
+                target: "backend",
+                "forking state dump at block {} from {}",
+                header.number,
+                path.display()
+            );
+
+            Blockchain::new_from_forked(
+                ForkedProvider::new_with_state_updates(dump.into_state_updates()),
+                block_hash,
+                header.parent_hash,
+                &block_context,
+                header.state_root,
+                FinalityStatus::AcceptedOnL2,
+            )
+            .expect("able to create forked blockchain")
+        } else if let Some(path) = &config.db_dir {
+            let db = init_db(path).expect("failed to initialize database");
+            let db = DbProvider::new(db);
+            // databases created before the event indexes were introduced have them empty
+            db.index_unindexed_events().expect("failed to index the events of the database");
+
+            if config.pruning != PruningConfig::archive() {
+                pruner = Some(Mutex::new(db.pruner(config.pruning).spawn(PRUNING_INTERVAL)));
+            }
+
+            // resume the chain persisted by a previous run, if any
+            if db.block_hash_by_num(block_context.block_number.0).unwrap().is_some() {
+                let latest_number = db.latest_number().unwrap();
+                let header = db.header_by_number(latest_number).unwrap().expect("should exist");
+
+                block_context.block_number = BlockNumber(header.number);
+                block_context.block_timestamp = BlockTimestamp(header.timestamp);
+
+                trace!(
+                    target: "backend",
+                    "resuming chain at block {} from {}",
+                    header.number,
+                    path.display()
+                );
+
+                resumed = true;
+                Blockchain::new(db)
+            } else {
+                Blockchain::new_with_genesis(db, &block_context)
+                    .expect("able to create blockchain from genesis block")
+            }
         } else {
             Blockchain::new_with_genesis(InMemoryProvider::new(), &block_context)
                 .expect("able to create blockchain from genesis block")
@@ -109,9 +233,21 @@ impl Backend {
 
         let env = Env { block: block_context };
 
-        for acc in &accounts {
-            acc.deploy_and_fund(blockchain.provider())
-                .expect("should be able to deploy and fund dev account");
+        // The dev accounts are written directly onto the state of the genesis block, which a
+        // resumed chain already has, with the nonces and balances to keep. The same goes for
+        // the accounts that already exist in a forked state.
+        if !resumed {
+            let state = blockchain.provider().latest().expect("should get latest state");
+            let undeployed = accounts
+                .iter()
+                .filter(|acc| state.class_hash_of_contract(acc.address.into()).unwrap().is_none())
+                .collect::<Vec<_>>();
+            drop(state);
+
+            for acc in undeployed {
+                acc.deploy_and_fund(blockchain.provider())
+                    .expect("should be able to deploy and fund dev account");
+            }
         }
 
         Self {
@@ -120,6 +256,8 @@ impl Backend {
             config: RwLock::new(config),
             env: Arc::new(RwLock::new(env)),
             block_context_generator: RwLock::new(block_context_generator),
+            executor_factory,
+            pruner,
         }
     }
 
@@ -140,7 +278,7 @@ impl Backend {
 
     pub fn do_mine_block(
         &self,
-        block_context: BlockContext,
+        block_context: BlockEnv,
         tx_receipt_pairs: Vec<(TxWithHash, Receipt)>,
         state_updates: StateUpdatesWithDeclaredClasses,
     ) -> MinedBlockOutcome {
@@ -152,11 +290,8 @@ impl Backend {
             parent_hash: prev_hash,
             version: CURRENT_STARKNET_VERSION,
             timestamp: block_context.block_timestamp.0,
-            sequencer_address: block_context.sequencer_address.into(),
-            gas_prices: GasPrices {
-                eth_gas_price: block_context.gas_prices.eth_l1_gas_price.try_into().unwrap(),
-                strk_gas_price: block_context.gas_prices.strk_l1_gas_price.try_into().unwrap(),
-            },
+            sequencer_address: block_context.sequencer_address,
+            gas_prices: block_context.gas_prices.clone(),
         };
 
         let tx_count = txs.len();
@@ -182,25 +317,112 @@ impl Backend {
     pub fn update_block_context(&self) {
         let mut context_gen = self.block_context_generator.write();
         let block_context = &mut self.env.write().block;
-        let current_timestamp_secs = get_current_timestamp().as_secs() as i64;
-
-        let timestamp = if context_gen.next_block_start_time == 0 {
-            (current_timestamp_secs + context_gen.block_timestamp_offset) as u64
-        } else {
-            let timestamp = context_gen.next_block_start_time;
-            context_gen.block_timestamp_offset = timestamp as i64 - current_timestamp_secs;
-            context_gen.next_block_start_time = 0;
-            timestamp
-        };
+        let timestamp = context_gen.next_timestamp(get_current_timestamp().as_secs());
 
         block_context.block_number = block_context.block_number.next();
         block_context.block_timestamp = BlockTimestamp(timestamp);
     }
 
+    /// Returns the block context that the block with the given header was executed on.
+    pub fn block_context_at(&self, header: &Header) -> BlockEnv {
+        let mut block_context = self.env.read().block.clone();
+        block_context.block_number = BlockNumber(header.number);
+        block_context.block_timestamp = BlockTimestamp(header.timestamp);
+        block_context.sequencer_address = header.sequencer_address;
+        block_context.gas_prices = header.gas_prices.clone();
+        block_context
+    }
+
+    /// Returns the flags to execute transactions with, based on the current config.
+    pub fn execution_flags(&self) -> ExecutionFlags {
+        let config = self.config.read();
+        ExecutionFlags {
+            charge_fee: !config.disable_fee,
+            validate: !config.disable_validate,
+            parallel: config.parallel_execution,
+        }
+    }
+
     /// Updates the block context and mines an empty block.
     pub fn mine_empty_block(&self) -> MinedBlockOutcome {
         self.update_block_context();
         let block_context = self.env.read().block.clone();
         self.do_mine_block(block_context, Default::default(), Default::default())
     }
+
+    /// Dumps the latest state of the chain.
+    ///
+    /// The state is rebuilt from the state updates of the blocks stored locally, so the dump of a
+    /// forked chain only contains the state that changed since it was forked.
+    pub fn dump_state(&self) -> anyhow::Result<StateDump> {
+        let provider = self.blockchain.provider();
+        let block_number = provider.latest_number()?;
+        let block_hash = provider.latest_hash()?;
+        let header = provider.header_by_number(block_number)?.expect("latest header must exist");
+
+        // collect every entry of the state that was written, then read their latest values
+        let mut contracts = HashSet::new();
+        let mut storage: HashMap<ContractAddress, HashSet<StorageKey>> = HashMap::new();
+        let mut classes = HashSet::new();
+
+        // the dev accounts are written directly onto the genesis state
+        for account in &self.accounts {
+            contracts.insert(ContractAddress::from(account.address));
+            for (address, key, _) in account.genesis_storage() {
+                storage.entry(address).or_default().insert(key);
+            }
+        }
+
+        for num in 0..=block_number {
+            let Some(updates) = provider.state_update(num.into())? else { continue };
+            contracts.extend(updates.nonce_updates.into_keys());
+            contracts.extend(updates.contract_updates.into_keys());
+            classes.extend(updates.declared_classes.into_keys());
+            for (address, entries) in updates.storage_updates {
+                storage.entry(address).or_default().extend(entries.into_keys());
+            }
+        }
+
+        let state = provider.latest()?;
+        let mut dump = StateDump {
+            block_hash,
+            header,
+            state_updates: Default::default(),
+            sierra_classes: BTreeMap::new(),
+            compiled_classes: BTreeMap::new(),
+        };
+
+        for address in contracts {
+            if let Some(nonce) = state.nonce(address)? {
+                dump.state_updates.nonce_updates.insert(address, nonce);
+            }
+            if let Some(class_hash) = state.class_hash_of_contract(address)? {
+                dump.state_updates.contract_updates.insert(address, class_hash);
+            }
+        }
+
+        for (address, keys) in storage {
+            let mut entries = BTreeMap::new();
+            for key in keys {
+                if let Some(value) = state.storage(address, key)? {
+                    entries.insert(key, value);
+                }
+            }
+            dump.state_updates.storage_updates.insert(address, entries);
+        }
+
+        for hash in classes {
+            if let Some(compiled_hash) = state.compiled_class_hash_of_class_hash(hash)? {
+                dump.state_updates.declared_classes.insert(hash, compiled_hash);
+            }
+            if let Some(class) = state.class(hash)? {
+                dump.compiled_classes.insert(hash, class.into());
+            }
+            if let Some(class) = state.sierra_class(hash)? {
+                dump.sierra_classes.insert(hash, class);
+            }
+        }
+
+        Ok(dump)
+    }
 }
@@ -1,13 +1,14 @@
 use anyhow::Result;
-use blockifier::block_context::BlockContext;
+use katana_executor::executor::BlockEnv;
 use katana_primitives::block::{
-    Block, BlockHash, FinalityStatus, GasPrices, Header, PartialHeader, SealedBlockWithStatus,
+    Block, BlockHash, FinalityStatus, Header, PartialHeader, SealedBlockWithStatus,
 };
 use katana_primitives::state::StateUpdatesWithDeclaredClasses;
 use katana_primitives::version::CURRENT_STARKNET_VERSION;
 use katana_primitives::FieldElement;
 use katana_provider::traits::block::{BlockProvider, BlockWriter};
 use katana_provider::traits::contract::ContractClassWriter;
+use katana_provider::traits::event::EventProvider;
 use katana_provider::traits::state::{StateFactoryProvider, StateRootProvider, StateWriter};
 use katana_provider::traits::state_update::StateUpdateProvider;
 use katana_provider::traits::transaction::{
@@ -25,6 +26,7 @@ pub trait Database:
     + TransactionStatusProvider
     + TransactionsProviderExt
     + ReceiptProvider
+    + EventProvider
     + StateUpdateProvider
     + StateRootProvider
     + StateWriter
@@ -43,6 +45,7 @@ impl<T> Database for T where
         + TransactionStatusProvider
         + TransactionsProviderExt
         + ReceiptProvider
+        + EventProvider
         + StateUpdateProvider
         + StateRootProvider
         + StateWriter
@@ -63,16 +66,13 @@ impl Blockchain {
         Self { inner: BlockchainProvider::new(Box::new(provider)) }
     }
 
-    pub fn new_with_genesis(provider: impl Database, block_context: &BlockContext) -> Result<Self> {
+    pub fn new_with_genesis(provider: impl Database, block_context: &BlockEnv) -> Result<Self> {
         let header = PartialHeader {
             parent_hash: 0u8.into(),
             version: CURRENT_STARKNET_VERSION,
             timestamp: block_context.block_timestamp.0,
             sequencer_address: *SEQUENCER_ADDRESS,
-            gas_prices: GasPrices {
-                eth_gas_price: block_context.gas_prices.eth_l1_gas_price.try_into().unwrap(),
-                strk_gas_price: block_context.gas_prices.strk_l1_gas_price.try_into().unwrap(),
-            },
+            gas_prices: block_context.gas_prices.clone(),
         };
 
         let block = SealedBlockWithStatus {
@@ -93,7 +93,7 @@ impl Blockchain {
         provider: impl Database,
         block_hash: BlockHash,
         parent_hash: FieldElement,
-        block_context: &BlockContext,
+        block_context: &BlockEnv,
         state_root: FieldElement,
         block_status: FinalityStatus,
     ) -> Result<Self> {
@@ -104,10 +104,7 @@ impl Blockchain {
             number: block_context.block_number.0,
             timestamp: block_context.block_timestamp.0,
             sequencer_address: *SEQUENCER_ADDRESS,
-            gas_prices: GasPrices {
-                eth_gas_price: block_context.gas_prices.eth_l1_gas_price.try_into().unwrap(),
-                strk_gas_price: block_context.gas_prices.strk_l1_gas_price.try_into().unwrap(),
-            },
+            gas_prices: block_context.gas_prices.clone(),
         };
 
         let block = SealedBlockWithStatus {
@@ -134,8 +131,8 @@ impl Blockchain {
 
 #[cfg(test)]
 mod tests {
-    use blockifier::block_context::{BlockContext, FeeTokenAddresses, GasPrices};
-    use katana_primitives::block::FinalityStatus;
+    use katana_executor::executor::BlockEnv;
+    use katana_primitives::block::{FinalityStatus, GasPrices};
     use katana_primitives::FieldElement;
     use katana_provider::providers::in_memory::InMemoryProvider;
     use katana_provider::traits::block::{
@@ -154,8 +151,8 @@ mod tests {
     #[test]
     fn blockchain_from_genesis_states() {
         let provider = InMemoryProvider::new();
-        let block_context = BlockContext {
-            gas_prices: GasPrices { eth_l1_gas_price: 0, strk_l1_gas_price: 0 },
+        let block_context = BlockEnv {
+            gas_prices: GasPrices::new(0, 0),
             max_recursion_depth: 0,
             validate_max_n_steps: 0,
             invoke_tx_max_n_steps: 0,
@@ -163,11 +160,7 @@ mod tests {
             chain_id: ChainId("test".into()),
             block_timestamp: BlockTimestamp(0),
             sequencer_address: Default::default(),
-            fee_token_addresses: FeeTokenAddresses {
-                eth_fee_token_address: Default::default(),
-                strk_fee_token_address: Default::default(),
-            },
-            vm_resource_fee_cost: Default::default(),
+            fee_token_address: Default::default(),
         };
 
         let blockchain = Blockchain::new_with_genesis(provider, &block_context)
@@ -188,8 +181,8 @@ mod tests {
     fn blockchain_from_fork() {
         let provider = InMemoryProvider::new();
 
-        let block_context = BlockContext {
-            gas_prices: GasPrices { eth_l1_gas_price: 9090, strk_l1_gas_price: 0 },
+        let block_context = BlockEnv {
+            gas_prices: GasPrices::new(9090, 0),
             max_recursion_depth: 0,
             validate_max_n_steps: 0,
             invoke_tx_max_n_steps: 0,
@@ -197,11 +190,7 @@ mod tests {
             block_number: BlockNumber(23),
             block_timestamp: BlockTimestamp(6868),
             sequencer_address: Default::default(),
-            fee_token_addresses: FeeTokenAddresses {
-                eth_fee_token_address: Default::default(),
-                strk_fee_token_address: Default::default(),
-            },
-            vm_resource_fee_cost: Default::default(),
+            fee_token_address: Default::default(),
         };
 
         let blockchain = Blockchain::new_from_forked(
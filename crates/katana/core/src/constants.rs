@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use katana_primitives::contract::{
     CompiledContractClass, ContractAddress, SierraClass, StorageKey,
 };
@@ -11,6 +13,13 @@ pub const DEFAULT_GAS_PRICE: u128 = 100 * u128::pow(10, 9); // Given in units of
 pub const DEFAULT_INVOKE_MAX_STEPS: u32 = 1_000_000;
 pub const DEFAULT_VALIDATE_MAX_STEPS: u32 = 1_000_000;
 
+/// The interval at which the history of the database is pruned, when pruning is enabled.
+pub const PRUNING_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The number of seconds between two blocks in deterministic mode, unless another fixed
+/// increment is specified.
+pub const DETERMINISTIC_BLOCK_TIME: u64 = 1;
+
 lazy_static! {
 
     // Predefined contract addresses
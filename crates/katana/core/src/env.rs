@@ -1,11 +1,9 @@
-use std::collections::HashMap;
-
-use blockifier::block_context::{BlockContext, FeeTokenAddresses, GasPrices};
-use cairo_vm::vm::runners::builtin_runner::{
-    BITWISE_BUILTIN_NAME, EC_OP_BUILTIN_NAME, HASH_BUILTIN_NAME, KECCAK_BUILTIN_NAME,
-    OUTPUT_BUILTIN_NAME, POSEIDON_BUILTIN_NAME, RANGE_CHECK_BUILTIN_NAME,
-    SEGMENT_ARENA_BUILTIN_NAME, SIGNATURE_BUILTIN_NAME,
-};
+use std::fmt;
+use std::str::FromStr;
+
+use katana_executor::executor::BlockEnv;
+use katana_primitives::block::GasPrices;
+use serde::{Deserialize, Serialize};
 use starknet_api::block::{BlockNumber, BlockTimestamp};
 use starknet_api::core::ChainId;
 
@@ -16,32 +14,131 @@ use crate::constants::{DEFAULT_GAS_PRICE, FEE_TOKEN_ADDRESS, SEQUENCER_ADDRESS};
 pub struct Env {
     /// The block environment of the current block. This is the context that
     /// the transactions will be executed on.
-    pub block: BlockContext,
+    pub block: BlockEnv,
+}
+
+/// The mode in which the timestamps of the blocks advance.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeMode {
+    /// The timestamps follow the wall clock.
+    #[default]
+    Realtime,
+    /// The timestamps only change when explicitly set or increased.
+    Frozen,
+    /// The timestamps advance the given number of times faster than the wall clock.
+    Accelerated(u64),
+    /// The timestamp of every block is the one of its parent plus the given number of seconds.
+    FixedIncrement(u64),
+}
+
+impl FromStr for TimeMode {
+    type Err = String;
+
+    /// Parses `realtime`, `frozen`, `accelerated:<FACTOR>` or `fixed_increment:<SECONDS>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (mode, value) = match s.split_once(':') {
+            Some((mode, value)) => {
+                (mode, Some(value.parse::<u64>().map_err(|e| format!("invalid value: {e}"))?))
+            }
+            None => (s, None),
+        };
+
+        match (mode, value) {
+            ("realtime", None) => Ok(Self::Realtime),
+            ("frozen", None) => Ok(Self::Frozen),
+            ("accelerated", Some(factor)) => Ok(Self::Accelerated(factor)),
+            ("fixed_increment", Some(seconds)) => Ok(Self::FixedIncrement(seconds)),
+            _ => Err(format!("invalid time mode `{s}`")),
+        }
+    }
 }
 
+impl fmt::Display for TimeMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Realtime => write!(f, "realtime"),
+            Self::Frozen => write!(f, "frozen"),
+            Self::Accelerated(factor) => write!(f, "accelerated:{factor}"),
+            Self::FixedIncrement(seconds) => write!(f, "fixed_increment:{seconds}"),
+        }
+    }
+}
+
+/// Generates the timestamps of the blocks, according to a [`TimeMode`].
+///
+/// The timestamp of a block is the time given by the mode, shifted by `block_timestamp_offset`.
+/// The time of every mode but [`TimeMode::Realtime`] is relative to the moment the mode was set.
 #[derive(Debug, Default)]
 pub struct BlockContextGenerator {
     pub block_timestamp_offset: i64,
     pub next_block_start_time: u64,
+    time_mode: TimeMode,
+    /// The wall-clock time and the block timestamp at the moment the mode was set.
+    anchor: (u64, u64),
+    /// The number of timestamps generated since the mode was set.
+    generated: u64,
+}
+
+impl BlockContextGenerator {
+    /// Creates a generator whose timestamps start from the wall-clock time `now`.
+    pub fn new(time_mode: TimeMode, now: u64) -> Self {
+        Self { time_mode, anchor: (now, now), ..Default::default() }
+    }
+
+    pub fn time_mode(&self) -> TimeMode {
+        self.time_mode
+    }
+
+    /// Switches to another time mode. The time of the new mode starts from the current time of the
+    /// previous one, so that the timestamps never jump when switching.
+    pub fn set_time_mode(&mut self, time_mode: TimeMode, now: u64) {
+        let timestamp = (self.time(now) + self.block_timestamp_offset).max(0) as u64;
+        self.time_mode = time_mode;
+        self.anchor = (now, timestamp);
+        self.generated = 0;
+        self.block_timestamp_offset = timestamp as i64 - self.time(now);
+    }
+
+    /// Returns the timestamp of the next block, given the wall-clock time `now`.
+    pub fn next_timestamp(&mut self, now: u64) -> u64 {
+        self.generated += 1;
+        let time = self.time(now);
+
+        if self.next_block_start_time != 0 {
+            self.block_timestamp_offset = self.next_block_start_time as i64 - time;
+            self.next_block_start_time = 0;
+        }
+
+        (time + self.block_timestamp_offset).max(0) as u64
+    }
+
+    /// Returns the time given by the mode, before applying the offset.
+    fn time(&self, now: u64) -> i64 {
+        let (anchor_now, anchor_timestamp) = (self.anchor.0 as i64, self.anchor.1 as i64);
+        match self.time_mode {
+            TimeMode::Realtime => now as i64,
+            TimeMode::Frozen => anchor_timestamp,
+            TimeMode::Accelerated(factor) => {
+                anchor_timestamp + (now as i64 - anchor_now) * factor as i64
+            }
+            TimeMode::FixedIncrement(seconds) => {
+                anchor_timestamp + (self.generated * seconds) as i64
+            }
+        }
+    }
 }
 
 impl Default for Env {
     fn default() -> Self {
         Self {
-            block: BlockContext {
+            block: BlockEnv {
                 chain_id: ChainId("KATANA".to_string()),
                 block_number: BlockNumber::default(),
                 block_timestamp: BlockTimestamp::default(),
-                sequencer_address: (*SEQUENCER_ADDRESS).into(),
-                fee_token_addresses: FeeTokenAddresses {
-                    eth_fee_token_address: (*FEE_TOKEN_ADDRESS).into(),
-                    strk_fee_token_address: Default::default(),
-                },
-                vm_resource_fee_cost: get_default_vm_resource_fee_cost().into(),
-                gas_prices: GasPrices {
-                    eth_l1_gas_price: DEFAULT_GAS_PRICE,
-                    strk_l1_gas_price: Default::default(),
-                },
+                sequencer_address: *SEQUENCER_ADDRESS,
+                fee_token_address: *FEE_TOKEN_ADDRESS,
+                gas_prices: GasPrices::new(DEFAULT_GAS_PRICE as u64, 0),
                 invoke_tx_max_n_steps: 1_000_000,
                 validate_max_n_steps: 1_000_000,
                 max_recursion_depth: 100,
@@ -50,17 +147,45 @@ impl Default for Env {
     }
 }
 
-pub fn get_default_vm_resource_fee_cost() -> HashMap<String, f64> {
-    HashMap::from([
-        (String::from("n_steps"), 1_f64),
-        (HASH_BUILTIN_NAME.to_string(), 1_f64),
-        (RANGE_CHECK_BUILTIN_NAME.to_string(), 1_f64),
-        (SIGNATURE_BUILTIN_NAME.to_string(), 1_f64),
-        (BITWISE_BUILTIN_NAME.to_string(), 1_f64),
-        (POSEIDON_BUILTIN_NAME.to_string(), 1_f64),
-        (OUTPUT_BUILTIN_NAME.to_string(), 1_f64),
-        (EC_OP_BUILTIN_NAME.to_string(), 1_f64),
-        (KECCAK_BUILTIN_NAME.to_string(), 1_f64),
-        (SEGMENT_ARENA_BUILTIN_NAME.to_string(), 1_f64),
-    ])
+#[cfg(test)]
+mod tests {
+    use super::{BlockContextGenerator, TimeMode};
+
+    #[test]
+    fn timestamps_of_time_modes() {
+        let mut generator = BlockContextGenerator::new(TimeMode::Realtime, 1000);
+        assert_eq!(generator.next_timestamp(1010), 1010);
+
+        generator.set_time_mode(TimeMode::Frozen, 1020);
+        assert_eq!(generator.next_timestamp(1030), 1020);
+        generator.block_timestamp_offset += 5;
+        assert_eq!(generator.next_timestamp(1040), 1025);
+
+        generator.set_time_mode(TimeMode::Accelerated(10), 1050);
+        assert_eq!(generator.next_timestamp(1052), 1045);
+
+        generator.set_time_mode(TimeMode::FixedIncrement(60), 1060);
+        assert_eq!(generator.next_timestamp(1060), 1185);
+        assert_eq!(generator.next_timestamp(1060), 1245);
+
+        generator.next_block_start_time = 2000;
+        assert_eq!(generator.next_timestamp(1061), 2000);
+        assert_eq!(generator.next_timestamp(1062), 2060);
+
+        // the wall clock is followed again from the current time
+        generator.set_time_mode(TimeMode::Realtime, 1070);
+        assert_eq!(generator.next_timestamp(1080), 2070);
+    }
+
+    #[test]
+    fn parse_time_mode() {
+        assert_eq!("frozen".parse::<TimeMode>(), Ok(TimeMode::Frozen));
+        assert_eq!("accelerated:10".parse::<TimeMode>(), Ok(TimeMode::Accelerated(10)));
+        assert_eq!("fixed_increment:60".parse::<TimeMode>(), Ok(TimeMode::FixedIncrement(60)));
+        assert!("accelerated".parse::<TimeMode>().is_err());
+        assert!("frozen:1".parse::<TimeMode>().is_err());
+
+        let mode = TimeMode::FixedIncrement(60);
+        assert_eq!(mode.to_string().parse::<TimeMode>(), Ok(mode));
+    }
 }
@@ -3,6 +3,7 @@ pub mod backend;
 pub mod constants;
 pub mod env;
 pub mod pool;
+pub mod replay;
 pub mod sequencer;
 pub mod service;
 pub mod utils;
@@ -44,6 +44,32 @@ impl TransactionPool {
         transactions
     }
 
+    /// Returns the transactions in the pool, without removing them.
+    pub fn transactions(&self) -> Vec<ExecutableTxWithHash> {
+        self.transactions.read().clone()
+    }
+
+    /// Removes a transaction from the pool. Returns `true` if the transaction was in the pool.
+    pub fn remove_transaction(&self, hash: FieldElement) -> bool {
+        let mut txs = self.transactions.write();
+        let len = txs.len();
+        txs.retain(|tx| tx.hash != hash);
+        txs.len() != len
+    }
+
+    /// Removes all the transactions from the pool, and returns the number of removed transactions.
+    pub fn clear(&self) -> usize {
+        let mut txs = self.transactions.write();
+        let len = txs.len();
+        txs.clear();
+        len
+    }
+
+    /// Returns `true` if the transaction is in the pool, waiting to be executed.
+    pub fn contains(&self, hash: FieldElement) -> bool {
+        self.transactions.read().iter().any(|tx| tx.hash == hash)
+    }
+
     /// notifies all listeners about the transaction
     fn notify_listener(&self, hash: FieldElement) {
         let mut listener = self.transaction_listeners.write();
@@ -0,0 +1,212 @@
+//! Types for reporting the differences between the stored outputs of mined transactions and the
+//! ones obtained by re-executing them with the current executor.
+
+use std::collections::{BTreeSet, HashMap};
+use std::hash::Hash;
+
+use katana_primitives::block::BlockNumber;
+use katana_primitives::contract::{ClassHash, ContractAddress, StorageKey};
+use katana_primitives::receipt::Receipt;
+use katana_primitives::state::StateUpdates;
+use katana_primitives::transaction::TxHash;
+use katana_primitives::FieldElement;
+use serde::Serialize;
+
+/// The outcome of replaying a transaction, or a whole block.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayOutcome {
+    /// The number of the block the transactions were mined in.
+    pub block_number: BlockNumber,
+    /// The replayed transactions.
+    pub transactions: Vec<TransactionReplay>,
+    /// The mismatches between the stored state update of the block and the replayed one. Only
+    /// computed when replaying a whole block.
+    pub state_mismatches: Vec<StateMismatch>,
+}
+
+impl ReplayOutcome {
+    /// Returns `true` if the replayed outputs are identical to the stored ones.
+    pub fn is_match(&self) -> bool {
+        self.state_mismatches.is_empty() && self.transactions.iter().all(|tx| tx.is_match())
+    }
+}
+
+/// The outcome of replaying a single transaction.
+#[derive(Debug, Clone, Serialize)]
+pub struct TransactionReplay {
+    pub transaction_hash: TxHash,
+    /// The stored receipt and the replayed one, if they differ.
+    pub receipt_mismatch: Option<ReceiptMismatch>,
+    /// The execution error, if the transaction failed to be executed when replayed.
+    pub execution_error: Option<String>,
+}
+
+impl TransactionReplay {
+    pub fn new(transaction_hash: TxHash, expected: Receipt, actual: Receipt) -> Self {
+        let receipt_mismatch =
+            if expected == actual { None } else { Some(ReceiptMismatch { expected, actual }) };
+        Self { transaction_hash, receipt_mismatch, execution_error: None }
+    }
+
+    pub fn failed(transaction_hash: TxHash, error: String) -> Self {
+        Self { transaction_hash, receipt_mismatch: None, execution_error: Some(error) }
+    }
+
+    /// Returns `true` if the transaction was executed and produced the stored receipt.
+    pub fn is_match(&self) -> bool {
+        self.receipt_mismatch.is_none() && self.execution_error.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReceiptMismatch {
+    pub expected: Receipt,
+    pub actual: Receipt,
+}
+
+/// A value of the state update that differs between the stored and the replayed block. A missing
+/// value means that the entry wasn't updated.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StateMismatch {
+    Nonce {
+        contract_address: ContractAddress,
+        expected: Option<FieldElement>,
+        actual: Option<FieldElement>,
+    },
+    Storage {
+        contract_address: ContractAddress,
+        key: StorageKey,
+        expected: Option<FieldElement>,
+        actual: Option<FieldElement>,
+    },
+    ContractClass {
+        contract_address: ContractAddress,
+        expected: Option<ClassHash>,
+        actual: Option<ClassHash>,
+    },
+    DeclaredClass {
+        class_hash: ClassHash,
+        expected: Option<FieldElement>,
+        actual: Option<FieldElement>,
+    },
+}
+
+/// Returns all the entries that differ between the `expected` and `actual` state updates.
+pub fn diff_state_updates(expected: &StateUpdates, actual: &StateUpdates) -> Vec<StateMismatch> {
+    let mut mismatches = Vec::new();
+
+    mismatches.extend(diff_maps(&expected.nonce_updates, &actual.nonce_updates).map(
+        |(contract_address, expected, actual)| StateMismatch::Nonce {
+            contract_address,
+            expected,
+            actual,
+        },
+    ));
+
+    let empty = HashMap::new();
+    let addresses = expected.storage_updates.keys().chain(actual.storage_updates.keys());
+    for address in addresses.collect::<BTreeSet<_>>() {
+        let expected = expected.storage_updates.get(address).unwrap_or(&empty);
+        let actual = actual.storage_updates.get(address).unwrap_or(&empty);
+        mismatches.extend(diff_maps(expected, actual).map(|(key, expected, actual)| {
+            StateMismatch::Storage { contract_address: *address, key, expected, actual }
+        }));
+    }
+
+    mismatches.extend(diff_maps(&expected.contract_updates, &actual.contract_updates).map(
+        |(contract_address, expected, actual)| StateMismatch::ContractClass {
+            contract_address,
+            expected,
+            actual,
+        },
+    ));
+
+    mismatches.extend(diff_maps(&expected.declared_classes, &actual.declared_classes).map(
+        |(class_hash, expected, actual)| StateMismatch::DeclaredClass {
+            class_hash,
+            expected,
+            actual,
+        },
+    ));
+
+    mismatches
+}
+
+/// Returns the keys whose values differ between the two maps, sorted by key.
+fn diff_maps<'a, K, V>(
+    expected: &'a HashMap<K, V>,
+    actual: &'a HashMap<K, V>,
+) -> impl Iterator<Item = (K, Option<V>, Option<V>)> + 'a
+where
+    K: Copy + Ord + Hash,
+    V: Copy + PartialEq,
+{
+    let keys = expected.keys().chain(actual.keys()).copied().collect::<BTreeSet<_>>();
+    keys.into_iter().filter_map(|key| {
+        let (expected, actual) = (expected.get(&key).copied(), actual.get(&key).copied());
+        (expected != actual).then_some((key, expected, actual))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use katana_primitives::contract::ContractAddress;
+    use katana_primitives::state::StateUpdates;
+    use starknet::macros::felt;
+
+    use super::{diff_state_updates, StateMismatch};
+
+    #[test]
+    fn identical_state_updates_have_no_mismatches() {
+        let mut state_updates = StateUpdates::default();
+        state_updates.nonce_updates.insert(ContractAddress::from(felt!("0x1")), felt!("0x2"));
+        state_updates
+            .storage_updates
+            .entry(ContractAddress::from(felt!("0x1")))
+            .or_default()
+            .insert(felt!("0x3"), felt!("0x4"));
+
+        assert!(diff_state_updates(&state_updates, &state_updates.clone()).is_empty());
+    }
+
+    #[test]
+    fn diff_reports_changed_missing_and_extra_entries() {
+        let address = ContractAddress::from(felt!("0x1"));
+
+        let mut expected = StateUpdates::default();
+        expected.nonce_updates.insert(address, felt!("0x1"));
+        expected.storage_updates.entry(address).or_default().insert(felt!("0xa"), felt!("0x1"));
+        expected.storage_updates.entry(address).or_default().insert(felt!("0xb"), felt!("0x2"));
+
+        let mut actual = StateUpdates::default();
+        actual.nonce_updates.insert(address, felt!("0x1"));
+        actual.storage_updates.entry(address).or_default().insert(felt!("0xa"), felt!("0x5"));
+        actual.declared_classes.insert(felt!("0xc"), felt!("0xd"));
+
+        let mismatches = diff_state_updates(&expected, &actual);
+
+        assert_eq!(
+            mismatches,
+            vec![
+                StateMismatch::Storage {
+                    contract_address: address,
+                    key: felt!("0xa"),
+                    expected: Some(felt!("0x1")),
+                    actual: Some(felt!("0x5")),
+                },
+                StateMismatch::Storage {
+                    contract_address: address,
+                    key: felt!("0xb"),
+                    expected: Some(felt!("0x2")),
+                    actual: None,
+                },
+                StateMismatch::DeclaredClass {
+                    class_hash: felt!("0xc"),
+                    expected: None,
+                    actual: Some(felt!("0xd")),
+                },
+            ]
+        );
+    }
+}
@@ -1,44 +1,55 @@
-use std::cmp::Ordering;
-use std::iter::Skip;
-use std::slice::Iter;
 use std::sync::Arc;
 
 use anyhow::Result;
-use blockifier::execution::errors::{EntryPointExecutionError, PreExecutionError};
-use blockifier::transaction::errors::TransactionExecutionError;
-use katana_executor::blockifier::state::StateRefDb;
-use katana_executor::blockifier::utils::EntryPointCall;
-use katana_executor::blockifier::PendingState;
-use katana_primitives::block::{BlockHash, BlockHashOrNumber, BlockIdOrTag, BlockNumber};
+use katana_executor::executor::{
+    BlockExecutor, EntryPointCall, ExecutionError, ExecutionFlags, ExecutorFactory, PendingState,
+    TxExecutionResult,
+};
+use katana_executor::outcome::TxReceiptWithExecInfo;
+use katana_executor::profile::TransactionProfile;
+use katana_primitives::block::{
+    BlockHash, BlockHashOrNumber, BlockIdOrTag, BlockNumber, PartialHeader,
+};
 use katana_primitives::contract::{
     ClassHash, CompiledContractClass, ContractAddress, Nonce, StorageKey, StorageValue,
 };
-use katana_primitives::event::{ContinuationToken, ContinuationTokenError};
-use katana_primitives::receipt::Event;
-use katana_primitives::transaction::{ExecutableTxWithHash, TxHash, TxWithHash};
+use katana_primitives::event::{ContinuationToken, ContinuationTokenError, EventFilter};
+use katana_primitives::receipt::Receipt;
+use katana_primitives::state::StateUpdates;
+use katana_primitives::transaction::{
+    DeclareTxWithClass, ExecutableTx, ExecutableTxWithHash, Tx, TxHash, TxWithHash,
+};
+use katana_primitives::version::CURRENT_STARKNET_VERSION;
 use katana_primitives::FieldElement;
 use katana_provider::traits::block::{
-    BlockHashProvider, BlockIdReader, BlockNumberProvider, BlockProvider,
+    BlockHashProvider, BlockIdReader, BlockNumberProvider, BlockProvider, HeaderProvider,
 };
 use katana_provider::traits::contract::ContractClassProvider;
+use katana_provider::traits::event::EventProvider;
 use katana_provider::traits::state::{StateFactoryProvider, StateProvider};
+use katana_provider::traits::state_update::StateUpdateProvider;
 use katana_provider::traits::transaction::{
     ReceiptProvider, TransactionProvider, TransactionsProviderExt,
 };
+use parking_lot::Mutex;
 use starknet::core::types::{BlockTag, EmittedEvent, EventsPage, FeeEstimate};
 use starknet_api::core::ChainId;
+use tokio::task::JoinHandle;
 
 use crate::backend::config::StarknetConfig;
 use crate::backend::contract::StarknetContract;
 use crate::backend::Backend;
+use crate::env::TimeMode;
 use crate::pool::TransactionPool;
+use crate::replay::{diff_state_updates, ReplayOutcome, TransactionReplay};
 use crate::sequencer_error::SequencerError;
-use crate::service::block_producer::{BlockProducer, BlockProducerMode};
+use crate::service::block_producer::{BlockProducer, BlockProducerMode, MiningMode};
 #[cfg(feature = "messaging")]
 use crate::service::messaging::MessagingConfig;
 #[cfg(feature = "messaging")]
 use crate::service::messaging::MessagingService;
 use crate::service::{NodeService, TransactionMiner};
+use crate::utils::get_current_timestamp;
 
 type SequencerResult<T> = Result<T, SequencerError>;
 
@@ -55,17 +66,33 @@ pub struct KatanaSequencer {
     pub pool: Arc<TransactionPool>,
     pub backend: Arc<Backend>,
     pub block_producer: BlockProducer,
+    /// The task of the [`NodeService`], taken once it has stopped.
+    service: Mutex<Option<JoinHandle<()>>>,
 }
 
 impl KatanaSequencer {
+    /// Creates a new sequencer that executes transactions with blockifier.
+    #[cfg(feature = "blockifier")]
     pub async fn new(config: SequencerConfig, starknet_config: StarknetConfig) -> Self {
-        let backend = Arc::new(Backend::new(starknet_config).await);
+        let executor_factory = Arc::new(katana_executor::blockifier::BlockifierFactory);
+        Self::with_executor_factory(config, starknet_config, executor_factory).await
+    }
+
+    /// Creates a new sequencer that executes transactions with the executors of the given
+    /// factory.
+    pub async fn with_executor_factory(
+        config: SequencerConfig,
+        starknet_config: StarknetConfig,
+        executor_factory: Arc<dyn ExecutorFactory>,
+    ) -> Self {
+        let deterministic = starknet_config.deterministic;
+        let backend =
+            Arc::new(Backend::with_executor_factory(starknet_config, executor_factory).await);
 
         let pool = Arc::new(TransactionPool::new());
-        let miner = TransactionMiner::new(pool.add_listener());
-        let state = StateFactoryProvider::latest(backend.blockchain.provider())
-            .map(StateRefDb::new)
-            .unwrap();
+        let miner =
+            TransactionMiner::new(pool.add_listener()).with_canonical_ordering(deterministic);
+        let state = StateFactoryProvider::latest(backend.blockchain.provider()).unwrap();
 
         let block_producer = if let Some(block_time) = config.block_time {
             BlockProducer::interval(Arc::clone(&backend), state, block_time)
@@ -82,7 +109,7 @@ impl KatanaSequencer {
             None
         };
 
-        tokio::spawn(NodeService {
+        let service = tokio::spawn(NodeService {
             miner,
             pool: Arc::clone(&pool),
             block_producer: block_producer.clone(),
@@ -90,7 +117,29 @@ impl KatanaSequencer {
             messaging,
         });
 
-        Self { pool, config, backend, block_producer }
+        Self { pool, config, backend, block_producer, service: Mutex::new(Some(service)) }
+    }
+
+    /// Stops the service that mines the transactions of the pool. Transactions can still be added
+    /// to the pool, but they won't be mined anymore.
+    pub fn stop(&self) {
+        if let Some(service) = &*self.service.lock() {
+            service.abort();
+        }
+    }
+
+    /// Waits until the service that mines the transactions of the pool has stopped.
+    pub async fn stopped(&self) {
+        let service = self.service.lock().take();
+        if let Some(service) = service {
+            // the service never completes on its own, so it either panicked or was aborted
+            let _ = service.await;
+        }
+    }
+
+    /// Returns `true` if the service that mines the transactions of the pool is running.
+    pub fn is_running(&self) -> bool {
+        self.service.lock().as_ref().is_some_and(|service| !service.is_finished())
     }
 
     /// Returns the pending state if the sequencer is running in _interval_ mode. Otherwise `None`.
@@ -101,6 +150,51 @@ impl KatanaSequencer {
         }
     }
 
+    /// Returns the header of the pending block, which is built on top of the latest block.
+    pub fn pending_header(&self) -> SequencerResult<PartialHeader> {
+        let parent_hash = BlockHashProvider::latest_hash(self.backend.blockchain.provider())?;
+        let block_context = self.backend.env.read().block.clone();
+
+        Ok(PartialHeader {
+            parent_hash,
+            version: CURRENT_STARKNET_VERSION,
+            gas_prices: block_context.gas_prices,
+            timestamp: block_context.block_timestamp.0,
+            sequencer_address: block_context.sequencer_address,
+        })
+    }
+
+    /// Returns the transactions executed in the pending block. Always empty when the sequencer
+    /// isn't running in _interval_ mode, as transactions are mined as soon as they're executed.
+    pub fn pending_transactions(&self) -> Vec<TxWithHash> {
+        self.pending_state()
+            .map(|state| state.executed_txs.read().iter().map(|(tx, _)| tx.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns the state updates made by the transactions of the pending block, along with the
+    /// state root of the latest block they're applied on.
+    pub fn pending_state_update(&self) -> SequencerResult<(FieldElement, StateUpdates)> {
+        let provider = self.backend.blockchain.provider();
+        let latest_num = BlockNumberProvider::latest_number(provider)?;
+        let old_root = HeaderProvider::header(provider, latest_num.into())?
+            .map(|header| header.state_root)
+            .ok_or(SequencerError::DataUnavailable)?;
+
+        let state_updates = self
+            .pending_state()
+            .map(|state| state.state.state_updates().state_updates)
+            .unwrap_or_default();
+
+        Ok((old_root, state_updates))
+    }
+
+    /// Returns `true` if the transaction has been received but not executed yet.
+    pub fn is_transaction_received(&self, hash: TxHash) -> bool {
+        self.pool.contains(hash)
+            || self.block_producer.queued_transactions().iter().any(|tx| tx.hash == hash)
+    }
+
     pub fn block_producer(&self) -> &BlockProducer {
         &self.block_producer
     }
@@ -143,20 +237,21 @@ impl KatanaSequencer {
         self.pool.add_transaction(tx);
     }
 
+    /// Estimates the fee of the transactions. The validation of the transactions is skipped if
+    /// `skip_validate` is set, or if it's disabled on the node.
     pub fn estimate_fee(
         &self,
         transactions: Vec<ExecutableTxWithHash>,
         block_id: BlockIdOrTag,
+        skip_validate: bool,
     ) -> SequencerResult<Vec<FeeEstimate>> {
         let state = self.state(&block_id)?;
         let block_context = self.backend.env.read().block.clone();
-        katana_executor::blockifier::utils::estimate_fee(
-            transactions.into_iter(),
-            block_context,
-            state,
-            !self.backend.config.read().disable_validate,
-        )
-        .map_err(SequencerError::TransactionExecution)
+        let validate = !skip_validate && !self.backend.config.read().disable_validate;
+        self.backend
+            .executor_factory
+            .estimate_fee(transactions, &block_context, state, validate)
+            .map_err(SequencerError::TransactionExecution)
     }
 
     pub fn block_hash_and_number(&self) -> SequencerResult<(BlockHash, BlockNumber)> {
@@ -269,16 +364,13 @@ impl KatanaSequencer {
         let state = self.state(&block_id)?;
         let block_context = self.backend.env.read().block.clone();
 
-        let retdata = katana_executor::blockifier::utils::call(request, block_context, state)
-            .map_err(|e| match e {
-                TransactionExecutionError::ExecutionError(exe) => match exe {
-                    EntryPointExecutionError::PreExecutionError(
-                        PreExecutionError::UninitializedStorageAddress(addr),
-                    ) => SequencerError::ContractNotFound(addr.into()),
-                    _ => SequencerError::EntryPointExecution(exe),
-                },
-                _ => SequencerError::TransactionExecution(e),
-            })?;
+        let retdata = self.backend.executor_factory.call(request, &block_context, state).map_err(
+            |e| match e {
+                ExecutionError::ContractNotDeployed(addr) => SequencerError::ContractNotFound(addr),
+                e @ ExecutionError::EntryPoint(_) => SequencerError::EntryPointExecution(e),
+                e => SequencerError::TransactionExecution(e),
+            },
+        )?;
 
         Ok(retdata)
     }
@@ -300,6 +392,136 @@ impl KatanaSequencer {
         Ok(tx)
     }
 
+    /// Returns the profile of the execution of a transaction.
+    ///
+    /// Transactions that have already been mined are re-executed on top of the state of their
+    /// parent block, along with all the transactions that precede them in the block.
+    pub fn transaction_profile(&self, hash: TxHash) -> SequencerResult<Option<TransactionProfile>> {
+        if let Some(pending) = self.pending_state() {
+            let executed_txs = pending.executed_txs.read();
+            if let Some((_, receipt)) = executed_txs.iter().find(|(tx, _)| tx.hash == hash) {
+                let profile =
+                    TransactionProfile::new(&receipt.execution_info, pending.state.as_ref());
+                return Ok(Some(profile));
+            }
+        }
+
+        let provider = self.backend.blockchain.provider();
+
+        let Some((block_num, _)) =
+            TransactionProvider::transaction_block_num_and_hash(provider, hash)?
+        else {
+            return Ok(None);
+        };
+
+        let (executor, mut results) = self.reexecute_block(block_num, Some(hash))?;
+        let (_, execution_info) = results.pop().expect("at least one transaction was executed");
+
+        Ok(Some(TransactionProfile::new(&execution_info?, executor.as_ref())))
+    }
+
+    /// Re-executes a mined transaction and compares its receipt against the stored one.
+    pub fn replay_transaction(&self, hash: TxHash) -> SequencerResult<Option<ReplayOutcome>> {
+        let provider = self.backend.blockchain.provider();
+
+        let Some((block_num, _)) =
+            TransactionProvider::transaction_block_num_and_hash(provider, hash)?
+        else {
+            return Ok(None);
+        };
+
+        let expected = ReceiptProvider::receipt_by_hash(provider, hash)?
+            .ok_or(SequencerError::TxnNotFound(hash))?;
+
+        let (_, mut results) = self.reexecute_block(block_num, Some(hash))?;
+        let (tx, result) = results.pop().expect("at least one transaction was executed");
+
+        Ok(Some(ReplayOutcome {
+            block_number: block_num,
+            transactions: vec![replay_transaction(tx, result, expected)],
+            state_mismatches: Vec::new(),
+        }))
+    }
+
+    /// Re-executes all the transactions of a mined block and compares their receipts, and the
+    /// state update of the block, against the stored ones.
+    pub fn replay_block(&self, block_id: BlockIdOrTag) -> SequencerResult<ReplayOutcome> {
+        let provider = self.backend.blockchain.provider();
+
+        let block_num = BlockIdReader::convert_block_id(provider, block_id)?
+            .ok_or(SequencerError::BlockNotFound(block_id))?;
+
+        let receipts = ReceiptProvider::receipts_by_block(provider, block_num.into())?
+            .ok_or(SequencerError::BlockNotFound(block_id))?;
+        let expected_state = StateUpdateProvider::state_update(provider, block_num.into())?
+            .ok_or(SequencerError::StateUpdateNotFound(block_id))?;
+
+        let (executor, results) = self.reexecute_block(block_num, None)?;
+
+        // zipping the replayed transactions with fewer receipts would skip the comparison of the
+        // ones without a receipt
+        if receipts.len() != results.len() {
+            return Err(SequencerError::ReceiptsUnavailable(block_id));
+        }
+
+        let transactions = results
+            .into_iter()
+            .zip(receipts)
+            .map(|((tx, result), expected)| replay_transaction(tx, result, expected))
+            .collect();
+
+        let actual_state = executor.state_updates().state_updates;
+        let state_mismatches = diff_state_updates(&expected_state, &actual_state);
+
+        Ok(ReplayOutcome { block_number: block_num, transactions, state_mismatches })
+    }
+
+    /// Re-executes the transactions of a mined block on top of the state of its parent block,
+    /// using the current executor and execution flags. If `until` is provided, only the
+    /// transactions up to and including it are executed.
+    fn reexecute_block(
+        &self,
+        block_num: BlockNumber,
+        until: Option<TxHash>,
+    ) -> SequencerResult<(Box<dyn BlockExecutor>, Vec<(TxWithHash, TxExecutionResult)>)> {
+        let provider = self.backend.blockchain.provider();
+
+        let header = HeaderProvider::header_by_number(provider, block_num)?
+            .ok_or(SequencerError::BlockNotFound(BlockIdOrTag::Number(block_num)))?;
+
+        // the genesis block has no parent state to execute the transactions on
+        let parent = block_num.checked_sub(1).ok_or(SequencerError::DataUnavailable)?;
+        let state = StateFactoryProvider::historical(provider, parent.into())?
+            .ok_or(SequencerError::StateNotFound(BlockIdOrTag::Number(parent)))?;
+
+        let mut txs = TransactionProvider::transactions_by_block(provider, block_num.into())?
+            .ok_or(SequencerError::BlockNotFound(BlockIdOrTag::Number(block_num)))?;
+
+        if let Some(hash) = until {
+            let end = txs
+                .iter()
+                .position(|tx| tx.hash == hash)
+                .ok_or(SequencerError::TxnNotFound(hash))?;
+            txs.truncate(end + 1);
+        }
+
+        let executable_txs =
+            txs.iter().cloned().map(|tx| self.executable_tx(tx)).collect::<Result<Vec<_>, _>>()?;
+
+        let block_context = self.backend.block_context_at(&header);
+        let flags = ExecutionFlags { parallel: false, ..self.backend.execution_flags() };
+
+        let executor = self.backend.executor_factory.with_state(state);
+        let results = executor.execute_transactions(&block_context, flags, executable_txs);
+
+        Ok((executor, txs.into_iter().zip(results).collect()))
+    }
+
+    /// Returns the events matching the filter, including the ones of the pending block when
+    /// `to_block` is the pending block.
+    ///
+    /// The blocks that can't contain any matching event are skipped using the event indexes of
+    /// the provider, without reading their receipts.
     pub async fn events(
         &self,
         from_block: BlockIdOrTag,
@@ -310,106 +532,116 @@ impl KatanaSequencer {
         chunk_size: u64,
     ) -> SequencerResult<EventsPage> {
         let provider = self.backend.blockchain.provider();
-        let mut current_block = 0;
-
-        let (mut from_block, to_block) = {
-            let from = BlockIdReader::convert_block_id(provider, from_block)?
-                .ok_or(SequencerError::BlockNotFound(to_block))?;
-            let to = BlockIdReader::convert_block_id(provider, to_block)?
-                .ok_or(SequencerError::BlockNotFound(to_block))?;
-            (from, to)
+        let filter = EventFilter { address, keys };
+
+        let pending_state = self.pending_state();
+        let latest_block = BlockNumberProvider::latest_number(provider)?;
+        // the pending block, if any, is the one that will be mined after the latest block
+        let pending_block = pending_state.as_ref().map(|_| latest_block + 1);
+
+        let block_number = |block_id: BlockIdOrTag| -> SequencerResult<BlockNumber> {
+            match block_id {
+                BlockIdOrTag::Tag(BlockTag::Pending) => Ok(pending_block.unwrap_or(latest_block)),
+                block_id => BlockIdReader::convert_block_id(provider, block_id)?
+                    .ok_or(SequencerError::BlockNotFound(block_id)),
+            }
         };
 
+        let from_block = block_number(from_block)?;
+        let to_block = block_number(to_block)?;
+
         let mut continuation_token = match continuation_token {
             Some(token) => ContinuationToken::parse(token)?,
             None => ContinuationToken::default(),
         };
 
         // skip blocks that have been already read
-        from_block += continuation_token.block_n;
-
-        let mut filtered_events = Vec::with_capacity(chunk_size as usize);
-
-        for i in from_block..=to_block {
-            let block_hash = BlockHashProvider::block_hash_by_num(provider, i)?
-                .ok_or(SequencerError::BlockNotFound(BlockIdOrTag::Number(i)))?;
+        let token_block = from_block + continuation_token.block_n;
+
+        let mut blocks = if token_block <= to_block.min(latest_block) {
+            EventProvider::blocks_with_events(
+                provider,
+                token_block..=to_block.min(latest_block),
+                &filter,
+            )?
+        } else {
+            Vec::new()
+        };
 
-            let receipts = ReceiptProvider::receipts_by_block(provider, BlockHashOrNumber::Num(i))?
-                .ok_or(SequencerError::BlockNotFound(BlockIdOrTag::Number(i)))?;
+        blocks.extend(pending_block.filter(|num| (token_block..=to_block).contains(num)));
 
-            let tx_range = BlockProvider::block_body_indices(provider, BlockHashOrNumber::Num(i))?
-                .ok_or(SequencerError::BlockNotFound(BlockIdOrTag::Number(i)))?;
-            let tx_hashes =
-                TransactionsProviderExt::transaction_hashes_in_range(provider, tx_range.into())?;
+        let mut filtered_events = Vec::with_capacity(chunk_size as usize);
 
-            let txn_n = receipts.len();
-            if (txn_n as u64) < continuation_token.txn_n {
+        for block in blocks {
+            // the transaction and event offsets of the token only apply to the block it points to
+            if block != token_block {
+                continuation_token.txn_n = 0;
+                continuation_token.event_n = 0;
+            }
+            continuation_token.block_n = block - from_block;
+
+            let (block_hash, receipts) = if Some(block) == pending_block {
+                let pending_state = pending_state.as_ref().expect("pending block must exist");
+                let executed_txs = pending_state.executed_txs.read();
+                let receipts = executed_txs.iter().map(|(tx, r)| (tx.hash, r.receipt.clone()));
+                // the pending block doesn't have a hash yet
+                (FieldElement::ZERO, receipts.collect::<Vec<_>>())
+            } else {
+                let block_id = BlockHashOrNumber::Num(block);
+
+                let block_hash = BlockHashProvider::block_hash_by_num(provider, block)?
+                    .ok_or(SequencerError::BlockNotFound(BlockIdOrTag::Number(block)))?;
+                let receipts = ReceiptProvider::receipts_by_block(provider, block_id)?
+                    .ok_or(SequencerError::BlockNotFound(BlockIdOrTag::Number(block)))?;
+                let tx_range = BlockProvider::block_body_indices(provider, block_id)?
+                    .ok_or(SequencerError::BlockNotFound(BlockIdOrTag::Number(block)))?;
+                let tx_hashes = TransactionsProviderExt::transaction_hashes_in_range(
+                    provider,
+                    tx_range.into(),
+                )?;
+
+                (block_hash, tx_hashes.into_iter().zip(receipts).collect())
+            };
+
+            if receipts.len() < continuation_token.txn_n as usize {
                 return Err(SequencerError::ContinuationToken(
                     ContinuationTokenError::InvalidToken,
                 ));
             }
 
-            for (tx_hash, events) in tx_hashes
-                .into_iter()
-                .zip(receipts.iter().map(|r| r.events()))
-                .skip(continuation_token.txn_n as usize)
-            {
-                let txn_events_len: usize = events.len();
-
-                // check if continuation_token.event_n is correct
-                match (txn_events_len as u64).cmp(&continuation_token.event_n) {
-                    Ordering::Greater => (),
-                    Ordering::Less => {
-                        return Err(SequencerError::ContinuationToken(
-                            ContinuationTokenError::InvalidToken,
-                        ));
-                    }
-                    Ordering::Equal => {
-                        continuation_token.txn_n += 1;
-                        continuation_token.event_n = 0;
-                        continue;
-                    }
+            for (tx_hash, receipt) in receipts.iter().skip(continuation_token.txn_n as usize) {
+                let events = receipt.events();
+
+                if events.len() < continuation_token.event_n as usize {
+                    return Err(SequencerError::ContinuationToken(
+                        ContinuationTokenError::InvalidToken,
+                    ));
                 }
 
-                // skip events
-                let txn_events = events.iter().skip(continuation_token.event_n as usize);
-
-                let (new_filtered_events, continuation_index) = filter_events_by_params(
-                    txn_events,
-                    address,
-                    keys.clone(),
-                    Some((chunk_size as usize) - filtered_events.len()),
-                );
-
-                filtered_events.extend(new_filtered_events.iter().map(|e| EmittedEvent {
-                    from_address: e.from_address.into(),
-                    keys: e.keys.clone(),
-                    data: e.data.clone(),
-                    block_hash,
-                    block_number: i,
-                    transaction_hash: tx_hash,
-                }));
-
-                if filtered_events.len() >= chunk_size as usize {
-                    let token = if current_block < to_block
-                        || continuation_token.txn_n < txn_n as u64 - 1
-                        || continuation_index < txn_events_len
-                    {
-                        continuation_token.event_n = continuation_index as u64;
-                        Some(continuation_token.to_string())
-                    } else {
-                        None
-                    };
-                    return Ok(EventsPage { events: filtered_events, continuation_token: token });
+                let events = events.iter().enumerate().skip(continuation_token.event_n as usize);
+                for (event_n, event) in events.filter(|(_, e)| filter.matches(e)) {
+                    // only return a token when there are more events to be read
+                    if filtered_events.len() >= chunk_size as usize {
+                        continuation_token.event_n = event_n as u64;
+                        return Ok(EventsPage {
+                            events: filtered_events,
+                            continuation_token: Some(continuation_token.to_string()),
+                        });
+                    }
+
+                    filtered_events.push(EmittedEvent {
+                        from_address: event.from_address.into(),
+                        keys: event.keys.clone(),
+                        data: event.data.clone(),
+                        block_hash,
+                        block_number: block,
+                        transaction_hash: *tx_hash,
+                    });
                 }
 
                 continuation_token.txn_n += 1;
                 continuation_token.event_n = 0;
             }
-
-            current_block += 1;
-            continuation_token.block_n += 1;
-            continuation_token.txn_n = 0;
         }
 
         Ok(EventsPage { events: filtered_events, continuation_token: None })
@@ -431,6 +663,17 @@ impl KatanaSequencer {
         Ok(())
     }
 
+    pub fn time_mode(&self) -> TimeMode {
+        self.backend.block_context_generator.read().time_mode()
+    }
+
+    /// Switches the mode in which the timestamps of the blocks advance. The current block isn't
+    /// affected, the new mode applies from the next one.
+    pub fn set_time_mode(&self, time_mode: TimeMode) {
+        let now = get_current_timestamp().as_secs();
+        self.backend.block_context_generator.write().set_time_mode(time_mode, now);
+    }
+
     pub fn has_pending_transactions(&self) -> bool {
         if let Some(ref pending) = self.pending_state() {
             !pending.executed_txs.read().is_empty()
@@ -439,6 +682,58 @@ impl KatanaSequencer {
         }
     }
 
+    /// Returns the transactions that haven't been mined yet: the ones executed in the pending
+    /// block, followed by the ones queued for execution and the ones waiting in the pool.
+    pub fn unmined_transactions(&self) -> Vec<TxWithHash> {
+        let mut transactions = self.pending_transactions();
+        transactions.extend(self.block_producer.queued_transactions().iter().map(TxWithHash::from));
+        transactions.extend(self.pool.transactions().iter().map(TxWithHash::from));
+        transactions
+    }
+
+    /// Drops a transaction that hasn't been mined yet. Dropping a transaction executed in the
+    /// pending block re-executes the block without it. Returns `false` if the transaction isn't
+    /// found among the unmined ones, or is part of a block being mined.
+    pub fn drop_transaction(&self, hash: TxHash) -> bool {
+        self.pool.remove_transaction(hash) || self.block_producer.remove_transaction(hash)
+    }
+
+    /// Drops all the transactions that haven't been mined yet, and returns the number of dropped
+    /// transactions.
+    pub fn clear_pool(&self) -> usize {
+        self.pool.clear() + self.block_producer.clear_transactions()
+    }
+
+    pub async fn set_mining_mode(&self, mode: MiningMode) {
+        self.block_producer.set_mining_mode(mode).await
+    }
+
+    /// Converts a stored transaction back into an executable one, by fetching the classes of
+    /// declare transactions from the latest state.
+    fn executable_tx(&self, tx: TxWithHash) -> SequencerResult<ExecutableTxWithHash> {
+        let transaction = match tx.transaction {
+            Tx::Invoke(tx) => ExecutableTx::Invoke(tx),
+            Tx::L1Handler(tx) => ExecutableTx::L1Handler(tx),
+            Tx::DeployAccount(tx) => ExecutableTx::DeployAccount(tx),
+            Tx::Declare(tx) => {
+                let state = StateFactoryProvider::latest(self.backend.blockchain.provider())?;
+                let class_hash = tx.class_hash();
+
+                let compiled_class = ContractClassProvider::class(&state, class_hash)?
+                    .ok_or(SequencerError::DataUnavailable)?;
+                let sierra_class = ContractClassProvider::sierra_class(&state, class_hash)?;
+
+                ExecutableTx::Declare(DeclareTxWithClass {
+                    sierra_class,
+                    compiled_class,
+                    transaction: tx,
+                })
+            }
+        };
+
+        Ok(ExecutableTxWithHash { hash: tx.hash, transaction })
+    }
+
     // pub async fn set_storage_at(
     //     &self,
     //     contract_address: ContractAddress,
@@ -449,52 +744,16 @@ impl KatanaSequencer {
     // }
 }
 
-fn filter_events_by_params(
-    events: Skip<Iter<'_, Event>>,
-    address: Option<ContractAddress>,
-    filter_keys: Option<Vec<Vec<FieldElement>>>,
-    max_results: Option<usize>,
-) -> (Vec<Event>, usize) {
-    let mut filtered_events = vec![];
-    let mut index = 0;
-
-    // Iterate on block events.
-    for event in events {
-        index += 1;
-        if !address.map_or(true, |addr| addr == event.from_address) {
-            continue;
-        }
-
-        let match_keys = match filter_keys {
-            // From starknet-api spec:
-            // Per key (by position), designate the possible values to be matched for events to be
-            // returned. Empty array designates 'any' value"
-            Some(ref filter_keys) => filter_keys.iter().enumerate().all(|(i, keys)| {
-                // Lets say we want to filter events which are either named `Event1` or `Event2` and
-                // custom key `0x1` or `0x2` Filter: [[sn_keccack("Event1"),
-                // sn_keccack("Event2")], ["0x1", "0x2"]]
-
-                // This checks: number of keys in event >= number of keys in filter (we check > i
-                // and not >= i because i is zero indexed) because otherwise this
-                // event doesn't contain all the keys we requested
-                event.keys.len() > i &&
-                    // This checks: Empty array desginates 'any' value
-                    (keys.is_empty()
-                    ||
-                    // This checks: If this events i'th value is one of the requested value in filter_keys[i]
-                    keys.contains(&event.keys[i]))
-            }),
-            None => true,
-        };
-
-        if match_keys {
-            filtered_events.push(event.clone());
-            if let Some(max_results) = max_results {
-                if filtered_events.len() >= max_results {
-                    break;
-                }
-            }
+fn replay_transaction(
+    tx: TxWithHash,
+    result: TxExecutionResult,
+    expected: Receipt,
+) -> TransactionReplay {
+    match result {
+        Ok(execution_info) => {
+            let actual = TxReceiptWithExecInfo::new(&tx, execution_info).receipt;
+            TransactionReplay::new(tx.hash, expected, actual)
         }
+        Err(err) => TransactionReplay::failed(tx.hash, err.to_string()),
     }
-    (filtered_events, index)
 }
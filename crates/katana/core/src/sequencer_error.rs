@@ -1,6 +1,4 @@
-use blockifier::execution::errors::EntryPointExecutionError;
-use blockifier::state::errors::StateError;
-use blockifier::transaction::errors::TransactionExecutionError;
+use katana_executor::executor::ExecutionError;
 use katana_primitives::block::BlockIdOrTag;
 use katana_primitives::contract::ContractAddress;
 use katana_primitives::event::ContinuationTokenError;
@@ -17,18 +15,18 @@ pub enum SequencerError {
     StateUpdateNotFound(BlockIdOrTag),
     #[error("State for block {0:?} not found.")]
     StateNotFound(BlockIdOrTag),
+    #[error("Receipts of block {0:?} are unavailable.")]
+    ReceiptsUnavailable(BlockIdOrTag),
     #[error("Transaction with {0} hash not found.")]
     TxnNotFound(TxHash),
     #[error(transparent)]
-    State(#[from] StateError),
-    #[error(transparent)]
-    TransactionExecution(#[from] TransactionExecutionError),
+    TransactionExecution(#[from] ExecutionError),
     #[error("Error converting {from} into {to}: {message}")]
     ConversionError { from: String, to: String, message: String },
     #[error(transparent)]
     StarknetApi(#[from] StarknetApiError),
     #[error(transparent)]
-    EntryPointExecution(#[from] EntryPointExecutionError),
+    EntryPointExecution(ExecutionError),
     #[error("Wait for pending transactions.")]
     PendingTransactions,
     #[error("Unsupported Transaction")]
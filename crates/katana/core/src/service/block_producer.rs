@@ -5,17 +5,18 @@ use std::sync::Arc;
 use std::task::{Context, Poll};
 use std::time::Duration;
 
+use futures::channel::oneshot;
 use futures::stream::{Stream, StreamExt};
+use futures::task::AtomicWaker;
 use futures::FutureExt;
-use katana_executor::blockifier::outcome::TxReceiptWithExecInfo;
-use katana_executor::blockifier::state::{CachedStateWrapper, StateRefDb};
-use katana_executor::blockifier::utils::get_state_update_from_cached_state;
-use katana_executor::blockifier::{PendingState, TransactionExecutor};
+use katana_executor::executor::{BlockEnv, BlockExecutor, PendingState};
+use katana_executor::outcome::TxReceiptWithExecInfo;
 use katana_primitives::receipt::Receipt;
 use katana_primitives::state::StateUpdatesWithDeclaredClasses;
-use katana_primitives::transaction::{ExecutableTxWithHash, TxWithHash};
-use katana_provider::traits::state::StateFactoryProvider;
-use parking_lot::RwLock;
+use katana_primitives::transaction::{ExecutableTxWithHash, TxHash, TxWithHash};
+use katana_provider::traits::state::{StateFactoryProvider, StateProvider};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
 use tokio::time::{interval_at, Instant, Interval};
 use tracing::trace;
 
@@ -29,43 +30,65 @@ type ServiceFuture<T> = Pin<Box<dyn Future<Output = T> + Send + Sync>>;
 type InstantBlockMiningFuture = ServiceFuture<MinedBlockOutcome>;
 type IntervalBlockMiningFuture = ServiceFuture<MinedBlockOutcome>;
 
+/// The mode in which blocks are mined.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MiningMode {
+    /// A block is mined as soon as there are transactions ready to be executed.
+    Instant,
+    /// A block is mined every given number of milliseconds.
+    Interval(u64),
+    /// A block is only mined when requested through the `katana_generateBlock` RPC method.
+    Manual,
+}
+
 /// The type which responsible for block production.
 #[must_use = "BlockProducer does nothing unless polled"]
 #[derive(Clone)]
 pub struct BlockProducer {
     /// The inner mode of mining.
     pub inner: Arc<RwLock<BlockProducerMode>>,
+    /// The requested mode of mining, and the channel notified once the producer has switched to
+    /// it.
+    mode_switch: Arc<Mutex<Option<(MiningMode, oneshot::Sender<()>)>>>,
+    /// Wakes the task polling the producer, eg. when the mode of mining is changed.
+    waker: Arc<AtomicWaker>,
 }
 
 impl BlockProducer {
     /// Creates a block producer that mines a new block every `interval` milliseconds.
-    pub fn interval(backend: Arc<Backend>, initial_state: StateRefDb, interval: u64) -> Self {
-        Self {
-            inner: Arc::new(RwLock::new(BlockProducerMode::Interval(IntervalBlockProducer::new(
-                backend,
-                initial_state,
-                interval,
-            )))),
-        }
+    pub fn interval(
+        backend: Arc<Backend>,
+        initial_state: Box<dyn StateProvider>,
+        interval: u64,
+    ) -> Self {
+        Self::new(BlockProducerMode::Interval(IntervalBlockProducer::new(
+            backend,
+            initial_state,
+            interval,
+        )))
     }
 
     /// Creates a new block producer that will only be possible to mine by calling the
     /// `katana_generateBlock` RPC method.
-    pub fn on_demand(backend: Arc<Backend>, initial_state: StateRefDb) -> Self {
-        Self {
-            inner: Arc::new(RwLock::new(BlockProducerMode::Interval(
-                IntervalBlockProducer::new_no_mining(backend, initial_state),
-            ))),
-        }
+    pub fn on_demand(backend: Arc<Backend>, initial_state: Box<dyn StateProvider>) -> Self {
+        Self::new(BlockProducerMode::Interval(IntervalBlockProducer::new_no_mining(
+            backend,
+            initial_state,
+        )))
     }
 
     /// Creates a block producer that mines a new block as soon as there are ready transactions in
     /// the transactions pool.
     pub fn instant(backend: Arc<Backend>) -> Self {
+        Self::new(BlockProducerMode::Instant(InstantBlockProducer::new(backend)))
+    }
+
+    fn new(mode: BlockProducerMode) -> Self {
         Self {
-            inner: Arc::new(RwLock::new(BlockProducerMode::Instant(InstantBlockProducer::new(
-                backend,
-            )))),
+            inner: Arc::new(RwLock::new(mode)),
+            mode_switch: Arc::new(Mutex::new(None)),
+            waker: Arc::new(AtomicWaker::new()),
         }
     }
 
@@ -87,6 +110,128 @@ impl BlockProducer {
         matches!(*self.inner.read(), BlockProducerMode::Instant(_))
     }
 
+    /// Returns the current mode of mining.
+    pub fn mining_mode(&self) -> MiningMode {
+        match &*self.inner.read() {
+            BlockProducerMode::Instant(_) => MiningMode::Instant,
+            BlockProducerMode::Interval(producer) => match &producer.interval {
+                Some(interval) => MiningMode::Interval(interval.period().as_millis() as u64),
+                None => MiningMode::Manual,
+            },
+        }
+    }
+
+    /// Switches to another mode of mining.
+    ///
+    /// The switch is made by the task polling the producer. The ongoing mining process, if any, is
+    /// completed first. When leaving the _interval_ mode, the pending block is mined so that its
+    /// transactions aren't lost. The blocks mined in the process are yielded by the producer like
+    /// any other block. The transactions still queued for execution are carried over to the new
+    /// mode.
+    ///
+    /// Resolves once the switch is made, or superseded by another one.
+    pub async fn set_mining_mode(&self, mode: MiningMode) {
+        trace!(target: "miner", ?mode, "requesting mining mode switch");
+        let (tx, rx) = oneshot::channel();
+        *self.mode_switch.lock() = Some((mode, tx));
+        self.waker.wake();
+        // the sender is dropped if the switch is superseded
+        let _ = rx.await;
+    }
+
+    /// Replaces the inner producer with one mining in `mode`, carrying over the queued
+    /// transactions.
+    fn switch_mode(inner: &mut BlockProducerMode, mode: MiningMode) {
+        trace!(target: "miner", ?mode, "switching mining mode");
+
+        let (backend, queued) = match inner {
+            BlockProducerMode::Instant(producer) => {
+                (producer.backend.clone(), std::mem::take(&mut producer.queued))
+            }
+            BlockProducerMode::Interval(producer) => {
+                (producer.backend.clone(), std::mem::take(&mut producer.queued))
+            }
+        };
+
+        let latest_state = || {
+            StateFactoryProvider::latest(backend.blockchain.provider())
+                .expect("able to get latest state")
+        };
+
+        *inner = match mode {
+            MiningMode::Instant => {
+                let mut producer = InstantBlockProducer::new(backend);
+                producer.queued = queued;
+                BlockProducerMode::Instant(producer)
+            }
+            MiningMode::Interval(interval) => {
+                let mut producer =
+                    IntervalBlockProducer::new(backend.clone(), latest_state(), interval);
+                producer.queued = queued;
+                BlockProducerMode::Interval(producer)
+            }
+            MiningMode::Manual => {
+                let mut producer =
+                    IntervalBlockProducer::new_no_mining(backend.clone(), latest_state());
+                producer.queued = queued;
+                BlockProducerMode::Interval(producer)
+            }
+        };
+    }
+
+    /// Returns the transactions queued for execution, in the order they will be executed.
+    pub fn queued_transactions(&self) -> Vec<ExecutableTxWithHash> {
+        match &*self.inner.read() {
+            BlockProducerMode::Instant(producer) => {
+                producer.queued.iter().flatten().cloned().collect()
+            }
+            BlockProducerMode::Interval(producer) => {
+                producer.queued.iter().flatten().cloned().collect()
+            }
+        }
+    }
+
+    /// Removes a transaction that hasn't been mined yet, either queued for execution or executed
+    /// in the pending block. Returns `true` if the transaction was removed.
+    pub fn remove_transaction(&self, hash: TxHash) -> bool {
+        let mut inner = self.inner.write();
+        let queued = match &mut *inner {
+            BlockProducerMode::Instant(producer) => &mut producer.queued,
+            BlockProducerMode::Interval(producer) => &mut producer.queued,
+        };
+
+        let mut removed = false;
+        for txs in queued.iter_mut() {
+            let len = txs.len();
+            txs.retain(|tx| tx.hash != hash);
+            removed |= txs.len() != len;
+        }
+        queued.retain(|txs| !txs.is_empty());
+
+        if let BlockProducerMode::Interval(producer) = &mut *inner {
+            removed |= producer.remove_executed_transactions(|tx| tx.hash == hash) > 0;
+        }
+
+        removed
+    }
+
+    /// Removes all the transactions that haven't been mined yet, both the queued ones and the ones
+    /// executed in the pending block, and returns the number of removed transactions.
+    pub fn clear_transactions(&self) -> usize {
+        let mut inner = self.inner.write();
+        let queued = match &mut *inner {
+            BlockProducerMode::Instant(producer) => &mut producer.queued,
+            BlockProducerMode::Interval(producer) => &mut producer.queued,
+        };
+        let mut removed = std::mem::take(queued).into_iter().map(|txs| txs.len()).sum();
+
+        if let BlockProducerMode::Interval(producer) = &mut *inner {
+            removed += producer.remove_executed_transactions(|_| true);
+        }
+
+        removed
+    }
+
     // Handler for the `katana_generateBlock` RPC method.
     pub fn force_mine(&self) {
         trace!(target: "miner", "force mining");
@@ -101,10 +246,44 @@ impl BlockProducer {
 impl Stream for BlockProducer {
     type Item = MinedBlockOutcome;
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        let mut mode = self.inner.write();
-        match &mut *mode {
-            BlockProducerMode::Instant(producer) => producer.poll_next_unpin(cx),
-            BlockProducerMode::Interval(producer) => producer.poll_next_unpin(cx),
+        self.waker.register(cx.waker());
+        let mut inner = self.inner.write();
+
+        loop {
+            let outcome = match &mut *inner {
+                BlockProducerMode::Instant(producer) => producer.poll_next_unpin(cx),
+                BlockProducerMode::Interval(producer) => producer.poll_next_unpin(cx),
+            };
+
+            if outcome.is_ready() {
+                return outcome;
+            }
+
+            let mut mode_switch = self.mode_switch.lock();
+            let Some((mode, _)) = mode_switch.as_ref() else { return Poll::Pending };
+            let mode = *mode;
+
+            match &mut *inner {
+                // the ongoing mining process has to be completed before switching
+                BlockProducerMode::Instant(producer) if producer.block_mining.is_some() => {
+                    return Poll::Pending;
+                }
+                BlockProducerMode::Interval(producer) if producer.block_mining.is_some() => {
+                    return Poll::Pending;
+                }
+                // mine the pending block before leaving the interval mode
+                BlockProducerMode::Interval(producer)
+                    if !producer.state.executed_txs.read().is_empty() =>
+                {
+                    producer.start_mining();
+                    continue;
+                }
+                _ => {}
+            }
+
+            let (_, done) = mode_switch.take().expect("checked above; qed");
+            Self::switch_mode(&mut inner, mode);
+            let _ = done.send(());
         }
     }
 }
@@ -137,13 +316,15 @@ pub struct IntervalBlockProducer {
     queued: VecDeque<Vec<ExecutableTxWithHash>>,
     /// The state of the pending block after executing all the transactions within the interval.
     state: Arc<PendingState>,
+    /// The transactions executed in the pending block, to re-execute them when some are removed.
+    executed: Vec<ExecutableTxWithHash>,
     /// This is to make sure that the block context is updated
     /// before the first block is opened.
     is_initialized: bool,
 }
 
 impl IntervalBlockProducer {
-    pub fn new(backend: Arc<Backend>, db: StateRefDb, interval: u64) -> Self {
+    pub fn new(backend: Arc<Backend>, db: Box<dyn StateProvider>, interval: u64) -> Self {
         let interval = {
             let duration = Duration::from_millis(interval);
             let mut interval = interval_at(Instant::now() + duration, duration);
@@ -151,7 +332,7 @@ impl IntervalBlockProducer {
             interval
         };
 
-        let state = Arc::new(PendingState::new(db));
+        let state = Arc::new(PendingState::new(backend.executor_factory.with_state(db)));
 
         Self {
             state,
@@ -160,14 +341,15 @@ impl IntervalBlockProducer {
             is_initialized: false,
             interval: Some(interval),
             queued: VecDeque::default(),
+            executed: Vec::new(),
         }
     }
 
     /// Creates a new [IntervalBlockProducer] with no `interval`. This mode will not produce blocks
     /// for every fixed interval, although it will still execute all queued transactions and
     /// keep hold of the pending state.
-    pub fn new_no_mining(backend: Arc<Backend>, db: StateRefDb) -> Self {
-        let state = Arc::new(PendingState::new(db));
+    pub fn new_no_mining(backend: Arc<Backend>, db: Box<dyn StateProvider>) -> Self {
+        let state = Arc::new(PendingState::new(backend.executor_factory.with_state(db)));
 
         Self {
             state,
@@ -176,6 +358,7 @@ impl IntervalBlockProducer {
             block_mining: None,
             is_initialized: false,
             queued: VecDeque::default(),
+            executed: Vec::new(),
         }
     }
 
@@ -184,8 +367,9 @@ impl IntervalBlockProducer {
     }
 
     /// Force mine a new block. It will only able to mine if there is no ongoing mining process.
-    pub fn force_mine(&self) {
+    pub fn force_mine(&mut self) {
         if self.block_mining.is_none() {
+            self.executed.clear();
             let outcome = self.outcome();
             let _ = Self::do_mine(outcome, self.backend.clone(), self.state.clone());
         } else {
@@ -193,6 +377,18 @@ impl IntervalBlockProducer {
         }
     }
 
+    /// Starts mining the pending block in the background. The outcome is yielded once mined.
+    fn start_mining(&mut self) {
+        self.executed.clear();
+        let backend = self.backend.clone();
+        let outcome = self.outcome();
+        let state = self.state.clone();
+
+        self.block_mining = Some(Box::pin(async move {
+            tokio::task::spawn_blocking(|| Self::do_mine(outcome, backend, state)).await.unwrap()
+        }));
+    }
+
     fn do_mine(
         state_updates: StateUpdatesWithDeclaredClasses,
         backend: Arc<Backend>,
@@ -208,38 +404,63 @@ impl IntervalBlockProducer {
         trace!(target: "miner", "created new block: {}", outcome.block_number);
 
         backend.update_block_context();
-        pending_state.reset_state_with(new_state.into());
+        pending_state.reset_state_with(new_state);
 
         outcome
     }
 
-    fn execute_transactions(&self, transactions: Vec<ExecutableTxWithHash>) {
-        let txs = transactions.iter().map(TxWithHash::from);
-        let results = {
-            TransactionExecutor::new(
-                &self.state.state,
-                &self.backend.env.read().block,
-                !self.backend.config.read().disable_fee,
-                !self.backend.config.read().disable_validate,
-                transactions.clone().into_iter(),
-            )
-            .with_error_log()
-            .with_events_log()
-            .with_resources_log()
-            .zip(txs)
-            .filter_map(|(res, tx)| {
-                let Ok(info) = res else { return None };
-                let receipt = TxReceiptWithExecInfo::new(&tx, info);
-                Some((tx, receipt))
-            })
-            .collect::<Vec<_>>()
-        };
+    fn execute_transactions(&mut self, transactions: Vec<ExecutableTxWithHash>) {
+        let results = execute_transactions(
+            &self.backend,
+            self.state.state.as_ref(),
+            &self.backend.env.read().block,
+            transactions.clone(),
+        );
 
+        // only the successfully executed transactions are part of the pending block
+        self.executed.extend(
+            transactions
+                .into_iter()
+                .filter(|tx| results.iter().any(|(res, _)| res.hash == tx.hash)),
+        );
         self.state.executed_txs.write().extend(results);
     }
 
+    /// Removes the transactions matching `predicate` from the pending block, by re-executing the
+    /// remaining ones on top of the latest state. The transactions that depended on the removed
+    /// ones, and no longer execute successfully, are dropped as well. Returns the number of
+    /// transactions removed by `predicate`.
+    ///
+    /// The transactions of a block that is being mined can't be removed.
+    fn remove_executed_transactions(
+        &mut self,
+        predicate: impl Fn(&ExecutableTxWithHash) -> bool,
+    ) -> usize {
+        if self.block_mining.is_some() {
+            return 0;
+        }
+
+        let (removed, remaining): (Vec<_>, Vec<_>) =
+            std::mem::take(&mut self.executed).into_iter().partition(|tx| predicate(tx));
+
+        if removed.is_empty() {
+            self.executed = remaining;
+            return 0;
+        }
+
+        trace!(target: "miner", removed = removed.len(), "re-executing the pending block");
+
+        let latest_state = StateFactoryProvider::latest(self.backend.blockchain.provider())
+            .expect("able to get latest state");
+        self.state.reset_state_with(latest_state);
+        self.state.executed_txs.write().clear();
+        self.execute_transactions(remaining);
+
+        removed.len()
+    }
+
     fn outcome(&self) -> StateUpdatesWithDeclaredClasses {
-        get_state_update_from_cached_state(&self.state.state)
+        self.state.state.state_updates()
     }
 }
 
@@ -255,18 +476,10 @@ impl Stream for IntervalBlockProducer {
             pin.is_initialized = true;
         }
 
-        if let Some(interval) = &mut pin.interval {
-            if interval.poll_tick(cx).is_ready() && pin.block_mining.is_none() {
-                let backend = pin.backend.clone();
-                let outcome = pin.outcome();
-                let state = pin.state.clone();
-
-                pin.block_mining = Some(Box::pin(async move {
-                    tokio::task::spawn_blocking(|| Self::do_mine(outcome, backend, state))
-                        .await
-                        .unwrap()
-                }));
-            }
+        let ticked =
+            pin.interval.as_mut().is_some_and(|interval| interval.poll_tick(cx).is_ready());
+        if ticked && pin.block_mining.is_none() {
+            pin.start_mining();
         }
 
         // only execute transactions if there is no mining in progress
@@ -322,37 +535,16 @@ impl InstantBlockProducer {
 
         let latest_state = StateFactoryProvider::latest(backend.blockchain.provider())
             .expect("able to get latest state");
-        let state = CachedStateWrapper::new(latest_state.into());
+        let state = backend.executor_factory.with_state(latest_state);
         let block_context = backend.env.read().block.clone();
 
-        let txs = transactions.iter().map(TxWithHash::from);
-
-        let tx_receipt_pairs: Vec<(TxWithHash, Receipt)> = TransactionExecutor::new(
-            &state,
-            &block_context,
-            !backend.config.read().disable_fee,
-            !backend.config.read().disable_validate,
-            transactions.clone().into_iter(),
-        )
-        .with_error_log()
-        .with_events_log()
-        .with_resources_log()
-        .zip(txs)
-        .filter_map(|(res, tx)| {
-            if let Ok(info) = res {
-                let receipt = TxReceiptWithExecInfo::new(&tx, info);
-                Some((tx, receipt.receipt))
-            } else {
-                None
-            }
-        })
-        .collect();
+        let tx_receipt_pairs: Vec<(TxWithHash, Receipt)> =
+            execute_transactions(&backend, state.as_ref(), &block_context, transactions)
+                .into_iter()
+                .map(|(tx, receipt)| (tx, receipt.receipt))
+                .collect();
 
-        let outcome = backend.do_mine_block(
-            block_context,
-            tx_receipt_pairs,
-            get_state_update_from_cached_state(&state),
-        );
+        let outcome = backend.do_mine_block(block_context, tx_receipt_pairs, state.state_updates());
 
         trace!(target: "miner", "created new block: {}", outcome.block_number);
 
@@ -388,3 +580,25 @@ impl Stream for InstantBlockProducer {
         Poll::Pending
     }
 }
+
+/// Executes the transactions on top of `state` and returns the receipts of the transactions that
+/// were executed successfully.
+fn execute_transactions(
+    backend: &Backend,
+    state: &dyn BlockExecutor,
+    block_context: &BlockEnv,
+    transactions: Vec<ExecutableTxWithHash>,
+) -> Vec<(TxWithHash, TxReceiptWithExecInfo)> {
+    let txs = transactions.iter().map(TxWithHash::from).collect::<Vec<_>>();
+
+    state
+        .execute_transactions(block_context, backend.execution_flags(), transactions)
+        .into_iter()
+        .zip(txs)
+        .filter_map(|(res, tx)| {
+            let Ok(info) = res else { return None };
+            let receipt = TxReceiptWithExecInfo::new(&tx, info);
+            Some((tx, receipt))
+        })
+        .collect()
+}
@@ -7,7 +7,7 @@ use std::task::{Context, Poll};
 
 use futures::channel::mpsc::Receiver;
 use futures::stream::{Fuse, Stream, StreamExt};
-use katana_primitives::transaction::ExecutableTxWithHash;
+use katana_primitives::transaction::{DeclareTx, ExecutableTx, ExecutableTxWithHash};
 use starknet::core::types::FieldElement;
 use tracing::trace;
 
@@ -67,7 +67,15 @@ impl Future for NodeService {
 
             if let Poll::Ready(transactions) = pin.miner.poll(&pin.pool, cx) {
                 // miner returned a set of transaction that we feed to the producer
-                pin.block_producer.queue(transactions);
+                if pin.miner.canonical_ordering && pin.block_producer.is_instant_mining() {
+                    // how the transactions are batched depends on when they arrived, so in
+                    // deterministic mode every transaction gets its own block instead
+                    for tx in transactions {
+                        pin.block_producer.queue(vec![tx]);
+                    }
+                } else {
+                    pin.block_producer.queue(transactions);
+                }
             } else {
                 // no progress made
                 break;
@@ -84,11 +92,24 @@ pub struct TransactionMiner {
     has_pending_txs: Option<bool>,
     /// Receives hashes of transactions that are ready from the pool
     rx: Fuse<Receiver<FieldElement>>,
+    /// whether the transactions are sorted in a canonical order instead of their arrival order
+    canonical_ordering: bool,
 }
 
 impl TransactionMiner {
     pub fn new(rx: Receiver<FieldElement>) -> Self {
-        Self { rx: rx.fuse(), has_pending_txs: None }
+        Self { rx: rx.fuse(), has_pending_txs: None, canonical_ordering: false }
+    }
+
+    /// Sorts the transactions taken from the pool at once by sender, nonce and hash, so that the
+    /// order in which they are mined doesn't depend on the order they were received in.
+    ///
+    /// The sets of transactions are still mined in the order they are taken from the pool, which
+    /// depends on when the transactions were received. Transactions only get the same order on
+    /// every run if they are received before the pool is drained, eg. when submitted together.
+    pub fn with_canonical_ordering(mut self, canonical_ordering: bool) -> Self {
+        self.canonical_ordering = canonical_ordering;
+        self
     }
 
     fn poll(
@@ -106,12 +127,30 @@ impl TransactionMiner {
         }
 
         // take all the transactions from the pool
-        let transactions = pool.get_transactions();
+        let mut transactions = pool.get_transactions();
 
         if transactions.is_empty() {
             return Poll::Pending;
         }
 
+        if self.canonical_ordering {
+            transactions.sort_by_key(canonical_order);
+        }
+
         Poll::Ready(transactions)
     }
 }
+
+/// The key by which transactions are sorted when mined in canonical order.
+fn canonical_order(tx: &ExecutableTxWithHash) -> (FieldElement, FieldElement, FieldElement) {
+    let (sender, nonce) = match &tx.transaction {
+        ExecutableTx::Invoke(tx) => (tx.sender_address, tx.nonce),
+        ExecutableTx::L1Handler(tx) => (tx.contract_address, tx.nonce),
+        ExecutableTx::DeployAccount(tx) => (tx.contract_address, tx.nonce),
+        ExecutableTx::Declare(tx) => match &tx.transaction {
+            DeclareTx::V1(tx) => (tx.sender_address, tx.nonce),
+            DeclareTx::V2(tx) => (tx.sender_address, tx.nonce),
+        },
+    };
+    (sender.into(), nonce, tx.hash)
+}
@@ -1,6 +1,16 @@
+use katana_core::accounts::Account;
 use katana_core::backend::config::{Environment, StarknetConfig};
 use katana_core::backend::Backend;
-use katana_provider::traits::block::{BlockNumberProvider, BlockProvider};
+use katana_core::constants::{ERC20_CONTRACT_CLASS_HASH, FEE_TOKEN_ADDRESS};
+use katana_executor::executor::ExecutionFlags;
+use katana_executor::outcome::TxReceiptWithExecInfo;
+use katana_primitives::contract::ContractAddress;
+use katana_primitives::transaction::{ExecutableTx, ExecutableTxWithHash, InvokeTx, TxWithHash};
+use katana_primitives::FieldElement;
+use katana_provider::traits::block::{BlockHashProvider, BlockNumberProvider, BlockProvider};
+use katana_provider::traits::contract::ContractClassProvider;
+use katana_provider::traits::state::{StateFactoryProvider, StateProvider};
+use starknet::macros::selector;
 use starknet_api::block::BlockNumber;
 
 fn create_test_starknet_config() -> StarknetConfig {
@@ -17,6 +27,60 @@ async fn create_test_backend() -> Backend {
     Backend::new(create_test_starknet_config()).await
 }
 
+fn transfer(from: &Account, to: &Account, nonce: u64, amount: u64) -> ExecutableTxWithHash {
+    let calldata = vec![
+        FieldElement::ONE,
+        FEE_TOKEN_ADDRESS.0,
+        selector!("transfer"),
+        FieldElement::THREE,
+        to.address,
+        amount.into(),
+        FieldElement::ZERO,
+    ];
+    ExecutableTxWithHash::new(ExecutableTx::Invoke(InvokeTx {
+        calldata,
+        nonce: nonce.into(),
+        version: FieldElement::ONE,
+        sender_address: from.address.into(),
+        ..Default::default()
+    }))
+}
+
+/// Executes the transactions and mines them in a new block, as the block producer would.
+fn mine_transactions(backend: &Backend, transactions: Vec<ExecutableTxWithHash>) {
+    backend.update_block_context();
+    let state = StateFactoryProvider::latest(backend.blockchain.provider()).unwrap();
+    let executor = backend.executor_factory.with_state(state);
+    let block_context = backend.env.read().block.clone();
+
+    let results = executor.execute_transactions(
+        &block_context,
+        backend.execution_flags(),
+        transactions.clone(),
+    );
+    let tx_receipt_pairs = transactions
+        .iter()
+        .zip(results)
+        .map(|(tx, res)| {
+            let tx = TxWithHash::from(tx);
+            let info = res.expect("transaction should succeed");
+            let receipt = TxReceiptWithExecInfo::new(&tx, info).receipt;
+            (tx, receipt)
+        })
+        .collect();
+
+    backend.mine_pending_block(tx_receipt_pairs, executor.state_updates());
+}
+
+/// Returns the nonce and the balance of a dev account in the latest state.
+fn nonce_and_balance(backend: &Backend, account: &Account) -> (FieldElement, FieldElement) {
+    let state = StateFactoryProvider::latest(backend.blockchain.provider()).unwrap();
+    let nonce = StateProvider::nonce(&state, account.address.into()).unwrap().unwrap();
+    let [_, (token, key, _)] = account.genesis_storage();
+    let balance = StateProvider::storage(&state, token, key).unwrap().unwrap();
+    (nonce, balance)
+}
+
 #[tokio::test]
 async fn test_creating_blocks() {
     let backend = create_test_backend().await;
@@ -39,3 +103,117 @@ async fn test_creating_blocks() {
     assert_eq!(block1.header.number, 1);
     assert_eq!(block2.header.number, 2);
 }
+
+#[tokio::test]
+async fn test_fork_from_state_dump() {
+    let config = StarknetConfig { disable_validate: true, ..create_test_starknet_config() };
+    let backend = Backend::new(config).await;
+    let (sender, recipient) = (&backend.accounts[0], &backend.accounts[1]);
+    mine_transactions(&backend, vec![transfer(sender, recipient, 1, 100)]);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("state.bin");
+    backend.dump_state().unwrap().save(&path).unwrap();
+
+    // the same state is always dumped the same way
+    let dump1 = serde_json::to_string(&backend.dump_state().unwrap()).unwrap();
+    let dump2 = serde_json::to_string(&backend.dump_state().unwrap()).unwrap();
+    assert_eq!(dump1, dump2);
+
+    let config = StarknetConfig { fork_state: Some(path), ..create_test_starknet_config() };
+    let forked = Backend::new(config).await;
+
+    let provider = backend.blockchain.provider();
+    let forked_provider = forked.blockchain.provider();
+
+    assert_eq!(BlockNumberProvider::latest_number(forked_provider).unwrap(), 1);
+    assert_eq!(
+        BlockHashProvider::latest_hash(forked_provider).unwrap(),
+        BlockHashProvider::latest_hash(provider).unwrap()
+    );
+
+    // the predeployed contracts are only part of the dumped state
+    let state = StateFactoryProvider::latest(forked_provider).unwrap();
+    let class_hash = StateProvider::class_hash_of_contract(&state, *FEE_TOKEN_ADDRESS).unwrap();
+    assert_eq!(class_hash, Some(*ERC20_CONTRACT_CLASS_HASH));
+    assert!(ContractClassProvider::class(&state, *ERC20_CONTRACT_CLASS_HASH).unwrap().is_some());
+
+    // the nonces and balances of the dev accounts aren't reset by deploying them again
+    for account in &backend.accounts {
+        assert_eq!(nonce_and_balance(&forked, account), nonce_and_balance(&backend, account));
+    }
+    assert_eq!(nonce_and_balance(&forked, sender).0, FieldElement::TWO);
+}
+
+#[tokio::test]
+async fn test_resume_from_db_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = StarknetConfig {
+        db_dir: Some(dir.path().to_path_buf()),
+        disable_validate: true,
+        ..create_test_starknet_config()
+    };
+
+    let backend = Backend::new(config.clone()).await;
+    let (sender, recipient) = (&backend.accounts[0], &backend.accounts[1]);
+    let genesis = nonce_and_balance(&backend, sender);
+    mine_transactions(
+        &backend,
+        vec![transfer(sender, recipient, 1, 100), transfer(sender, recipient, 2, 100)],
+    );
+
+    let transacted = nonce_and_balance(&backend, sender);
+    assert_ne!(transacted.0, genesis.0);
+    assert_eq!(transacted.1, genesis.1 - FieldElement::from(200u32));
+    drop(backend);
+
+    // the dev accounts aren't deployed again onto the resumed chain
+    let resumed = Backend::new(config).await;
+    assert_eq!(BlockNumberProvider::latest_number(resumed.blockchain.provider()).unwrap(), 1);
+    assert_eq!(nonce_and_balance(&resumed, &resumed.accounts[0]), transacted);
+}
+
+#[tokio::test]
+async fn test_parallel_execution_matches_sequential() {
+    let config = StarknetConfig { disable_validate: true, ..create_test_starknet_config() };
+    let backend = Backend::new(config).await;
+
+    let (sender, recipient) = (&backend.accounts[0], &backend.accounts[1]);
+
+    // the transactions of the same sender conflict on its nonce, and all of them write the
+    // balances of both accounts
+    let transactions = vec![
+        transfer(sender, recipient, 1, 100),
+        transfer(sender, recipient, 2, 200),
+        transfer(recipient, sender, 1, 50),
+    ];
+
+    let execute = |parallel: bool| {
+        let state = StateFactoryProvider::latest(backend.blockchain.provider()).unwrap();
+        let executor = backend.executor_factory.with_state(state);
+        let block_context = backend.env.read().block.clone();
+        let flags = ExecutionFlags { parallel, ..backend.execution_flags() };
+
+        let results = executor.execute_transactions(&block_context, flags, transactions.clone());
+        let receipts = transactions
+            .iter()
+            .zip(results)
+            .map(|(tx, res)| {
+                let info = res.expect("transaction should succeed");
+                TxReceiptWithExecInfo::new(TxWithHash::from(tx), info).receipt
+            })
+            .collect::<Vec<_>>();
+
+        (receipts, executor.state_updates().state_updates)
+    };
+
+    let (sequential_receipts, sequential_state) = execute(false);
+    let (parallel_receipts, parallel_state) = execute(true);
+
+    assert_eq!(parallel_receipts, sequential_receipts);
+    assert_eq!(parallel_state, sequential_state);
+    assert_eq!(
+        parallel_state.nonce_updates.get(&ContractAddress::from(sender.address)),
+        Some(&FieldElement::THREE)
+    );
+}
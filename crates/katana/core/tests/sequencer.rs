@@ -1,6 +1,22 @@
+use std::time::Duration;
+
 use katana_core::backend::config::{Environment, StarknetConfig};
+use katana_core::constants::FEE_TOKEN_ADDRESS;
+use katana_core::env::TimeMode;
 use katana_core::sequencer::{KatanaSequencer, SequencerConfig};
-use katana_provider::traits::block::BlockProvider;
+use katana_core::service::block_producer::MiningMode;
+use katana_executor::outcome::TxReceiptWithExecInfo;
+use katana_primitives::block::BlockIdOrTag;
+use katana_primitives::receipt::Receipt;
+use katana_primitives::state::StateUpdatesWithDeclaredClasses;
+use katana_primitives::transaction::{ExecutableTx, ExecutableTxWithHash, InvokeTx, TxWithHash};
+use katana_primitives::FieldElement;
+use katana_provider::traits::block::{
+    BlockHashProvider, BlockNumberProvider, BlockProvider, HeaderProvider,
+};
+use katana_provider::traits::state::{StateFactoryProvider, StateProvider};
+use katana_provider::traits::transaction::ReceiptProvider;
+use starknet::macros::selector;
 
 fn create_test_sequencer_config() -> (SequencerConfig, StarknetConfig) {
     (
@@ -20,6 +36,56 @@ async fn create_test_sequencer() -> KatanaSequencer {
     KatanaSequencer::new(sequencer_config, starknet_config).await
 }
 
+fn transfer(sender: FieldElement, recipient: FieldElement, nonce: u64) -> ExecutableTxWithHash {
+    ExecutableTxWithHash::new(ExecutableTx::Invoke(InvokeTx {
+        calldata: vec![
+            FieldElement::ONE,
+            FEE_TOKEN_ADDRESS.0,
+            selector!("transfer"),
+            FieldElement::THREE,
+            recipient,
+            100u64.into(),
+            FieldElement::ZERO,
+        ],
+        nonce: nonce.into(),
+        version: FieldElement::ONE,
+        sender_address: sender.into(),
+        ..Default::default()
+    }))
+}
+
+/// Executes the transactions on top of the latest state in the context of a new block, as the
+/// block producer would, and returns them with their receipts and the state updates to mine them.
+fn execute_transactions(
+    sequencer: &KatanaSequencer,
+    transactions: Vec<ExecutableTxWithHash>,
+) -> (Vec<(TxWithHash, Receipt)>, StateUpdatesWithDeclaredClasses) {
+    let backend = &sequencer.backend;
+    backend.update_block_context();
+
+    let state = StateFactoryProvider::latest(backend.blockchain.provider()).unwrap();
+    let executor = backend.executor_factory.with_state(state);
+    let block_context = backend.env.read().block.clone();
+
+    let results = executor.execute_transactions(
+        &block_context,
+        backend.execution_flags(),
+        transactions.clone(),
+    );
+    let tx_receipt_pairs = transactions
+        .iter()
+        .zip(results)
+        .map(|(tx, res)| {
+            let tx = TxWithHash::from(tx);
+            let info = res.expect("transaction should succeed");
+            let receipt = TxReceiptWithExecInfo::new(&tx, info).receipt;
+            (tx, receipt)
+        })
+        .collect();
+
+    (tx_receipt_pairs, executor.state_updates())
+}
+
 #[tokio::test]
 async fn test_next_block_timestamp_in_past() {
     let sequencer = create_test_sequencer().await;
@@ -97,3 +163,363 @@ async fn test_increase_next_block_timestamp() {
 //         assert_eq!(val, read_val, "latest storage value incorrect after generate");
 //     }
 // }
+
+#[tokio::test]
+async fn test_replay_empty_block() {
+    let sequencer = create_test_sequencer().await;
+    let block = sequencer.backend.mine_empty_block().block_number;
+
+    let outcome = sequencer.replay_block(BlockIdOrTag::Number(block)).unwrap();
+
+    assert_eq!(outcome.block_number, block);
+    assert!(outcome.transactions.is_empty());
+    assert!(outcome.is_match(), "replayed block should match the stored one");
+}
+
+#[tokio::test]
+async fn test_replay_block_with_transfer() {
+    let (sequencer_config, mut starknet_config) = create_test_sequencer_config();
+    starknet_config.disable_validate = true;
+    let sequencer = KatanaSequencer::new(sequencer_config, starknet_config).await;
+    let (sender, recipient) = (&sequencer.backend.accounts[0], &sequencer.backend.accounts[1]);
+
+    // the dev accounts are deployed after the state of the genesis block is stored, so the
+    // transfer is replayed on top of the first block instead
+    sequencer.backend.mine_empty_block();
+    let tx = transfer(sender.address, recipient.address, 1);
+    let (tx_receipt_pairs, state_updates) = execute_transactions(&sequencer, vec![tx.clone()]);
+    let (mined, _) = sequencer.backend.mine_pending_block(tx_receipt_pairs, state_updates);
+
+    let outcome = sequencer.replay_block(BlockIdOrTag::Number(mined.block_number)).unwrap();
+    assert_eq!(outcome.block_number, mined.block_number);
+    assert_eq!(outcome.transactions.len(), 1);
+    assert!(outcome.is_match(), "replayed block should match the stored one: {outcome:?}");
+
+    let outcome = sequencer.replay_transaction(tx.hash).unwrap().expect("transaction is mined");
+    assert_eq!(outcome.block_number, mined.block_number);
+    assert!(outcome.is_match(), "replayed transaction should match the stored one: {outcome:?}");
+}
+
+#[tokio::test]
+async fn test_replay_block_detects_receipt_mismatch() {
+    let (sequencer_config, mut starknet_config) = create_test_sequencer_config();
+    starknet_config.disable_validate = true;
+    let sequencer = KatanaSequencer::new(sequencer_config, starknet_config).await;
+    let (sender, recipient) = (&sequencer.backend.accounts[0], &sequencer.backend.accounts[1]);
+
+    sequencer.backend.mine_empty_block();
+    let tx = transfer(sender.address, recipient.address, 1);
+    let (mut tx_receipt_pairs, state_updates) = execute_transactions(&sequencer, vec![tx]);
+
+    // store a receipt without the transfer event, which the replayed transaction does emit
+    let Receipt::Invoke(receipt) = &mut tx_receipt_pairs[0].1 else {
+        panic!("transfer should have an invoke receipt")
+    };
+    receipt.events.clear();
+    let stored = tx_receipt_pairs[0].1.clone();
+    let (mined, _) = sequencer.backend.mine_pending_block(tx_receipt_pairs, state_updates);
+
+    let outcome = sequencer.replay_block(BlockIdOrTag::Number(mined.block_number)).unwrap();
+    assert!(!outcome.is_match(), "replayed block shouldn't match the stored one");
+    assert!(outcome.state_mismatches.is_empty());
+
+    let mismatch = outcome.transactions[0].receipt_mismatch.as_ref().expect("receipts differ");
+    assert_eq!(mismatch.expected, stored);
+    assert!(matches!(&mismatch.actual, Receipt::Invoke(receipt) if !receipt.events.is_empty()));
+}
+
+#[tokio::test]
+async fn test_transaction_profile() {
+    let (sequencer_config, mut starknet_config) = create_test_sequencer_config();
+    starknet_config.disable_validate = true;
+    let sequencer = KatanaSequencer::new(sequencer_config, starknet_config).await;
+    let backend = &sequencer.backend;
+
+    let (sender, recipient) = (&backend.accounts[0], &backend.accounts[1]);
+    let tx = transfer(sender.address, recipient.address, 1);
+
+    // the transaction is re-executed on top of the state of its parent block, which must already
+    // include the dev accounts
+    backend.mine_empty_block();
+    let (tx_receipt_pairs, state_updates) = execute_transactions(&sequencer, vec![tx.clone()]);
+    backend.mine_pending_block(tx_receipt_pairs, state_updates);
+
+    let profile = sequencer.transaction_profile(tx.hash).unwrap().expect("transaction is mined");
+    let entry_point = |name: &str| {
+        profile
+            .entry_points
+            .iter()
+            .find(|f| f.name.ends_with(&format!("::{name}")))
+            .unwrap_or_else(|| panic!("{name} should be profiled"))
+    };
+
+    let execute = entry_point("__execute__");
+    let transfer = entry_point("transfer");
+    assert_eq!(execute.calls, 1);
+    assert_eq!(transfer.calls, 1);
+
+    // the account calls the token, which is the one that reads the balances and emits the event
+    assert_eq!(execute.self_resources.syscalls.get("call_contract"), Some(&1));
+    assert_eq!(execute.total_resources.syscalls.get("emit_event"), Some(&1));
+    assert_eq!(transfer.self_resources.syscalls.get("emit_event"), Some(&1));
+    assert!(transfer.self_resources.syscalls.contains_key("storage_read"));
+    assert!(transfer.self_resources.steps > 0);
+    assert!(execute.total_resources.steps > transfer.total_resources.steps);
+
+    let stack = vec!["execute".to_string(), execute.name.clone(), transfer.name.clone()];
+    assert!(profile.stacks.iter().any(|sample| sample.frames == stack));
+    assert!(profile.to_folded().contains(&stack.join(";")));
+}
+
+#[tokio::test]
+async fn test_pending_block_without_pending_state() {
+    let sequencer = create_test_sequencer().await;
+    let provider = sequencer.backend.blockchain.provider();
+    let block = sequencer.backend.mine_empty_block().block_number;
+
+    let latest_hash = BlockHashProvider::latest_hash(provider).unwrap();
+    let latest_root = HeaderProvider::header(provider, block.into()).unwrap().unwrap().state_root;
+
+    // in instant mining mode, the pending block is always empty and built on the latest block
+    let header = sequencer.pending_header().unwrap();
+    assert_eq!(header.parent_hash, latest_hash);
+    assert!(sequencer.pending_transactions().is_empty());
+
+    let (old_root, state_updates) = sequencer.pending_state_update().unwrap();
+    assert_eq!(old_root, latest_root);
+    assert!(state_updates.nonce_updates.is_empty());
+    assert!(state_updates.storage_updates.is_empty());
+
+    assert!(!sequencer.is_transaction_received(FieldElement::ONE));
+}
+
+#[tokio::test]
+async fn test_set_mining_mode() {
+    let sequencer = create_test_sequencer().await;
+    assert_eq!(sequencer.block_producer().mining_mode(), MiningMode::Instant);
+    assert!(sequencer.pending_state().is_none());
+
+    sequencer.set_mining_mode(MiningMode::Manual).await;
+    assert_eq!(sequencer.block_producer().mining_mode(), MiningMode::Manual);
+    assert!(sequencer.pending_state().is_some());
+
+    sequencer.set_mining_mode(MiningMode::Interval(1000)).await;
+    assert_eq!(sequencer.block_producer().mining_mode(), MiningMode::Interval(1000));
+
+    sequencer.set_mining_mode(MiningMode::Instant).await;
+    assert!(sequencer.pending_state().is_none());
+
+    assert!(sequencer.unmined_transactions().is_empty());
+    assert_eq!(sequencer.clear_pool(), 0);
+    assert!(!sequencer.drop_transaction(FieldElement::ONE));
+
+    let mode: MiningMode = serde_json::from_str(r#"{ "interval": 500 }"#).unwrap();
+    assert_eq!(mode, MiningMode::Interval(500));
+    let mode: MiningMode = serde_json::from_str(r#""manual""#).unwrap();
+    assert_eq!(mode, MiningMode::Manual);
+}
+
+#[tokio::test]
+async fn test_set_mining_mode_mines_pending_transactions() {
+    let (mut sequencer_config, mut starknet_config) = create_test_sequencer_config();
+    sequencer_config.no_mining = true;
+    starknet_config.disable_validate = true;
+    let sequencer = KatanaSequencer::new(sequencer_config, starknet_config).await;
+    let provider = sequencer.backend.blockchain.provider();
+    let latest_block = BlockNumberProvider::latest_number(provider).unwrap();
+
+    let (sender, recipient) = (&sequencer.backend.accounts[0], &sequencer.backend.accounts[1]);
+    let txs =
+        (1..3).map(|nonce| transfer(sender.address, recipient.address, nonce)).collect::<Vec<_>>();
+    let hashes = txs.iter().map(|tx| tx.hash).collect::<Vec<_>>();
+    txs.into_iter().for_each(|tx| sequencer.add_transaction_to_pool(tx));
+
+    // wait for the transactions to be executed in the pending block
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while sequencer.pending_transactions().len() < hashes.len() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("transactions should be executed");
+
+    sequencer.set_mining_mode(MiningMode::Instant).await;
+    assert_eq!(sequencer.block_producer().mining_mode(), MiningMode::Instant);
+
+    // the pending block is mined when leaving the manual mode, not dropped
+    assert_eq!(BlockNumberProvider::latest_number(provider).unwrap(), latest_block + 1);
+    let block = BlockProvider::block(provider, (latest_block + 1).into()).unwrap().unwrap();
+    assert_eq!(block.body.iter().map(|tx| tx.hash).collect::<Vec<_>>(), hashes);
+    assert!(sequencer.unmined_transactions().is_empty());
+}
+
+#[tokio::test]
+async fn test_drop_transaction_from_pending_block() {
+    let (mut sequencer_config, mut starknet_config) = create_test_sequencer_config();
+    sequencer_config.no_mining = true;
+    starknet_config.disable_validate = true;
+    let sequencer = KatanaSequencer::new(sequencer_config, starknet_config).await;
+    let provider = sequencer.backend.blockchain.provider();
+    let latest_block = BlockNumberProvider::latest_number(provider).unwrap();
+
+    let (account1, account2) = (&sequencer.backend.accounts[0], &sequencer.backend.accounts[1]);
+    let dropped = transfer(account1.address, account2.address, 1);
+    let kept = transfer(account2.address, account1.address, 1);
+    let (dropped_hash, kept_hash) = (dropped.hash, kept.hash);
+    sequencer.add_transaction_to_pool(dropped);
+    sequencer.add_transaction_to_pool(kept);
+
+    // wait for the transactions to be executed in the pending block
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while sequencer.pending_transactions().len() < 2 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("transactions should be executed");
+
+    // the pending block is re-executed without the dropped transaction
+    assert!(sequencer.drop_transaction(dropped_hash));
+    assert!(!sequencer.drop_transaction(dropped_hash));
+    let pending = sequencer.pending_transactions();
+    assert_eq!(pending.iter().map(|tx| tx.hash).collect::<Vec<_>>(), vec![kept_hash]);
+
+    sequencer.block_producer().force_mine();
+
+    assert_eq!(BlockNumberProvider::latest_number(provider).unwrap(), latest_block + 1);
+    let block = BlockProvider::block(provider, (latest_block + 1).into()).unwrap().unwrap();
+    assert_eq!(block.body.iter().map(|tx| tx.hash).collect::<Vec<_>>(), vec![kept_hash]);
+    assert!(sequencer.unmined_transactions().is_empty());
+    assert!(!sequencer.drop_transaction(kept_hash), "mined transactions can't be dropped");
+
+    // only the kept transaction has been applied to the state
+    let state = StateFactoryProvider::latest(provider).unwrap();
+    let nonce = |address: FieldElement| StateProvider::nonce(&state, address.into()).unwrap();
+    assert_eq!(nonce(account1.address), Some(FieldElement::ONE));
+    assert_eq!(nonce(account2.address), Some(FieldElement::TWO));
+}
+
+#[tokio::test]
+async fn test_frozen_time_mode() {
+    let sequencer = create_test_sequencer().await;
+    let provider = sequencer.backend.blockchain.provider();
+
+    sequencer.set_time_mode(TimeMode::Frozen);
+    assert_eq!(sequencer.time_mode(), TimeMode::Frozen);
+
+    let block1 = sequencer.backend.mine_empty_block().block_number;
+    let block2 = sequencer.backend.mine_empty_block().block_number;
+
+    let block1_timestamp =
+        BlockProvider::block(provider, block1.into()).unwrap().unwrap().header.timestamp;
+    let block2_timestamp =
+        BlockProvider::block(provider, block2.into()).unwrap().unwrap().header.timestamp;
+    assert_eq!(block1_timestamp, block2_timestamp, "timestamp should be frozen");
+
+    sequencer.increase_next_block_timestamp(100).unwrap();
+
+    let block3 = sequencer.backend.mine_empty_block().block_number;
+    let block3_timestamp =
+        BlockProvider::block(provider, block3.into()).unwrap().unwrap().header.timestamp;
+    assert_eq!(block3_timestamp, block2_timestamp + 100, "timestamp should be increased");
+}
+
+#[tokio::test]
+async fn test_fixed_increment_time_mode() {
+    let sequencer = create_test_sequencer().await;
+    let provider = sequencer.backend.blockchain.provider();
+
+    sequencer.set_time_mode(TimeMode::FixedIncrement(60));
+
+    let block1 = sequencer.backend.mine_empty_block().block_number;
+    let block2 = sequencer.backend.mine_empty_block().block_number;
+
+    let block1_timestamp =
+        BlockProvider::block(provider, block1.into()).unwrap().unwrap().header.timestamp;
+    let block2_timestamp =
+        BlockProvider::block(provider, block2.into()).unwrap().unwrap().header.timestamp;
+    assert_eq!(block2_timestamp, block1_timestamp + 60);
+}
+
+#[tokio::test]
+async fn test_deterministic_chain() {
+    let mut sequencers = Vec::new();
+    for _ in 0..2 {
+        let (sequencer_config, mut starknet_config) = create_test_sequencer_config();
+        starknet_config.deterministic = true;
+
+        let sequencer = KatanaSequencer::new(sequencer_config, starknet_config).await;
+        sequencer.backend.mine_empty_block();
+        sequencer.backend.mine_empty_block();
+        sequencers.push(sequencer);
+    }
+
+    let provider1 = sequencers[0].backend.blockchain.provider();
+    let provider2 = sequencers[1].backend.blockchain.provider();
+
+    for block in 0..=2u64 {
+        let header1 = HeaderProvider::header_by_number(provider1, block).unwrap().unwrap();
+        let header2 = HeaderProvider::header_by_number(provider2, block).unwrap().unwrap();
+        assert_eq!(header1.timestamp, block, "timestamp should be derived from the block number");
+        assert_eq!(header1, header2);
+
+        let hash1 = BlockHashProvider::block_hash_by_num(provider1, block).unwrap();
+        let hash2 = BlockHashProvider::block_hash_by_num(provider2, block).unwrap();
+        assert_eq!(hash1, hash2);
+    }
+}
+
+#[tokio::test]
+async fn test_deterministic_chain_with_transactions() {
+    let mut sequencers = Vec::new();
+    for reversed in [false, true] {
+        let (sequencer_config, mut starknet_config) = create_test_sequencer_config();
+        starknet_config.deterministic = true;
+        starknet_config.disable_validate = true;
+        let sequencer = KatanaSequencer::new(sequencer_config, starknet_config).await;
+
+        let (account1, account2) = (&sequencer.backend.accounts[0], &sequencer.backend.accounts[1]);
+        let mut txs = vec![
+            transfer(account1.address, account2.address, 1),
+            transfer(account1.address, account2.address, 2),
+            transfer(account2.address, account1.address, 1),
+        ];
+        if reversed {
+            txs.reverse();
+        }
+
+        // the transactions are submitted together, before the pool is drained
+        txs.into_iter().for_each(|tx| sequencer.add_transaction_to_pool(tx));
+        sequencers.push(sequencer);
+    }
+
+    for sequencer in &sequencers {
+        let provider = sequencer.backend.blockchain.provider();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while BlockNumberProvider::latest_number(provider).unwrap() < 3 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("transactions should be mined");
+    }
+
+    let provider1 = sequencers[0].backend.blockchain.provider();
+    let provider2 = sequencers[1].backend.blockchain.provider();
+
+    for block in 1..=3u64 {
+        let hash1 = BlockHashProvider::block_hash_by_num(provider1, block).unwrap();
+        let hash2 = BlockHashProvider::block_hash_by_num(provider2, block).unwrap();
+        assert_eq!(hash1, hash2);
+
+        let receipts1 = ReceiptProvider::receipts_by_block(provider1, block.into()).unwrap();
+        let receipts2 = ReceiptProvider::receipts_by_block(provider2, block.into()).unwrap();
+        assert_eq!(receipts1.as_ref().map(Vec::len), Some(1), "one transaction per block");
+        assert_eq!(receipts1, receipts2);
+    }
+
+    let dump1 = sequencers[0].backend.dump_state().unwrap();
+    let dump2 = sequencers[1].backend.dump_state().unwrap();
+    assert_eq!(dump1.block_hash, dump2.block_hash);
+    assert_eq!(dump1.state_updates, dump2.state_updates);
+}
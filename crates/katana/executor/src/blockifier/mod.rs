@@ -1,32 +1,37 @@
 pub mod outcome;
+pub mod parallel;
 pub mod state;
 pub mod transactions;
 pub mod utils;
 
-use std::sync::Arc;
-
 use blockifier::block_context::BlockContext;
 use blockifier::state::state_api::StateReader;
 use blockifier::transaction::errors::TransactionExecutionError;
 use blockifier::transaction::objects::TransactionExecutionInfo;
 use blockifier::transaction::transaction_execution::Transaction;
 use blockifier::transaction::transactions::ExecutableTransaction;
-use katana_primitives::transaction::{
-    DeclareTxWithClass, ExecutableTx, ExecutableTxWithHash, TxWithHash,
-};
-use parking_lot::RwLock;
+use katana_primitives::state::StateUpdatesWithDeclaredClasses;
+use katana_primitives::transaction::{DeclareTxWithClass, ExecutableTx, ExecutableTxWithHash};
+use katana_primitives::FieldElement;
+use katana_provider::traits::state::StateProvider;
+use starknet::core::types::FeeEstimate;
 use tracing::{trace, warn};
 
-use self::outcome::TxReceiptWithExecInfo;
+use self::outcome::{block_context_from_env, to_exec_info};
+use self::parallel::ParallelTransactionExecutor;
 use self::state::{CachedStateWrapper, StateRefDb};
 use self::transactions::BlockifierTx;
-use self::utils::events_from_exec_info;
+use self::utils::get_state_update_from_cached_state;
 use crate::blockifier::utils::{
     pretty_print_resources, trace_events, warn_message_transaction_error_exec_error,
 };
+use crate::executor::{
+    BlockEnv, BlockExecutor, EntryPointCall, ExecutionError, ExecutionFlags, ExecutorFactory,
+    TxExecutionResult,
+};
 
-/// The result of a transaction execution.
-type TxExecutionResult = Result<TransactionExecutionInfo, TransactionExecutionError>;
+/// The result of a transaction execution by blockifier.
+pub type BlockifierTxExecutionResult = Result<TransactionExecutionInfo, TransactionExecutionError>;
 
 /// A transaction executor.
 ///
@@ -88,7 +93,7 @@ where
     }
 
     /// A method to conveniently execute all the transactions and return their results.
-    pub fn execute(self) -> Vec<TxExecutionResult> {
+    pub fn execute(self) -> Vec<BlockifierTxExecutionResult> {
         self.collect()
     }
 }
@@ -98,43 +103,50 @@ where
     S: StateReader,
     T: Iterator<Item = ExecutableTxWithHash>,
 {
-    type Item = TxExecutionResult;
+    type Item = BlockifierTxExecutionResult;
 
     fn next(&mut self) -> Option<Self::Item> {
         let res = self.transactions.next().map(|tx| {
             execute_tx(tx, self.state, self.block_context, self.charge_fee, self.validate)
         })?;
 
-        match res {
-            Ok(ref info) => {
-                if self.error_log {
-                    if let Some(err) = &info.revert_error {
-                        let formatted_err = format!("{err:?}").replace("\\n", "\n");
-                        warn!(target: "executor", "Transaction execution error: {formatted_err}");
-                    }
-                }
+        log_execution_result(&res, self.error_log, self.events_log, self.resources_log);
 
-                if self.resources_log {
-                    trace!(
-                        target: "executor",
-                        "Transaction resource usage: {}",
-                        pretty_print_resources(&info.actual_resources)
-                    );
-                }
+        Some(res)
+    }
+}
 
-                if self.events_log {
-                    trace_events(&events_from_exec_info(info));
+fn log_execution_result(
+    res: &BlockifierTxExecutionResult,
+    error_log: bool,
+    events_log: bool,
+    resources_log: bool,
+) {
+    match res {
+        Ok(info) => {
+            if error_log {
+                if let Some(err) = &info.revert_error {
+                    let formatted_err = format!("{err:?}").replace("\\n", "\n");
+                    warn!(target: "executor", "Transaction execution error: {formatted_err}");
                 }
+            }
 
-                Some(res)
+            if resources_log {
+                trace!(
+                    target: "executor",
+                    "Transaction resource usage: {}",
+                    pretty_print_resources(&info.actual_resources)
+                );
             }
 
-            Err(ref err) => {
-                if self.error_log {
-                    warn_message_transaction_error_exec_error(err);
-                }
+            if events_log {
+                trace_events(info);
+            }
+        }
 
-                Some(res)
+        Err(err) => {
+            if error_log {
+                warn_message_transaction_error_exec_error(err);
             }
         }
     }
@@ -146,7 +158,7 @@ fn execute_tx<S: StateReader>(
     block_context: &BlockContext,
     charge_fee: bool,
     validate: bool,
-) -> TxExecutionResult {
+) -> BlockifierTxExecutionResult {
     let sierra = if let ExecutableTx::Declare(DeclareTxWithClass {
         transaction,
         sierra_class: Some(sierra_class),
@@ -176,50 +188,83 @@ fn execute_tx<S: StateReader>(
     res
 }
 
-pub type AcceptedTxPair = (TxWithHash, TxReceiptWithExecInfo);
-pub type RejectedTxPair = (TxWithHash, TransactionExecutionError);
+/// The [`ExecutorFactory`] of the blockifier execution backend.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BlockifierFactory;
 
-pub struct PendingState {
-    pub state: Arc<CachedStateWrapper<StateRefDb>>,
-    /// The transactions that have been executed.
-    pub executed_txs: RwLock<Vec<(TxWithHash, TxReceiptWithExecInfo)>>,
-    /// The transactions that have been rejected.
-    pub rejected_txs: RwLock<Vec<(TxWithHash, TransactionExecutionError)>>,
-}
+impl ExecutorFactory for BlockifierFactory {
+    fn with_state(&self, state: Box<dyn StateProvider>) -> Box<dyn BlockExecutor> {
+        Box::new(CachedStateWrapper::new(StateRefDb::new(state)))
+    }
 
-impl PendingState {
-    pub fn new(state: StateRefDb) -> Self {
-        Self {
-            state: Arc::new(CachedStateWrapper::new(state)),
-            executed_txs: RwLock::new(Vec::new()),
-            rejected_txs: RwLock::new(Vec::new()),
-        }
+    fn call(
+        &self,
+        request: EntryPointCall,
+        block_env: &BlockEnv,
+        state: Box<dyn StateProvider>,
+    ) -> Result<Vec<FieldElement>, ExecutionError> {
+        Ok(utils::call(request, block_context_from_env(block_env), state)?)
     }
 
-    pub fn reset_state_with(&self, state: StateRefDb) {
-        self.state.reset_with_new_state(state);
+    fn estimate_fee(
+        &self,
+        transactions: Vec<ExecutableTxWithHash>,
+        block_env: &BlockEnv,
+        state: Box<dyn StateProvider>,
+        validate: bool,
+    ) -> Result<Vec<FeeEstimate>, ExecutionError> {
+        let block_context = block_context_from_env(block_env);
+        Ok(utils::estimate_fee(transactions.into_iter(), block_context, state, validate)?)
     }
+}
+
+impl BlockExecutor for CachedStateWrapper<StateRefDb> {
+    fn execute_transactions(
+        &self,
+        block_env: &BlockEnv,
+        flags: ExecutionFlags,
+        transactions: Vec<ExecutableTxWithHash>,
+    ) -> Vec<TxExecutionResult> {
+        let ExecutionFlags { charge_fee, validate, parallel } = flags;
+        let block_context = &block_context_from_env(block_env);
+
+        let results = if parallel {
+            ParallelTransactionExecutor::new(
+                self,
+                block_context,
+                charge_fee,
+                validate,
+                transactions,
+            )
+            .with_error_log()
+            .with_events_log()
+            .with_resources_log()
+            .execute()
+        } else {
+            TransactionExecutor::new(
+                self,
+                block_context,
+                charge_fee,
+                validate,
+                transactions.into_iter(),
+            )
+            .with_error_log()
+            .with_events_log()
+            .with_resources_log()
+            .execute()
+        };
 
-    pub fn add_executed_txs(&self, transactions: Vec<(TxWithHash, TxExecutionResult)>) {
-        transactions.into_iter().for_each(|(tx, res)| self.add_executed_tx(tx, res));
+        results
+            .into_iter()
+            .map(|res| res.map(|info| to_exec_info(&info)).map_err(ExecutionError::from))
+            .collect()
     }
 
-    /// Drain the pending transactions, returning the executed and rejected transactions.
-    pub fn take_txs_all(&self) -> (Vec<AcceptedTxPair>, Vec<RejectedTxPair>) {
-        let executed_txs = std::mem::take(&mut *self.executed_txs.write());
-        let rejected_txs = std::mem::take(&mut *self.rejected_txs.write());
-        (executed_txs, rejected_txs)
+    fn state_updates(&self) -> StateUpdatesWithDeclaredClasses {
+        get_state_update_from_cached_state(self)
     }
 
-    fn add_executed_tx(&self, tx: TxWithHash, execution_result: TxExecutionResult) {
-        match execution_result {
-            Ok(execution_info) => {
-                let receipt = TxReceiptWithExecInfo::new(&tx, execution_info);
-                self.executed_txs.write().push((tx, receipt));
-            }
-            Err(err) => {
-                self.rejected_txs.write().push((tx, err));
-            }
-        }
+    fn reset(&self, state: Box<dyn StateProvider>) {
+        self.reset_with_new_state(StateRefDb::new(state));
     }
 }
@@ -1,65 +1,198 @@
-use std::collections::HashMap;
+//! Conversions between the types of blockifier and the backend-agnostic types of the executor.
 
+use std::collections::{BTreeMap, HashMap};
+
+use blockifier::block_context::{BlockContext, FeeTokenAddresses, GasPrices};
+use blockifier::execution::call_info::CallInfo;
+use blockifier::execution::entry_point::CallType;
+use blockifier::execution::errors::{EntryPointExecutionError, PreExecutionError};
+use blockifier::transaction::errors::TransactionExecutionError;
 use blockifier::transaction::objects::TransactionExecutionInfo;
-use katana_primitives::receipt::{
-    DeclareTxReceipt, DeployAccountTxReceipt, InvokeTxReceipt, L1HandlerTxReceipt, Receipt,
-    TxExecutionResources,
+use cairo_vm::vm::runners::builtin_runner::{
+    BITWISE_BUILTIN_NAME, EC_OP_BUILTIN_NAME, HASH_BUILTIN_NAME, KECCAK_BUILTIN_NAME,
+    OUTPUT_BUILTIN_NAME, POSEIDON_BUILTIN_NAME, RANGE_CHECK_BUILTIN_NAME,
+    SEGMENT_ARENA_BUILTIN_NAME, SIGNATURE_BUILTIN_NAME,
 };
-use katana_primitives::transaction::Tx;
+use katana_primitives::contract::ContractAddress;
+use katana_primitives::receipt::{Event, MessageToL1, TxExecutionResources};
+use katana_primitives::FieldElement;
+use starknet_api::deprecated_contract_class::EntryPointType;
+
+use crate::executor::{BlockEnv, ExecutionError};
+use crate::outcome::{self, TxExecutionInfo};
+
+/// Returns the blockifier [`BlockContext`] of a block environment.
+pub fn block_context_from_env(env: &BlockEnv) -> BlockContext {
+    BlockContext {
+        chain_id: env.chain_id.clone(),
+        block_number: env.block_number,
+        block_timestamp: env.block_timestamp,
+        sequencer_address: env.sequencer_address.into(),
+        // As the fee has two currencies, we also have to adjust their addresses.
+        // https://github.com/starkware-libs/blockifier/blob/51b343fe38139a309a69b2482f4b484e8caa5edf/crates/blockifier/src/block_context.rs#L34
+        fee_token_addresses: FeeTokenAddresses {
+            eth_fee_token_address: env.fee_token_address.into(),
+            strk_fee_token_address: Default::default(),
+        },
+        vm_resource_fee_cost: get_default_vm_resource_fee_cost().into(),
+        // Gas prices are dual too.
+        // https://github.com/starkware-libs/blockifier/blob/51b343fe38139a309a69b2482f4b484e8caa5edf/crates/blockifier/src/block_context.rs#L49
+        gas_prices: GasPrices {
+            eth_l1_gas_price: env.gas_prices.eth_gas_price.into(),
+            strk_l1_gas_price: env.gas_prices.strk_gas_price.into(),
+        },
+        invoke_tx_max_n_steps: env.invoke_tx_max_n_steps,
+        validate_max_n_steps: env.validate_max_n_steps,
+        max_recursion_depth: env.max_recursion_depth,
+    }
+}
+
+pub fn get_default_vm_resource_fee_cost() -> HashMap<String, f64> {
+    HashMap::from([
+        (String::from("n_steps"), 1_f64),
+        (HASH_BUILTIN_NAME.to_string(), 1_f64),
+        (RANGE_CHECK_BUILTIN_NAME.to_string(), 1_f64),
+        (SIGNATURE_BUILTIN_NAME.to_string(), 1_f64),
+        (BITWISE_BUILTIN_NAME.to_string(), 1_f64),
+        (POSEIDON_BUILTIN_NAME.to_string(), 1_f64),
+        (OUTPUT_BUILTIN_NAME.to_string(), 1_f64),
+        (EC_OP_BUILTIN_NAME.to_string(), 1_f64),
+        (KECCAK_BUILTIN_NAME.to_string(), 1_f64),
+        (SEGMENT_ARENA_BUILTIN_NAME.to_string(), 1_f64),
+    ])
+}
+
+impl From<TransactionExecutionError> for ExecutionError {
+    fn from(err: TransactionExecutionError) -> Self {
+        match err {
+            TransactionExecutionError::ExecutionError(err) => Self::from(err),
+            err => Self::Other(err.to_string()),
+        }
+    }
+}
+
+impl From<EntryPointExecutionError> for ExecutionError {
+    fn from(err: EntryPointExecutionError) -> Self {
+        match err {
+            EntryPointExecutionError::PreExecutionError(
+                PreExecutionError::UninitializedStorageAddress(address),
+            ) => Self::ContractNotDeployed(address.into()),
+            err => Self::EntryPoint(err.to_string()),
+        }
+    }
+}
 
-use super::utils::{events_from_exec_info, l2_to_l1_messages_from_exec_info};
+/// Converts the execution info of a transaction executed by blockifier.
+pub fn to_exec_info(info: &TransactionExecutionInfo) -> TxExecutionInfo {
+    TxExecutionInfo {
+        validate_call_info: info.validate_call_info.as_ref().map(to_call_info),
+        execute_call_info: info.execute_call_info.as_ref().map(to_call_info),
+        fee_transfer_call_info: info.fee_transfer_call_info.as_ref().map(to_call_info),
+        actual_fee: info.actual_fee.0,
+        actual_resources: parse_actual_resources(&info.actual_resources.0),
+        revert_error: info.revert_error.clone(),
+    }
+}
+
+/// Converts a call made by a transaction executed by blockifier, along with its inner calls.
+pub fn to_call_info(call: &CallInfo) -> outcome::CallInfo {
+    let contract_address: ContractAddress = call.call.storage_address.into();
+
+    let events = call
+        .execution
+        .events
+        .iter()
+        .map(|e| Event {
+            from_address: contract_address,
+            data: e.event.data.0.iter().map(|d| (*d).into()).collect(),
+            keys: e.event.keys.iter().map(|k| k.0.into()).collect(),
+        })
+        .collect();
+
+    // By default, `from_address` must correspond to the contract address that
+    // is sending the message. In the case of library calls, `code_address` is `None`,
+    // we then use the `caller_address` instead (which can also be an account).
+    let from_address = if let Some(code_address) = call.call.code_address {
+        *code_address.0.key()
+    } else {
+        *call.call.caller_address.0.key()
+    };
+
+    let l2_to_l1_messages = call
+        .execution
+        .l2_to_l1_messages
+        .iter()
+        .map(|m| MessageToL1 {
+            to_address: FieldElement::from_byte_slice_be(m.message.to_address.0.as_bytes())
+                .unwrap(),
+            from_address: ContractAddress(from_address.into()),
+            payload: m.message.payload.0.iter().map(|p| (*p).into()).collect(),
+        })
+        .collect();
+
+    let inner_calls = call.inner_calls.iter().map(to_call_info).collect::<Vec<_>>();
 
-pub struct TxReceiptWithExecInfo {
-    pub receipt: Receipt,
-    pub execution_info: TransactionExecutionInfo,
+    let execution_resources = outcome::ExecutionResources {
+        syscall_counter: syscall_counter(call, &inner_calls),
+        n_steps: call.vm_resources.n_steps as u64,
+        n_memory_holes: call.vm_resources.n_memory_holes as u64,
+        builtin_instance_counter: call
+            .vm_resources
+            .builtin_instance_counter
+            .iter()
+            .map(|(name, count)| (name.clone(), *count as u64))
+            .collect(),
+    };
+
+    outcome::CallInfo {
+        events,
+        l2_to_l1_messages,
+        execution_resources,
+        contract_address,
+        code_address: call.call.code_address.map(Into::into),
+        caller_address: call.call.caller_address.into(),
+        class_hash: call.call.class_hash.map(|hash| hash.0.into()),
+        entry_point_selector: call.call.entry_point_selector.0.into(),
+        retdata: call.execution.retdata.0.iter().map(|f| (*f).into()).collect(),
+        storage_read_values: call.storage_read_values.iter().map(|v| (*v).into()).collect(),
+        inner_calls,
+    }
 }
 
-impl TxReceiptWithExecInfo {
-    pub fn new(tx: impl AsRef<Tx>, execution_info: TransactionExecutionInfo) -> Self {
-        let actual_fee = execution_info.actual_fee.0;
-        let events = events_from_exec_info(&execution_info);
-        let revert_error = execution_info.revert_error.clone();
-        let messages_sent = l2_to_l1_messages_from_exec_info(&execution_info);
-        let actual_resources = parse_actual_resources(&execution_info.actual_resources.0);
-
-        let receipt = match tx.as_ref() {
-            Tx::Invoke(_) => Receipt::Invoke(InvokeTxReceipt {
-                events,
-                actual_fee,
-                revert_error,
-                messages_sent,
-                execution_resources: actual_resources,
-            }),
-
-            Tx::Declare(_) => Receipt::Declare(DeclareTxReceipt {
-                events,
-                actual_fee,
-                revert_error,
-                messages_sent,
-                execution_resources: actual_resources,
-            }),
-
-            Tx::L1Handler(tx) => Receipt::L1Handler(L1HandlerTxReceipt {
-                events,
-                actual_fee,
-                revert_error,
-                messages_sent,
-                message_hash: tx.message_hash,
-                execution_resources: actual_resources,
-            }),
-
-            Tx::DeployAccount(tx) => Receipt::DeployAccount(DeployAccountTxReceipt {
-                events,
-                actual_fee,
-                revert_error,
-                messages_sent,
-                execution_resources: actual_resources,
-                contract_address: tx.contract_address,
-            }),
+/// Returns the number of syscalls made by a call, including the ones of its inner calls.
+///
+/// Blockifier only counts the syscalls of a whole transaction, so the ones of a call are derived
+/// from what they leave in its [`CallInfo`]: the calls it made, the storage values it read, and
+/// the events and messages it emitted. Syscalls that don't leave a trace there (eg.
+/// `storage_write`) are not counted.
+fn syscall_counter(call: &CallInfo, inner_calls: &[outcome::CallInfo]) -> BTreeMap<String, u64> {
+    let mut counter = BTreeMap::new();
+    let mut count = |name: &str, n: u64| {
+        if n > 0 {
+            *counter.entry(name.to_string()).or_default() += n;
+        }
+    };
+
+    for inner in &call.inner_calls {
+        let name = match (inner.call.entry_point_type, inner.call.call_type) {
+            (EntryPointType::Constructor, _) => "deploy",
+            (_, CallType::Delegate) => "library_call",
+            (_, CallType::Call) => "call_contract",
         };
+        count(name, 1);
+    }
+
+    count("storage_read", call.storage_read_values.len() as u64);
+    count("emit_event", call.execution.events.len() as u64);
+    count("send_message_to_l1", call.execution.l2_to_l1_messages.len() as u64);
 
-        Self { receipt, execution_info }
+    for inner in inner_calls {
+        for (name, n) in &inner.execution_resources.syscall_counter {
+            count(name, *n);
+        }
     }
+
+    counter
 }
 
 /// Parse the `actual resources` field from the execution info into a more structured type,
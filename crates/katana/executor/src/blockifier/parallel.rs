@@ -0,0 +1,387 @@
+//! Optimistic parallel execution of transactions.
+//!
+//! The transactions are first executed speculatively in parallel, each against the state as it was
+//! before any of the transactions were executed, while recording every state key they read. The
+//! speculative results are then validated and committed in the original order of the
+//! transactions: a result is only valid if none of the keys it read were written by a transaction
+//! committed before it. Otherwise, the transaction is re-executed on top of the committed state.
+//!
+//! Because a transaction is only committed once it has been executed against the exact state that
+//! a sequential execution would have seen, the resulting state diff is identical to the one
+//! produced by the sequential [`TransactionExecutor`](super::TransactionExecutor).
+//!
+//! Declare transactions act as barriers and are always executed sequentially, as the declared
+//! classes are not part of the state diff. Note that when fee charging is enabled, every
+//! transaction reads and writes the balance of the sequencer, which makes them all conflict.
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+
+use blockifier::block_context::BlockContext;
+use blockifier::execution::contract_class::ContractClass;
+use blockifier::state::cached_state::{CachedState, CommitmentStateDiff, GlobalContractCache};
+use blockifier::state::state_api::{State, StateReader, StateResult};
+use blockifier::transaction::errors::TransactionExecutionError;
+use blockifier::transaction::transaction_execution::Transaction;
+use blockifier::transaction::transactions::ExecutableTransaction;
+use katana_primitives::transaction::{ExecutableTx, ExecutableTxWithHash};
+use katana_primitives::FieldElement;
+use parking_lot::RwLock;
+use rayon::prelude::*;
+use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce};
+use starknet_api::hash::StarkFelt;
+use starknet_api::state::StorageKey;
+use tracing::trace;
+
+use super::state::CachedStateWrapper;
+use super::transactions::BlockifierTx;
+use super::{execute_tx, log_execution_result, BlockifierTxExecutionResult};
+
+/// A transaction executor that optimistically executes the transactions in parallel.
+///
+/// Produces the same results as [`TransactionExecutor`](super::TransactionExecutor), in the exact
+/// order the transactions are provided to the executor.
+pub struct ParallelTransactionExecutor<'a, S: StateReader> {
+    /// A flag to enable/disable fee charging.
+    charge_fee: bool,
+    /// The block context the transactions will be executed on.
+    block_context: &'a BlockContext,
+    /// The transactions to be executed.
+    transactions: Vec<ExecutableTxWithHash>,
+    /// The state the transactions will be executed on.
+    state: &'a CachedStateWrapper<S>,
+    /// A flag to enable/disable transaction validation.
+    validate: bool,
+
+    // logs flags
+    error_log: bool,
+    events_log: bool,
+    resources_log: bool,
+}
+
+impl<'a, S> ParallelTransactionExecutor<'a, S>
+where
+    S: StateReader + Send,
+{
+    pub fn new(
+        state: &'a CachedStateWrapper<S>,
+        block_context: &'a BlockContext,
+        charge_fee: bool,
+        validate: bool,
+        transactions: impl IntoIterator<Item = ExecutableTxWithHash>,
+    ) -> Self {
+        Self {
+            state,
+            charge_fee,
+            block_context,
+            validate,
+            transactions: transactions.into_iter().collect(),
+            error_log: false,
+            events_log: false,
+            resources_log: false,
+        }
+    }
+
+    pub fn with_events_log(self) -> Self {
+        Self { events_log: true, ..self }
+    }
+
+    pub fn with_error_log(self) -> Self {
+        Self { error_log: true, ..self }
+    }
+
+    pub fn with_resources_log(self) -> Self {
+        Self { resources_log: true, ..self }
+    }
+
+    /// Executes all the transactions and returns their results.
+    pub fn execute(mut self) -> Vec<BlockifierTxExecutionResult> {
+        let transactions = std::mem::take(&mut self.transactions);
+
+        let mut results = Vec::with_capacity(transactions.len());
+        let mut batch = Vec::new();
+
+        for tx in transactions {
+            if let ExecutableTx::Declare(_) = tx.as_ref() {
+                results.extend(self.execute_batch(std::mem::take(&mut batch)));
+                results.push(execute_tx(
+                    tx,
+                    self.state,
+                    self.block_context,
+                    self.charge_fee,
+                    self.validate,
+                ));
+            } else {
+                batch.push(tx);
+            }
+        }
+
+        results.extend(self.execute_batch(batch));
+
+        for res in &results {
+            log_execution_result(res, self.error_log, self.events_log, self.resources_log);
+        }
+
+        results
+    }
+
+    /// Executes a batch of transactions that doesn't contain any declare transactions.
+    fn execute_batch(
+        &self,
+        transactions: Vec<ExecutableTxWithHash>,
+    ) -> Vec<BlockifierTxExecutionResult> {
+        let Self { state, block_context, charge_fee, validate, .. } = *self;
+
+        let snapshot = StateSnapshot::new(state);
+        let speculative_outcomes = transactions
+            .par_iter()
+            .map(|tx| {
+                execute_speculatively(tx.clone(), &snapshot, block_context, charge_fee, validate)
+            })
+            .collect::<Vec<_>>();
+
+        let mut written_keys = HashSet::new();
+        let mut results = Vec::with_capacity(transactions.len());
+
+        for (tx, outcome) in transactions.into_iter().zip(speculative_outcomes) {
+            let outcome = if outcome.read_keys.is_disjoint(&written_keys) {
+                outcome
+            } else {
+                trace!(target: "executor", hash = %format!("{:#x}", tx.hash), "Re-executing conflicting transaction.");
+                // the snapshot is stale at this point, so read from the committed state instead
+                let snapshot = StateSnapshot::new(state);
+                execute_speculatively(tx, &snapshot, block_context, charge_fee, validate)
+            };
+
+            let SpeculativeOutcome { result, state_diff, .. } = outcome;
+
+            // transactions that failed to execute don't modify the state
+            let result = match result {
+                Ok(info) => {
+                    let keys = state_diff_keys(&state_diff).collect::<Vec<_>>();
+                    match commit_state_diff(&mut state.inner(), state_diff) {
+                        Ok(()) => {
+                            written_keys.extend(keys);
+                            Ok(info)
+                        }
+                        Err(err) => Err(TransactionExecutionError::from(err)),
+                    }
+                }
+                Err(err) => Err(err),
+            };
+
+            results.push(result);
+        }
+
+        results
+    }
+}
+
+/// A key of the state that can be read or written by a transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum StateKey {
+    Nonce(ContractAddress),
+    ClassHash(ContractAddress),
+    Storage(ContractAddress, StorageKey),
+    CompiledClassHash(ClassHash),
+}
+
+/// The result of executing a transaction against a snapshot of the state.
+struct SpeculativeOutcome {
+    /// The execution result of the transaction.
+    result: BlockifierTxExecutionResult,
+    /// All the keys read from the underlying state during the execution.
+    read_keys: HashSet<StateKey>,
+    /// The state changes made by the transaction.
+    state_diff: CommitmentStateDiff,
+}
+
+/// A read-only view of the shared state, as it was before the speculative executions.
+///
+/// The values read from the state are cached behind a [`RwLock`], so that the transactions
+/// executed in parallel only contend on the lock of the shared state the first time a key is read.
+struct StateSnapshot<'a, S: StateReader> {
+    state: &'a CachedStateWrapper<S>,
+    values: RwLock<HashMap<StateKey, StarkFelt>>,
+    classes: RwLock<HashMap<ClassHash, ContractClass>>,
+}
+
+impl<'a, S: StateReader> StateSnapshot<'a, S> {
+    fn new(state: &'a CachedStateWrapper<S>) -> Self {
+        Self { state, values: Default::default(), classes: Default::default() }
+    }
+
+    fn value(
+        &self,
+        key: StateKey,
+        read: impl FnOnce(&mut CachedState<S>) -> StateResult<StarkFelt>,
+    ) -> StateResult<StarkFelt> {
+        if let Some(value) = self.values.read().get(&key) {
+            return Ok(*value);
+        }
+
+        let value = read(&mut self.state.inner())?;
+        self.values.write().insert(key, value);
+        Ok(value)
+    }
+
+    fn class(&self, class_hash: &ClassHash) -> StateResult<ContractClass> {
+        if let Some(class) = self.classes.read().get(class_hash) {
+            return Ok(class.clone());
+        }
+
+        let class = self.state.inner().get_compiled_contract_class(class_hash)?;
+        self.classes.write().insert(*class_hash, class.clone());
+        Ok(class)
+    }
+}
+
+/// A [`StateReader`] that reads from a [`StateSnapshot`] and records every key it reads.
+struct RecordingStateReader<'a, 'b, S: StateReader> {
+    snapshot: &'a StateSnapshot<'b, S>,
+    read_keys: &'a RefCell<HashSet<StateKey>>,
+}
+
+impl<'a, 'b, S: StateReader> RecordingStateReader<'a, 'b, S> {
+    fn record(&self, key: StateKey) {
+        self.read_keys.borrow_mut().insert(key);
+    }
+}
+
+impl<'a, 'b, S: StateReader> StateReader for RecordingStateReader<'a, 'b, S> {
+    fn get_storage_at(
+        &mut self,
+        contract_address: ContractAddress,
+        key: StorageKey,
+    ) -> StateResult<StarkFelt> {
+        let state_key = StateKey::Storage(contract_address, key);
+        self.record(state_key);
+        self.snapshot.value(state_key, |state| state.get_storage_at(contract_address, key))
+    }
+
+    fn get_nonce_at(&mut self, contract_address: ContractAddress) -> StateResult<Nonce> {
+        let state_key = StateKey::Nonce(contract_address);
+        self.record(state_key);
+        self.snapshot
+            .value(state_key, |state| state.get_nonce_at(contract_address).map(|n| n.0))
+            .map(Nonce)
+    }
+
+    fn get_class_hash_at(&mut self, contract_address: ContractAddress) -> StateResult<ClassHash> {
+        let state_key = StateKey::ClassHash(contract_address);
+        self.record(state_key);
+        self.snapshot
+            .value(state_key, |state| state.get_class_hash_at(contract_address).map(|h| h.0))
+            .map(ClassHash)
+    }
+
+    fn get_compiled_contract_class(
+        &mut self,
+        class_hash: &ClassHash,
+    ) -> StateResult<ContractClass> {
+        self.record(StateKey::CompiledClassHash(*class_hash));
+        self.snapshot.class(class_hash)
+    }
+
+    fn get_compiled_class_hash(&mut self, class_hash: ClassHash) -> StateResult<CompiledClassHash> {
+        let state_key = StateKey::CompiledClassHash(class_hash);
+        self.record(state_key);
+        self.snapshot
+            .value(state_key, |state| state.get_compiled_class_hash(class_hash).map(|h| h.0))
+            .map(CompiledClassHash)
+    }
+}
+
+/// Executes a transaction on a fresh cache on top of `snapshot`, without modifying the state.
+fn execute_speculatively<S: StateReader>(
+    tx: ExecutableTxWithHash,
+    snapshot: &StateSnapshot<'_, S>,
+    block_context: &BlockContext,
+    charge_fee: bool,
+    validate: bool,
+) -> SpeculativeOutcome {
+    let read_keys = RefCell::new(HashSet::new());
+    let reader = RecordingStateReader { snapshot, read_keys: &read_keys };
+    let mut cached_state = CachedState::new(reader, GlobalContractCache::default());
+
+    let result = match BlockifierTx::from(tx).0 {
+        Transaction::AccountTransaction(tx) => {
+            tx.execute(&mut cached_state, block_context, charge_fee, validate)
+        }
+        Transaction::L1HandlerTransaction(tx) => {
+            tx.execute(&mut cached_state, block_context, charge_fee, validate)
+        }
+    };
+
+    let state_diff = cached_state.to_state_diff();
+    drop(cached_state);
+
+    SpeculativeOutcome { result, read_keys: read_keys.into_inner(), state_diff }
+}
+
+/// Returns all the keys that are written in the state diff.
+fn state_diff_keys(state_diff: &CommitmentStateDiff) -> impl Iterator<Item = StateKey> + '_ {
+    let storage = state_diff
+        .storage_updates
+        .iter()
+        .flat_map(|(address, entries)| entries.keys().map(|key| StateKey::Storage(*address, *key)));
+    let nonces = state_diff.address_to_nonce.keys().map(|address| StateKey::Nonce(*address));
+    let classes =
+        state_diff.address_to_class_hash.keys().map(|address| StateKey::ClassHash(*address));
+    let compiled_classes = state_diff
+        .class_hash_to_compiled_class_hash
+        .keys()
+        .map(|class_hash| StateKey::CompiledClassHash(*class_hash));
+
+    storage.chain(nonces).chain(classes).chain(compiled_classes)
+}
+
+/// Applies the state diff of a transaction to the state.
+///
+/// The diff is applied atomically: if any of its changes can't be applied, the state is left
+/// untouched.
+fn commit_state_diff<S: StateReader>(
+    state: &mut CachedState<S>,
+    state_diff: CommitmentStateDiff,
+) -> StateResult<()> {
+    let mut transactional_state = state.create_transactional();
+    match apply_state_diff(&mut transactional_state, state_diff) {
+        Ok(()) => {
+            transactional_state.commit();
+            Ok(())
+        }
+        Err(err) => {
+            transactional_state.abort();
+            Err(err)
+        }
+    }
+}
+
+fn apply_state_diff<S: StateReader>(
+    state: &mut CachedState<S>,
+    state_diff: CommitmentStateDiff,
+) -> StateResult<()> {
+    for (address, entries) in state_diff.storage_updates {
+        for (key, value) in entries {
+            state.set_storage_at(address, key, value);
+        }
+    }
+
+    for (address, class_hash) in state_diff.address_to_class_hash {
+        state.set_class_hash_at(address, class_hash)?;
+    }
+
+    // the state only allows the nonce to be incremented
+    for (address, nonce) in state_diff.address_to_nonce {
+        let target = FieldElement::from(nonce.0);
+        while FieldElement::from(state.get_nonce_at(address)?.0) < target {
+            state.increment_nonce(address)?;
+        }
+    }
+
+    for (class_hash, compiled_class_hash) in state_diff.class_hash_to_compiled_class_hash {
+        state.set_compiled_class_hash(class_hash, compiled_class_hash)?;
+    }
+
+    Ok(())
+}
@@ -17,8 +17,6 @@ use blockifier::transaction::objects::{
     DeprecatedAccountTransactionContext, ResourcesMapping, TransactionExecutionInfo,
 };
 use convert_case::{Case, Casing};
-use katana_primitives::contract::ContractAddress;
-use katana_primitives::receipt::{Event, MessageToL1};
 use katana_primitives::state::{StateUpdates, StateUpdatesWithDeclaredClasses};
 use katana_primitives::transaction::ExecutableTxWithHash;
 use katana_primitives::FieldElement;
@@ -32,16 +30,7 @@ use tracing::trace;
 
 use super::state::{CachedStateWrapper, StateRefDb};
 use super::TransactionExecutor;
-
-#[derive(Debug)]
-pub struct EntryPointCall {
-    /// The address of the contract whose function you're calling.
-    pub contract_address: ContractAddress,
-    /// The input to the function.
-    pub calldata: Vec<FieldElement>,
-    /// The function selector.
-    pub entry_point_selector: FieldElement,
-}
+use crate::executor::EntryPointCall;
 
 /// Perform a function call on a contract and retrieve the return values.
 pub fn call(
@@ -270,91 +259,29 @@ pub fn get_state_update_from_cached_state(
     }
 }
 
-pub(super) fn trace_events(events: &[Event]) {
-    for e in events {
-        let formatted_keys =
-            e.keys.iter().map(|k| format!("{k:#x}")).collect::<Vec<_>>().join(", ");
-
-        trace!(target: "executor", "Event emitted keys=[{}]", formatted_keys);
-    }
-}
-
-pub(super) fn events_from_exec_info(execution_info: &TransactionExecutionInfo) -> Vec<Event> {
-    let mut events: Vec<Event> = vec![];
-
-    fn get_events_recursively(call_info: &CallInfo) -> Vec<Event> {
-        let mut events: Vec<Event> = vec![];
-
-        events.extend(call_info.execution.events.iter().map(|e| Event {
-            from_address: call_info.call.storage_address.into(),
-            data: e.event.data.0.iter().map(|d| (*d).into()).collect(),
-            keys: e.event.keys.iter().map(|k| k.0.into()).collect(),
-        }));
-
-        call_info.inner_calls.iter().for_each(|call| {
-            events.extend(get_events_recursively(call));
-        });
-
-        events
-    }
-
-    if let Some(ref call) = execution_info.validate_call_info {
-        events.extend(get_events_recursively(call));
-    }
-
-    if let Some(ref call) = execution_info.execute_call_info {
-        events.extend(get_events_recursively(call));
-    }
-
-    if let Some(ref call) = execution_info.fee_transfer_call_info {
-        events.extend(get_events_recursively(call));
-    }
-
-    events
-}
-
-pub(super) fn l2_to_l1_messages_from_exec_info(
-    execution_info: &TransactionExecutionInfo,
-) -> Vec<MessageToL1> {
-    let mut messages = vec![];
-
-    fn get_messages_recursively(info: &CallInfo) -> Vec<MessageToL1> {
-        let mut messages = vec![];
-
-        // By default, `from_address` must correspond to the contract address that
-        // is sending the message. In the case of library calls, `code_address` is `None`,
-        // we then use the `caller_address` instead (which can also be an account).
-        let from_address = if let Some(code_address) = info.call.code_address {
-            *code_address.0.key()
-        } else {
-            *info.call.caller_address.0.key()
-        };
-
-        messages.extend(info.execution.l2_to_l1_messages.iter().map(|m| MessageToL1 {
-            to_address:
-                FieldElement::from_byte_slice_be(m.message.to_address.0.as_bytes()).unwrap(),
-            from_address: ContractAddress(from_address.into()),
-            payload: m.message.payload.0.iter().map(|p| (*p).into()).collect(),
-        }));
-
-        info.inner_calls.iter().for_each(|call| {
-            messages.extend(get_messages_recursively(call));
-        });
-
-        messages
-    }
-
-    if let Some(ref info) = execution_info.validate_call_info {
-        messages.extend(get_messages_recursively(info));
-    }
-
-    if let Some(ref info) = execution_info.execute_call_info {
-        messages.extend(get_messages_recursively(info));
-    }
-
-    if let Some(ref info) = execution_info.fee_transfer_call_info {
-        messages.extend(get_messages_recursively(info));
+pub(super) fn trace_events(execution_info: &TransactionExecutionInfo) {
+    fn trace_call_events(call: &CallInfo) {
+        for e in &call.execution.events {
+            let formatted_keys = e
+                .event
+                .keys
+                .iter()
+                .map(|k| format!("{:#x}", FieldElement::from(k.0)))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            trace!(target: "executor", "Event emitted keys=[{}]", formatted_keys);
+        }
+
+        call.inner_calls.iter().for_each(trace_call_events);
     }
 
-    messages
+    [
+        &execution_info.validate_call_info,
+        &execution_info.execute_call_info,
+        &execution_info.fee_transfer_call_info,
+    ]
+    .into_iter()
+    .flatten()
+    .for_each(trace_call_events);
 }
@@ -0,0 +1,174 @@
+//! Abstraction over the backend used to execute transactions.
+//!
+//! The sequencer and the RPC layers only interact with the execution engine through the
+//! [`ExecutorFactory`] and [`BlockExecutor`] traits, so that alternative backends (eg. an
+//! instrumented VM, or a native Cairo runtime) can be plugged in without touching them. The
+//! traits only expose types defined by Katana, and every backend is enabled through its own
+//! cargo feature (eg. `blockifier`).
+
+use std::sync::Arc;
+
+use katana_primitives::block::GasPrices;
+use katana_primitives::contract::ContractAddress;
+use katana_primitives::state::StateUpdatesWithDeclaredClasses;
+use katana_primitives::transaction::{ExecutableTxWithHash, TxWithHash};
+use katana_primitives::FieldElement;
+use katana_provider::traits::state::StateProvider;
+use parking_lot::RwLock;
+use starknet::core::types::FeeEstimate;
+use starknet_api::block::{BlockNumber, BlockTimestamp};
+use starknet_api::core::ChainId;
+
+use crate::outcome::{TxExecutionInfo, TxReceiptWithExecInfo};
+
+/// The result of a transaction execution.
+pub type TxExecutionResult = Result<TxExecutionInfo, ExecutionError>;
+
+/// An error raised by the execution backend while executing a transaction or a call.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ExecutionError {
+    /// The called contract is not deployed.
+    #[error("Contract {0} is not deployed.")]
+    ContractNotDeployed(ContractAddress),
+    /// The execution of a contract entry point failed.
+    #[error("{0}")]
+    EntryPoint(String),
+    /// Any other error raised by the execution backend.
+    #[error("{0}")]
+    Other(String),
+}
+
+/// The environment of the block the transactions are executed in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockEnv {
+    /// The chain id of the network.
+    pub chain_id: ChainId,
+    /// The number of the block.
+    pub block_number: BlockNumber,
+    /// The timestamp of the block.
+    pub block_timestamp: BlockTimestamp,
+    /// The address of the sequencer of the block, which receives the transaction fees.
+    pub sequencer_address: ContractAddress,
+    /// The address of the token the transaction fees are paid in.
+    pub fee_token_address: ContractAddress,
+    /// The L1 gas prices of the block.
+    pub gas_prices: GasPrices,
+    /// The maximum number of steps of the execution of a transaction.
+    pub invoke_tx_max_n_steps: u32,
+    /// The maximum number of steps of the validation of a transaction.
+    pub validate_max_n_steps: u32,
+    /// The maximum depth of nested calls.
+    pub max_recursion_depth: usize,
+}
+
+#[derive(Debug)]
+pub struct EntryPointCall {
+    /// The address of the contract whose function you're calling.
+    pub contract_address: ContractAddress,
+    /// The input to the function.
+    pub calldata: Vec<FieldElement>,
+    /// The function selector.
+    pub entry_point_selector: FieldElement,
+}
+
+/// Flags to control how the transactions are executed.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExecutionFlags {
+    /// Charge the fee of the transactions.
+    pub charge_fee: bool,
+    /// Run the validation entrypoint of the transactions.
+    pub validate: bool,
+    /// Execute the transactions in parallel, if the backend supports it.
+    pub parallel: bool,
+}
+
+/// A factory for creating executors of a specific execution backend.
+pub trait ExecutorFactory: Send + Sync {
+    /// Creates a new executor that executes transactions on top of the given state.
+    fn with_state(&self, state: Box<dyn StateProvider>) -> Box<dyn BlockExecutor>;
+
+    /// Perform a function call on a contract and retrieve the return values.
+    fn call(
+        &self,
+        request: EntryPointCall,
+        block_env: &BlockEnv,
+        state: Box<dyn StateProvider>,
+    ) -> Result<Vec<FieldElement>, ExecutionError>;
+
+    /// Estimate the execution fee for a list of transactions.
+    fn estimate_fee(
+        &self,
+        transactions: Vec<ExecutableTxWithHash>,
+        block_env: &BlockEnv,
+        state: Box<dyn StateProvider>,
+        validate: bool,
+    ) -> Result<Vec<FeeEstimate>, ExecutionError>;
+}
+
+/// An executor that accumulates the state changes of the transactions it executes.
+///
+/// The executor is also a [`StateProvider`] which exposes the state after all the transactions
+/// that have been executed so far.
+pub trait BlockExecutor: StateProvider {
+    /// Executes the transactions, in order, on top of the current state of the executor.
+    fn execute_transactions(
+        &self,
+        block_env: &BlockEnv,
+        flags: ExecutionFlags,
+        transactions: Vec<ExecutableTxWithHash>,
+    ) -> Vec<TxExecutionResult>;
+
+    /// Returns the state changes made by all the transactions executed so far.
+    fn state_updates(&self) -> StateUpdatesWithDeclaredClasses;
+
+    /// Discards all the state changes and resets the executor on top of a new state.
+    fn reset(&self, state: Box<dyn StateProvider>);
+}
+
+pub type AcceptedTxPair = (TxWithHash, TxReceiptWithExecInfo);
+pub type RejectedTxPair = (TxWithHash, ExecutionError);
+
+pub struct PendingState {
+    pub state: Arc<dyn BlockExecutor>,
+    /// The transactions that have been executed.
+    pub executed_txs: RwLock<Vec<(TxWithHash, TxReceiptWithExecInfo)>>,
+    /// The transactions that have been rejected.
+    pub rejected_txs: RwLock<Vec<(TxWithHash, ExecutionError)>>,
+}
+
+impl PendingState {
+    pub fn new(executor: Box<dyn BlockExecutor>) -> Self {
+        Self {
+            state: Arc::from(executor),
+            executed_txs: RwLock::new(Vec::new()),
+            rejected_txs: RwLock::new(Vec::new()),
+        }
+    }
+
+    pub fn reset_state_with(&self, state: Box<dyn StateProvider>) {
+        self.state.reset(state);
+    }
+
+    pub fn add_executed_txs(&self, transactions: Vec<(TxWithHash, TxExecutionResult)>) {
+        transactions.into_iter().for_each(|(tx, res)| self.add_executed_tx(tx, res));
+    }
+
+    /// Drain the pending transactions, returning the executed and rejected transactions.
+    pub fn take_txs_all(&self) -> (Vec<AcceptedTxPair>, Vec<RejectedTxPair>) {
+        let executed_txs = std::mem::take(&mut *self.executed_txs.write());
+        let rejected_txs = std::mem::take(&mut *self.rejected_txs.write());
+        (executed_txs, rejected_txs)
+    }
+
+    fn add_executed_tx(&self, tx: TxWithHash, execution_result: TxExecutionResult) {
+        match execution_result {
+            Ok(execution_info) => {
+                let receipt = TxReceiptWithExecInfo::new(&tx, execution_info);
+                self.executed_txs.write().push((tx, receipt));
+            }
+            Err(err) => {
+                self.rejected_txs.write().push((tx, err));
+            }
+        }
+    }
+}
@@ -1 +1,6 @@
+pub mod executor;
+pub mod outcome;
+pub mod profile;
+
+#[cfg(feature = "blockifier")]
 pub mod blockifier;
@@ -0,0 +1,156 @@
+use std::collections::BTreeMap;
+
+use katana_primitives::contract::{ClassHash, ContractAddress};
+use katana_primitives::receipt::{
+    DeclareTxReceipt, DeployAccountTxReceipt, Event, InvokeTxReceipt, L1HandlerTxReceipt,
+    MessageToL1, Receipt, TxExecutionResources,
+};
+use katana_primitives::transaction::Tx;
+use katana_primitives::FieldElement;
+
+/// The outputs of a transaction execution, independent of the backend that executed it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TxExecutionInfo {
+    /// The call to the validation entry point of the transaction, if any.
+    pub validate_call_info: Option<CallInfo>,
+    /// The call to the execution entry point of the transaction, if any.
+    pub execute_call_info: Option<CallInfo>,
+    /// The call that transferred the fee of the transaction, if the fee was charged.
+    pub fee_transfer_call_info: Option<CallInfo>,
+    /// The fee charged for the transaction.
+    pub actual_fee: u128,
+    /// The resources consumed by the transaction.
+    pub actual_resources: TxExecutionResources,
+    /// The reason the transaction was reverted, if it was.
+    pub revert_error: Option<String>,
+}
+
+impl TxExecutionInfo {
+    /// Returns the top-level calls of the transaction, in the order they were executed.
+    pub fn calls(&self) -> impl Iterator<Item = (&'static str, &CallInfo)> {
+        [
+            ("validate", &self.validate_call_info),
+            ("execute", &self.execute_call_info),
+            ("fee_transfer", &self.fee_transfer_call_info),
+        ]
+        .into_iter()
+        .filter_map(|(phase, call)| call.as_ref().map(|call| (phase, call)))
+    }
+
+    /// Returns all the events emitted during the execution, in the order they were emitted.
+    pub fn events(&self) -> Vec<Event> {
+        let mut events = Vec::new();
+        self.calls().for_each(|(_, call)| collect_events(call, &mut events));
+        events
+    }
+
+    /// Returns all the messages sent to L1 during the execution, in the order they were sent.
+    pub fn l2_to_l1_messages(&self) -> Vec<MessageToL1> {
+        let mut messages = Vec::new();
+        self.calls().for_each(|(_, call)| collect_messages(call, &mut messages));
+        messages
+    }
+}
+
+/// A call made during a transaction execution, along with the calls it made in turn.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CallInfo {
+    /// The address of the called contract, whose storage the call executed on.
+    pub contract_address: ContractAddress,
+    /// The address of the contract whose code was executed. `None` for library calls.
+    pub code_address: Option<ContractAddress>,
+    /// The address of the caller.
+    pub caller_address: ContractAddress,
+    /// The hash of the class whose code was executed, if known.
+    pub class_hash: Option<ClassHash>,
+    /// The selector of the called entry point.
+    pub entry_point_selector: FieldElement,
+    /// The values returned by the call.
+    pub retdata: Vec<FieldElement>,
+    /// The events emitted by the call itself, excluding the ones of its inner calls.
+    pub events: Vec<Event>,
+    /// The messages sent to L1 by the call itself, excluding the ones of its inner calls.
+    pub l2_to_l1_messages: Vec<MessageToL1>,
+    /// The values read from the storage by the call itself.
+    pub storage_read_values: Vec<FieldElement>,
+    /// The resources used by the call.
+    pub execution_resources: ExecutionResources,
+    /// The calls made by the call.
+    pub inner_calls: Vec<CallInfo>,
+}
+
+/// The resources used by a call, including the ones of its inner calls.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecutionResources {
+    /// The number of Cairo steps.
+    pub n_steps: u64,
+    /// The number of memory holes.
+    pub n_memory_holes: u64,
+    /// The number of instances used of each builtin, by builtin name.
+    pub builtin_instance_counter: BTreeMap<String, u64>,
+    /// The number of syscalls made, by syscall name.
+    pub syscall_counter: BTreeMap<String, u64>,
+}
+
+pub struct TxReceiptWithExecInfo {
+    pub receipt: Receipt,
+    pub execution_info: TxExecutionInfo,
+}
+
+impl TxReceiptWithExecInfo {
+    pub fn new(tx: impl AsRef<Tx>, execution_info: TxExecutionInfo) -> Self {
+        let actual_fee = execution_info.actual_fee;
+        let events = execution_info.events();
+        let revert_error = execution_info.revert_error.clone();
+        let messages_sent = execution_info.l2_to_l1_messages();
+        let actual_resources = execution_info.actual_resources.clone();
+
+        let receipt = match tx.as_ref() {
+            Tx::Invoke(_) => Receipt::Invoke(InvokeTxReceipt {
+                events,
+                actual_fee,
+                revert_error,
+                messages_sent,
+                execution_resources: actual_resources,
+            }),
+
+            Tx::Declare(_) => Receipt::Declare(DeclareTxReceipt {
+                events,
+                actual_fee,
+                revert_error,
+                messages_sent,
+                execution_resources: actual_resources,
+            }),
+
+            Tx::L1Handler(tx) => Receipt::L1Handler(L1HandlerTxReceipt {
+                events,
+                actual_fee,
+                revert_error,
+                messages_sent,
+                message_hash: tx.message_hash,
+                execution_resources: actual_resources,
+            }),
+
+            Tx::DeployAccount(tx) => Receipt::DeployAccount(DeployAccountTxReceipt {
+                events,
+                actual_fee,
+                revert_error,
+                messages_sent,
+                execution_resources: actual_resources,
+                contract_address: tx.contract_address,
+            }),
+        };
+
+        Self { receipt, execution_info }
+    }
+}
+
+fn collect_events(call: &CallInfo, events: &mut Vec<Event>) {
+    events.extend(call.events.iter().cloned());
+    call.inner_calls.iter().for_each(|call| collect_events(call, events));
+}
+
+fn collect_messages(call: &CallInfo, messages: &mut Vec<MessageToL1>) {
+    messages.extend(call.l2_to_l1_messages.iter().cloned());
+    call.inner_calls.iter().for_each(|call| collect_messages(call, messages));
+}
@@ -0,0 +1,307 @@
+//! Entry point and syscall profiling of transaction executions.
+//!
+//! A profile is built from the tree of [`CallInfo`]s of a transaction execution, where every call
+//! is attributed to the contract entry point it executed. Entry point names are resolved from the
+//! ABI of the contract's Sierra class, and fall back to the hex-encoded selector when the class
+//! doesn't have one (eg. legacy classes).
+//!
+//! The granularity of a profile is the entry point, not the Sierra functions it calls internally:
+//! the executor doesn't record the VM trace of a call, so the steps spent in the internal functions
+//! of a contract are reported under the entry point that called them.
+//!
+//! The resources of a [`CallInfo`], syscalls included, cover all of its inner calls, so the
+//! resources reported for every frame are the ones spent exclusively in that frame.
+
+use std::collections::{BTreeMap, HashMap};
+
+use katana_primitives::contract::ClassHash;
+use katana_primitives::FieldElement;
+use katana_provider::traits::contract::ContractClassProvider;
+use katana_provider::traits::state::StateProvider;
+use serde::Serialize;
+use starknet::core::utils::get_selector_from_name;
+
+use crate::outcome::{CallInfo, TxExecutionInfo};
+
+/// Entry points of well-known legacy contracts, whose classes don't carry an ABI.
+const KNOWN_ENTRY_POINTS: &[&str] = &[
+    "__execute__",
+    "__validate__",
+    "__validate_declare__",
+    "__validate_deploy__",
+    "constructor",
+    "approve",
+    "balanceOf",
+    "deployContract",
+    "transfer",
+    "transferFrom",
+];
+
+/// The resources spent by a frame of the call stack.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct FrameResources {
+    /// The number of Cairo steps.
+    pub steps: u64,
+    /// The number of memory holes.
+    pub memory_holes: u64,
+    /// The number of instances used of each builtin.
+    pub builtins: BTreeMap<String, u64>,
+    /// The number of syscalls made, by syscall name.
+    pub syscalls: BTreeMap<String, u64>,
+}
+
+impl FrameResources {
+    fn add(&mut self, other: &FrameResources) {
+        self.steps += other.steps;
+        self.memory_holes += other.memory_holes;
+        for (name, count) in &other.builtins {
+            *self.builtins.entry(name.clone()).or_default() += count;
+        }
+        for (name, count) in &other.syscalls {
+            *self.syscalls.entry(name.clone()).or_default() += count;
+        }
+    }
+}
+
+/// A unique call stack, and the resources spent exclusively in its top-most frame.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StackSample {
+    /// The frames of the stack, from the root to the top-most frame.
+    pub frames: Vec<String>,
+    pub resources: FrameResources,
+}
+
+/// The aggregated resources of a single contract entry point.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EntryPointProfile {
+    /// The name of the entry point, prefixed with the path of its contract.
+    pub name: String,
+    /// The number of times the entry point was called.
+    pub calls: u64,
+    /// The resources spent in the entry point itself, excluding its inner calls.
+    pub self_resources: FrameResources,
+    /// The resources spent in the entry point, including its inner calls.
+    pub total_resources: FrameResources,
+}
+
+/// The profile of a transaction execution.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct TransactionProfile {
+    /// The entry points called during the execution, sorted by the number of steps spent in
+    /// them.
+    pub entry_points: Vec<EntryPointProfile>,
+    /// All the unique call stacks of the execution.
+    pub stacks: Vec<StackSample>,
+}
+
+impl TransactionProfile {
+    /// Builds the profile of a transaction execution.
+    ///
+    /// The `state` is used to resolve the names of the entry points, and must contain all the
+    /// classes that were called during the execution.
+    pub fn new<S>(execution_info: &TxExecutionInfo, state: &S) -> Self
+    where
+        S: StateProvider + ?Sized,
+    {
+        let mut builder = ProfileBuilder {
+            state,
+            names: HashMap::new(),
+            calls: HashMap::new(),
+            stacks: BTreeMap::new(),
+        };
+
+        for (phase, call) in execution_info.calls() {
+            builder.visit_call(&mut vec![phase.to_string()], call);
+        }
+
+        builder.build()
+    }
+
+    /// Returns the profile in the folded stacks format, where every line is a call stack followed
+    /// by the number of steps spent in its top-most frame. This is the input format of
+    /// flamegraph tools such as `inferno` or `flamegraph.pl`.
+    pub fn to_folded(&self) -> String {
+        self.stacks
+            .iter()
+            .filter(|stack| stack.resources.steps > 0)
+            .map(|stack| format!("{} {}\n", stack.frames.join(";"), stack.resources.steps))
+            .collect()
+    }
+}
+
+struct ProfileBuilder<'a, S: ?Sized> {
+    state: &'a S,
+    /// Cache of the resolved frame names, keyed by class hash and entry point selector.
+    names: HashMap<(ClassHash, FieldElement), String>,
+    /// The number of times each entry point was called.
+    calls: HashMap<String, u64>,
+    /// The exclusive resources of every unique call stack.
+    stacks: BTreeMap<Vec<String>, FrameResources>,
+}
+
+impl<'a, S: StateProvider + ?Sized> ProfileBuilder<'a, S> {
+    /// Records the resources of `call`, and recursively the ones of its inner calls. Returns the
+    /// inclusive resources of the call.
+    fn visit_call(&mut self, stack: &mut Vec<String>, call: &CallInfo) -> FrameResources {
+        let name = self.frame_name(call);
+        *self.calls.entry(name.clone()).or_default() += 1;
+        stack.push(name);
+
+        let total = call_resources(call);
+        let mut exclusive = total.clone();
+
+        for inner in &call.inner_calls {
+            let inner_total = self.visit_call(stack, inner);
+            exclusive.steps = exclusive.steps.saturating_sub(inner_total.steps);
+            exclusive.memory_holes =
+                exclusive.memory_holes.saturating_sub(inner_total.memory_holes);
+            subtract_counts(&mut exclusive.builtins, &inner_total.builtins);
+            subtract_counts(&mut exclusive.syscalls, &inner_total.syscalls);
+        }
+
+        self.stacks.entry(stack.clone()).or_default().add(&exclusive);
+        stack.pop();
+
+        total
+    }
+
+    fn build(self) -> TransactionProfile {
+        let mut entry_points: BTreeMap<String, EntryPointProfile> = BTreeMap::new();
+
+        for (frames, resources) in &self.stacks {
+            let name = frames.last().expect("stack can't be empty");
+            let entry_point =
+                entry_points.entry(name.clone()).or_insert_with(|| EntryPointProfile {
+                    name: name.clone(),
+                    calls: self.calls.get(name).copied().unwrap_or_default(),
+                    self_resources: FrameResources::default(),
+                    total_resources: FrameResources::default(),
+                });
+            entry_point.self_resources.add(resources);
+        }
+
+        // the total resources of an entry point are the exclusive resources of all the stacks it's
+        // part of, counting recursive calls only once
+        for (frames, resources) in &self.stacks {
+            let mut seen = Vec::with_capacity(frames.len());
+            for name in frames {
+                if seen.contains(&name) {
+                    continue;
+                }
+                seen.push(name);
+                if let Some(entry_point) = entry_points.get_mut(name) {
+                    entry_point.total_resources.add(resources);
+                }
+            }
+        }
+
+        let mut entry_points = entry_points.into_values().collect::<Vec<_>>();
+        entry_points.sort_by(|a, b| b.self_resources.steps.cmp(&a.self_resources.steps));
+
+        let stacks = self
+            .stacks
+            .into_iter()
+            .map(|(frames, resources)| StackSample { frames, resources })
+            .collect();
+
+        TransactionProfile { entry_points, stacks }
+    }
+
+    fn frame_name(&mut self, call: &CallInfo) -> String {
+        let selector = call.entry_point_selector;
+        let address = call.code_address.unwrap_or(call.contract_address);
+
+        let class_hash = match call.class_hash {
+            Some(hash) => Some(hash),
+            None => self.state.class_hash_of_contract(address).ok().flatten(),
+        };
+
+        let Some(class_hash) = class_hash else {
+            return format!("{:#x}::{selector:#x}", address.0);
+        };
+
+        if let Some(name) = self.names.get(&(class_hash, selector)) {
+            return name.clone();
+        }
+
+        let name = resolve_frame_name(self.state, class_hash, selector);
+        self.names.insert((class_hash, selector), name.clone());
+        name
+    }
+}
+
+/// Returns the resources of a call, including its inner calls.
+fn call_resources(call: &CallInfo) -> FrameResources {
+    let resources = &call.execution_resources;
+    let non_zero = |counter: &BTreeMap<String, u64>| {
+        counter
+            .iter()
+            .filter(|(_, count)| **count > 0)
+            .map(|(name, count)| (name.clone(), *count))
+            .collect()
+    };
+
+    FrameResources {
+        steps: resources.n_steps,
+        memory_holes: resources.n_memory_holes,
+        builtins: non_zero(&resources.builtin_instance_counter),
+        syscalls: non_zero(&resources.syscall_counter),
+    }
+}
+
+/// Subtracts the counts of `other` from `counts`, dropping the entries that reach zero.
+fn subtract_counts(counts: &mut BTreeMap<String, u64>, other: &BTreeMap<String, u64>) {
+    for (name, count) in other {
+        if let Some(value) = counts.get_mut(name) {
+            *value = value.saturating_sub(*count);
+        }
+    }
+    counts.retain(|_, count| *count > 0);
+}
+
+/// Resolves the name of the entry point with the given selector from the ABI of the class.
+fn resolve_frame_name<S: StateProvider + ?Sized>(
+    state: &S,
+    class_hash: ClassHash,
+    selector: FieldElement,
+) -> String {
+    let abi = ContractClassProvider::sierra_class(state, class_hash)
+        .ok()
+        .flatten()
+        .and_then(|class| serde_json::from_str::<Vec<serde_json::Value>>(&class.abi).ok())
+        .unwrap_or_default();
+
+    let contract = contract_path(&abi).unwrap_or_else(|| format!("{class_hash:#x}"));
+
+    let function = abi_function_names(&abi)
+        .chain(KNOWN_ENTRY_POINTS.iter().map(|name| name.to_string()))
+        .find(|name| get_selector_from_name(name).is_ok_and(|s| s == selector))
+        .unwrap_or_else(|| format!("{selector:#x}"));
+
+    format!("{contract}::{function}")
+}
+
+/// Returns the path of the contract module, derived from the name of its event enum (eg.
+/// `dojo_examples::actions::actions::Event`). The contract's own event is always the last one
+/// in the ABI, after the events of its components.
+fn contract_path(abi: &[serde_json::Value]) -> Option<String> {
+    abi.iter()
+        .rev()
+        .filter(|item| item["type"] == "event" && item["kind"] == "enum")
+        .find_map(|item| item["name"].as_str()?.strip_suffix("::Event").map(String::from))
+}
+
+/// Returns the names of all the functions in the ABI, including the ones of its interfaces.
+fn abi_function_names(abi: &[serde_json::Value]) -> impl Iterator<Item = String> + '_ {
+    abi.iter()
+        .flat_map(|item| match item["type"].as_str() {
+            Some("interface") => {
+                item["items"].as_array().map(|i| i.iter().collect()).unwrap_or_default()
+            }
+            _ => vec![item],
+        })
+        .filter(|item| {
+            matches!(item["type"].as_str(), Some("function" | "constructor" | "l1_handler"))
+        })
+        .filter_map(|item| item["name"].as_str().map(String::from))
+}
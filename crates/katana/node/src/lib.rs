@@ -0,0 +1,228 @@
+//! An embeddable Katana node.
+//!
+//! The node runs the sequencer and its JSON-RPC server in the current process, which makes it
+//! possible to boot a devnet from any crate without requiring the `katana` binary.
+//!
+//! ```no_run
+//! # async fn run() -> anyhow::Result<()> {
+//! let node = katana_node::NodeBuilder::new().start().await?;
+//! let client = node.rpc_client();
+//! # Ok(())
+//! # }
+//! ```
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use anyhow::Result;
+use katana_core::accounts::Account;
+pub use katana_core::backend::config::{Environment, StarknetConfig};
+use katana_core::backend::storage::Database;
+pub use katana_core::env::TimeMode;
+use katana_core::pool::TransactionPool;
+use katana_core::sequencer::KatanaSequencer;
+pub use katana_core::sequencer::SequencerConfig;
+use katana_core::service::block_producer::BlockProducer;
+use katana_provider::BlockchainProvider;
+pub use katana_rpc::api::ApiKind;
+pub use katana_rpc::config::ServerConfig;
+use katana_rpc::NodeHandle;
+use starknet::providers::jsonrpc::HttpTransport;
+use starknet::providers::JsonRpcClient;
+use url::Url;
+
+/// The configuration of a [`Node`].
+#[derive(Debug)]
+pub struct NodeConfig {
+    pub sequencer: SequencerConfig,
+    pub starknet: StarknetConfig,
+    pub server: ServerConfig,
+}
+
+impl Default for NodeConfig {
+    /// The server listens on an ephemeral port of the loopback interface, and serves both the
+    /// `starknet` and `katana` APIs.
+    fn default() -> Self {
+        Self {
+            sequencer: SequencerConfig::default(),
+            starknet: StarknetConfig::default(),
+            server: ServerConfig {
+                port: 0,
+                host: "127.0.0.1".into(),
+                apis: vec![ApiKind::Starknet, ApiKind::Katana],
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// Builder for a [`Node`].
+#[derive(Debug, Default)]
+pub struct NodeBuilder {
+    config: NodeConfig,
+}
+
+impl NodeBuilder {
+    /// Creates a builder with the default configuration. See [`NodeConfig::default`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a builder from an existing configuration.
+    pub fn with_config(config: NodeConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn sequencer_config(mut self, config: SequencerConfig) -> Self {
+        self.config.sequencer = config;
+        self
+    }
+
+    pub fn starknet_config(mut self, config: StarknetConfig) -> Self {
+        self.config.starknet = config;
+        self
+    }
+
+    pub fn server_config(mut self, config: ServerConfig) -> Self {
+        self.config.server = config;
+        self
+    }
+
+    /// Mines a block every `block_time` milliseconds, instead of a block per transaction.
+    pub fn block_time(mut self, block_time: u64) -> Self {
+        self.config.sequencer.block_time = Some(block_time);
+        self
+    }
+
+    /// Only mines blocks when requested through the `katana_generateBlock` method.
+    pub fn no_mining(mut self) -> Self {
+        self.config.sequencer.no_mining = true;
+        self
+    }
+
+    /// Sets the seed used to generate the prefunded accounts.
+    pub fn seed(mut self, seed: [u8; 32]) -> Self {
+        self.config.starknet.seed = seed;
+        self
+    }
+
+    /// Sets the number of prefunded accounts.
+    pub fn total_accounts(mut self, total_accounts: u8) -> Self {
+        self.config.starknet.total_accounts = total_accounts;
+        self
+    }
+
+    pub fn disable_fee(mut self, disable_fee: bool) -> Self {
+        self.config.starknet.disable_fee = disable_fee;
+        self
+    }
+
+    pub fn disable_validate(mut self, disable_validate: bool) -> Self {
+        self.config.starknet.disable_validate = disable_validate;
+        self
+    }
+
+    /// Sets the mode in which the timestamps of the blocks advance.
+    pub fn time_mode(mut self, time_mode: TimeMode) -> Self {
+        self.config.starknet.time_mode = time_mode;
+        self
+    }
+
+    /// Produces the same chain for the same transactions, regardless of when they are sent.
+    pub fn deterministic(mut self) -> Self {
+        self.config.starknet.deterministic = true;
+        self
+    }
+
+    pub fn chain_id(mut self, chain_id: impl Into<String>) -> Self {
+        self.config.starknet.env.chain_id = chain_id.into();
+        self
+    }
+
+    /// Sets the port of the JSON-RPC server. A port of `0` lets the OS pick an available one.
+    pub fn port(mut self, port: u16) -> Self {
+        self.config.server.port = port;
+        self
+    }
+
+    /// Sets the APIs served by the JSON-RPC server.
+    pub fn apis(mut self, apis: Vec<ApiKind>) -> Self {
+        self.config.server.apis = apis;
+        self
+    }
+
+    /// Starts the sequencer and its JSON-RPC server. The node is ready to accept requests once
+    /// the returned future resolves.
+    pub async fn start(self) -> Result<Node> {
+        let NodeConfig { sequencer, starknet, server } = self.config;
+
+        let sequencer = Arc::new(KatanaSequencer::new(sequencer, starknet).await);
+        let rpc = katana_rpc::spawn(Arc::clone(&sequencer), server).await?;
+
+        Ok(Node { sequencer, rpc })
+    }
+}
+
+/// A running Katana node.
+pub struct Node {
+    sequencer: Arc<KatanaSequencer>,
+    rpc: NodeHandle,
+}
+
+impl Node {
+    pub fn sequencer(&self) -> &Arc<KatanaSequencer> {
+        &self.sequencer
+    }
+
+    /// Returns the provider of the blockchain data of the node.
+    pub fn provider(&self) -> &BlockchainProvider<Box<dyn Database>> {
+        self.sequencer.backend.blockchain.provider()
+    }
+
+    /// Returns the pool of the transactions waiting to be executed.
+    pub fn pool(&self) -> &Arc<TransactionPool> {
+        &self.sequencer.pool
+    }
+
+    pub fn block_producer(&self) -> &BlockProducer {
+        self.sequencer.block_producer()
+    }
+
+    /// Returns the prefunded accounts of the node.
+    pub fn accounts(&self) -> &[Account] {
+        &self.sequencer.backend.accounts
+    }
+
+    /// Returns the handle of the JSON-RPC server.
+    pub fn rpc(&self) -> &NodeHandle {
+        &self.rpc
+    }
+
+    /// Returns the address the JSON-RPC server is listening on.
+    pub fn addr(&self) -> SocketAddr {
+        self.rpc.addr
+    }
+
+    /// Returns the URL of the JSON-RPC server.
+    pub fn url(&self) -> Url {
+        Url::parse(&format!("http://{}", self.rpc.addr)).expect("valid url")
+    }
+
+    /// Returns a client connected to the JSON-RPC server of the node.
+    pub fn rpc_client(&self) -> JsonRpcClient<HttpTransport> {
+        JsonRpcClient::new(HttpTransport::new(self.url()))
+    }
+
+    /// Stops the JSON-RPC server of the node, and the service that mines its transactions.
+    pub fn stop(&self) -> Result<()> {
+        self.sequencer.stop();
+        Ok(self.rpc.handle.stop()?)
+    }
+
+    /// Waits until the JSON-RPC server of the node and the service that mines its transactions
+    /// have stopped.
+    pub async fn stopped(self) {
+        self.rpc.handle.stopped().await;
+        self.sequencer.stopped().await;
+    }
+}
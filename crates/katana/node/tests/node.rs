@@ -0,0 +1,51 @@
+use katana_node::NodeBuilder;
+use katana_provider::traits::block::BlockNumberProvider;
+use starknet::providers::Provider;
+
+#[tokio::test]
+async fn start_node_on_ephemeral_port() {
+    let node = NodeBuilder::new().total_accounts(2).start().await.unwrap();
+
+    assert_ne!(node.addr().port(), 0);
+    assert_eq!(node.accounts().len(), 2);
+    assert!(node.block_producer().is_instant_mining());
+
+    let latest = BlockNumberProvider::latest_number(node.provider()).unwrap();
+    let block_number = node.rpc_client().block_number().await.unwrap();
+    assert_eq!(block_number, latest);
+
+    let client = node.rpc_client();
+    let sequencer = node.sequencer().clone();
+    assert!(sequencer.is_running());
+
+    node.stop().unwrap();
+    node.stopped().await;
+
+    assert!(!sequencer.is_running());
+    assert!(client.block_number().await.is_err(), "server should be stopped");
+}
+
+#[tokio::test]
+async fn start_multiple_nodes() {
+    let first = NodeBuilder::new().block_time(1000).start().await.unwrap();
+    let second = NodeBuilder::new().no_mining().start().await.unwrap();
+
+    assert_ne!(first.addr(), second.addr());
+    assert!(first.block_producer().is_interval_mining());
+    assert!(first.sequencer().pending_state().is_some());
+    assert!(second.pool().get_transactions().is_empty());
+
+    // stopping a node doesn't affect the other one
+    let (first_sequencer, second_sequencer) =
+        (first.sequencer().clone(), second.sequencer().clone());
+    first.stop().unwrap();
+    first.stopped().await;
+
+    assert!(!first_sequencer.is_running());
+    assert!(second_sequencer.is_running());
+    assert!(second.rpc_client().block_number().await.is_ok());
+
+    second.stop().unwrap();
+    second.stopped().await;
+    assert!(!second_sequencer.is_running());
+}
@@ -1,6 +1,12 @@
 use core::fmt;
 use std::num::ParseIntError;
 
+use starknet::core::utils::starknet_keccak;
+
+use crate::contract::ContractAddress;
+use crate::receipt::Event;
+use crate::FieldElement;
+
 #[derive(PartialEq, Eq, Debug, Default)]
 pub struct ContinuationToken {
     pub block_n: u64,
@@ -39,6 +45,119 @@ impl fmt::Display for ContinuationToken {
     }
 }
 
+/// A filter for the events emitted by transactions.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventFilter {
+    /// The address of the contract that emitted the event.
+    pub address: Option<ContractAddress>,
+    /// The values allowed for each key of the event, by position. An empty list of values matches
+    /// any key at that position, but the event must still have a key there.
+    pub keys: Option<Vec<Vec<FieldElement>>>,
+}
+
+impl EventFilter {
+    /// Returns `true` if the event matches the filter.
+    pub fn matches(&self, event: &Event) -> bool {
+        if self.address.is_some_and(|address| address != event.from_address) {
+            return false;
+        }
+
+        let Some(keys) = &self.keys else { return true };
+
+        // the event must have at least as many keys as the filter, and an empty list of values
+        // accepts any key at its position
+        keys.iter().enumerate().all(|(i, allowed)| {
+            event.keys.get(i).is_some_and(|key| allowed.is_empty() || allowed.contains(key))
+        })
+    }
+}
+
+/// The size of an [`EventBloom`] in bytes.
+pub const EVENT_BLOOM_SIZE: usize = 256;
+
+/// A bloom filter of the events emitted in a block.
+///
+/// The bloom contains the address of every emitting contract and every event key along with its
+/// position, so that blocks which can't contain events matching an [`EventFilter`] can be
+/// skipped without reading their receipts. Each item sets 3 of the 2048 bits of the bloom, in the
+/// same fashion as Ethereum's logs bloom.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct EventBloom(pub [u8; EVENT_BLOOM_SIZE]);
+
+impl Default for EventBloom {
+    fn default() -> Self {
+        Self([0; EVENT_BLOOM_SIZE])
+    }
+}
+
+impl fmt::Debug for EventBloom {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EventBloom(0x")?;
+        self.0.iter().try_for_each(|byte| write!(f, "{byte:02x}"))?;
+        write!(f, ")")
+    }
+}
+
+impl EventBloom {
+    /// Creates a bloom containing all the given events.
+    pub fn from_events<'a>(events: impl IntoIterator<Item = &'a Event>) -> Self {
+        let mut bloom = Self::default();
+        events.into_iter().for_each(|event| bloom.accrue_event(event));
+        bloom
+    }
+
+    /// Adds an event to the bloom.
+    pub fn accrue_event(&mut self, event: &Event) {
+        self.accrue(&Self::address_item(event.from_address));
+        for (i, key) in event.keys.iter().enumerate() {
+            self.accrue(&Self::key_item(i, *key));
+        }
+    }
+
+    /// Returns `false` if the block of this bloom definitely doesn't contain any event matching
+    /// the filter.
+    pub fn may_match(&self, filter: &EventFilter) -> bool {
+        if let Some(address) = filter.address {
+            if !self.contains(&Self::address_item(address)) {
+                return false;
+            }
+        }
+
+        let Some(keys) = &filter.keys else { return true };
+
+        keys.iter().enumerate().all(|(i, allowed)| {
+            allowed.is_empty() || allowed.iter().any(|key| self.contains(&Self::key_item(i, *key)))
+        })
+    }
+
+    fn accrue(&mut self, item: &[u8]) {
+        for bit in Self::bits(item) {
+            self.0[bit / 8] |= 1 << (bit % 8);
+        }
+    }
+
+    fn contains(&self, item: &[u8]) -> bool {
+        Self::bits(item).into_iter().all(|bit| self.0[bit / 8] & (1 << (bit % 8)) != 0)
+    }
+
+    /// Returns the positions of the 3 bits set by the item.
+    fn bits(item: &[u8]) -> [usize; 3] {
+        let hash = starknet_keccak(item).to_bytes_be();
+        // the upper bits of the hash are always zero, so use the lower bytes
+        [30, 28, 26].map(|i| (usize::from(hash[i]) << 8 | usize::from(hash[i + 1])) % 2048)
+    }
+
+    fn address_item(address: ContractAddress) -> Vec<u8> {
+        address.0.to_bytes_be().to_vec()
+    }
+
+    fn key_item(position: usize, key: FieldElement) -> Vec<u8> {
+        let mut item = key.to_bytes_be().to_vec();
+        item.extend_from_slice(&(position as u32).to_be_bytes());
+        item
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -93,4 +212,53 @@ mod test {
             ContinuationTokenError::ParseFailed(_)
         );
     }
+
+    #[test]
+    fn event_filter_matches() {
+        let event = Event {
+            from_address: ContractAddress::from(FieldElement::ONE),
+            keys: vec![FieldElement::TWO, FieldElement::THREE],
+            data: vec![],
+        };
+
+        let filter = |address: Option<u64>, keys: Option<Vec<Vec<u64>>>| EventFilter {
+            address: address.map(|a| FieldElement::from(a).into()),
+            keys: keys
+                .map(|k| k.into_iter().map(|k| k.into_iter().map(Into::into).collect()).collect()),
+        };
+
+        assert!(filter(None, None).matches(&event));
+        assert!(filter(Some(1), None).matches(&event));
+        assert!(!filter(Some(2), None).matches(&event));
+        assert!(filter(Some(1), Some(vec![vec![2]])).matches(&event));
+        assert!(filter(None, Some(vec![vec![], vec![4, 3]])).matches(&event));
+        assert!(!filter(None, Some(vec![vec![3]])).matches(&event));
+        assert!(!filter(None, Some(vec![vec![2], vec![3], vec![4]])).matches(&event));
+        assert!(!filter(None, Some(vec![vec![2], vec![3], vec![]])).matches(&event));
+    }
+
+    #[test]
+    fn event_bloom_may_match() {
+        let event = Event {
+            from_address: ContractAddress::from(FieldElement::ONE),
+            keys: vec![FieldElement::TWO, FieldElement::THREE],
+            data: vec![],
+        };
+
+        let bloom = EventBloom::from_events([&event]);
+        let address = |a: u64| Some(ContractAddress::from(FieldElement::from(a)));
+
+        assert!(bloom.may_match(&EventFilter { address: address(1), keys: None }));
+        assert!(bloom.may_match(&EventFilter {
+            address: address(1),
+            keys: Some(vec![vec![], vec![FieldElement::THREE]]),
+        }));
+        assert!(!bloom.may_match(&EventFilter { address: address(2), keys: None }));
+        // the keys are only contained at their position
+        assert!(!bloom.may_match(&EventFilter {
+            address: None,
+            keys: Some(vec![vec![FieldElement::THREE]]),
+        }));
+        assert!(!EventBloom::default().may_match(&EventFilter { address: address(1), keys: None }));
+    }
 }
@@ -4,6 +4,8 @@ mod block;
 mod receipt;
 mod state_update;
 
+pub mod v0_6;
+
 pub use block::*;
 pub use receipt::*;
 pub use state_update::*;
@@ -0,0 +1,308 @@
+//! Conversions from the types of the v0.5 specification to the ones of the v0.6 specification.
+//!
+//! The RPC types are built for the v0.5 specification, and are then converted to the other
+//! versions, so that the provider is only queried in one place regardless of the version served.
+
+use katana_primitives::FieldElement;
+use katana_rpc_types::block::{MaybePendingBlockWithTxHashes, MaybePendingBlockWithTxs};
+use katana_rpc_types::event::EventsPage;
+use katana_rpc_types::receipt::MaybePendingTxReceipt;
+use katana_rpc_types::{v0_6, FeeEstimate};
+use starknet::core::types::{
+    ExecutionResult, PendingTransactionReceipt, TransactionExecutionStatus,
+    TransactionFinalityStatus, TransactionReceipt,
+};
+
+pub fn block_with_txs(block: MaybePendingBlockWithTxs) -> v0_6::MaybePendingBlockWithTxs {
+    match block {
+        MaybePendingBlockWithTxs::Block(block) => {
+            let block = block.0;
+            v0_6::MaybePendingBlockWithTxs::Block(v0_6::BlockWithTxs {
+                status: block.status,
+                transactions: block.transactions,
+                header: v0_6::BlockHeader {
+                    block_hash: block.block_hash,
+                    parent_hash: block.parent_hash,
+                    block_number: block.block_number,
+                    new_root: block.new_root,
+                    timestamp: block.timestamp,
+                    sequencer_address: block.sequencer_address,
+                    l1_gas_price: resource_price(block.l1_gas_price),
+                    starknet_version: block.starknet_version,
+                },
+            })
+        }
+
+        MaybePendingBlockWithTxs::Pending(block) => {
+            let block = block.0;
+            v0_6::MaybePendingBlockWithTxs::Pending(v0_6::PendingBlockWithTxs {
+                transactions: block.transactions,
+                header: v0_6::PendingBlockHeader {
+                    parent_hash: block.parent_hash,
+                    timestamp: block.timestamp,
+                    sequencer_address: block.sequencer_address,
+                    l1_gas_price: resource_price(block.l1_gas_price),
+                    starknet_version: block.starknet_version,
+                },
+            })
+        }
+    }
+}
+
+pub fn block_with_tx_hashes(
+    block: MaybePendingBlockWithTxHashes,
+) -> v0_6::MaybePendingBlockWithTxHashes {
+    match block {
+        MaybePendingBlockWithTxHashes::Block(block) => {
+            let block = block.0;
+            v0_6::MaybePendingBlockWithTxHashes::Block(v0_6::BlockWithTxHashes {
+                status: block.status,
+                transactions: block.transactions,
+                header: v0_6::BlockHeader {
+                    block_hash: block.block_hash,
+                    parent_hash: block.parent_hash,
+                    block_number: block.block_number,
+                    new_root: block.new_root,
+                    timestamp: block.timestamp,
+                    sequencer_address: block.sequencer_address,
+                    l1_gas_price: resource_price(block.l1_gas_price),
+                    starknet_version: block.starknet_version,
+                },
+            })
+        }
+
+        MaybePendingBlockWithTxHashes::Pending(block) => {
+            let block = block.0;
+            v0_6::MaybePendingBlockWithTxHashes::Pending(v0_6::PendingBlockWithTxHashes {
+                transactions: block.transactions,
+                header: v0_6::PendingBlockHeader {
+                    parent_hash: block.parent_hash,
+                    timestamp: block.timestamp,
+                    sequencer_address: block.sequencer_address,
+                    l1_gas_price: resource_price(block.l1_gas_price),
+                    starknet_version: block.starknet_version,
+                },
+            })
+        }
+    }
+}
+
+pub fn receipt(receipt: MaybePendingTxReceipt) -> v0_6::TxReceipt {
+    match receipt {
+        MaybePendingTxReceipt::Receipt(receipt) => match receipt.0 {
+            TransactionReceipt::Invoke(r) => v0_6::TxReceipt {
+                block_hash: Some(r.block_hash),
+                block_number: Some(r.block_number),
+                finality_status: r.finality_status,
+                ..receipt_common(
+                    v0_6::TxReceiptType::Invoke,
+                    r.transaction_hash,
+                    r.actual_fee,
+                    r.execution_result,
+                    r.messages_sent,
+                    r.events,
+                    r.execution_resources,
+                )
+            },
+
+            TransactionReceipt::Declare(r) => v0_6::TxReceipt {
+                block_hash: Some(r.block_hash),
+                block_number: Some(r.block_number),
+                finality_status: r.finality_status,
+                ..receipt_common(
+                    v0_6::TxReceiptType::Declare,
+                    r.transaction_hash,
+                    r.actual_fee,
+                    r.execution_result,
+                    r.messages_sent,
+                    r.events,
+                    r.execution_resources,
+                )
+            },
+
+            TransactionReceipt::Deploy(r) => v0_6::TxReceipt {
+                block_hash: Some(r.block_hash),
+                block_number: Some(r.block_number),
+                finality_status: r.finality_status,
+                contract_address: Some(r.contract_address),
+                ..receipt_common(
+                    v0_6::TxReceiptType::Deploy,
+                    r.transaction_hash,
+                    r.actual_fee,
+                    r.execution_result,
+                    r.messages_sent,
+                    r.events,
+                    r.execution_resources,
+                )
+            },
+
+            TransactionReceipt::DeployAccount(r) => v0_6::TxReceipt {
+                block_hash: Some(r.block_hash),
+                block_number: Some(r.block_number),
+                finality_status: r.finality_status,
+                contract_address: Some(r.contract_address),
+                ..receipt_common(
+                    v0_6::TxReceiptType::DeployAccount,
+                    r.transaction_hash,
+                    r.actual_fee,
+                    r.execution_result,
+                    r.messages_sent,
+                    r.events,
+                    r.execution_resources,
+                )
+            },
+
+            TransactionReceipt::L1Handler(r) => v0_6::TxReceipt {
+                block_hash: Some(r.block_hash),
+                block_number: Some(r.block_number),
+                finality_status: r.finality_status,
+                message_hash: Some(r.message_hash),
+                ..receipt_common(
+                    v0_6::TxReceiptType::L1Handler,
+                    r.transaction_hash,
+                    r.actual_fee,
+                    r.execution_result,
+                    r.messages_sent,
+                    r.events,
+                    r.execution_resources,
+                )
+            },
+        },
+
+        MaybePendingTxReceipt::Pending(receipt) => match receipt.0 {
+            PendingTransactionReceipt::Invoke(r) => receipt_common(
+                v0_6::TxReceiptType::Invoke,
+                r.transaction_hash,
+                r.actual_fee,
+                r.execution_result,
+                r.messages_sent,
+                r.events,
+                r.execution_resources,
+            ),
+
+            PendingTransactionReceipt::Declare(r) => receipt_common(
+                v0_6::TxReceiptType::Declare,
+                r.transaction_hash,
+                r.actual_fee,
+                r.execution_result,
+                r.messages_sent,
+                r.events,
+                r.execution_resources,
+            ),
+
+            PendingTransactionReceipt::DeployAccount(r) => v0_6::TxReceipt {
+                contract_address: Some(r.contract_address),
+                ..receipt_common(
+                    v0_6::TxReceiptType::DeployAccount,
+                    r.transaction_hash,
+                    r.actual_fee,
+                    r.execution_result,
+                    r.messages_sent,
+                    r.events,
+                    r.execution_resources,
+                )
+            },
+
+            PendingTransactionReceipt::L1Handler(r) => v0_6::TxReceipt {
+                message_hash: Some(r.message_hash),
+                ..receipt_common(
+                    v0_6::TxReceiptType::L1Handler,
+                    r.transaction_hash,
+                    r.actual_fee,
+                    r.execution_result,
+                    r.messages_sent,
+                    r.events,
+                    r.execution_resources,
+                )
+            },
+        },
+    }
+}
+
+pub fn fee_estimate(estimate: FeeEstimate) -> v0_6::FeeEstimate {
+    v0_6::FeeEstimate {
+        gas_consumed: estimate.gas_consumed.into(),
+        gas_price: estimate.gas_price.into(),
+        overall_fee: estimate.overall_fee.into(),
+        unit: v0_6::PriceUnit::Wei,
+    }
+}
+
+pub fn events_page(page: EventsPage) -> v0_6::EventsPage {
+    let events = page
+        .events
+        .into_iter()
+        .map(|event| {
+            // events of the pending block are reported with a zero block hash
+            let is_pending = event.block_hash == FieldElement::ZERO;
+            v0_6::EmittedEvent {
+                from_address: event.from_address,
+                keys: event.keys,
+                data: event.data,
+                block_hash: (!is_pending).then_some(event.block_hash),
+                block_number: (!is_pending).then_some(event.block_number),
+                transaction_hash: event.transaction_hash,
+            }
+        })
+        .collect();
+
+    v0_6::EventsPage { events, continuation_token: page.continuation_token }
+}
+
+fn resource_price(price: starknet::core::types::ResourcePrice) -> v0_6::ResourcePrice {
+    v0_6::ResourcePrice {
+        price_in_fri: price.price_in_strk.unwrap_or_default().into(),
+        price_in_wei: price.price_in_wei.into(),
+    }
+}
+
+fn execution_resources(
+    resources: starknet::core::types::ExecutionResources,
+) -> v0_6::ExecutionResources {
+    let non_zero = |count: u64| (count > 0).then_some(count);
+    v0_6::ExecutionResources {
+        steps: resources.steps,
+        memory_holes: resources.memory_holes,
+        range_check_builtin_applications: non_zero(resources.range_check_builtin_applications),
+        pedersen_builtin_applications: non_zero(resources.pedersen_builtin_applications),
+        poseidon_builtin_applications: non_zero(resources.poseidon_builtin_applications),
+        ec_op_builtin_applications: non_zero(resources.ec_op_builtin_applications),
+        ecdsa_builtin_applications: non_zero(resources.ecdsa_builtin_applications),
+        bitwise_builtin_applications: non_zero(resources.bitwise_builtin_applications),
+        keccak_builtin_applications: non_zero(resources.keccak_builtin_applications),
+    }
+}
+
+/// Builds a receipt of a pending transaction from the fields shared by all receipt types.
+fn receipt_common(
+    r#type: v0_6::TxReceiptType,
+    transaction_hash: FieldElement,
+    actual_fee: FieldElement,
+    execution_result: ExecutionResult,
+    messages_sent: Vec<starknet::core::types::MsgToL1>,
+    events: Vec<starknet::core::types::Event>,
+    resources: starknet::core::types::ExecutionResources,
+) -> v0_6::TxReceipt {
+    let (execution_status, revert_reason) = match execution_result {
+        ExecutionResult::Succeeded => (TransactionExecutionStatus::Succeeded, None),
+        ExecutionResult::Reverted { reason } => {
+            (TransactionExecutionStatus::Reverted, Some(reason))
+        }
+    };
+
+    v0_6::TxReceipt {
+        r#type,
+        transaction_hash,
+        events,
+        messages_sent,
+        revert_reason,
+        execution_status,
+        actual_fee: v0_6::FeePayment { amount: actual_fee, unit: v0_6::PriceUnit::Wei },
+        finality_status: TransactionFinalityStatus::AcceptedOnL2,
+        block_hash: None,
+        block_number: None,
+        execution_resources: execution_resources(resources),
+        contract_address: None,
+        message_hash: None,
+    }
+}
@@ -7,7 +7,7 @@ pub type BlockTxCount = u64;
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(transparent)]
-pub struct BlockWithTxs(starknet::core::types::BlockWithTxs);
+pub struct BlockWithTxs(pub starknet::core::types::BlockWithTxs);
 
 impl BlockWithTxs {
     pub fn new(block_hash: BlockHash, block: Block, finality_status: FinalityStatus) -> Self {
@@ -39,7 +39,7 @@ impl BlockWithTxs {
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(transparent)]
-pub struct PendingBlockWithTxs(starknet::core::types::PendingBlockWithTxs);
+pub struct PendingBlockWithTxs(pub starknet::core::types::PendingBlockWithTxs);
 
 impl PendingBlockWithTxs {
     pub fn new(header: PartialHeader, transactions: Vec<TxWithHash>) -> Self {
@@ -71,7 +71,7 @@ pub enum MaybePendingBlockWithTxs {
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(transparent)]
-pub struct BlockWithTxHashes(starknet::core::types::BlockWithTxHashes);
+pub struct BlockWithTxHashes(pub starknet::core::types::BlockWithTxHashes);
 
 impl BlockWithTxHashes {
     pub fn new(
@@ -104,7 +104,7 @@ impl BlockWithTxHashes {
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(transparent)]
-pub struct PendingBlockWithTxHashes(starknet::core::types::PendingBlockWithTxHashes);
+pub struct PendingBlockWithTxHashes(pub starknet::core::types::PendingBlockWithTxHashes);
 
 impl PendingBlockWithTxHashes {
     pub fn new(header: PartialHeader, transactions: Vec<TxHash>) -> Self {
@@ -9,6 +9,7 @@ pub mod message;
 pub mod receipt;
 pub mod state_update;
 pub mod transaction;
+pub mod v0_6;
 
 use std::ops::Deref;
 
@@ -12,7 +12,7 @@ use starknet::core::types::{
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(transparent)]
-pub struct TxReceipt(starknet::core::types::TransactionReceipt);
+pub struct TxReceipt(pub starknet::core::types::TransactionReceipt);
 
 impl TxReceipt {
     pub fn new(
@@ -125,7 +125,7 @@ impl TxReceipt {
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(transparent)]
-pub struct PendingTxReceipt(starknet::core::types::PendingTransactionReceipt);
+pub struct PendingTxReceipt(pub starknet::core::types::PendingTransactionReceipt);
 
 impl PendingTxReceipt {
     pub fn new(transaction_hash: TxHash, receipt: Receipt) -> Self {
@@ -1,3 +1,4 @@
+use katana_primitives::FieldElement;
 use serde::Serialize;
 use starknet::core::types::{
     ContractStorageDiffItem, DeclaredClassItem, DeployedContractItem, NonceUpdate, StorageEntry,
@@ -18,6 +19,12 @@ pub struct StateUpdate(starknet::core::types::StateUpdate);
 #[serde(transparent)]
 pub struct PendingStateUpdate(starknet::core::types::PendingStateUpdate);
 
+impl PendingStateUpdate {
+    pub fn new(old_root: FieldElement, state_diff: StateDiff) -> Self {
+        Self(starknet::core::types::PendingStateUpdate { old_root, state_diff: state_diff.0 })
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(transparent)]
 pub struct StateDiff(pub starknet::core::types::StateDiff);
@@ -0,0 +1,234 @@
+//! Types of the v0.6 JSON-RPC specification.
+//!
+//! Only the types whose wire format differs from the v0.5 specification are defined here, the
+//! other ones are shared between the two versions.
+
+use katana_primitives::block::{BlockHash, BlockNumber};
+use katana_primitives::transaction::TxHash;
+use katana_primitives::FieldElement;
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use starknet::core::serde::unsigned_field_element::UfeHex;
+use starknet::core::types::{
+    BlockStatus, Event, Hash256, MsgToL1, Transaction, TransactionExecutionStatus,
+    TransactionFinalityStatus,
+};
+
+/// The version of the specification.
+pub const SPEC_VERSION: &str = "0.6.0";
+
+/// The unit in which a fee or a price is denominated.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PriceUnit {
+    #[serde(rename = "WEI")]
+    Wei,
+    #[serde(rename = "FRI")]
+    Fri,
+}
+
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ResourcePrice {
+    #[serde_as(as = "UfeHex")]
+    pub price_in_fri: FieldElement,
+    #[serde_as(as = "UfeHex")]
+    pub price_in_wei: FieldElement,
+}
+
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BlockHeader {
+    #[serde_as(as = "UfeHex")]
+    pub block_hash: BlockHash,
+    #[serde_as(as = "UfeHex")]
+    pub parent_hash: BlockHash,
+    pub block_number: BlockNumber,
+    #[serde_as(as = "UfeHex")]
+    pub new_root: FieldElement,
+    pub timestamp: u64,
+    #[serde_as(as = "UfeHex")]
+    pub sequencer_address: FieldElement,
+    pub l1_gas_price: ResourcePrice,
+    pub starknet_version: String,
+}
+
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PendingBlockHeader {
+    #[serde_as(as = "UfeHex")]
+    pub parent_hash: BlockHash,
+    pub timestamp: u64,
+    #[serde_as(as = "UfeHex")]
+    pub sequencer_address: FieldElement,
+    pub l1_gas_price: ResourcePrice,
+    pub starknet_version: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BlockWithTxs {
+    pub status: BlockStatus,
+    #[serde(flatten)]
+    pub header: BlockHeader,
+    pub transactions: Vec<Transaction>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingBlockWithTxs {
+    #[serde(flatten)]
+    pub header: PendingBlockHeader,
+    pub transactions: Vec<Transaction>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum MaybePendingBlockWithTxs {
+    Pending(PendingBlockWithTxs),
+    Block(BlockWithTxs),
+}
+
+#[serde_as]
+#[derive(Debug, Clone, Serialize)]
+pub struct BlockWithTxHashes {
+    pub status: BlockStatus,
+    #[serde(flatten)]
+    pub header: BlockHeader,
+    #[serde_as(as = "Vec<UfeHex>")]
+    pub transactions: Vec<TxHash>,
+}
+
+#[serde_as]
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingBlockWithTxHashes {
+    #[serde(flatten)]
+    pub header: PendingBlockHeader,
+    #[serde_as(as = "Vec<UfeHex>")]
+    pub transactions: Vec<TxHash>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum MaybePendingBlockWithTxHashes {
+    Pending(PendingBlockWithTxHashes),
+    Block(BlockWithTxHashes),
+}
+
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FeePayment {
+    #[serde_as(as = "UfeHex")]
+    pub amount: FieldElement,
+    pub unit: PriceUnit,
+}
+
+/// The resources consumed by a transaction. Builtins that weren't used are omitted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ExecutionResources {
+    pub steps: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_holes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range_check_builtin_applications: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pedersen_builtin_applications: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub poseidon_builtin_applications: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ec_op_builtin_applications: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ecdsa_builtin_applications: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bitwise_builtin_applications: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keccak_builtin_applications: Option<u64>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub enum TxReceiptType {
+    #[serde(rename = "INVOKE")]
+    Invoke,
+    #[serde(rename = "DECLARE")]
+    Declare,
+    #[serde(rename = "DEPLOY")]
+    Deploy,
+    #[serde(rename = "DEPLOY_ACCOUNT")]
+    DeployAccount,
+    #[serde(rename = "L1_HANDLER")]
+    L1Handler,
+}
+
+/// The receipt of a transaction. The block fields are omitted for transactions of the pending
+/// block.
+#[serde_as]
+#[derive(Debug, Clone, Serialize)]
+pub struct TxReceipt {
+    #[serde(rename = "type")]
+    pub r#type: TxReceiptType,
+    #[serde_as(as = "UfeHex")]
+    pub transaction_hash: TxHash,
+    pub actual_fee: FeePayment,
+    pub finality_status: TransactionFinalityStatus,
+    pub execution_status: TransactionExecutionStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revert_reason: Option<String>,
+    #[serde_as(as = "Option<UfeHex>")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_hash: Option<BlockHash>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_number: Option<BlockNumber>,
+    pub messages_sent: Vec<MsgToL1>,
+    pub events: Vec<Event>,
+    pub execution_resources: ExecutionResources,
+    /// The address of the deployed contract, for `DEPLOY` and `DEPLOY_ACCOUNT` transactions.
+    #[serde_as(as = "Option<UfeHex>")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contract_address: Option<FieldElement>,
+    /// The hash of the L1 message, for `L1_HANDLER` transactions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_hash: Option<Hash256>,
+}
+
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FeeEstimate {
+    #[serde_as(as = "UfeHex")]
+    pub gas_consumed: FieldElement,
+    #[serde_as(as = "UfeHex")]
+    pub gas_price: FieldElement,
+    #[serde_as(as = "UfeHex")]
+    pub overall_fee: FieldElement,
+    pub unit: PriceUnit,
+}
+
+/// Flags that control how the fee of transactions is estimated.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SimulationFlagForEstimateFee {
+    #[serde(rename = "SKIP_VALIDATE")]
+    SkipValidate,
+}
+
+/// An event emitted by a transaction. The block fields are omitted for events of the pending
+/// block.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EmittedEvent {
+    #[serde_as(as = "UfeHex")]
+    pub from_address: FieldElement,
+    #[serde_as(as = "Vec<UfeHex>")]
+    pub keys: Vec<FieldElement>,
+    #[serde_as(as = "Vec<UfeHex>")]
+    pub data: Vec<FieldElement>,
+    #[serde_as(as = "Option<UfeHex>")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_hash: Option<BlockHash>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_number: Option<BlockNumber>,
+    #[serde_as(as = "UfeHex")]
+    pub transaction_hash: TxHash,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EventsPage {
+    pub events: Vec<EmittedEvent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub continuation_token: Option<String>,
+}
@@ -3,6 +3,12 @@ use jsonrpsee::proc_macros::rpc;
 use jsonrpsee::types::error::CallError;
 use jsonrpsee::types::ErrorObject;
 use katana_core::accounts::Account;
+use katana_core::env::TimeMode;
+use katana_core::replay::ReplayOutcome;
+use katana_core::service::block_producer::MiningMode;
+use katana_executor::profile::TransactionProfile;
+use katana_primitives::block::BlockIdOrTag;
+use katana_rpc_types::transaction::Tx;
 use starknet::core::types::FieldElement;
 
 #[derive(thiserror::Error, Clone, Copy, Debug)]
@@ -14,6 +20,12 @@ pub enum KatanaApiError {
     FailedToDumpState = 2,
     #[error("Failed to update storage.")]
     FailedToUpdateStorage = 3,
+    #[error("Transaction not found.")]
+    TransactionNotFound = 4,
+    #[error("Failed to profile transaction.")]
+    FailedToProfileTransaction = 5,
+    #[error("Failed to replay transactions.")]
+    FailedToReplay = 6,
 }
 
 impl From<KatanaApiError> for Error {
@@ -36,6 +48,14 @@ pub trait KatanaApi {
     #[method(name = "increaseNextBlockTimestamp")]
     async fn increase_next_block_timestamp(&self, timestamp: u64) -> Result<(), Error>;
 
+    #[method(name = "timeMode")]
+    async fn time_mode(&self) -> Result<TimeMode, Error>;
+
+    /// Switches the mode in which the timestamps of the blocks advance, starting from the next
+    /// block.
+    #[method(name = "setTimeMode")]
+    async fn set_time_mode(&self, mode: TimeMode) -> Result<(), Error>;
+
     #[method(name = "predeployedAccounts")]
     async fn predeployed_accounts(&self) -> Result<Vec<Account>, Error>;
 
@@ -46,4 +66,52 @@ pub trait KatanaApi {
         key: FieldElement,
         value: FieldElement,
     ) -> Result<(), Error>;
+
+    /// Returns the per-entry point steps, builtins and syscalls profile of a transaction.
+    #[method(name = "transactionProfile")]
+    async fn transaction_profile(
+        &self,
+        transaction_hash: FieldElement,
+    ) -> Result<TransactionProfile, Error>;
+
+    /// Returns the profile of a transaction in the folded stacks format, which can be fed to
+    /// flamegraph tools.
+    #[method(name = "transactionProfileFolded")]
+    async fn transaction_profile_folded(
+        &self,
+        transaction_hash: FieldElement,
+    ) -> Result<String, Error>;
+
+    /// Re-executes a mined transaction on top of the state of its parent block, and returns the
+    /// differences between the replayed receipt and the stored one.
+    #[method(name = "replayTransaction")]
+    async fn replay_transaction(
+        &self,
+        transaction_hash: FieldElement,
+    ) -> Result<ReplayOutcome, Error>;
+
+    /// Re-executes all the transactions of a mined block on top of the state of its parent
+    /// block, and returns the differences between the replayed receipts and state update and the
+    /// stored ones.
+    #[method(name = "replayBlock")]
+    async fn replay_block(&self, block_id: BlockIdOrTag) -> Result<ReplayOutcome, Error>;
+
+    /// Returns the transactions that haven't been mined yet: the ones executed in the pending
+    /// block, followed by the ones waiting to be executed.
+    #[method(name = "pendingTransactions")]
+    async fn pending_transactions(&self) -> Result<Vec<Tx>, Error>;
+
+    /// Drops a transaction that hasn't been mined yet. Dropping a transaction executed in the
+    /// pending block re-executes the block without it. Returns `false` if the transaction isn't
+    /// found among the unmined ones, or is part of a block being mined.
+    #[method(name = "dropTransaction")]
+    async fn drop_transaction(&self, transaction_hash: FieldElement) -> Result<bool, Error>;
+
+    /// Drops all the transactions that haven't been mined yet, and returns their number.
+    #[method(name = "clearPool")]
+    async fn clear_pool(&self) -> Result<usize, Error>;
+
+    /// Switches the mode in which blocks are mined. The pending block, if any, is mined first.
+    #[method(name = "setMiningMode")]
+    async fn set_mining_mode(&self, mode: MiningMode) -> Result<(), Error>;
 }
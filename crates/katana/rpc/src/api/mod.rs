@@ -1,5 +1,6 @@
 pub mod katana;
 pub mod starknet;
+pub mod v0_6;
 
 /// List of APIs supported by Katana.
 #[derive(Debug, Copy, Clone)]
@@ -10,7 +10,7 @@ use katana_rpc_types::block::{
 use katana_rpc_types::event::{EventFilterWithPage, EventsPage};
 use katana_rpc_types::message::MsgFromL1;
 use katana_rpc_types::receipt::MaybePendingTxReceipt;
-use katana_rpc_types::state_update::StateUpdate;
+use katana_rpc_types::state_update::MaybePendingStateUpdate;
 use katana_rpc_types::transaction::{
     BroadcastedDeclareTx, BroadcastedDeployAccountTx, BroadcastedInvokeTx, BroadcastedTx,
     DeclareTxResult, DeployAccountTxResult, InvokeTxResult, Tx,
@@ -191,7 +191,7 @@ pub trait StarknetApi {
     ) -> Result<MaybePendingBlockWithTxs, Error>;
 
     #[method(name = "getStateUpdate")]
-    async fn state_update(&self, block_id: BlockIdOrTag) -> Result<StateUpdate, Error>;
+    async fn state_update(&self, block_id: BlockIdOrTag) -> Result<MaybePendingStateUpdate, Error>;
 
     #[method(name = "getTransactionReceipt")]
     async fn transaction_receipt(
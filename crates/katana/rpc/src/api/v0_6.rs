@@ -0,0 +1,161 @@
+//! The Starknet API of the v0.6 specification.
+//!
+//! The methods are registered under an internal namespace, and are served under the `starknet`
+//! namespace at the `/rpc/v0_6` path. See [`crate::version`].
+
+use jsonrpsee::core::Error;
+use jsonrpsee::proc_macros::rpc;
+use katana_primitives::block::{BlockIdOrTag, BlockNumber};
+use katana_primitives::transaction::TxHash;
+use katana_primitives::FieldElement;
+use katana_rpc_types::block::{BlockHashAndNumber, BlockTxCount};
+use katana_rpc_types::event::EventFilterWithPage;
+use katana_rpc_types::message::MsgFromL1;
+use katana_rpc_types::state_update::MaybePendingStateUpdate;
+use katana_rpc_types::transaction::{
+    BroadcastedDeclareTx, BroadcastedDeployAccountTx, BroadcastedInvokeTx, BroadcastedTx,
+    DeclareTxResult, DeployAccountTxResult, InvokeTxResult, Tx,
+};
+use katana_rpc_types::v0_6::{
+    EventsPage, FeeEstimate, MaybePendingBlockWithTxHashes, MaybePendingBlockWithTxs,
+    SimulationFlagForEstimateFee, TxReceipt, SPEC_VERSION,
+};
+use katana_rpc_types::{ContractClass, FeltAsHex, FunctionCall};
+use starknet::core::types::TransactionStatus;
+
+#[rpc(server, namespace = "starknetV0_6")]
+pub trait StarknetApiV0_6 {
+    // Read API
+
+    #[method(name = "specVersion")]
+    async fn spec_version(&self) -> Result<String, Error> {
+        Ok(SPEC_VERSION.into())
+    }
+
+    #[method(name = "chainId")]
+    async fn chain_id(&self) -> Result<FeltAsHex, Error>;
+
+    #[method(name = "getNonce")]
+    async fn nonce(
+        &self,
+        block_id: BlockIdOrTag,
+        contract_address: FieldElement,
+    ) -> Result<FeltAsHex, Error>;
+
+    #[method(name = "blockNumber")]
+    async fn block_number(&self) -> Result<BlockNumber, Error>;
+
+    #[method(name = "getTransactionByHash")]
+    async fn transaction_by_hash(&self, transaction_hash: TxHash) -> Result<Tx, Error>;
+
+    #[method(name = "getBlockTransactionCount")]
+    async fn block_transaction_count(&self, block_id: BlockIdOrTag) -> Result<BlockTxCount, Error>;
+
+    #[method(name = "getClassAt")]
+    async fn class_at(
+        &self,
+        block_id: BlockIdOrTag,
+        contract_address: FieldElement,
+    ) -> Result<ContractClass, Error>;
+
+    #[method(name = "blockHashAndNumber")]
+    async fn block_hash_and_number(&self) -> Result<BlockHashAndNumber, Error>;
+
+    #[method(name = "getBlockWithTxHashes")]
+    async fn block_with_tx_hashes(
+        &self,
+        block_id: BlockIdOrTag,
+    ) -> Result<MaybePendingBlockWithTxHashes, Error>;
+
+    #[method(name = "getTransactionByBlockIdAndIndex")]
+    async fn transaction_by_block_id_and_index(
+        &self,
+        block_id: BlockIdOrTag,
+        index: u64,
+    ) -> Result<Tx, Error>;
+
+    #[method(name = "getBlockWithTxs")]
+    async fn block_with_txs(
+        &self,
+        block_id: BlockIdOrTag,
+    ) -> Result<MaybePendingBlockWithTxs, Error>;
+
+    #[method(name = "getStateUpdate")]
+    async fn state_update(&self, block_id: BlockIdOrTag) -> Result<MaybePendingStateUpdate, Error>;
+
+    #[method(name = "getTransactionReceipt")]
+    async fn transaction_receipt(&self, transaction_hash: TxHash) -> Result<TxReceipt, Error>;
+
+    #[method(name = "getTransactionStatus")]
+    async fn transaction_status(
+        &self,
+        transaction_hash: TxHash,
+    ) -> Result<TransactionStatus, Error>;
+
+    #[method(name = "getClassHashAt")]
+    async fn class_hash_at(
+        &self,
+        block_id: BlockIdOrTag,
+        contract_address: FieldElement,
+    ) -> Result<FeltAsHex, Error>;
+
+    #[method(name = "getClass")]
+    async fn class(
+        &self,
+        block_id: BlockIdOrTag,
+        class_hash: FieldElement,
+    ) -> Result<ContractClass, Error>;
+
+    #[method(name = "getEvents")]
+    async fn events(&self, filter: EventFilterWithPage) -> Result<EventsPage, Error>;
+
+    #[method(name = "estimateFee")]
+    async fn estimate_fee(
+        &self,
+        request: Vec<BroadcastedTx>,
+        simulation_flags: Vec<SimulationFlagForEstimateFee>,
+        block_id: BlockIdOrTag,
+    ) -> Result<Vec<FeeEstimate>, Error>;
+
+    #[method(name = "estimateMessageFee")]
+    async fn estimate_message_fee(
+        &self,
+        message: MsgFromL1,
+        block_id: BlockIdOrTag,
+    ) -> Result<FeeEstimate, Error>;
+
+    #[method(name = "call")]
+    async fn call(
+        &self,
+        request: FunctionCall,
+        block_id: BlockIdOrTag,
+    ) -> Result<Vec<FeltAsHex>, Error>;
+
+    #[method(name = "getStorageAt")]
+    async fn storage_at(
+        &self,
+        contract_address: FieldElement,
+        key: FieldElement,
+        block_id: BlockIdOrTag,
+    ) -> Result<FeltAsHex, Error>;
+
+    // Write API
+
+    #[method(name = "addDeployAccountTransaction")]
+    async fn add_deploy_account_transaction(
+        &self,
+        deploy_account_transaction: BroadcastedDeployAccountTx,
+    ) -> Result<DeployAccountTxResult, Error>;
+
+    #[method(name = "addDeclareTransaction")]
+    async fn add_declare_transaction(
+        &self,
+        declare_transaction: BroadcastedDeclareTx,
+    ) -> Result<DeclareTxResult, Error>;
+
+    #[method(name = "addInvokeTransaction")]
+    async fn add_invoke_transaction(
+        &self,
+        invoke_transaction: BroadcastedInvokeTx,
+    ) -> Result<InvokeTxResult, Error>;
+}
@@ -1,11 +1,38 @@
+use std::collections::HashSet;
+use std::net::IpAddr;
+use std::time::Duration;
+
 use crate::api::ApiKind;
 
+/// The default maximum size of a request or a response body, in bytes.
+pub const DEFAULT_MAX_BODY_SIZE: u32 = 10 * 1024 * 1024;
+/// The default maximum time allowed to process a request.
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
 #[derive(Debug, Clone)]
 pub struct ServerConfig {
     pub port: u16,
     pub host: String,
     pub max_connections: u32,
     pub apis: Vec<ApiKind>,
+    /// The origins allowed to make cross-origin requests. Any origin is allowed if `None`.
+    pub allowed_origins: Option<Vec<String>>,
+    /// The maximum number of calls per second accepted from a single client address.
+    pub rate_limit: Option<u32>,
+    /// The addresses of the reverse proxies trusted to forward the address of the clients.
+    pub trusted_proxies: Vec<IpAddr>,
+    /// The bearer token required to call the methods of the `katana` namespace.
+    pub auth_token: Option<String>,
+    /// Restricts the methods that can be called.
+    pub method_filter: MethodFilter,
+    /// The maximum time allowed to process a request.
+    pub request_timeout: Duration,
+    /// The maximum size of a request body, in bytes.
+    pub max_request_body_size: u32,
+    /// The maximum size of a response body, in bytes.
+    pub max_response_body_size: u32,
+    /// The maximum number of calls in a batch request. Unlimited if `None`.
+    pub max_batch_size: Option<usize>,
 }
 
 impl ServerConfig {
@@ -13,3 +40,46 @@ impl ServerConfig {
         format!("{}:{}", self.host, self.port)
     }
 }
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            port: 5050,
+            host: "0.0.0.0".into(),
+            max_connections: 100,
+            apis: vec![ApiKind::Starknet],
+            allowed_origins: None,
+            rate_limit: None,
+            trusted_proxies: Vec::new(),
+            auth_token: None,
+            method_filter: MethodFilter::All,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            max_request_body_size: DEFAULT_MAX_BODY_SIZE,
+            max_response_body_size: DEFAULT_MAX_BODY_SIZE,
+            max_batch_size: None,
+        }
+    }
+}
+
+/// Filter on the names of the methods that can be called.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum MethodFilter {
+    /// All methods can be called.
+    #[default]
+    All,
+    /// Only the listed methods can be called.
+    Allow(HashSet<String>),
+    /// All methods but the listed ones can be called.
+    Deny(HashSet<String>),
+}
+
+impl MethodFilter {
+    /// Returns `true` if the method is allowed to be called.
+    pub fn is_allowed(&self, method: &str) -> bool {
+        match self {
+            Self::All => true,
+            Self::Allow(methods) => methods.contains(method),
+            Self::Deny(methods) => !methods.contains(method),
+        }
+    }
+}
@@ -2,8 +2,14 @@ use std::sync::Arc;
 
 use jsonrpsee::core::{async_trait, Error};
 use katana_core::accounts::Account;
+use katana_core::env::TimeMode;
+use katana_core::replay::ReplayOutcome;
 use katana_core::sequencer::KatanaSequencer;
+use katana_core::service::block_producer::MiningMode;
+use katana_executor::profile::TransactionProfile;
+use katana_primitives::block::BlockIdOrTag;
 use katana_primitives::FieldElement;
+use katana_rpc_types::transaction::Tx;
 
 use crate::api::katana::{KatanaApiError, KatanaApiServer};
 
@@ -40,6 +46,15 @@ impl KatanaApiServer for KatanaApi {
             .map_err(|_| Error::from(KatanaApiError::FailedToChangeNextBlockTimestamp))
     }
 
+    async fn time_mode(&self) -> Result<TimeMode, Error> {
+        Ok(self.sequencer.time_mode())
+    }
+
+    async fn set_time_mode(&self, mode: TimeMode) -> Result<(), Error> {
+        self.sequencer.set_time_mode(mode);
+        Ok(())
+    }
+
     async fn predeployed_accounts(&self) -> Result<Vec<Account>, Error> {
         Ok(self.sequencer.backend().accounts.clone())
     }
@@ -56,4 +71,55 @@ impl KatanaApiServer for KatanaApi {
         //     .map_err(|_| Error::from(KatanaApiError::FailedToUpdateStorage))
         Ok(())
     }
+
+    async fn transaction_profile(
+        &self,
+        transaction_hash: FieldElement,
+    ) -> Result<TransactionProfile, Error> {
+        self.sequencer
+            .transaction_profile(transaction_hash)
+            .map_err(|_| Error::from(KatanaApiError::FailedToProfileTransaction))?
+            .ok_or(Error::from(KatanaApiError::TransactionNotFound))
+    }
+
+    async fn transaction_profile_folded(
+        &self,
+        transaction_hash: FieldElement,
+    ) -> Result<String, Error> {
+        let profile = self.transaction_profile(transaction_hash).await?;
+        Ok(profile.to_folded())
+    }
+
+    async fn replay_transaction(
+        &self,
+        transaction_hash: FieldElement,
+    ) -> Result<ReplayOutcome, Error> {
+        self.sequencer
+            .replay_transaction(transaction_hash)
+            .map_err(|_| Error::from(KatanaApiError::FailedToReplay))?
+            .ok_or(Error::from(KatanaApiError::TransactionNotFound))
+    }
+
+    async fn replay_block(&self, block_id: BlockIdOrTag) -> Result<ReplayOutcome, Error> {
+        self.sequencer
+            .replay_block(block_id)
+            .map_err(|_| Error::from(KatanaApiError::FailedToReplay))
+    }
+
+    async fn pending_transactions(&self) -> Result<Vec<Tx>, Error> {
+        Ok(self.sequencer.unmined_transactions().into_iter().map(Tx::from).collect())
+    }
+
+    async fn drop_transaction(&self, transaction_hash: FieldElement) -> Result<bool, Error> {
+        Ok(self.sequencer.drop_transaction(transaction_hash))
+    }
+
+    async fn clear_pool(&self) -> Result<usize, Error> {
+        Ok(self.sequencer.clear_pool())
+    }
+
+    async fn set_mining_mode(&self, mode: MiningMode) -> Result<(), Error> {
+        self.sequencer.set_mining_mode(mode).await;
+        Ok(())
+    }
 }
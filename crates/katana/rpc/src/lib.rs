@@ -1,15 +1,19 @@
 pub mod api;
 pub mod config;
 pub mod katana;
+pub mod middleware;
 pub mod starknet;
+pub mod v0_6;
+pub mod version;
 
 use std::net::SocketAddr;
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::Instant;
 
 use anyhow::Result;
 use api::ApiKind;
 use config::ServerConfig;
+use hyper::header::{HeaderValue, AUTHORIZATION, CONTENT_TYPE};
 use hyper::Method;
 use jsonrpsee::server::logger::{Logger, MethodKind, TransportProtocol};
 use jsonrpsee::server::middleware::proxy_get_request::ProxyGetRequestLayer;
@@ -18,12 +22,16 @@ use jsonrpsee::tracing::debug;
 use jsonrpsee::types::Params;
 use jsonrpsee::RpcModule;
 use katana_core::sequencer::KatanaSequencer;
-use tower_http::cors::{Any, CorsLayer};
+use middleware::RpcGuardLayer;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use version::VersionRouterLayer;
 
 use crate::api::katana::KatanaApiServer;
 use crate::api::starknet::StarknetApiServer;
+use crate::api::v0_6::StarknetApiV0_6Server;
 use crate::katana::KatanaApi;
 use crate::starknet::StarknetApi;
+use crate::v0_6::StarknetApiV0_6;
 
 pub async fn spawn(sequencer: Arc<KatanaSequencer>, config: ServerConfig) -> Result<NodeHandle> {
     let mut methods = RpcModule::new(());
@@ -33,6 +41,7 @@ pub async fn spawn(sequencer: Arc<KatanaSequencer>, config: ServerConfig) -> Res
         match api {
             ApiKind::Starknet => {
                 methods.merge(StarknetApi::new(sequencer.clone()).into_rpc())?;
+                methods.merge(StarknetApiV0_6::new(sequencer.clone()).into_rpc())?;
             }
             ApiKind::Katana => {
                 methods.merge(KatanaApi::new(sequencer.clone()).into_rpc())?;
@@ -40,23 +49,40 @@ pub async fn spawn(sequencer: Arc<KatanaSequencer>, config: ServerConfig) -> Res
         }
     }
 
+    // the filter is applied to the methods, rather than to the requests, so that it also applies
+    // to the calls made over WebSocket connections
+    middleware::apply_method_filter(&mut methods, &config.method_filter);
+
+    let allowed_origins = match &config.allowed_origins {
+        Some(origins) => AllowOrigin::list(
+            origins
+                .iter()
+                .map(|origin| origin.parse::<HeaderValue>())
+                .collect::<Result<Vec<_>, _>>()?,
+        ),
+        None => AllowOrigin::any(),
+    };
+
     let cors = CorsLayer::new()
             // Allow `POST` when accessing the resource
             .allow_methods([Method::POST, Method::GET])
-            // Allow requests from any origin
-            .allow_origin(Any)
-            .allow_headers([hyper::header::CONTENT_TYPE]);
+            .allow_origin(allowed_origins)
+            .allow_headers([CONTENT_TYPE, AUTHORIZATION]);
 
     let middleware = tower::ServiceBuilder::new()
         .layer(cors)
+        .layer(RpcGuardLayer::new(&config))
+        .layer(VersionRouterLayer)
         .layer(ProxyGetRequestLayer::new("/", "health")?)
-        .timeout(Duration::from_secs(2));
+        .timeout(config.request_timeout);
 
     let server = ServerBuilder::new()
         .set_logger(RpcLogger)
         .set_host_filtering(AllowHosts::Any)
         .set_middleware(middleware)
         .max_connections(config.max_connections)
+        .max_request_body_size(config.max_request_body_size)
+        .max_response_body_size(config.max_response_body_size)
         .build(config.addr())
         .await?;
 
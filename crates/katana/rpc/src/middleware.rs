@@ -0,0 +1,558 @@
+//! A tower middleware that enforces the access policy of the server before the requests are
+//! dispatched to the RPC methods.
+//!
+//! The middleware inspects the JSON-RPC calls of every `POST` request, and rejects the request if
+//! it exceeds the batch size limit or the rate limit of the client, or if it calls a method of the
+//! `katana` namespace without the expected bearer token. The method filter isn't part of the
+//! middleware: the filtered methods are removed from the server instead, see
+//! [`apply_method_filter`].
+//!
+//! The calls made over a WebSocket connection don't go through the middleware, so the policy is
+//! enforced on the handshake of the connection: it must carry the bearer token if one is required,
+//! and the connection is refused if the calls are rate limited, as they couldn't be counted.
+//!
+//! The calls are rate limited by client. If trusted proxies are configured, the client is the last
+//! address of the `X-Forwarded-For` or `X-Real-IP` headers that isn't a trusted proxy. Otherwise
+//! the client is the peer of the connection the request was received on. The server doesn't expose
+//! the socket address of the peer to the middlewares, but it creates a middleware service for every
+//! connection it accepts, so the connection is identified by its service instead.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::IpAddr;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use hyper::body::HttpBody;
+use hyper::header::{HeaderMap, AUTHORIZATION, CONTENT_TYPE, UPGRADE};
+use hyper::http::request::Parts;
+use hyper::{Body, Method, Request, Response, StatusCode};
+use jsonrpsee::RpcModule;
+use serde_json::Value;
+use tower::{BoxError, Layer, Service};
+use tracing::debug;
+
+use crate::config::{MethodFilter, ServerConfig};
+use crate::version::public_method_name;
+
+/// The prefix of the methods that require the bearer token.
+const KATANA_NAMESPACE_PREFIX: &str = "katana_";
+/// The methods that can always be called, regardless of the method filter.
+const ALWAYS_ALLOWED_METHODS: &[&str] = &["health"];
+
+/// The period over which the calls are counted for the rate limit.
+const RATE_LIMIT_PERIOD: Duration = Duration::from_secs(1);
+/// The number of tracked clients above which the expired entries are pruned.
+const RATE_LIMIT_PRUNE_THRESHOLD: usize = 10_000;
+
+// JSON-RPC error codes
+const INVALID_REQUEST: i64 = -32600;
+const UNAUTHORIZED: i64 = -32001;
+const LIMIT_EXCEEDED: i64 = -32005;
+
+#[derive(Debug, Clone)]
+pub struct RpcGuardLayer {
+    guard: Arc<Guard>,
+    /// The id of the next connection, as a service is created for every accepted connection.
+    next_connection: Arc<AtomicU64>,
+}
+
+impl RpcGuardLayer {
+    pub fn new(config: &ServerConfig) -> Self {
+        let guard = Guard {
+            trusted_proxies: config.trusted_proxies.clone(),
+            auth_token: config.auth_token.clone(),
+            max_batch_size: config.max_batch_size,
+            max_request_body_size: config.max_request_body_size,
+            rate_limiter: config.rate_limit.map(RateLimiter::new),
+        };
+        Self { guard: Arc::new(guard), next_connection: Arc::new(AtomicU64::new(0)) }
+    }
+}
+
+impl<S> Layer<S> for RpcGuardLayer {
+    type Service = RpcGuard<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RpcGuard {
+            inner: Arc::new(tokio::sync::Mutex::new(inner)),
+            guard: self.guard.clone(),
+            connection: self.next_connection.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+}
+
+/// The service created by [`RpcGuardLayer`].
+///
+/// The inner service is only called once the body of the request has been read, so it's shared
+/// with the response future.
+#[derive(Debug)]
+pub struct RpcGuard<S> {
+    inner: Arc<tokio::sync::Mutex<S>>,
+    guard: Arc<Guard>,
+    /// The id of the connection the service handles the requests of.
+    connection: u64,
+}
+
+impl<S> Service<Request<Body>> for RpcGuard<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Send + 'static,
+    S::Error: Into<BoxError>,
+    S::Future: Send,
+{
+    type Response = Response<Body>;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // the readiness of the inner service is awaited when the request is forwarded to it
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        let inner = self.inner.clone();
+        let guard = self.guard.clone();
+        let connection = self.connection;
+
+        Box::pin(async move {
+            let request = if request.method() == Method::POST {
+                let (parts, body) = request.into_parts();
+
+                let Some(body) = read_body(body, guard.max_request_body_size).await? else {
+                    return Ok(error_response(
+                        StatusCode::PAYLOAD_TOO_LARGE,
+                        INVALID_REQUEST,
+                        "Request body is too large",
+                    ));
+                };
+
+                if let Err(response) = guard.check(&parts, &body, connection) {
+                    return Ok(response);
+                }
+
+                Request::from_parts(parts, Body::from(body))
+            } else {
+                if is_websocket_upgrade(&request) {
+                    if let Err(response) = guard.check_websocket(request.headers()) {
+                        return Ok(response);
+                    }
+                }
+                request
+            };
+
+            forward(&inner, request).await
+        })
+    }
+}
+
+/// The access policy of the server.
+#[derive(Debug)]
+struct Guard {
+    trusted_proxies: Vec<IpAddr>,
+    auth_token: Option<String>,
+    max_batch_size: Option<usize>,
+    max_request_body_size: u32,
+    rate_limiter: Option<RateLimiter>,
+}
+
+impl Guard {
+    /// Checks whether the request is allowed, and returns the response to reject it with if it
+    /// isn't.
+    fn check(&self, parts: &Parts, body: &[u8], connection: u64) -> Result<(), Response<Body>> {
+        // malformed requests are forwarded, and rejected by the server itself
+        let request = serde_json::from_slice::<Value>(body).unwrap_or(Value::Null);
+
+        let methods: Vec<&str> = match &request {
+            Value::Array(calls) => {
+                if let Some(max) = self.max_batch_size {
+                    if calls.len() > max {
+                        return Err(error_response(
+                            StatusCode::OK,
+                            INVALID_REQUEST,
+                            &format!("Batch request exceeds the limit of {max} calls"),
+                        ));
+                    }
+                }
+                calls.iter().filter_map(method_name).collect()
+            }
+            call => method_name(call).into_iter().collect(),
+        };
+
+        self.check_calls(&parts.headers, &methods, connection)
+    }
+
+    fn check_calls(
+        &self,
+        headers: &HeaderMap,
+        methods: &[&str],
+        connection: u64,
+    ) -> Result<(), Response<Body>> {
+        if let Some(limiter) = &self.rate_limiter {
+            let client = match client_ip(headers, &self.trusted_proxies) {
+                Some(ip) => Client::Ip(ip),
+                None => Client::Connection(connection),
+            };
+            if !limiter.check(client, methods.len().max(1) as u32) {
+                debug!(target: "server", ?client, "Rate limit exceeded.");
+                return Err(error_response(
+                    StatusCode::TOO_MANY_REQUESTS,
+                    LIMIT_EXCEEDED,
+                    "Rate limit exceeded",
+                ));
+            }
+        }
+
+        if let Some(token) = &self.auth_token {
+            let requires_auth = methods.iter().any(|m| m.starts_with(KATANA_NAMESPACE_PREFIX));
+            if requires_auth && !is_authorized(headers, token) {
+                return Err(error_response(
+                    StatusCode::UNAUTHORIZED,
+                    UNAUTHORIZED,
+                    "Missing or invalid authorization token",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks whether a WebSocket connection can be opened, and returns the response to refuse it
+    /// with if it can't.
+    fn check_websocket(&self, headers: &HeaderMap) -> Result<(), Response<Body>> {
+        if self.rate_limiter.is_some() {
+            return Err(error_response(
+                StatusCode::FORBIDDEN,
+                INVALID_REQUEST,
+                "WebSocket connections are not allowed when calls are rate limited",
+            ));
+        }
+
+        // the calls of the connection can't be inspected, so all of them require the token
+        if let Some(token) = &self.auth_token {
+            if !is_authorized(headers, token) {
+                return Err(error_response(
+                    StatusCode::UNAUTHORIZED,
+                    UNAUTHORIZED,
+                    "Missing or invalid authorization token",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Removes the methods that aren't allowed by the filter from the module, so that they can't be
+/// called over any transport. The methods of the versioned namespaces are filtered by the name
+/// clients call them with.
+pub(crate) fn apply_method_filter<Context>(module: &mut RpcModule<Context>, filter: &MethodFilter) {
+    let denied = module
+        .method_names()
+        .filter(|method| {
+            !ALWAYS_ALLOWED_METHODS.contains(method)
+                && !filter.is_allowed(&public_method_name(method))
+        })
+        .collect::<Vec<_>>();
+
+    for method in denied {
+        module.remove_method(method);
+    }
+}
+
+/// A client of the server, as identified by the rate limiter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Client {
+    /// The address of the client, as reported by the trusted proxies.
+    Ip(IpAddr),
+    /// The peer of the connection with the given id.
+    Connection(u64),
+}
+
+/// A fixed window rate limiter, keyed by client.
+#[derive(Debug)]
+struct RateLimiter {
+    max_calls: u32,
+    windows: Mutex<HashMap<Client, Window>>,
+}
+
+#[derive(Debug)]
+struct Window {
+    start: Instant,
+    calls: u32,
+}
+
+impl RateLimiter {
+    fn new(max_calls: u32) -> Self {
+        Self { max_calls, windows: Mutex::new(HashMap::new()) }
+    }
+
+    /// Records `calls` calls from the client, and returns `false` if they exceed its limit.
+    fn check(&self, client: Client, calls: u32) -> bool {
+        let now = Instant::now();
+        let mut windows = self.windows.lock().expect("poisoned lock");
+
+        if windows.len() > RATE_LIMIT_PRUNE_THRESHOLD {
+            windows.retain(|_, window| now.duration_since(window.start) < RATE_LIMIT_PERIOD);
+        }
+
+        let window = windows.entry(client).or_insert(Window { start: now, calls: 0 });
+        if now.duration_since(window.start) >= RATE_LIMIT_PERIOD {
+            *window = Window { start: now, calls: 0 };
+        }
+
+        if window.calls.saturating_add(calls) > self.max_calls {
+            return false;
+        }
+
+        window.calls += calls;
+        true
+    }
+}
+
+/// Calls the inner service of a middleware once it's ready.
+pub(crate) async fn forward<S>(
+    inner: &tokio::sync::Mutex<S>,
+    request: Request<Body>,
+) -> Result<Response<Body>, BoxError>
+where
+    S: Service<Request<Body>, Response = Response<Body>>,
+    S::Error: Into<BoxError>,
+{
+    let future = {
+        let mut inner = inner.lock().await;
+        futures::future::poll_fn(|cx| inner.poll_ready(cx)).await.map_err(Into::into)?;
+        inner.call(request)
+    };
+
+    future.await.map_err(Into::into)
+}
+
+/// Reads the whole body, or returns `None` if it's larger than `limit` bytes.
+pub(crate) async fn read_body(mut body: Body, limit: u32) -> Result<Option<Vec<u8>>, hyper::Error> {
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk?;
+        if bytes.len() + chunk.len() > limit as usize {
+            return Ok(None);
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(Some(bytes))
+}
+
+fn method_name(call: &Value) -> Option<&str> {
+    call.get("method")?.as_str()
+}
+
+pub(crate) fn is_websocket_upgrade(request: &Request<Body>) -> bool {
+    request.method() == Method::GET
+        && request
+            .headers()
+            .get(UPGRADE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.eq_ignore_ascii_case("websocket"))
+}
+
+/// Returns the address of the client, as reported by the trusted proxies.
+///
+/// Every proxy appends the address it received the request from to `X-Forwarded-For`, so the
+/// client is the last address of the list that isn't a trusted proxy. Any address before it may
+/// have been forged by the client itself. `X-Real-IP` is used if there's no `X-Forwarded-For`.
+fn client_ip(headers: &HeaderMap, trusted_proxies: &[IpAddr]) -> Option<IpAddr> {
+    if trusted_proxies.is_empty() {
+        return None;
+    }
+
+    if let Some(forwarded_for) = headers.get("x-forwarded-for") {
+        let addresses = forwarded_for.to_str().ok()?.split(',').rev();
+        return addresses
+            .map(|ip| ip.trim().parse::<IpAddr>().ok())
+            .find(|ip| ip.map_or(true, |ip| !trusted_proxies.contains(&ip)))
+            .flatten();
+    }
+
+    headers.get("x-real-ip")?.to_str().ok()?.trim().parse().ok()
+}
+
+fn is_authorized(headers: &HeaderMap, token: &str) -> bool {
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|value| constant_time_eq(value.trim().as_bytes(), token.as_bytes()))
+}
+
+/// Compares the two byte strings in a time that doesn't depend on their content.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+pub(crate) fn error_response(status: StatusCode, code: i64, message: &str) -> Response<Body> {
+    let body = serde_json::json!({
+        "jsonrpc": "2.0",
+        "error": { "code": code, "message": message },
+        "id": null,
+    });
+
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .expect("valid response")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use std::net::IpAddr;
+
+    use hyper::header::HeaderMap;
+    use hyper::{Body, Request, StatusCode};
+    use jsonrpsee::RpcModule;
+
+    use super::{apply_method_filter, client_ip, Client, Guard, RateLimiter};
+    use crate::config::MethodFilter;
+
+    fn guard() -> Guard {
+        Guard {
+            trusted_proxies: Vec::new(),
+            auth_token: Some("secret".into()),
+            max_batch_size: Some(2),
+            max_request_body_size: u32::MAX,
+            rate_limiter: None,
+        }
+    }
+
+    fn check(guard: &Guard, auth: Option<&str>, body: &str) -> Result<(), StatusCode> {
+        let mut request = Request::post("/");
+        if let Some(auth) = auth {
+            request = request.header("authorization", auth);
+        }
+        let (parts, _) = request.body(Body::empty()).unwrap().into_parts();
+        guard.check(&parts, body.as_bytes(), 0).map_err(|response| response.status())
+    }
+
+    fn headers(headers: &[(&'static str, &str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in headers {
+            map.insert(*name, value.parse().unwrap());
+        }
+        map
+    }
+
+    #[test]
+    fn method_filter_applies_to_module() {
+        let filtered = |filter: MethodFilter| {
+            let mut module = RpcModule::new(());
+            for method in [
+                "health",
+                "starknet_chainId",
+                "starknet_addDeclareTransaction",
+                "starknetV0_6_chainId",
+                "starknetV0_6_addDeclareTransaction",
+            ] {
+                module.register_method(method, |_, _| Ok(())).unwrap();
+            }
+            apply_method_filter(&mut module, &filter);
+            module.method_names().collect::<BTreeSet<_>>()
+        };
+
+        let expected = BTreeSet::from(["health", "starknet_chainId", "starknetV0_6_chainId"]);
+        let deny = MethodFilter::Deny(["starknet_addDeclareTransaction".into()].into());
+        assert_eq!(filtered(deny), expected);
+        let allow = MethodFilter::Allow(["starknet_chainId".into()].into());
+        assert_eq!(filtered(allow), expected);
+        assert_eq!(filtered(MethodFilter::All).len(), 5);
+    }
+
+    #[test]
+    fn batch_size() {
+        let guard = guard();
+        let call = |method: &str| format!(r#"{{"jsonrpc":"2.0","id":1,"method":"{method}"}}"#);
+
+        let batch = format!("[{},{}]", call("starknet_chainId"), call("starknet_blockNumber"));
+        assert!(check(&guard, None, &batch).is_ok());
+
+        let batch = format!("[{},{},{}]", call("health"), call("health"), call("health"));
+        assert!(check(&guard, None, &batch).is_err());
+
+        // malformed requests are forwarded to the server
+        assert!(check(&guard, None, "not json").is_ok());
+    }
+
+    #[test]
+    fn katana_namespace_requires_token() {
+        let guard = guard();
+        let call = r#"{"jsonrpc":"2.0","id":1,"method":"katana_generateBlock"}"#;
+
+        assert_eq!(check(&guard, None, call), Err(StatusCode::UNAUTHORIZED));
+        assert_eq!(check(&guard, Some("Bearer wrong"), call), Err(StatusCode::UNAUTHORIZED));
+        assert_eq!(check(&guard, Some("Bearer secret"), call), Ok(()));
+    }
+
+    #[test]
+    fn websocket_handshake() {
+        let check = |guard: &Guard, auth: &str| {
+            guard.check_websocket(&headers(&[("authorization", auth)])).map_err(|r| r.status())
+        };
+
+        let guard = guard();
+        assert_eq!(check(&guard, "Bearer wrong"), Err(StatusCode::UNAUTHORIZED));
+        assert_eq!(check(&guard, "Bearer secret"), Ok(()));
+
+        // the calls of a connection can't be counted
+        let guard = Guard { rate_limiter: Some(RateLimiter::new(10)), ..guard };
+        assert_eq!(check(&guard, "Bearer secret"), Err(StatusCode::FORBIDDEN));
+    }
+
+    #[test]
+    fn forwarded_addresses_are_only_honored_for_trusted_proxies() {
+        let proxy: IpAddr = "10.0.0.1".parse().unwrap();
+        let client: IpAddr = "1.2.3.4".parse().unwrap();
+
+        // the client forged the first address, and the proxy appended the real one
+        let forwarded = headers(&[("x-forwarded-for", "6.6.6.6, 1.2.3.4, 10.0.0.1")]);
+        assert_eq!(client_ip(&forwarded, &[]), None);
+        assert_eq!(client_ip(&forwarded, &[proxy]), Some(client));
+
+        let real_ip = headers(&[("x-real-ip", "1.2.3.4")]);
+        assert_eq!(client_ip(&real_ip, &[]), None);
+        assert_eq!(client_ip(&real_ip, &[proxy]), Some(client));
+
+        let only_proxies = headers(&[("x-forwarded-for", "10.0.0.1")]);
+        assert_eq!(client_ip(&only_proxies, &[proxy]), None);
+    }
+
+    #[test]
+    fn rate_limit_is_per_client() {
+        let limiter = RateLimiter::new(2);
+        let client = Client::Ip("10.0.0.1".parse().unwrap());
+
+        assert!(limiter.check(client, 1));
+        assert!(limiter.check(client, 1));
+        assert!(!limiter.check(client, 1));
+        assert!(limiter.check(Client::Ip("10.0.0.2".parse().unwrap()), 2));
+        assert!(!limiter.check(Client::Connection(0), 3));
+    }
+
+    #[test]
+    fn rate_limit_is_per_connection_without_trusted_proxies() {
+        let guard = Guard { rate_limiter: Some(RateLimiter::new(1)), auth_token: None, ..guard() };
+        let check = |forwarded_for: &str, connection: u64| {
+            let request = Request::post("/").header("x-forwarded-for", forwarded_for);
+            let (parts, _) = request.body(Body::empty()).unwrap().into_parts();
+            let call = r#"{"jsonrpc":"2.0","id":1,"method":"starknet_chainId"}"#;
+            guard.check(&parts, call.as_bytes(), connection).map_err(|r| r.status())
+        };
+
+        // the forwarded addresses are ignored, so they can't be used to get around the limit
+        assert_eq!(check("1.2.3.4", 0), Ok(()));
+        assert_eq!(check("5.6.7.8", 0), Err(StatusCode::TOO_MANY_REQUESTS));
+
+        // a connection doesn't use up the limit of the others
+        assert_eq!(check("1.2.3.4", 1), Ok(()));
+    }
+}
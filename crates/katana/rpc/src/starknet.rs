@@ -5,15 +5,12 @@ use jsonrpsee::core::{async_trait, Error};
 use katana_core::backend::contract::StarknetContract;
 use katana_core::sequencer::KatanaSequencer;
 use katana_core::sequencer_error::SequencerError;
-use katana_executor::blockifier::utils::EntryPointCall;
-use katana_primitives::block::{
-    BlockHashOrNumber, BlockIdOrTag, FinalityStatus, GasPrices, PartialHeader,
-};
+use katana_executor::executor::EntryPointCall;
+use katana_primitives::block::{BlockHashOrNumber, BlockIdOrTag, FinalityStatus};
 use katana_primitives::conversion::rpc::legacy_inner_to_rpc_class;
 use katana_primitives::transaction::{ExecutableTx, ExecutableTxWithHash, TxHash};
-use katana_primitives::version::CURRENT_STARKNET_VERSION;
 use katana_primitives::FieldElement;
-use katana_provider::traits::block::{BlockHashProvider, BlockIdReader, BlockNumberProvider};
+use katana_provider::traits::block::{BlockIdReader, BlockNumberProvider};
 use katana_provider::traits::transaction::{
     ReceiptProvider, TransactionProvider, TransactionStatusProvider,
 };
@@ -24,7 +21,7 @@ use katana_rpc_types::block::{
 use katana_rpc_types::event::{EventFilterWithPage, EventsPage};
 use katana_rpc_types::message::MsgFromL1;
 use katana_rpc_types::receipt::{MaybePendingTxReceipt, PendingTxReceipt};
-use katana_rpc_types::state_update::StateUpdate;
+use katana_rpc_types::state_update::{MaybePendingStateUpdate, PendingStateUpdate};
 use katana_rpc_types::transaction::{
     BroadcastedDeclareTx, BroadcastedDeployAccountTx, BroadcastedInvokeTx, BroadcastedTx,
     DeclareTxResult, DeployAccountTxResult, InvokeTxResult, Tx,
@@ -43,6 +40,55 @@ impl StarknetApi {
     pub fn new(sequencer: Arc<KatanaSequencer>) -> Self {
         Self { sequencer }
     }
+
+    /// Estimates the fee of the transactions, optionally skipping their validation.
+    pub(crate) fn estimate_fees(
+        &self,
+        request: Vec<BroadcastedTx>,
+        block_id: BlockIdOrTag,
+        skip_validate: bool,
+    ) -> Result<Vec<FeeEstimate>, Error> {
+        let chain_id = FieldElement::from_hex_be(&self.sequencer.chain_id().as_hex())
+            .map_err(|_| StarknetApiError::UnexpectedError)?;
+
+        let transactions = request
+            .into_iter()
+            .map(|tx| {
+                let tx = match tx {
+                    BroadcastedTx::Invoke(tx) => {
+                        let tx = tx.into_tx_with_chain_id(chain_id);
+                        ExecutableTxWithHash::new_query(ExecutableTx::Invoke(tx))
+                    }
+
+                    BroadcastedTx::DeployAccount(tx) => {
+                        let tx = tx.into_tx_with_chain_id(chain_id);
+                        ExecutableTxWithHash::new_query(ExecutableTx::DeployAccount(tx))
+                    }
+
+                    BroadcastedTx::Declare(tx) => {
+                        let tx = tx
+                            .try_into_tx_with_chain_id(chain_id)
+                            .map_err(|_| StarknetApiError::InvalidContractClass)?;
+                        ExecutableTxWithHash::new_query(ExecutableTx::Declare(tx))
+                    }
+                };
+
+                Result::<ExecutableTxWithHash, StarknetApiError>::Ok(tx)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let res = self.sequencer.estimate_fee(transactions, block_id, skip_validate).map_err(
+            |e| match e {
+                SequencerError::BlockNotFound(_) => StarknetApiError::BlockNotFound,
+                SequencerError::TransactionExecution(e) => {
+                    StarknetApiError::ContractError { revert_error: e.to_string() }
+                }
+                _ => StarknetApiError::UnexpectedError,
+            },
+        )?;
+
+        Ok(res)
+    }
 }
 #[async_trait]
 impl StarknetApiServer for StarknetApi {
@@ -120,26 +166,10 @@ impl StarknetApiServer for StarknetApi {
         let provider = self.sequencer.backend.blockchain.provider();
 
         if BlockIdOrTag::Tag(BlockTag::Pending) == block_id {
-            let pending_state = self.sequencer.pending_state().expect("pending state should exist");
-
-            let block_context = self.sequencer.backend.env.read().block.clone();
-            let latest_hash = BlockHashProvider::latest_hash(provider)?;
-
-            let gas_prices = GasPrices {
-                eth_gas_price: block_context.gas_prices.eth_l1_gas_price.try_into().unwrap(),
-                strk_gas_price: block_context.gas_prices.strk_l1_gas_price.try_into().unwrap(),
-            };
-
-            let header = PartialHeader {
-                gas_prices,
-                parent_hash: latest_hash,
-                version: CURRENT_STARKNET_VERSION,
-                timestamp: block_context.block_timestamp.0,
-                sequencer_address: block_context.sequencer_address.into(),
-            };
-
+            let header =
+                self.sequencer.pending_header().map_err(|_| StarknetApiError::UnexpectedError)?;
             let transactions =
-                pending_state.executed_txs.read().iter().map(|(tx, _)| tx.hash).collect::<Vec<_>>();
+                self.sequencer.pending_transactions().into_iter().map(|tx| tx.hash).collect();
 
             Ok(MaybePendingBlockWithTxHashes::Pending(PendingBlockWithTxHashes::new(
                 header,
@@ -164,14 +194,8 @@ impl StarknetApiServer for StarknetApi {
         block_id: BlockIdOrTag,
         index: u64,
     ) -> Result<Tx, Error> {
-        // TEMP: have to handle pending tag independently for now
         let tx = if BlockIdOrTag::Tag(BlockTag::Pending) == block_id {
-            let Some(pending_state) = self.sequencer.pending_state() else {
-                return Err(StarknetApiError::BlockNotFound.into());
-            };
-
-            let pending_txs = pending_state.executed_txs.read();
-            pending_txs.iter().nth(index as usize).map(|(tx, _)| tx.clone())
+            self.sequencer.pending_transactions().into_iter().nth(index as usize)
         } else {
             let provider = &self.sequencer.backend.blockchain.provider();
 
@@ -194,30 +218,9 @@ impl StarknetApiServer for StarknetApi {
         let provider = self.sequencer.backend.blockchain.provider();
 
         if BlockIdOrTag::Tag(BlockTag::Pending) == block_id {
-            let pending_state = self.sequencer.pending_state().expect("pending state should exist");
-
-            let block_context = self.sequencer.backend.env.read().block.clone();
-            let latest_hash = BlockHashProvider::latest_hash(provider)?;
-
-            let gas_prices = GasPrices {
-                eth_gas_price: block_context.gas_prices.eth_l1_gas_price.try_into().unwrap(),
-                strk_gas_price: block_context.gas_prices.strk_l1_gas_price.try_into().unwrap(),
-            };
-
-            let header = PartialHeader {
-                gas_prices,
-                parent_hash: latest_hash,
-                version: CURRENT_STARKNET_VERSION,
-                timestamp: block_context.block_timestamp.0,
-                sequencer_address: block_context.sequencer_address.into(),
-            };
-
-            let transactions = pending_state
-                .executed_txs
-                .read()
-                .iter()
-                .map(|(tx, _)| tx.clone())
-                .collect::<Vec<_>>();
+            let header =
+                self.sequencer.pending_header().map_err(|_| StarknetApiError::UnexpectedError)?;
+            let transactions = self.sequencer.pending_transactions();
 
             Ok(MaybePendingBlockWithTxs::Pending(PendingBlockWithTxs::new(header, transactions)))
         } else {
@@ -234,7 +237,7 @@ impl StarknetApiServer for StarknetApi {
         }
     }
 
-    async fn state_update(&self, block_id: BlockIdOrTag) -> Result<StateUpdate, Error> {
+    async fn state_update(&self, block_id: BlockIdOrTag) -> Result<MaybePendingStateUpdate, Error> {
         let provider = self.sequencer.backend.blockchain.provider();
 
         let block_id = match block_id {
@@ -245,13 +248,22 @@ impl StarknetApiServer for StarknetApi {
                 .map(BlockHashOrNumber::Num)
                 .map_err(|_| StarknetApiError::BlockNotFound)?,
             BlockIdOrTag::Tag(BlockTag::Pending) => {
-                return Err(StarknetApiError::BlockNotFound.into());
+                let (old_root, state_updates) = self
+                    .sequencer
+                    .pending_state_update()
+                    .map_err(|_| StarknetApiError::UnexpectedError)?;
+
+                return Ok(MaybePendingStateUpdate::Pending(PendingStateUpdate::new(
+                    old_root,
+                    state_updates.into(),
+                )));
             }
         };
 
         katana_rpc_types_builder::StateUpdateBuilder::new(block_id, provider)
             .build()
             .map_err(|_| StarknetApiError::UnexpectedError)?
+            .map(MaybePendingStateUpdate::Update)
             .ok_or(Error::from(StarknetApiError::BlockNotFound))
     }
 
@@ -420,44 +432,7 @@ impl StarknetApiServer for StarknetApi {
         request: Vec<BroadcastedTx>,
         block_id: BlockIdOrTag,
     ) -> Result<Vec<FeeEstimate>, Error> {
-        let chain_id = FieldElement::from_hex_be(&self.sequencer.chain_id().as_hex())
-            .map_err(|_| StarknetApiError::UnexpectedError)?;
-
-        let transactions = request
-            .into_iter()
-            .map(|tx| {
-                let tx = match tx {
-                    BroadcastedTx::Invoke(tx) => {
-                        let tx = tx.into_tx_with_chain_id(chain_id);
-                        ExecutableTxWithHash::new_query(ExecutableTx::Invoke(tx))
-                    }
-
-                    BroadcastedTx::DeployAccount(tx) => {
-                        let tx = tx.into_tx_with_chain_id(chain_id);
-                        ExecutableTxWithHash::new_query(ExecutableTx::DeployAccount(tx))
-                    }
-
-                    BroadcastedTx::Declare(tx) => {
-                        let tx = tx
-                            .try_into_tx_with_chain_id(chain_id)
-                            .map_err(|_| StarknetApiError::InvalidContractClass)?;
-                        ExecutableTxWithHash::new_query(ExecutableTx::Declare(tx))
-                    }
-                };
-
-                Result::<ExecutableTxWithHash, StarknetApiError>::Ok(tx)
-            })
-            .collect::<Result<Vec<_>, _>>()?;
-
-        let res = self.sequencer.estimate_fee(transactions, block_id).map_err(|e| match e {
-            SequencerError::BlockNotFound(_) => StarknetApiError::BlockNotFound,
-            SequencerError::TransactionExecution(e) => {
-                StarknetApiError::ContractError { revert_error: e.to_string() }
-            }
-            _ => StarknetApiError::UnexpectedError,
-        })?;
-
-        Ok(res)
+        self.estimate_fees(request, block_id, false)
     }
 
     async fn estimate_message_fee(
@@ -474,7 +449,7 @@ impl StarknetApiServer for StarknetApi {
 
         let res = self
             .sequencer
-            .estimate_fee(vec![tx], block_id)
+            .estimate_fee(vec![tx], block_id, false)
             .map_err(|e| match e {
                 SequencerError::BlockNotFound(_) => StarknetApiError::BlockNotFound,
                 SequencerError::TransactionExecution(e) => {
@@ -566,32 +541,36 @@ impl StarknetApiServer for StarknetApi {
                 FinalityStatus::AcceptedOnL2 => TransactionStatus::AcceptedOnL2(execution_status),
             })
         } else {
-            let pending_state = self.sequencer.pending_state();
-            let state = pending_state.ok_or(StarknetApiError::TxnHashNotFound)?;
-            let executed_txs = state.executed_txs.read();
-
-            // attemps to find in the valid transactions list first (executed_txs)
-            // if not found, then search in the rejected transactions list (rejected_txs)
-            if let Some(is_reverted) = executed_txs
-                .iter()
-                .find(|(tx, _)| tx.hash == transaction_hash)
-                .map(|(_, rct)| rct.receipt.is_reverted())
-            {
-                let exec_status = if is_reverted {
-                    TransactionExecutionStatus::Reverted
-                } else {
-                    TransactionExecutionStatus::Succeeded
-                };
-
-                Ok(TransactionStatus::AcceptedOnL2(exec_status))
-            } else {
-                let rejected_txs = state.rejected_txs.read();
-
-                rejected_txs
+            if let Some(state) = self.sequencer.pending_state() {
+                // attemps to find in the valid transactions list first (executed_txs)
+                // if not found, then search in the rejected transactions list (rejected_txs)
+                let is_reverted = state
+                    .executed_txs
+                    .read()
                     .iter()
                     .find(|(tx, _)| tx.hash == transaction_hash)
-                    .map(|_| TransactionStatus::Rejected)
-                    .ok_or(Error::from(StarknetApiError::TxnHashNotFound))
+                    .map(|(_, rct)| rct.receipt.is_reverted());
+
+                if let Some(is_reverted) = is_reverted {
+                    let exec_status = if is_reverted {
+                        TransactionExecutionStatus::Reverted
+                    } else {
+                        TransactionExecutionStatus::Succeeded
+                    };
+
+                    return Ok(TransactionStatus::AcceptedOnL2(exec_status));
+                }
+
+                if state.rejected_txs.read().iter().any(|(tx, _)| tx.hash == transaction_hash) {
+                    return Ok(TransactionStatus::Rejected);
+                }
+            }
+
+            // transactions that are still in the pool haven't been executed yet
+            if self.sequencer.is_transaction_received(transaction_hash) {
+                Ok(TransactionStatus::Received)
+            } else {
+                Err(StarknetApiError::TxnHashNotFound.into())
             }
         }
     }
@@ -0,0 +1,197 @@
+use std::sync::Arc;
+
+use jsonrpsee::core::{async_trait, Error};
+use katana_core::sequencer::KatanaSequencer;
+use katana_primitives::block::{BlockIdOrTag, BlockNumber};
+use katana_primitives::transaction::TxHash;
+use katana_primitives::FieldElement;
+use katana_rpc_types::block::{BlockHashAndNumber, BlockTxCount};
+use katana_rpc_types::event::EventFilterWithPage;
+use katana_rpc_types::message::MsgFromL1;
+use katana_rpc_types::state_update::MaybePendingStateUpdate;
+use katana_rpc_types::transaction::{
+    BroadcastedDeclareTx, BroadcastedDeployAccountTx, BroadcastedInvokeTx, BroadcastedTx,
+    DeclareTxResult, DeployAccountTxResult, InvokeTxResult, Tx,
+};
+use katana_rpc_types::v0_6::{
+    EventsPage, FeeEstimate, MaybePendingBlockWithTxHashes, MaybePendingBlockWithTxs,
+    SimulationFlagForEstimateFee, TxReceipt,
+};
+use katana_rpc_types::{ContractClass, FeltAsHex, FunctionCall};
+use katana_rpc_types_builder::v0_6 as convert;
+use starknet::core::types::TransactionStatus;
+
+use crate::api::starknet::StarknetApiServer;
+use crate::api::v0_6::StarknetApiV0_6Server;
+use crate::starknet::StarknetApi;
+
+/// The implementation of the v0.6 Starknet API, on top of the v0.5 one.
+pub struct StarknetApiV0_6 {
+    api: StarknetApi,
+}
+
+impl StarknetApiV0_6 {
+    pub fn new(sequencer: Arc<KatanaSequencer>) -> Self {
+        Self { api: StarknetApi::new(sequencer) }
+    }
+}
+
+#[async_trait]
+impl StarknetApiV0_6Server for StarknetApiV0_6 {
+    async fn chain_id(&self) -> Result<FeltAsHex, Error> {
+        StarknetApiServer::chain_id(&self.api).await
+    }
+
+    async fn nonce(
+        &self,
+        block_id: BlockIdOrTag,
+        contract_address: FieldElement,
+    ) -> Result<FeltAsHex, Error> {
+        StarknetApiServer::nonce(&self.api, block_id, contract_address).await
+    }
+
+    async fn block_number(&self) -> Result<BlockNumber, Error> {
+        StarknetApiServer::block_number(&self.api).await
+    }
+
+    async fn transaction_by_hash(&self, transaction_hash: TxHash) -> Result<Tx, Error> {
+        StarknetApiServer::transaction_by_hash(&self.api, transaction_hash).await
+    }
+
+    async fn block_transaction_count(&self, block_id: BlockIdOrTag) -> Result<BlockTxCount, Error> {
+        StarknetApiServer::block_transaction_count(&self.api, block_id).await
+    }
+
+    async fn class_at(
+        &self,
+        block_id: BlockIdOrTag,
+        contract_address: FieldElement,
+    ) -> Result<ContractClass, Error> {
+        StarknetApiServer::class_at(&self.api, block_id, contract_address).await
+    }
+
+    async fn block_hash_and_number(&self) -> Result<BlockHashAndNumber, Error> {
+        StarknetApiServer::block_hash_and_number(&self.api).await
+    }
+
+    async fn block_with_tx_hashes(
+        &self,
+        block_id: BlockIdOrTag,
+    ) -> Result<MaybePendingBlockWithTxHashes, Error> {
+        let block = StarknetApiServer::block_with_tx_hashes(&self.api, block_id).await?;
+        Ok(convert::block_with_tx_hashes(block))
+    }
+
+    async fn transaction_by_block_id_and_index(
+        &self,
+        block_id: BlockIdOrTag,
+        index: u64,
+    ) -> Result<Tx, Error> {
+        StarknetApiServer::transaction_by_block_id_and_index(&self.api, block_id, index).await
+    }
+
+    async fn block_with_txs(
+        &self,
+        block_id: BlockIdOrTag,
+    ) -> Result<MaybePendingBlockWithTxs, Error> {
+        let block = StarknetApiServer::block_with_txs(&self.api, block_id).await?;
+        Ok(convert::block_with_txs(block))
+    }
+
+    async fn state_update(&self, block_id: BlockIdOrTag) -> Result<MaybePendingStateUpdate, Error> {
+        StarknetApiServer::state_update(&self.api, block_id).await
+    }
+
+    async fn transaction_receipt(&self, transaction_hash: TxHash) -> Result<TxReceipt, Error> {
+        let receipt = StarknetApiServer::transaction_receipt(&self.api, transaction_hash).await?;
+        Ok(convert::receipt(receipt))
+    }
+
+    async fn transaction_status(
+        &self,
+        transaction_hash: TxHash,
+    ) -> Result<TransactionStatus, Error> {
+        StarknetApiServer::transaction_status(&self.api, transaction_hash).await
+    }
+
+    async fn class_hash_at(
+        &self,
+        block_id: BlockIdOrTag,
+        contract_address: FieldElement,
+    ) -> Result<FeltAsHex, Error> {
+        StarknetApiServer::class_hash_at(&self.api, block_id, contract_address).await
+    }
+
+    async fn class(
+        &self,
+        block_id: BlockIdOrTag,
+        class_hash: FieldElement,
+    ) -> Result<ContractClass, Error> {
+        StarknetApiServer::class(&self.api, block_id, class_hash).await
+    }
+
+    async fn events(&self, filter: EventFilterWithPage) -> Result<EventsPage, Error> {
+        let page = StarknetApiServer::events(&self.api, filter).await?;
+        Ok(convert::events_page(page))
+    }
+
+    async fn estimate_fee(
+        &self,
+        request: Vec<BroadcastedTx>,
+        simulation_flags: Vec<SimulationFlagForEstimateFee>,
+        block_id: BlockIdOrTag,
+    ) -> Result<Vec<FeeEstimate>, Error> {
+        let skip_validate = simulation_flags.contains(&SimulationFlagForEstimateFee::SkipValidate);
+        let estimates = self.api.estimate_fees(request, block_id, skip_validate)?;
+        Ok(estimates.into_iter().map(convert::fee_estimate).collect())
+    }
+
+    async fn estimate_message_fee(
+        &self,
+        message: MsgFromL1,
+        block_id: BlockIdOrTag,
+    ) -> Result<FeeEstimate, Error> {
+        let estimate =
+            StarknetApiServer::estimate_message_fee(&self.api, message, block_id).await?;
+        Ok(convert::fee_estimate(estimate))
+    }
+
+    async fn call(
+        &self,
+        request: FunctionCall,
+        block_id: BlockIdOrTag,
+    ) -> Result<Vec<FeltAsHex>, Error> {
+        StarknetApiServer::call(&self.api, request, block_id).await
+    }
+
+    async fn storage_at(
+        &self,
+        contract_address: FieldElement,
+        key: FieldElement,
+        block_id: BlockIdOrTag,
+    ) -> Result<FeltAsHex, Error> {
+        StarknetApiServer::storage_at(&self.api, contract_address, key, block_id).await
+    }
+
+    async fn add_deploy_account_transaction(
+        &self,
+        deploy_account_transaction: BroadcastedDeployAccountTx,
+    ) -> Result<DeployAccountTxResult, Error> {
+        StarknetApiServer::add_deploy_account_transaction(&self.api, deploy_account_transaction)
+            .await
+    }
+
+    async fn add_declare_transaction(
+        &self,
+        declare_transaction: BroadcastedDeclareTx,
+    ) -> Result<DeclareTxResult, Error> {
+        StarknetApiServer::add_declare_transaction(&self.api, declare_transaction).await
+    }
+
+    async fn add_invoke_transaction(
+        &self,
+        invoke_transaction: BroadcastedInvokeTx,
+    ) -> Result<InvokeTxResult, Error> {
+        StarknetApiServer::add_invoke_transaction(&self.api, invoke_transaction).await
+    }
+}
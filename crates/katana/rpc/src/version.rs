@@ -0,0 +1,279 @@
+//! Routing of the requests to the version of the Starknet API requested by the client.
+//!
+//! The server can only serve a single set of methods, so the methods of every version other than
+//! the default one are registered under an internal namespace (eg. `starknetV0_6`). Requests sent
+//! to a versioned path (eg. `/rpc/v0_6`) have the `starknet` namespace of their methods replaced
+//! with the namespace of that version, before being dispatched to the server.
+//!
+//! Requests sent to unversioned paths are served by the default version.
+//!
+//! The internal namespaces are not part of the API: requests calling them by name are rejected.
+//!
+//! The calls made over a WebSocket connection don't go through the router, so they can't be routed
+//! to another version. WebSocket connections are only accepted on the paths of the default version,
+//! and the calls made over them aren't checked for internal namespaces.
+
+use std::borrow::Cow;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use hyper::{Body, Method, Request, Response, StatusCode, Uri};
+use serde_json::Value;
+use tower::{BoxError, Layer, Service};
+
+use crate::middleware::{error_response, forward, is_websocket_upgrade, read_body};
+
+/// The namespace of the Starknet methods in the requests.
+const STARKNET_NAMESPACE: &str = "starknet_";
+/// The prefix of the versioned paths.
+const VERSIONED_PATH_PREFIX: &str = "/rpc/";
+
+// JSON-RPC error codes
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// A version of the Starknet JSON-RPC specification served by the node.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RpcVersion {
+    V0_5,
+    V0_6,
+}
+
+impl RpcVersion {
+    /// The version served at the unversioned paths.
+    pub const DEFAULT: RpcVersion = RpcVersion::V0_5;
+
+    /// All the supported versions.
+    pub const ALL: [RpcVersion; 2] = [RpcVersion::V0_5, RpcVersion::V0_6];
+
+    /// Returns the path at which the version is served, relative to `/rpc/`.
+    pub fn path(&self) -> &'static str {
+        match self {
+            Self::V0_5 => "v0_5",
+            Self::V0_6 => "v0_6",
+        }
+    }
+
+    /// Returns the namespace under which the Starknet methods of the version are registered.
+    fn namespace(&self) -> &'static str {
+        match self {
+            Self::V0_5 => STARKNET_NAMESPACE,
+            Self::V0_6 => "starknetV0_6_",
+        }
+    }
+
+    /// Returns the version served at `path`, or `None` if the path is a versioned path of an
+    /// unsupported version. Unversioned paths are served by the default version.
+    fn from_path(path: &str) -> Option<Self> {
+        match path.trim_end_matches('/').strip_prefix(VERSIONED_PATH_PREFIX) {
+            Some(version) => Self::ALL.into_iter().find(|v| v.path() == version),
+            None => Some(Self::DEFAULT),
+        }
+    }
+}
+
+/// Returns the name clients call a method with, ie. the name of the method with the namespace of
+/// its version replaced by the `starknet` namespace.
+pub(crate) fn public_method_name(method: &str) -> Cow<'_, str> {
+    let versioned = RpcVersion::ALL.into_iter().filter(|v| *v != RpcVersion::DEFAULT);
+    for version in versioned {
+        if let Some(name) = method.strip_prefix(version.namespace()) {
+            return Cow::Owned(format!("{STARKNET_NAMESPACE}{name}"));
+        }
+    }
+    Cow::Borrowed(method)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct VersionRouterLayer;
+
+impl<S> Layer<S> for VersionRouterLayer {
+    type Service = VersionRouter<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        VersionRouter { inner: Arc::new(tokio::sync::Mutex::new(inner)) }
+    }
+}
+
+/// The service created by [`VersionRouterLayer`].
+#[derive(Debug)]
+pub struct VersionRouter<S> {
+    inner: Arc<tokio::sync::Mutex<S>>,
+}
+
+impl<S> Service<Request<Body>> for VersionRouter<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Send + 'static,
+    S::Error: Into<BoxError>,
+    S::Future: Send,
+{
+    type Response = Response<Body>;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // the readiness of the inner service is awaited when the request is forwarded to it
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        let inner = self.inner.clone();
+
+        Box::pin(async move {
+            if is_websocket_upgrade(&request)
+                && RpcVersion::from_path(request.uri().path()) != Some(RpcVersion::DEFAULT)
+            {
+                return Ok(error_response(
+                    StatusCode::BAD_REQUEST,
+                    INVALID_REQUEST,
+                    "WebSocket connections are only supported by the default RPC version",
+                ));
+            }
+
+            if request.method() != Method::POST {
+                return forward(&inner, request).await;
+            }
+
+            let Some(version) = RpcVersion::from_path(request.uri().path()) else {
+                return Ok(error_response(
+                    StatusCode::NOT_FOUND,
+                    METHOD_NOT_FOUND,
+                    "Unsupported RPC version",
+                ));
+            };
+
+            let (mut parts, body) = request.into_parts();
+            // the size of the body is limited by the server itself
+            let body = read_body(body, u32::MAX).await?.unwrap_or_default();
+
+            if let Some(method) = internal_method(&body) {
+                return Ok(error_response(
+                    StatusCode::OK,
+                    METHOD_NOT_FOUND,
+                    &format!("Method `{method}` not found"),
+                ));
+            }
+
+            let body = if version == RpcVersion::DEFAULT {
+                body
+            } else {
+                parts.uri = Uri::from_static("/");
+                rename_methods(&body, version).unwrap_or(body)
+            };
+
+            forward(&inner, Request::from_parts(parts, Body::from(body))).await
+        })
+    }
+}
+
+/// Returns the first method of the request body that is called by the name of an internal
+/// namespace, if any.
+fn internal_method(body: &[u8]) -> Option<String> {
+    let request = serde_json::from_slice::<Value>(body).ok()?;
+
+    let calls: Vec<&Value> = match &request {
+        Value::Array(calls) => calls.iter().collect(),
+        call => vec![call],
+    };
+
+    calls.into_iter().filter_map(|call| call.get("method")?.as_str()).find_map(|method| {
+        let internal = public_method_name(method) != method;
+        internal.then(|| method.to_string())
+    })
+}
+
+/// Replaces the `starknet` namespace of the methods in the request body with the namespace of
+/// `version`. Returns `None` if the body isn't valid JSON, in which case it's left as is for the
+/// server to reject.
+fn rename_methods(body: &[u8], version: RpcVersion) -> Option<Vec<u8>> {
+    let mut request = serde_json::from_slice::<Value>(body).ok()?;
+
+    let calls: Vec<&mut Value> = match &mut request {
+        Value::Array(calls) => calls.iter_mut().collect(),
+        call => vec![call],
+    };
+
+    for call in calls {
+        if let Some(Value::String(method)) = call.get_mut("method") {
+            if let Some(name) = method.strip_prefix(STARKNET_NAMESPACE) {
+                *method = format!("{}{name}", version.namespace());
+            }
+        }
+    }
+
+    serde_json::to_vec(&request).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use hyper::{Body, Request, Response, StatusCode};
+    use serde_json::{json, Value};
+    use tower::{Layer, Service};
+
+    use super::{internal_method, rename_methods, RpcVersion, VersionRouterLayer};
+
+    #[test]
+    fn version_from_path() {
+        assert_eq!(RpcVersion::from_path("/"), Some(RpcVersion::V0_5));
+        assert_eq!(RpcVersion::from_path("/rpc/v0_5"), Some(RpcVersion::V0_5));
+        assert_eq!(RpcVersion::from_path("/rpc/v0_6/"), Some(RpcVersion::V0_6));
+        assert_eq!(RpcVersion::from_path("/rpc/v0_4"), None);
+        assert_eq!(RpcVersion::from_path("/rpc/latest"), None);
+        assert_eq!(RpcVersion::from_path("/foo"), Some(RpcVersion::V0_5));
+    }
+
+    #[test]
+    fn rename_batch_methods() {
+        let body = json!([
+            { "jsonrpc": "2.0", "id": 1, "method": "starknet_getBlockWithTxs", "params": [] },
+            { "jsonrpc": "2.0", "id": 2, "method": "katana_generateBlock" },
+        ]);
+
+        let renamed = rename_methods(&serde_json::to_vec(&body).unwrap(), RpcVersion::V0_6);
+        let renamed: Value = serde_json::from_slice(&renamed.unwrap()).unwrap();
+
+        assert_eq!(renamed[0]["method"], "starknetV0_6_getBlockWithTxs");
+        assert_eq!(renamed[1]["method"], "katana_generateBlock");
+        assert_eq!(rename_methods(b"not json", RpcVersion::V0_6), None);
+    }
+
+    #[test]
+    fn internal_methods_are_detected() {
+        let call = |method: &str| json!({ "jsonrpc": "2.0", "id": 1, "method": method });
+        let internal = |body: Value| internal_method(&serde_json::to_vec(&body).unwrap());
+
+        assert_eq!(internal(call("starknet_chainId")), None);
+        assert_eq!(internal(call("starknetV0_6_chainId")), Some("starknetV0_6_chainId".into()));
+        assert_eq!(
+            internal(json!([call("starknet_chainId"), call("starknetV0_6_addInvokeTransaction")])),
+            Some("starknetV0_6_addInvokeTransaction".into())
+        );
+        assert_eq!(internal_method(b"not json"), None);
+    }
+
+    #[tokio::test]
+    async fn websocket_upgrades_on_versioned_paths_are_rejected() {
+        let inner =
+            tower::service_fn(|_| async { Ok::<_, Infallible>(Response::new(Body::empty())) });
+        let mut router = VersionRouterLayer.layer(inner);
+
+        let mut upgrade = |path: &str| {
+            let request = Request::get(path)
+                .header("connection", "upgrade")
+                .header("upgrade", "websocket")
+                .body(Body::empty())
+                .unwrap();
+            let response = router.call(request);
+            async move { response.await.unwrap().status() }
+        };
+
+        assert_eq!(upgrade("/").await, StatusCode::OK);
+        assert_eq!(upgrade("/rpc/v0_5").await, StatusCode::OK);
+        assert_eq!(upgrade("/rpc/v0_6").await, StatusCode::BAD_REQUEST);
+        assert_eq!(upgrade("/rpc/v0_4").await, StatusCode::BAD_REQUEST);
+    }
+}
@@ -7,16 +7,20 @@ use anyhow::{anyhow, Result};
 use cairo_lang_starknet::casm_contract_class::CasmContractClass;
 use cairo_lang_starknet::contract_class::ContractClass;
 use dojo_test_utils::sequencer::{get_default_test_starknet_config, TestSequencer};
+use katana_core::constants::FEE_TOKEN_ADDRESS;
 use katana_core::sequencer::SequencerConfig;
 use starknet::accounts::{Account, Call, ConnectedAccount};
 use starknet::core::types::contract::legacy::LegacyContractClass;
 use starknet::core::types::contract::{CompiledClass, SierraClass};
 use starknet::core::types::{
-    BlockId, BlockTag, DeclareTransactionReceipt, FieldElement, FlattenedSierraClass,
-    MaybePendingTransactionReceipt, TransactionFinalityStatus, TransactionReceipt,
+    BlockId, BlockTag, DeclareTransactionReceipt, EventFilter, FieldElement, FlattenedSierraClass,
+    InvokeTransaction, MaybePendingBlockWithTxs, MaybePendingStateUpdate,
+    MaybePendingTransactionReceipt, PendingTransactionReceipt, Transaction,
+    TransactionExecutionStatus, TransactionFinalityStatus, TransactionReceipt, TransactionStatus,
 };
 use starknet::core::utils::{get_contract_address, get_selector_from_name};
-use starknet::providers::Provider;
+use starknet::providers::jsonrpc::HttpTransport;
+use starknet::providers::{JsonRpcClient, Provider};
 
 const WAIT_TX_DELAY_MILLIS: u64 = 1000;
 
@@ -176,6 +180,123 @@ async fn test_send_declare_and_deploy_legacy_contract() {
     sequencer.stop().expect("failed to stop sequencer");
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_versioned_rpc_paths() {
+    let sequencer =
+        TestSequencer::start(SequencerConfig::default(), get_default_test_starknet_config()).await;
+
+    let provider =
+        |path: &str| JsonRpcClient::new(HttpTransport::new(sequencer.url().join(path).unwrap()));
+
+    let chain_id = sequencer.account().provider().chain_id().await.unwrap();
+
+    for path in ["rpc/v0_5", "rpc/v0_6"] {
+        let provider = provider(path);
+        assert_eq!(provider.chain_id().await.unwrap(), chain_id);
+        assert_eq!(provider.block_number().await.unwrap(), 0);
+    }
+
+    assert!(provider("rpc/v0_4").chain_id().await.is_err());
+
+    sequencer.stop().expect("failed to stop sequencer");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_pending_block_in_interval_mode() {
+    // the interval is long enough for the transactions to stay in the pending block
+    let config = SequencerConfig { block_time: Some(3_600_000), ..Default::default() };
+    let sequencer = TestSequencer::start(config, get_default_test_starknet_config()).await;
+    let account = sequencer.account();
+    let provider = account.provider();
+
+    let transfer = Call {
+        to: FEE_TOKEN_ADDRESS.0,
+        selector: get_selector_from_name("transfer").unwrap(),
+        calldata: vec![
+            FieldElement::from(0x1337u32),
+            FieldElement::from(100u32),
+            FieldElement::ZERO,
+        ],
+    };
+    let res = account.execute(vec![transfer.clone()]).send().await.unwrap();
+    let hash = res.transaction_hash;
+
+    // wait for the transaction to be executed in the pending block
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while sequencer.sequencer.pending_transactions().is_empty() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("transaction should be executed");
+
+    let pending = BlockId::Tag(BlockTag::Pending);
+
+    let MaybePendingBlockWithTxs::PendingBlock(block) =
+        provider.get_block_with_txs(pending).await.unwrap()
+    else {
+        panic!("should be the pending block")
+    };
+    let hashes = block
+        .transactions
+        .iter()
+        .filter_map(|tx| match tx {
+            Transaction::Invoke(InvokeTransaction::V1(tx)) => Some(tx.transaction_hash),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(hashes, vec![hash]);
+
+    let MaybePendingStateUpdate::PendingUpdate(update) =
+        provider.get_state_update(pending).await.unwrap()
+    else {
+        panic!("should be the pending state update")
+    };
+    let nonce = update.state_diff.nonces.iter().find(|n| n.contract_address == account.address());
+    assert_eq!(nonce.map(|n| n.nonce), Some(FieldElement::TWO));
+    assert!(update.state_diff.storage_diffs.iter().any(|d| d.address == FEE_TOKEN_ADDRESS.0));
+
+    let MaybePendingTransactionReceipt::PendingReceipt(PendingTransactionReceipt::Invoke(receipt)) =
+        provider.get_transaction_receipt(hash).await.unwrap()
+    else {
+        panic!("should be a pending invoke receipt")
+    };
+    assert_eq!(receipt.transaction_hash, hash);
+    assert!(receipt.events.iter().any(|e| e.from_address == FEE_TOKEN_ADDRESS.0));
+
+    let filter = EventFilter {
+        from_block: Some(BlockId::Number(0)),
+        to_block: Some(pending),
+        address: Some(FEE_TOKEN_ADDRESS.0),
+        keys: None,
+    };
+    let events = provider.get_events(filter, None, 10).await.unwrap();
+    // the pending block doesn't have a hash yet
+    let event = events.events.iter().find(|e| e.transaction_hash == hash).expect("should be found");
+    assert_eq!(event.block_hash, FieldElement::ZERO);
+
+    assert_eq!(
+        provider.get_transaction_status(hash).await.unwrap(),
+        TransactionStatus::AcceptedOnL2(TransactionExecutionStatus::Succeeded)
+    );
+
+    // a transaction that isn't executed yet is only received
+    sequencer.sequencer.stop();
+    let res = account
+        .execute(vec![transfer])
+        .nonce(FieldElement::TWO)
+        .max_fee(FieldElement::from(1_000_000_000_000u64))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(
+        provider.get_transaction_status(res.transaction_hash).await.unwrap(),
+        TransactionStatus::Received
+    );
+
+    sequencer.stop().expect("failed to stop sequencer");
+}
+
 fn prepare_contract_declaration_params(
     artifact_path: &PathBuf,
 ) -> Result<(FlattenedSierraClass, FieldElement)> {
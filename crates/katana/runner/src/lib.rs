@@ -1,22 +1,170 @@
+//! Runs `katana` processes for tests.
+//!
+//! Each node started by the runner writes its logs, and optionally its state dump, to its own
+//! artifacts directory. The directory is removed when the node is dropped, unless the current
+//! thread is panicking (ie. the test has failed) or the artifacts are explicitly kept.
+//!
+//! Pairs of nodes settling through each other require the `starknet-messaging` feature, and a
+//! `katana` executable built with the feature of the same name.
+
 use std::fs::{self, File};
-use std::io::{BufRead, BufReader, Write};
 use std::net::TcpListener;
-use std::path::Path;
-use std::process::{Child, ChildStdout, Command, Stdio};
-use std::sync::mpsc::{self, Sender};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::{json, Value};
+#[cfg(test)]
+use starknet::accounts::{Account, ConnectedAccount};
+use starknet::accounts::{ExecutionEncoding, SingleOwnerAccount};
+use starknet::core::types::FieldElement;
+#[cfg(test)]
+use starknet::core::types::{BlockId, BlockTag};
 use starknet::providers::jsonrpc::HttpTransport;
 use starknet::providers::JsonRpcClient;
 #[cfg(test)]
 use starknet::providers::Provider;
+use starknet::signers::{LocalWallet, SigningKey};
 use url::Url;
 
+/// The interval at which the health of a starting node is checked.
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+/// The maximum time given to a node to shutdown gracefully before being killed.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+/// The maximum time to wait for the response of a request to the node.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The configuration of a node started by [`KatanaRunner`].
+#[derive(Debug, Clone)]
+pub struct KatanaRunnerConfig {
+    /// The name or path of the `katana` executable.
+    pub program_name: String,
+    /// The name of the node, used to name its artifacts directory. Defaults to `katana-<port>`.
+    pub node_name: Option<String>,
+    /// The port to listen on. A free port is picked if `None`.
+    pub port: Option<u16>,
+    pub block_time: Option<u64>,
+    pub no_mining: bool,
+    /// The Starknet RPC provider to fork the network from.
+    pub fork_rpc_url: Option<Url>,
+    pub fork_block_number: Option<u64>,
+    pub seed: Option<String>,
+    pub total_accounts: Option<u8>,
+    pub disable_fee: bool,
+    pub chain_id: Option<String>,
+    /// Produces the same chain for the same transactions on every run, for snapshot tests.
+    pub deterministic: bool,
+    /// The directory of the database to persist the chain to. The chain is resumed if the
+    /// directory already holds one.
+    pub db_dir: Option<PathBuf>,
+    /// The path of the messaging configuration file. See [`KatanaRunner::messaging_config`].
+    pub messaging: Option<PathBuf>,
+    /// Dumps the state of the node to its artifacts directory on shutdown.
+    pub dump_state: bool,
+    /// Additional arguments passed as-is to the executable.
+    pub args: Vec<String>,
+    /// The directory in which the artifacts directory of the node is created.
+    pub artifacts_dir: PathBuf,
+    /// Keeps the artifacts of the node even if the test succeeds.
+    pub keep_artifacts: bool,
+    /// The maximum time to wait for the node to be healthy.
+    pub startup_timeout: Duration,
+}
+
+impl Default for KatanaRunnerConfig {
+    fn default() -> Self {
+        Self {
+            program_name: "katana".into(),
+            node_name: None,
+            port: None,
+            block_time: None,
+            no_mining: false,
+            fork_rpc_url: None,
+            fork_block_number: None,
+            seed: None,
+            total_accounts: None,
+            disable_fee: false,
+            chain_id: None,
+            deterministic: false,
+            db_dir: None,
+            messaging: None,
+            dump_state: false,
+            args: Vec::new(),
+            artifacts_dir: PathBuf::from("logs"),
+            keep_artifacts: false,
+            startup_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl KatanaRunnerConfig {
+    /// Returns the command line arguments of the node listening on `port`, whose artifacts are
+    /// written to `artifacts_dir`.
+    fn args(&self, port: u16, artifacts_dir: &Path) -> Vec<String> {
+        // the `katana` namespace is required to retrieve the predeployed accounts
+        let mut args = vec!["--dev".to_string(), "--json-log".to_string()];
+        args.extend(["--port".to_string(), port.to_string()]);
+
+        if let Some(block_time) = self.block_time {
+            args.extend(["--block-time".to_string(), block_time.to_string()]);
+        }
+        if self.no_mining {
+            args.push("--no-mining".to_string());
+        }
+        if let Some(url) = &self.fork_rpc_url {
+            args.extend(["--rpc-url".to_string(), url.to_string()]);
+        }
+        if let Some(block_number) = self.fork_block_number {
+            args.extend(["--fork-block-number".to_string(), block_number.to_string()]);
+        }
+        if let Some(seed) = &self.seed {
+            args.extend(["--seed".to_string(), seed.clone()]);
+        }
+        if let Some(total_accounts) = self.total_accounts {
+            args.extend(["--accounts".to_string(), total_accounts.to_string()]);
+        }
+        if self.disable_fee {
+            args.push("--disable-fee".to_string());
+        }
+        if let Some(chain_id) = &self.chain_id {
+            args.extend(["--chain-id".to_string(), chain_id.clone()]);
+        }
+        if self.deterministic {
+            args.push("--deterministic".to_string());
+        }
+        if let Some(path) = &self.db_dir {
+            args.extend(["--db-dir".to_string(), path.display().to_string()]);
+        }
+        if let Some(path) = &self.messaging {
+            args.extend(["--messaging".to_string(), path.display().to_string()]);
+        }
+        if self.dump_state {
+            let path = artifacts_dir.join("state.bin");
+            args.extend(["--dump-state".to_string(), path.display().to_string()]);
+        }
+
+        args.extend(self.args.iter().cloned());
+        args
+    }
+}
+
+/// A predeployed account of a node.
+#[derive(Debug, Clone)]
+pub struct PredeployedAccount {
+    pub address: FieldElement,
+    pub private_key: FieldElement,
+}
+
 #[derive(Debug)]
 pub struct KatanaRunner {
     child: Child,
+    port: u16,
+    chain_id: FieldElement,
+    accounts: Vec<PredeployedAccount>,
+    artifacts_dir: PathBuf,
+    keep_artifacts: bool,
 }
 
 fn find_free_port() -> u16 {
@@ -29,70 +177,287 @@ impl KatanaRunner {
     }
 
     pub fn new_with_port(port: u16) -> Result<(Self, JsonRpcClient<HttpTransport>)> {
-        let log_filename = format!("logs/katana-{}.log", port);
+        let runner =
+            Self::new_with_config(KatanaRunnerConfig { port: Some(port), ..Default::default() })?;
+        let provider = runner.provider();
+        Ok((runner, provider))
+    }
 
-        let mut child = Command::new("katana")
-            .args(["-p", &port.to_string()])
-            .args(["--json-log"])
-            .stdout(Stdio::piped())
+    /// Starts a node and waits until it's healthy.
+    pub fn new_with_config(config: KatanaRunnerConfig) -> Result<Self> {
+        let port = config.port.unwrap_or_else(find_free_port);
+        let node_name = config.node_name.clone().unwrap_or_else(|| format!("katana-{port}"));
+
+        let artifacts_dir = config.artifacts_dir.join(node_name);
+        fs::create_dir_all(&artifacts_dir).context("failed to create artifacts directory")?;
+
+        let log_path = artifacts_dir.join("katana.log");
+        let log_file = File::create(&log_path).context("failed to create log file")?;
+
+        let child = Command::new(&config.program_name)
+            .args(config.args(port, &artifacts_dir))
+            .stdout(Stdio::from(log_file.try_clone()?))
+            .stderr(Stdio::from(log_file))
             .spawn()
             .context("failed to start subprocess")?;
 
-        let stdout = child.stdout.take().context("failed to take subprocess stdout")?;
+        let mut runner = KatanaRunner {
+            child,
+            port,
+            artifacts_dir,
+            chain_id: FieldElement::ZERO,
+            accounts: Vec::new(),
+            keep_artifacts: config.keep_artifacts,
+        };
 
-        let (sender, receiver) = mpsc::channel();
+        runner.wait_until_healthy(config.startup_timeout).with_context(|| {
+            runner.keep_artifacts = true;
+            format!("katana failed to start, see logs at {}", log_path.display())
+        })?;
 
-        thread::spawn(move || {
-            KatanaRunner::wait_for_server_started_and_signal(
-                Path::new(&log_filename),
-                stdout,
-                sender,
-            );
-        });
+        runner.chain_id = runner.fetch_chain_id()?;
+        runner.accounts = runner.fetch_accounts()?;
 
-        receiver
-            .recv_timeout(Duration::from_secs(5))
-            .context("timeout waiting for server to start")?;
+        Ok(runner)
+    }
 
-        let url =
-            Url::parse(&format!("http://127.0.0.1:{}/", port)).context("Failed to parse url")?;
-        let provider = JsonRpcClient::new(HttpTransport::new(url));
+    /// Starts a pair of nodes, where the second one uses the first one as its settlement chain.
+    ///
+    /// `messaging` is called once the first node is healthy, with the first node as argument, and
+    /// must return the address of the messaging contract deployed on it.
+    #[cfg(feature = "starknet-messaging")]
+    pub fn new_with_settlement(
+        l1: KatanaRunnerConfig,
+        l2: KatanaRunnerConfig,
+        messaging: impl FnOnce(&KatanaRunner) -> Result<FieldElement>,
+    ) -> Result<(Self, Self)> {
+        let l1 = Self::new_with_config(l1)?;
+        let contract_address = messaging(&l1)?;
+        let config_path = l1.messaging_config(contract_address)?;
 
-        Ok((KatanaRunner { child }, provider))
+        let l2 = Self::new_with_config(KatanaRunnerConfig { messaging: Some(config_path), ..l2 })?;
+        Ok((l1, l2))
     }
 
-    fn wait_for_server_started_and_signal(path: &Path, stdout: ChildStdout, sender: Sender<()>) {
-        let reader = BufReader::new(stdout);
+    pub fn port(&self) -> u16 {
+        self.port
+    }
 
-        if let Some(dir_path) = path.parent() {
-            if !dir_path.exists() {
-                fs::create_dir_all(dir_path).unwrap();
+    pub fn url(&self) -> Url {
+        Url::parse(&format!("http://127.0.0.1:{}/", self.port)).expect("valid url")
+    }
+
+    pub fn provider(&self) -> JsonRpcClient<HttpTransport> {
+        JsonRpcClient::new(HttpTransport::new(self.url()))
+    }
+
+    pub fn chain_id(&self) -> FieldElement {
+        self.chain_id
+    }
+
+    /// Returns the directory in which the logs and state dump of the node are written.
+    pub fn artifacts_dir(&self) -> &Path {
+        &self.artifacts_dir
+    }
+
+    pub fn predeployed_accounts(&self) -> &[PredeployedAccount] {
+        &self.accounts
+    }
+
+    /// Returns the predeployed account at `index`, connected to the node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn account(
+        &self,
+        index: usize,
+    ) -> SingleOwnerAccount<JsonRpcClient<HttpTransport>, LocalWallet> {
+        let account = &self.accounts[index];
+        SingleOwnerAccount::new(
+            self.provider(),
+            LocalWallet::from_signing_key(SigningKey::from_secret_scalar(account.private_key)),
+            account.address,
+            self.chain_id,
+            ExecutionEncoding::New,
+        )
+    }
+
+    /// Returns all the predeployed accounts, connected to the node.
+    pub fn accounts(&self) -> Vec<SingleOwnerAccount<JsonRpcClient<HttpTransport>, LocalWallet>> {
+        (0..self.accounts.len()).map(|i| self.account(i)).collect()
+    }
+
+    /// Writes a messaging configuration to use this node as the settlement chain of another node,
+    /// and returns its path. Messages are settled using the first predeployed account.
+    #[cfg(feature = "starknet-messaging")]
+    pub fn messaging_config(&self, contract_address: FieldElement) -> Result<PathBuf> {
+        let account = self.accounts.first().context("node has no predeployed account")?;
+
+        let config = json!({
+            "chain": "starknet",
+            "rpc_url": self.url().to_string(),
+            "contract_address": format!("{contract_address:#x}"),
+            "sender_address": format!("{:#x}", account.address),
+            "private_key": format!("{:#x}", account.private_key),
+            "interval": 2,
+            "from_block": 0,
+        });
+
+        let path = self.artifacts_dir.join("messaging.json");
+        fs::write(&path, serde_json::to_vec_pretty(&config)?)?;
+        Ok(path)
+    }
+
+    fn wait_until_healthy(&mut self, timeout: Duration) -> Result<()> {
+        let started_at = Instant::now();
+
+        loop {
+            if let Some(status) = self.child.try_wait()? {
+                bail!("katana exited with {status}");
             }
+
+            // `GET /` is served by the `health` method
+            if http_request(self.url(), None).is_ok_and(|body| body["health"] == true) {
+                return Ok(());
+            }
+
+            if started_at.elapsed() > timeout {
+                bail!("timeout waiting for katana to be healthy");
+            }
+
+            thread::sleep(HEALTH_CHECK_INTERVAL);
         }
-        let mut log_writer = File::create(path).expect("failed to create log file");
+    }
+
+    fn fetch_chain_id(&self) -> Result<FieldElement> {
+        let chain_id = rpc_call(self.url(), "starknet_chainId")?;
+        let chain_id = chain_id.as_str().context("invalid chain id")?;
+        Ok(FieldElement::from_hex_be(chain_id)?)
+    }
+
+    fn fetch_accounts(&self) -> Result<Vec<PredeployedAccount>> {
+        let accounts = rpc_call(self.url(), "katana_predeployedAccounts")?;
+        let accounts = accounts.as_array().context("invalid predeployed accounts")?;
 
-        for line in reader.lines() {
-            let line = line.expect("failed to read line from subprocess stdout");
-            writeln!(log_writer, "{}", line).expect("failed to write to log file");
+        let felt = |account: &Value, field: &str| -> Result<FieldElement> {
+            let value = account[field].as_str().with_context(|| format!("missing {field}"))?;
+            Ok(FieldElement::from_hex_be(value)?)
+        };
 
-            if line.contains(r#""target":"katana""#) {
-                sender.send(()).expect("failed to send start signal");
+        accounts
+            .iter()
+            .map(|account| {
+                Ok(PredeployedAccount {
+                    address: felt(account, "address")?,
+                    private_key: felt(account, "private_key")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Interrupts the node so that it can shutdown gracefully, and kills it if it doesn't exit in
+    /// time.
+    fn shutdown(&mut self) -> std::io::Result<()> {
+        #[cfg(unix)]
+        {
+            let interrupted = Command::new("kill")
+                .args(["-INT", &self.child.id().to_string()])
+                .status()
+                .is_ok_and(|status| status.success());
+
+            if interrupted {
+                let started_at = Instant::now();
+                while started_at.elapsed() < SHUTDOWN_TIMEOUT {
+                    if self.child.try_wait()?.is_some() {
+                        return Ok(());
+                    }
+                    thread::sleep(HEALTH_CHECK_INTERVAL);
+                }
             }
         }
+
+        self.child.kill()?;
+        self.child.wait().map(|_| ())
     }
 }
 
 impl Drop for KatanaRunner {
     fn drop(&mut self) {
-        if let Err(e) = self.child.kill() {
-            eprintln!("Failed to kill katana subprocess: {}", e);
+        if let Err(e) = self.shutdown() {
+            eprintln!("Failed to stop katana subprocess: {}", e);
         }
-        if let Err(e) = self.child.wait() {
-            eprintln!("Failed to wait for katana subprocess: {}", e);
+
+        if self.keep_artifacts || thread::panicking() {
+            eprintln!("Katana artifacts kept at {}", self.artifacts_dir.display());
+        } else if let Err(e) = fs::remove_dir_all(&self.artifacts_dir) {
+            eprintln!("Failed to remove katana artifacts: {}", e);
         }
     }
 }
 
+/// Calls a JSON-RPC method without parameters and returns its result.
+fn rpc_call(url: Url, method: &str) -> Result<Value> {
+    let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": [] });
+    let mut response = http_request(url, Some(request))?;
+
+    match response.get_mut("result") {
+        Some(result) => Ok(result.take()),
+        None => bail!("{method} failed: {}", response["error"]),
+    }
+}
+
+/// Sends a request to the node, a `POST` with the given body or a `GET` otherwise, and returns the
+/// JSON body of the response.
+///
+/// A blocking client is used so that nodes can be started outside of an async runtime. It can't be
+/// used from within one though, which is where the tests starting nodes usually run, so the request
+/// is sent from its own thread.
+fn http_request(url: Url, body: Option<Value>) -> Result<Value> {
+    let request = move || -> Result<Value> {
+        let client = reqwest::blocking::Client::builder().timeout(REQUEST_TIMEOUT).build()?;
+        let request = match body {
+            Some(body) => client.post(url).json(&body),
+            None => client.get(url),
+        };
+        Ok(request.send()?.error_for_status()?.json()?)
+    };
+
+    thread::spawn(request).join().map_err(|_| anyhow!("request thread panicked"))?
+}
+
+#[test]
+fn config_to_args() {
+    let config = KatanaRunnerConfig {
+        block_time: Some(1000),
+        seed: Some("1".into()),
+        db_dir: Some("db".into()),
+        dump_state: true,
+        args: vec!["--silent".into()],
+        ..Default::default()
+    };
+
+    let args = config.args(5050, Path::new("logs/node"));
+    assert_eq!(
+        args,
+        [
+            "--dev",
+            "--json-log",
+            "--port",
+            "5050",
+            "--block-time",
+            "1000",
+            "--seed",
+            "1",
+            "--db-dir",
+            "db",
+            "--dump-state",
+            "logs/node/state.bin",
+            "--silent"
+        ]
+    );
+}
+
 #[tokio::test]
 async fn test_run() {
     let (_katana_guard, long_lived_provider) =
@@ -106,3 +471,53 @@ async fn test_run() {
         let _other_block_number = long_lived_provider.block_number().await.unwrap();
     }
 }
+
+#[tokio::test]
+async fn test_accounts_of_two_nodes() {
+    let config = |seed: &str| KatanaRunnerConfig {
+        seed: Some(seed.into()),
+        total_accounts: Some(3),
+        ..Default::default()
+    };
+    let node1 = KatanaRunner::new_with_config(config("1")).expect("failed to start katana");
+    let node2 = KatanaRunner::new_with_config(config("2")).expect("failed to start another katana");
+
+    assert_eq!(node1.accounts().len(), 3);
+    assert_eq!(node2.accounts().len(), 3);
+    assert_ne!(node1.account(0).address(), node2.account(0).address());
+
+    // the accounts are connected to the node they are predeployed on
+    for node in [&node1, &node2] {
+        for account in node.accounts() {
+            assert_eq!(account.chain_id(), node.chain_id());
+            assert_eq!(account.get_nonce().await.unwrap(), FieldElement::ONE);
+        }
+    }
+
+    let address = node1.account(0).address();
+    let nonce = node2.provider().get_nonce(BlockId::Tag(BlockTag::Latest), address).await;
+    assert!(nonce.is_err(), "account shouldn't be deployed on the other node");
+}
+
+#[cfg(feature = "starknet-messaging")]
+#[tokio::test]
+async fn test_settlement_pair() {
+    // no message is sent, so the messaging contract doesn't have to be deployed
+    let contract_address = FieldElement::from(0x1337u32);
+    let (l1, l2) = KatanaRunner::new_with_settlement(
+        KatanaRunnerConfig::default(),
+        KatanaRunnerConfig::default(),
+        |_| Ok(contract_address),
+    )
+    .expect("failed to start the settlement pair");
+
+    let config = fs::read(l1.artifacts_dir().join("messaging.json")).unwrap();
+    let config: Value = serde_json::from_slice(&config).unwrap();
+    assert_eq!(config["rpc_url"], l1.url().to_string());
+    assert_eq!(config["contract_address"], format!("{contract_address:#x}"));
+    assert_eq!(config["sender_address"], format!("{:#x}", l1.predeployed_accounts()[0].address));
+
+    // the settled node is up, polling the settlement chain for messages
+    assert_eq!(l2.provider().block_number().await.unwrap(), 0);
+    assert_eq!(l1.provider().block_number().await.unwrap(), 0);
+}
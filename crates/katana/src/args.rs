@@ -10,8 +10,9 @@
 //!   documentation for usage details. This is **not recommended on Windows**. See [here](https://rust-lang.github.io/rfcs/1974-global-allocators.html#jemalloc)
 //!   for more info.
 
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::path::PathBuf;
+use std::time::Duration;
 
 use clap::{Args, Parser, Subcommand};
 use clap_complete::Shell;
@@ -19,9 +20,11 @@ use katana_core::backend::config::{Environment, StarknetConfig};
 use katana_core::constants::{
     DEFAULT_GAS_PRICE, DEFAULT_INVOKE_MAX_STEPS, DEFAULT_VALIDATE_MAX_STEPS,
 };
+use katana_core::env::TimeMode;
 use katana_core::sequencer::SequencerConfig;
+use katana_provider::providers::db::pruner::PruningConfig;
 use katana_rpc::api::ApiKind;
-use katana_rpc::config::ServerConfig;
+use katana_rpc::config::{MethodFilter, ServerConfig, DEFAULT_MAX_BODY_SIZE};
 use metrics::utils::parse_socket_address;
 use tracing::Subscriber;
 use tracing_subscriber::{fmt, EnvFilter};
@@ -54,9 +57,39 @@ pub struct KatanaArgs {
 
     #[arg(long)]
     #[arg(value_name = "URL")]
+    #[arg(group = "fork_source")]
     #[arg(help = "The Starknet RPC provider to fork the network from.")]
     pub rpc_url: Option<Url>,
 
+    #[arg(long)]
+    #[arg(value_name = "PATH")]
+    #[arg(group = "fork_source")]
+    #[arg(help = "The database directory of another Katana instance to fork from.")]
+    pub fork_db: Option<PathBuf>,
+
+    #[arg(long)]
+    #[arg(value_name = "PATH")]
+    #[arg(conflicts_with_all = ["fork_source", "fork_block_number"])]
+    #[arg(help = "The state dump to fork from, as written by `--dump-state`.")]
+    pub fork_state: Option<PathBuf>,
+
+    #[arg(long)]
+    #[arg(value_name = "PATH")]
+    #[arg(conflicts_with_all = ["fork_source", "fork_state"])]
+    #[arg(help = "Persist the chain to a database in the given directory.")]
+    #[arg(long_help = "Persist the chain to a database in the given directory. If the directory \
+                       already holds a chain, the node resumes from its latest block.")]
+    pub db_dir: Option<PathBuf>,
+
+    #[arg(long)]
+    #[arg(value_name = "BLOCKS")]
+    #[arg(requires = "db_dir")]
+    #[arg(help = "Only keep the history of the last BLOCKS blocks in the database.")]
+    #[arg(long_help = "Only keep the history of the last BLOCKS blocks in the database. The \
+                       transactions, receipts and historical state of the older blocks are \
+                       pruned in the background. The full history is kept if not specified.")]
+    pub prune: Option<u64>,
+
     #[arg(long)]
     pub dev: bool,
 
@@ -71,11 +104,21 @@ pub struct KatanaArgs {
     pub metrics: Option<SocketAddr>,
 
     #[arg(long)]
-    #[arg(requires = "rpc_url")]
+    #[arg(requires = "fork_source")]
     #[arg(value_name = "BLOCK_NUMBER")]
-    #[arg(help = "Fork the network at a specific block.")]
+    #[arg(help = "Fork the network or the database at a specific block.")]
     pub fork_block_number: Option<u64>,
 
+    #[arg(long)]
+    #[arg(conflicts_with_all = ["fork_source", "fork_state", "block_time"])]
+    #[arg(help = "Produce the same chain for the same transactions on every run.")]
+    #[arg(long_help = "Produce the same chain for the same transactions on every run. The block \
+                       timestamps are derived from the block numbers, the transactions received \
+                       together are mined in a canonical order and, when mining instantly, each \
+                       in its own block. Transactions received at different times are still \
+                       mined in the order they were received in.")]
+    pub deterministic: bool,
+
     #[cfg(feature = "messaging")]
     #[arg(long)]
     #[arg(value_name = "PATH")]
@@ -119,6 +162,74 @@ pub struct ServerOptions {
     #[arg(default_value = "100")]
     #[arg(help = "Maximum number of concurrent connections allowed.")]
     pub max_connections: u32,
+
+    #[arg(long)]
+    #[arg(value_delimiter = ',')]
+    #[arg(help = "Comma separated list of origins allowed to make cross-origin requests. Any \
+                  origin is allowed if not specified.")]
+    pub allowed_origins: Option<Vec<String>>,
+
+    #[arg(long)]
+    #[arg(value_name = "CALLS_PER_SECOND")]
+    #[arg(help = "Maximum number of calls per second accepted from a single client. Clients are \
+                  identified by the X-Forwarded-For or X-Real-IP headers set by the trusted \
+                  proxies, or by their connection otherwise. WebSocket connections are refused \
+                  when set.")]
+    pub rate_limit: Option<u32>,
+
+    #[arg(long)]
+    #[arg(value_delimiter = ',')]
+    #[arg(help = "Comma separated list of the addresses of the reverse proxies trusted to \
+                  forward the address of the clients. Forwarding headers are ignored if not \
+                  specified.")]
+    pub trusted_proxies: Option<Vec<IpAddr>>,
+
+    #[arg(long)]
+    #[arg(help = "Bearer token required to call the methods of the `katana` namespace.")]
+    pub auth_token: Option<String>,
+
+    #[arg(long)]
+    #[arg(value_delimiter = ',')]
+    #[arg(conflicts_with = "rpc_deny")]
+    #[arg(help = "Comma separated list of the only RPC methods that can be called.")]
+    pub rpc_allow: Option<Vec<String>>,
+
+    #[arg(long)]
+    #[arg(value_delimiter = ',')]
+    #[arg(help = "Comma separated list of RPC methods that can't be called.")]
+    pub rpc_deny: Option<Vec<String>>,
+
+    #[arg(long)]
+    #[arg(value_name = "SECONDS")]
+    #[arg(default_value = "2")]
+    #[arg(help = "Maximum time allowed to process a request.")]
+    pub request_timeout: u64,
+
+    #[arg(long)]
+    #[arg(value_name = "BYTES")]
+    #[arg(default_value_t = DEFAULT_MAX_BODY_SIZE)]
+    #[arg(help = "Maximum size of a request body.")]
+    pub max_request_size: u32,
+
+    #[arg(long)]
+    #[arg(value_name = "BYTES")]
+    #[arg(default_value_t = DEFAULT_MAX_BODY_SIZE)]
+    #[arg(help = "Maximum size of a response body.")]
+    pub max_response_size: u32,
+
+    #[arg(long)]
+    #[arg(help = "Maximum number of calls in a batch request.")]
+    pub max_batch_size: Option<usize>,
+}
+
+impl ServerOptions {
+    fn method_filter(&self) -> MethodFilter {
+        match (&self.rpc_allow, &self.rpc_deny) {
+            (Some(methods), _) => MethodFilter::Allow(methods.iter().cloned().collect()),
+            (None, Some(methods)) => MethodFilter::Deny(methods.iter().cloned().collect()),
+            (None, None) => MethodFilter::All,
+        }
+    }
 }
 
 #[derive(Debug, Args, Clone)]
@@ -142,6 +253,21 @@ pub struct StarknetOptions {
     #[arg(help = "Disable validation when executing transactions.")]
     pub disable_validate: bool,
 
+    #[arg(long)]
+    #[arg(help = "Execute the transactions of a block optimistically in parallel.")]
+    pub parallel_execution: bool,
+
+    #[arg(long)]
+    #[arg(value_name = "MODE")]
+    #[arg(default_value_t = TimeMode::Realtime)]
+    #[arg(help = "The mode in which the block timestamps advance.")]
+    #[arg(long_help = "The mode in which the block timestamps advance. Either `realtime` to \
+                       follow the wall clock, `frozen` to only change when set through the RPC, \
+                       `accelerated:<FACTOR>` to advance FACTOR times faster than the wall \
+                       clock, or `fixed_increment:<SECONDS>` to increase by SECONDS on every \
+                       block.")]
+    pub time_mode: TimeMode,
+
     #[command(flatten)]
     #[command(next_help_heading = "Environment options")]
     pub environment: EnvironmentOptions,
@@ -207,6 +333,15 @@ impl KatanaArgs {
             port: self.server.port,
             host: self.server.host.clone().unwrap_or("0.0.0.0".into()),
             max_connections: self.server.max_connections,
+            allowed_origins: self.server.allowed_origins.clone(),
+            rate_limit: self.server.rate_limit,
+            trusted_proxies: self.server.trusted_proxies.clone().unwrap_or_default(),
+            auth_token: self.server.auth_token.clone(),
+            method_filter: self.server.method_filter(),
+            request_timeout: Duration::from_secs(self.server.request_timeout),
+            max_request_body_size: self.server.max_request_size,
+            max_response_body_size: self.server.max_response_size,
+            max_batch_size: self.server.max_batch_size,
         }
     }
 
@@ -216,8 +351,15 @@ impl KatanaArgs {
             seed: parse_seed(&self.starknet.seed),
             disable_fee: self.starknet.disable_fee,
             disable_validate: self.starknet.disable_validate,
+            parallel_execution: self.starknet.parallel_execution,
+            time_mode: self.starknet.time_mode,
             fork_rpc_url: self.rpc_url.clone(),
             fork_block_number: self.fork_block_number,
+            fork_db: self.fork_db.clone(),
+            fork_state: self.fork_state.clone(),
+            db_dir: self.db_dir.clone(),
+            pruning: self.prune.map(PruningConfig::distance).unwrap_or_default(),
+            deterministic: self.deterministic,
             env: Environment {
                 chain_id: self.starknet.environment.chain_id.clone(),
                 gas_price: self.starknet.environment.gas_price.unwrap_or(DEFAULT_GAS_PRICE),
@@ -250,18 +392,58 @@ fn parse_seed(seed: &str) -> [u8; 32] {
 
 #[cfg(test)]
 mod test {
+    use katana_provider::providers::db::pruner::PruneMode;
+
     use super::*;
 
     #[test]
     fn default_block_context_from_args() {
         let args = KatanaArgs::parse_from(["katana"]);
         let block_context = args.starknet_config().block_context();
-        assert_eq!(block_context.gas_prices.eth_l1_gas_price, DEFAULT_GAS_PRICE);
+        assert_eq!(block_context.gas_prices.eth_gas_price, DEFAULT_GAS_PRICE as u64);
         assert_eq!(block_context.chain_id.0, "KATANA".to_string());
         assert_eq!(block_context.validate_max_n_steps, DEFAULT_VALIDATE_MAX_STEPS);
         assert_eq!(block_context.invoke_tx_max_n_steps, DEFAULT_INVOKE_MAX_STEPS);
     }
 
+    #[test]
+    fn server_config_from_args() {
+        let args = KatanaArgs::parse_from([
+            "katana",
+            "--allowed-origins",
+            "https://a.xyz,https://b.xyz",
+            "--rate-limit",
+            "50",
+            "--trusted-proxies",
+            "10.0.0.1,10.0.0.2",
+            "--rpc-deny",
+            "starknet_addDeclareTransaction",
+            "--request-timeout",
+            "10",
+        ]);
+
+        let config = args.server_config();
+
+        assert_eq!(
+            config.allowed_origins,
+            Some(vec!["https://a.xyz".to_string(), "https://b.xyz".to_string()])
+        );
+        assert_eq!(config.rate_limit, Some(50));
+        assert_eq!(
+            config.trusted_proxies,
+            vec!["10.0.0.1".parse::<IpAddr>().unwrap(), "10.0.0.2".parse().unwrap()]
+        );
+        assert_eq!(
+            config.method_filter,
+            MethodFilter::Deny(["starknet_addDeclareTransaction".to_string()].into())
+        );
+        assert_eq!(config.request_timeout, Duration::from_secs(10));
+        assert_eq!(config.max_response_body_size, DEFAULT_MAX_BODY_SIZE);
+        assert!(
+            KatanaArgs::try_parse_from(["katana", "--rpc-allow", "a", "--rpc-deny", "b"]).is_err()
+        );
+    }
+
     #[test]
     fn custom_block_context_from_args() {
         let args = KatanaArgs::parse_from([
@@ -278,9 +460,68 @@ mod test {
 
         let block_context = args.starknet_config().block_context();
 
-        assert_eq!(block_context.gas_prices.eth_l1_gas_price, 10);
+        assert_eq!(block_context.gas_prices.eth_gas_price, 10);
         assert_eq!(block_context.chain_id.0, "SN_GOERLI".to_string());
         assert_eq!(block_context.validate_max_n_steps, 100);
         assert_eq!(block_context.invoke_tx_max_n_steps, 200);
     }
+
+    #[test]
+    fn time_mode_from_args() {
+        let args = KatanaArgs::parse_from(["katana"]);
+        assert_eq!(args.starknet_config().time_mode, TimeMode::Realtime);
+
+        let args = KatanaArgs::parse_from(["katana", "--time-mode", "accelerated:60"]);
+        assert_eq!(args.starknet_config().time_mode, TimeMode::Accelerated(60));
+
+        assert!(KatanaArgs::try_parse_from(["katana", "--time-mode", "slow"]).is_err());
+    }
+
+    #[test]
+    fn fork_source_from_args() {
+        let args =
+            KatanaArgs::parse_from(["katana", "--fork-db", "db", "--fork-block-number", "5"]);
+        let config = args.starknet_config();
+        assert_eq!(config.fork_db, Some(PathBuf::from("db")));
+        assert_eq!(config.fork_block_number, Some(5));
+
+        let args = KatanaArgs::parse_from(["katana", "--fork-state", "state.bin"]);
+        assert_eq!(args.starknet_config().fork_state, Some(PathBuf::from("state.bin")));
+
+        let args = ["katana", "--fork-db", "db", "--rpc-url", "http://localhost:5050"];
+        assert!(KatanaArgs::try_parse_from(args).is_err());
+        let args = ["katana", "--fork-state", "state.bin", "--fork-block-number", "5"];
+        assert!(KatanaArgs::try_parse_from(args).is_err());
+        assert!(KatanaArgs::try_parse_from(["katana", "--fork-block-number", "5"]).is_err());
+    }
+
+    #[test]
+    fn deterministic_from_args() {
+        let args = KatanaArgs::parse_from(["katana"]);
+        assert!(!args.starknet_config().deterministic);
+
+        let args = KatanaArgs::parse_from(["katana", "--deterministic"]);
+        assert!(args.starknet_config().deterministic);
+
+        let args = ["katana", "--deterministic", "--block-time", "1000"];
+        assert!(KatanaArgs::try_parse_from(args).is_err());
+    }
+
+    #[test]
+    fn pruning_from_args() {
+        let config = KatanaArgs::parse_from(["katana"]).starknet_config();
+        assert_eq!(config.db_dir, None);
+        assert_eq!(config.pruning, PruningConfig::archive());
+
+        let args = KatanaArgs::parse_from(["katana", "--db-dir", "db", "--prune", "64"]);
+        let config = args.starknet_config();
+        assert_eq!(config.db_dir, Some(PathBuf::from("db")));
+        assert_eq!(config.pruning.state_history, PruneMode::Distance(64));
+        assert_eq!(config.pruning.transactions, PruneMode::Distance(64));
+
+        assert!(KatanaArgs::try_parse_from(["katana", "--prune", "64"]).is_err());
+        assert!(
+            KatanaArgs::try_parse_from(["katana", "--db-dir", "db", "--fork-db", "f"]).is_err()
+        );
+    }
 }
@@ -1,5 +1,4 @@
 use std::io;
-use std::sync::Arc;
 
 use clap::{CommandFactory, Parser};
 use clap_complete::{generate, Shell};
@@ -7,8 +6,7 @@ use console::Style;
 use katana_core::constants::{
     ERC20_CONTRACT_CLASS_HASH, FEE_TOKEN_ADDRESS, UDC_ADDRESS, UDC_CLASS_HASH,
 };
-use katana_core::sequencer::KatanaSequencer;
-use katana_rpc::{spawn, NodeHandle};
+use katana_node::{NodeBuilder, NodeConfig};
 use metrics::prometheus_exporter;
 use tokio::signal::ctrl_c;
 use tracing::info;
@@ -32,15 +30,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
-    let server_config = config.server_config();
-    let sequencer_config = config.sequencer_config();
-    let starknet_config = config.starknet_config();
+    let node = NodeBuilder::with_config(NodeConfig {
+        server: config.server_config(),
+        sequencer: config.sequencer_config(),
+        starknet: config.starknet_config(),
+    })
+    .start()
+    .await?;
 
-    let sequencer = Arc::new(KatanaSequencer::new(sequencer_config, starknet_config).await);
-    let NodeHandle { addr, handle, .. } = spawn(Arc::clone(&sequencer), server_config).await?;
+    let addr = node.addr();
 
     if !config.silent {
-        let mut accounts = sequencer.backend.accounts.iter().peekable();
+        let mut accounts = node.accounts().iter().peekable();
         let account_class_hash = accounts.peek().unwrap().class_hash;
 
         if config.json_log {
@@ -80,7 +81,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Wait until Ctrl + C is pressed, then shutdown
     ctrl_c().await?;
-    handle.stop()?;
+    node.stop()?;
+
+    if let Some(path) = config.dump_state {
+        let path = if path.is_dir() { path.join("state.bin") } else { path };
+        node.sequencer().backend.dump_state()?.save(&path)?;
+        info!(target: "katana::cli", path = %path.display(), "Dumped state");
+    }
 
     Ok(())
 }
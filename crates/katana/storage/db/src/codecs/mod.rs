@@ -3,6 +3,7 @@ pub mod postcard;
 
 use katana_primitives::block::FinalityStatus;
 use katana_primitives::contract::{ContractAddress, FlattenedSierraClass};
+use katana_primitives::event::{EventBloom, EVENT_BLOOM_SIZE};
 use katana_primitives::FieldElement;
 
 use crate::error::CodecError;
@@ -101,3 +102,18 @@ impl Decompress for FinalityStatus {
         }
     }
 }
+
+impl Compress for EventBloom {
+    type Compressed = [u8; EVENT_BLOOM_SIZE];
+    fn compress(self) -> Self::Compressed {
+        self.0
+    }
+}
+
+impl Decompress for EventBloom {
+    fn decompress<B: AsRef<[u8]>>(bytes: B) -> Result<Self, CodecError> {
+        let bytes = <[u8; EVENT_BLOOM_SIZE]>::try_from(bytes.as_ref())
+            .map_err(|_| CodecError::Decompress("Invalid event bloom length".into()))?;
+        Ok(EventBloom(bytes))
+    }
+}
@@ -49,6 +49,22 @@ pub fn init_db<P: AsRef<Path>>(path: P) -> anyhow::Result<DbEnv> {
     Ok(env)
 }
 
+/// Open the existing database at the given `path` in read-only mode, eg. to fork it.
+///
+/// Unlike [`init_db`], this never writes to the database directory, so a database with a different
+/// version is rejected instead of being upgraded.
+pub fn open_db_ro<P: AsRef<Path>>(path: P) -> anyhow::Result<DbEnv> {
+    match check_db_version(&path) {
+        // databases created before the version file was introduced are of the current version
+        Ok(_) | Err(DatabaseVersionError::FileNotFound) => {}
+        Err(err) => return Err(anyhow!(err)),
+    }
+
+    DbEnv::open(path.as_ref(), DbEnvKind::RO).with_context(|| {
+        format!("Opening database in read-only mode at path {}", path.as_ref().display())
+    })
+}
+
 /// Open the database at the given `path` in read-write mode.
 pub fn open_db<P: AsRef<Path>>(path: P) -> anyhow::Result<DbEnv> {
     DbEnv::open(path.as_ref(), DbEnvKind::RW).with_context(|| {
@@ -61,8 +77,8 @@ mod tests {
 
     use std::fs;
 
-    use crate::init_db;
     use crate::version::{default_version_file_path, get_db_version, CURRENT_DB_VERSION};
+    use crate::{init_db, open_db_ro};
 
     #[test]
     fn initialize_db_in_empty_dir() {
@@ -112,6 +128,20 @@ mod tests {
         assert!(err.to_string().contains("Database version mismatch"));
     }
 
+    #[test]
+    fn open_db_ro_with_mismatch_version() {
+        let path = tempfile::tempdir().unwrap();
+        init_db(path.path()).unwrap();
+        open_db_ro(path.path()).unwrap();
+
+        let version_file_path = default_version_file_path(path.path());
+        fs::remove_file(&version_file_path).unwrap();
+        fs::write(version_file_path, 99u32.to_be_bytes()).unwrap();
+
+        let err = open_db_ro(path.path()).unwrap_err();
+        assert!(err.to_string().contains("Database version mismatch"));
+    }
+
     #[test]
     fn initialize_db_with_missing_version_file() {
         let path = tempfile::tempdir().unwrap();
@@ -1,4 +1,5 @@
 pub mod block;
 pub mod class;
 pub mod contract;
+pub mod prune;
 pub mod storage;
@@ -0,0 +1,32 @@
+use crate::codecs::{Decode, Encode};
+use crate::error::CodecError;
+
+/// The parts of the database history that can be pruned independently.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(u8)]
+pub enum PruneSegment {
+    /// The nonce, class hash and storage change sets used to serve historical state.
+    StateHistory = 0,
+    /// The transaction receipts.
+    Receipts = 1,
+    /// The transactions, along with their hashes and block numbers.
+    Transactions = 2,
+}
+
+impl Encode for PruneSegment {
+    type Encoded = [u8; 1];
+    fn encode(self) -> Self::Encoded {
+        [self as u8]
+    }
+}
+
+impl Decode for PruneSegment {
+    fn decode<B: AsRef<[u8]>>(bytes: B) -> Result<Self, CodecError> {
+        match bytes.as_ref().first() {
+            Some(0) => Ok(PruneSegment::StateHistory),
+            Some(1) => Ok(PruneSegment::Receipts),
+            Some(2) => Ok(PruneSegment::Transactions),
+            _ => Err(CodecError::Decode("Invalid prune segment".into())),
+        }
+    }
+}
@@ -3,13 +3,16 @@ use katana_primitives::contract::{
     ClassHash, CompiledClassHash, ContractAddress, FlattenedSierraClass, GenericContractInfo,
     StorageKey,
 };
+use katana_primitives::event::EventBloom;
 use katana_primitives::receipt::Receipt;
 use katana_primitives::transaction::{Tx, TxHash, TxNumber};
+use katana_primitives::FieldElement;
 
 use crate::codecs::{Compress, Decode, Decompress, Encode};
 use crate::models::block::StoredBlockBodyIndices;
 use crate::models::class::StoredContractClass;
 use crate::models::contract::{ContractClassChange, ContractInfoChangeList, ContractNonceChange};
+use crate::models::prune::PruneSegment;
 use crate::models::storage::{
     ContractStorageEntry, ContractStorageKey, StorageEntry, StorageEntryChangeList,
 };
@@ -47,7 +50,7 @@ pub enum TableType {
     DupSort,
 }
 
-pub const NUM_TABLES: usize = 22;
+pub const NUM_TABLES: usize = 26;
 
 /// Macro to declare `libmdbx` tables.
 #[macro_export]
@@ -169,7 +172,11 @@ define_tables_enum! {[
     (NonceChanges, TableType::DupSort),
     (ContractClassChanges, TableType::DupSort),
     (StorageChanges, TableType::DupSort),
-    (StorageChangeSet, TableType::DupSort)
+    (StorageChangeSet, TableType::DupSort),
+    (PruneCheckpoints, TableType::Table),
+    (BlockEventBlooms, TableType::Table),
+    (ContractEventBlocks, TableType::DupSort),
+    (EventKeyBlocks, TableType::DupSort)
 ]}
 
 tables! {
@@ -224,8 +231,18 @@ tables! {
     /// storage change set
     StorageChangeSet: (ContractAddress, StorageKey) => StorageEntryChangeList,
     /// Account storage change set
-    StorageChanges: (BlockNumber, ContractStorageKey) => ContractStorageEntry
+    StorageChanges: (BlockNumber, ContractStorageKey) => ContractStorageEntry,
 
+    /// Stores the highest block number whose history has been pruned for each segment.
+    PruneCheckpoints: (PruneSegment) => BlockNumber,
+
+    /// Stores the bloom filter of the events emitted in a block.
+    BlockEventBlooms: (BlockNumber) => EventBloom,
+    /// Stores the blocks in which a contract has emitted events, one entry per block.
+    ContractEventBlocks: (ContractAddress, BlockNumber) => BlockNumber,
+    /// Stores the blocks in which events have been emitted with the given first key, one entry
+    /// per block.
+    EventKeyBlocks: (FieldElement, BlockNumber) => BlockNumber
 }
 
 #[cfg(test)]
@@ -257,5 +274,9 @@ mod tests {
         assert_eq!(Tables::ALL[19].name(), ContractClassChanges::NAME);
         assert_eq!(Tables::ALL[20].name(), StorageChanges::NAME);
         assert_eq!(Tables::ALL[21].name(), StorageChangeSet::NAME);
+        assert_eq!(Tables::ALL[22].name(), PruneCheckpoints::NAME);
+        assert_eq!(Tables::ALL[23].name(), BlockEventBlooms::NAME);
+        assert_eq!(Tables::ALL[24].name(), ContractEventBlocks::NAME);
+        assert_eq!(Tables::ALL[25].name(), EventKeyBlocks::NAME);
     }
 }
@@ -10,6 +10,7 @@ use katana_primitives::contract::{
     ClassHash, CompiledClassHash, CompiledContractClass, ContractAddress, FlattenedSierraClass,
     GenericContractInfo, StorageKey, StorageValue,
 };
+use katana_primitives::event::EventFilter;
 use katana_primitives::receipt::Receipt;
 use katana_primitives::state::{StateUpdates, StateUpdatesWithDeclaredClasses};
 use katana_primitives::transaction::{TxHash, TxNumber, TxWithHash};
@@ -24,6 +25,7 @@ pub mod traits;
 
 use crate::traits::block::{BlockHashProvider, BlockNumberProvider, BlockProvider, HeaderProvider};
 use crate::traits::contract::ContractInfoProvider;
+use crate::traits::event::EventProvider;
 use crate::traits::state::{StateFactoryProvider, StateProvider};
 use crate::traits::state_update::StateUpdateProvider;
 use crate::traits::transaction::{ReceiptProvider, TransactionProvider, TransactionsProviderExt};
@@ -246,6 +248,19 @@ where
     }
 }
 
+impl<Db> EventProvider for BlockchainProvider<Db>
+where
+    Db: EventProvider,
+{
+    fn blocks_with_events(
+        &self,
+        range: RangeInclusive<BlockNumber>,
+        filter: &EventFilter,
+    ) -> Result<Vec<BlockNumber>> {
+        self.provider.blocks_with_events(range, filter)
+    }
+}
+
 impl<Db> StateUpdateProvider for BlockchainProvider<Db>
 where
     Db: StateUpdateProvider,
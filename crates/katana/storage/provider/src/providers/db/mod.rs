@@ -1,8 +1,10 @@
+pub mod pruner;
 pub mod state;
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::fmt::Debug;
 use std::ops::{Range, RangeInclusive};
+use std::sync::Arc;
 
 use anyhow::Result;
 use katana_db::error::DatabaseError;
@@ -11,13 +13,15 @@ use katana_db::models::block::StoredBlockBodyIndices;
 use katana_db::models::contract::{
     ContractClassChange, ContractInfoChangeList, ContractNonceChange,
 };
+use katana_db::models::prune::PruneSegment;
 use katana_db::models::storage::{
     ContractStorageEntry, ContractStorageKey, StorageEntry, StorageEntryChangeList,
 };
 use katana_db::tables::{
-    BlockBodyIndices, BlockHashes, BlockNumbers, BlockStatusses, ClassDeclarationBlock,
-    ClassDeclarations, CompiledClassHashes, CompiledContractClasses, ContractClassChanges,
-    ContractInfo, ContractInfoChangeSet, ContractStorage, DupSort, Headers, NonceChanges, Receipts,
+    BlockBodyIndices, BlockEventBlooms, BlockHashes, BlockNumbers, BlockStatusses,
+    ClassDeclarationBlock, ClassDeclarations, CompiledClassHashes, CompiledContractClasses,
+    ContractClassChanges, ContractEventBlocks, ContractInfo, ContractInfoChangeSet,
+    ContractStorage, DupSort, EventKeyBlocks, Headers, NonceChanges, PruneCheckpoints, Receipts,
     SierraClasses, StorageChangeSet, StorageChanges, Table, Transactions, TxBlocks, TxHashes,
     TxNumbers,
 };
@@ -30,15 +34,19 @@ use katana_primitives::contract::{
     ClassHash, CompiledClassHash, ContractAddress, GenericContractInfo, Nonce, StorageKey,
     StorageValue,
 };
+use katana_primitives::event::{EventBloom, EventFilter};
 use katana_primitives::receipt::Receipt;
 use katana_primitives::state::{StateUpdates, StateUpdatesWithDeclaredClasses};
 use katana_primitives::transaction::{TxHash, TxNumber, TxWithHash};
 use katana_primitives::FieldElement;
 
+use self::pruner::{Pruner, PruningConfig};
+use self::state::PinnedStateProvider;
 use crate::traits::block::{
     BlockHashProvider, BlockNumberProvider, BlockProvider, BlockStatusProvider, BlockWriter,
     HeaderProvider,
 };
+use crate::traits::event::EventProvider;
 use crate::traits::state::{StateFactoryProvider, StateProvider, StateRootProvider};
 use crate::traits::state_update::StateUpdateProvider;
 use crate::traits::transaction::{
@@ -47,12 +55,62 @@ use crate::traits::transaction::{
 
 /// A provider implementation that uses a database as a backend.
 #[derive(Debug)]
-pub struct DbProvider(DbEnv);
+pub struct DbProvider(Arc<DbEnv>);
 
 impl DbProvider {
     /// Creates a new [`DbProvider`] from the given [`DbEnv`].
     pub fn new(db: DbEnv) -> Self {
-        Self(db)
+        Self(Arc::new(db))
+    }
+
+    /// Creates a [`Pruner`] that prunes the history of this provider's database according to
+    /// the given [`PruningConfig`]. Call [`Pruner::spawn`] to run it in the background.
+    pub fn pruner(&self, config: PruningConfig) -> Pruner {
+        Pruner::new(Arc::clone(&self.0), config)
+    }
+
+    /// Indexes the events of the blocks stored before the event indexes were introduced. Those
+    /// blocks have no entries in the indexes, so their events would never be returned when
+    /// filtering by contract address or key. This is a no-op once all the blocks are indexed.
+    pub fn index_unindexed_events(&self) -> Result<()> {
+        self.0.update(|db_tx| -> Result<()> {
+            // every block is indexed when it's inserted, so the blocks left to index are the
+            // ones before the first block with an event bloom
+            let end = match db_tx.cursor::<BlockEventBlooms>()?.first()? {
+                Some((first_indexed, _)) => first_indexed,
+                None => db_tx.entries::<BlockNumbers>()? as u64,
+            };
+
+            // the events of the blocks whose receipts have been pruned can't be indexed anymore
+            let start = match db_tx.get::<PruneCheckpoints>(PruneSegment::Receipts)? {
+                Some(pruned) => pruned + 1,
+                None => 0,
+            };
+
+            for block_number in start..end {
+                let Some(indices) = db_tx.get::<BlockBodyIndices>(block_number)? else {
+                    continue;
+                };
+
+                let mut receipts = Vec::with_capacity(indices.tx_count as usize);
+                for i in Range::from(indices) {
+                    receipts.push(db_tx.get::<Receipts>(i)?.expect("should exist"));
+                }
+
+                index_events(db_tx, block_number, &receipts)?;
+            }
+
+            Ok(())
+        })?
+    }
+
+    /// Returns a provider of the state at the given block, or `None` if the block doesn't exist.
+    ///
+    /// Unlike the providers returned by [`StateFactoryProvider`], it doesn't hold a database
+    /// transaction for as long as it's alive, so it can be kept around, eg. to fork the database.
+    pub fn pinned_state(&self, block_number: BlockNumber) -> Result<Option<PinnedStateProvider>> {
+        let state = self.historical(block_number.into())?;
+        Ok(state.map(|_| PinnedStateProvider::new(Arc::clone(&self.0), block_number)))
     }
 }
 
@@ -78,7 +136,9 @@ impl StateFactoryProvider for DbProvider {
 
         let Some(num) = block_number else { return Ok(None) };
 
-        Ok(Some(Box::new(self::state::HistoricalStateProvider::new(self.0.tx()?, num))))
+        let db_tx = self.0.tx()?;
+        self::pruner::ensure_state_not_pruned(&db_tx, num)?;
+        Ok(Some(Box::new(self::state::HistoricalStateProvider::new(db_tx, num))))
     }
 }
 
@@ -139,12 +199,7 @@ impl BlockProvider for DbProvider {
     fn block_body_indices(&self, id: BlockHashOrNumber) -> Result<Option<StoredBlockBodyIndices>> {
         let db_tx = self.0.tx()?;
 
-        let block_num = match id {
-            BlockHashOrNumber::Num(num) => Some(num),
-            BlockHashOrNumber::Hash(hash) => db_tx.get::<BlockNumbers>(hash)?,
-        };
-
-        if let Some(num) = block_num {
+        if let Some(num) = block_number(&db_tx, id)? {
             let indices = db_tx.get::<BlockBodyIndices>(num)?;
             db_tx.commit()?;
             Ok(indices)
@@ -245,6 +300,44 @@ impl StateRootProvider for DbProvider {
     }
 }
 
+impl EventProvider for DbProvider {
+    fn blocks_with_events(
+        &self,
+        range: RangeInclusive<BlockNumber>,
+        filter: &EventFilter,
+    ) -> Result<Vec<BlockNumber>> {
+        // The blocks indexed under a key are stored as its duplicate entries, in no particular
+        // order.
+        fn indexed_blocks<Tb>(
+            db_tx: &mdbx::tx::TxRO,
+            key: <Tb as Table>::Key,
+        ) -> Result<Vec<BlockNumber>, DatabaseError>
+        where
+            Tb: DupSort<Value = BlockNumber> + Debug,
+        {
+            Ok(db_tx
+                .cursor::<Tb>()?
+                .walk_dup(Some(key), None)?
+                .map(|walker| {
+                    walker.map(|entry| entry.map(|(_, block)| block)).collect::<Result<_, _>>()
+                })
+                .transpose()?
+                .unwrap_or_default())
+        }
+
+        let db_tx = self.0.tx()?;
+        let blocks = crate::providers::blocks_with_events(
+            range,
+            filter,
+            |address| Ok(indexed_blocks::<ContractEventBlocks>(&db_tx, address)?),
+            |key| Ok(indexed_blocks::<EventKeyBlocks>(&db_tx, key)?),
+            |block| db_tx.get::<BlockEventBlooms>(block),
+        )?;
+        db_tx.commit()?;
+        Ok(blocks)
+    }
+}
+
 impl StateUpdateProvider for DbProvider {
     fn state_update(&self, block_id: BlockHashOrNumber) -> Result<Option<StateUpdates>> {
         // A helper function that iterates over all entries in a dupsort table and collects the
@@ -350,11 +443,13 @@ impl TransactionProvider for DbProvider {
         &self,
         block_id: BlockHashOrNumber,
     ) -> Result<Option<Vec<TxWithHash>>> {
-        if let Some(indices) = self.block_body_indices(block_id)? {
-            Ok(Some(self.transaction_in_range(Range::from(indices))?))
-        } else {
-            Ok(None)
-        }
+        let db_tx = self.0.tx()?;
+        let Some(num) = block_number(&db_tx, block_id)? else { return Ok(None) };
+        let Some(indices) = db_tx.get::<BlockBodyIndices>(num)? else { return Ok(None) };
+        self::pruner::ensure_transactions_not_pruned(&db_tx, num)?;
+        db_tx.commit()?;
+
+        Ok(Some(self.transaction_in_range(Range::from(indices))?))
     }
 
     fn transaction_in_range(&self, range: Range<TxNumber>) -> Result<Vec<TxWithHash>> {
@@ -395,16 +490,18 @@ impl TransactionProvider for DbProvider {
         idx: u64,
     ) -> Result<Option<TxWithHash>> {
         let db_tx = self.0.tx()?;
+        let Some(block_num) = block_number(&db_tx, block_id)? else { return Ok(None) };
 
-        match self.block_body_indices(block_id)? {
+        match db_tx.get::<BlockBodyIndices>(block_num)? {
             // make sure the requested idx is within the range of the block tx count
             Some(indices) if idx < indices.tx_count => {
+                self::pruner::ensure_transactions_not_pruned(&db_tx, block_num)?;
+
                 let num = indices.tx_offset + idx;
                 let hash = db_tx.get::<TxHashes>(num)?.expect("should exist");
                 let transaction = db_tx.get::<Transactions>(num)?.expect("should exist");
-                let transaction = TxWithHash { hash, transaction };
                 db_tx.commit()?;
-                Ok(Some(transaction))
+                Ok(Some(TxWithHash { hash, transaction }))
             }
 
             _ => Ok(None),
@@ -458,7 +555,10 @@ impl ReceiptProvider for DbProvider {
     fn receipt_by_hash(&self, hash: TxHash) -> Result<Option<Receipt>> {
         let db_tx = self.0.tx()?;
         if let Some(num) = db_tx.get::<TxNumbers>(hash)? {
-            let receipt = db_tx.get::<katana_db::tables::Receipts>(num)?.expect("should exist");
+            let block_num = db_tx.get::<TxBlocks>(num)?.expect("should exist");
+            self::pruner::ensure_receipts_not_pruned(&db_tx, block_num)?;
+
+            let receipt = db_tx.get::<Receipts>(num)?.expect("should exist");
             db_tx.commit()?;
             Ok(Some(receipt))
         } else {
@@ -467,22 +567,18 @@ impl ReceiptProvider for DbProvider {
     }
 
     fn receipts_by_block(&self, block_id: BlockHashOrNumber) -> Result<Option<Vec<Receipt>>> {
-        if let Some(indices) = self.block_body_indices(block_id)? {
-            let db_tx = self.0.tx()?;
-            let mut receipts = Vec::with_capacity(indices.tx_count as usize);
-
-            let range = indices.tx_offset..indices.tx_offset + indices.tx_count;
-            for i in range {
-                if let Some(receipt) = db_tx.get::<Receipts>(i)? {
-                    receipts.push(receipt);
-                }
-            }
+        let db_tx = self.0.tx()?;
+        let Some(num) = block_number(&db_tx, block_id)? else { return Ok(None) };
+        let Some(indices) = db_tx.get::<BlockBodyIndices>(num)? else { return Ok(None) };
+        self::pruner::ensure_receipts_not_pruned(&db_tx, num)?;
 
-            db_tx.commit()?;
-            Ok(Some(receipts))
-        } else {
-            Ok(None)
+        let mut receipts = Vec::with_capacity(indices.tx_count as usize);
+        for i in Range::from(indices) {
+            receipts.push(db_tx.get::<Receipts>(i)?.expect("should exist"));
         }
+
+        db_tx.commit()?;
+        Ok(Some(receipts))
     }
 }
 
@@ -501,7 +597,12 @@ impl BlockWriter for DbProvider {
             let transactions = block.block.body;
 
             let tx_count = transactions.len() as u64;
-            let tx_offset = db_tx.entries::<Transactions>()? as u64;
+            // the transactions of the previous blocks may have been pruned, so the offset is
+            // derived from the body indices of the last block rather than from the table size
+            let tx_offset = db_tx
+                .cursor::<BlockBodyIndices>()?
+                .last()?
+                .map_or(0, |(_, indices)| indices.tx_offset + indices.tx_count);
             let block_body_indices = StoredBlockBodyIndices { tx_offset, tx_count };
 
             db_tx.put::<BlockHashes>(block_number, block_hash)?;
@@ -511,6 +612,8 @@ impl BlockWriter for DbProvider {
             db_tx.put::<Headers>(block_number, block_header)?;
             db_tx.put::<BlockBodyIndices>(block_number, block_body_indices)?;
 
+            index_events(db_tx, block_number, &receipts)?;
+
             for (i, (transaction, receipt)) in transactions.into_iter().zip(receipts).enumerate() {
                 let tx_number = tx_offset + i as u64;
                 let tx_hash = transaction.hash;
@@ -652,16 +755,59 @@ impl BlockWriter for DbProvider {
     }
 }
 
+/// Returns the number of the block with the given id, `None` for an unknown block hash.
+fn block_number(
+    db_tx: &mdbx::tx::TxRO,
+    id: BlockHashOrNumber,
+) -> Result<Option<BlockNumber>, DatabaseError> {
+    match id {
+        BlockHashOrNumber::Num(num) => Ok(Some(num)),
+        BlockHashOrNumber::Hash(hash) => db_tx.get::<BlockNumbers>(hash),
+    }
+}
+
+/// Indexes the events emitted in the block by their emitting contract and first key.
+fn index_events(
+    db_tx: &mdbx::tx::TxRW,
+    block_number: BlockNumber,
+    receipts: &[Receipt],
+) -> Result<(), DatabaseError> {
+    let events = receipts.iter().flat_map(|receipt| receipt.events());
+    db_tx.put::<BlockEventBlooms>(block_number, EventBloom::from_events(events.clone()))?;
+
+    let mut contracts = BTreeSet::new();
+    let mut keys = BTreeSet::new();
+    for event in events {
+        contracts.insert(event.from_address);
+        keys.extend(event.keys.first().copied());
+    }
+
+    // every block is a duplicate entry of the key, so that indexing a block doesn't require
+    // rewriting all the blocks previously indexed under the same key
+    for address in contracts {
+        db_tx.put::<ContractEventBlocks>(address, block_number)?;
+    }
+
+    for key in keys {
+        db_tx.put::<EventKeyBlocks>(key, block_number)?;
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
 
+    use katana_db::error::DatabaseError;
     use katana_db::mdbx::DbEnvKind;
+    use katana_db::tables::{BlockEventBlooms, ContractEventBlocks, EventKeyBlocks};
     use katana_primitives::block::{
         Block, BlockHashOrNumber, FinalityStatus, Header, SealedBlockWithStatus,
     };
     use katana_primitives::contract::ContractAddress;
-    use katana_primitives::receipt::Receipt;
+    use katana_primitives::event::EventFilter;
+    use katana_primitives::receipt::{Event, InvokeTxReceipt, Receipt};
     use katana_primitives::state::{StateUpdates, StateUpdatesWithDeclaredClasses};
     use katana_primitives::transaction::{Tx, TxHash, TxWithHash};
     use starknet::macros::felt;
@@ -670,6 +816,7 @@ mod tests {
     use crate::traits::block::{
         BlockHashProvider, BlockNumberProvider, BlockProvider, BlockStatusProvider, BlockWriter,
     };
+    use crate::traits::event::EventProvider;
     use crate::traits::state::StateFactoryProvider;
     use crate::traits::transaction::TransactionProvider;
 
@@ -732,7 +879,7 @@ mod tests {
     }
 
     fn create_db_provider() -> DbProvider {
-        DbProvider(katana_db::mdbx::test_utils::create_test_db(DbEnvKind::RW))
+        DbProvider::new(katana_db::mdbx::test_utils::create_test_db(DbEnvKind::RW))
     }
 
     #[test]
@@ -861,4 +1008,45 @@ mod tests {
         assert_eq!(storage1, felt!("100"));
         assert_eq!(storage2, felt!("200"));
     }
+
+    #[test]
+    fn index_events_of_unindexed_blocks() {
+        let provider = create_db_provider();
+
+        let event =
+            Event { from_address: felt!("0x5").into(), keys: vec![felt!("0x6")], data: vec![] };
+        let receipt =
+            Receipt::Invoke(InvokeTxReceipt { events: vec![event], ..Default::default() });
+        BlockWriter::insert_block_with_states_and_receipts(
+            &provider,
+            create_dummy_block(),
+            create_dummy_state_updates(),
+            vec![receipt],
+        )
+        .expect("failed to insert block");
+
+        // a block stored before the event indexes were introduced has no entries in them
+        provider
+            .0
+            .update(|db_tx| -> Result<(), DatabaseError> {
+                db_tx.clear::<BlockEventBlooms>()?;
+                db_tx.clear::<ContractEventBlocks>()?;
+                db_tx.clear::<EventKeyBlocks>()
+            })
+            .unwrap()
+            .unwrap();
+
+        let by_address = EventFilter { address: Some(felt!("0x5").into()), keys: None };
+        let by_key = EventFilter { address: None, keys: Some(vec![vec![felt!("0x6")]]) };
+        assert!(provider.blocks_with_events(0..=0, &by_address).unwrap().is_empty());
+        assert!(provider.blocks_with_events(0..=0, &by_key).unwrap().is_empty());
+
+        provider.index_unindexed_events().unwrap();
+        assert_eq!(provider.blocks_with_events(0..=0, &by_address).unwrap(), vec![0]);
+        assert_eq!(provider.blocks_with_events(0..=0, &by_key).unwrap(), vec![0]);
+
+        // the blocks that are already indexed aren't indexed again
+        provider.index_unindexed_events().unwrap();
+        assert_eq!(provider.blocks_with_events(0..=0, &by_address).unwrap(), vec![0]);
+    }
 }
@@ -0,0 +1,539 @@
+//! Background pruning of the database history.
+//!
+//! By default, the database keeps the full history of the chain, which allows serving the state at
+//! any block but grows without bound. The [`Pruner`] removes the history that is older than a
+//! configured distance from the chain tip, for each [`PruneSegment`] independently.
+
+use std::collections::BTreeSet;
+use std::ops::Range;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use anyhow::Result;
+use katana_db::error::DatabaseError;
+use katana_db::mdbx::tx::{TxRO, TxRW};
+use katana_db::mdbx::DbEnv;
+use katana_db::models::contract::BlockList;
+use katana_db::models::prune::PruneSegment;
+use katana_db::models::storage::{ContractStorageKey, StorageEntryChangeList};
+use katana_db::tables::{
+    BlockBodyIndices, BlockEventBlooms, BlockNumbers, ContractClassChanges, ContractEventBlocks,
+    ContractInfoChangeSet, DupSort, EventKeyBlocks, NonceChanges, PruneCheckpoints, Receipts,
+    StorageChangeSet, StorageChanges, Table, Transactions, TxBlocks, TxHashes, TxNumbers,
+};
+use katana_primitives::block::BlockNumber;
+use tracing::{error, trace};
+
+#[derive(Debug, thiserror::Error)]
+pub enum PruningError {
+    #[error(
+        "state at block {requested} is no longer available as it has been pruned, the earliest \
+         available block is {earliest}"
+    )]
+    StateHistoryPruned { requested: BlockNumber, earliest: BlockNumber },
+    #[error(
+        "receipts of block {requested} are no longer available as they have been pruned, the \
+         earliest available block is {earliest}"
+    )]
+    ReceiptsPruned { requested: BlockNumber, earliest: BlockNumber },
+    #[error(
+        "transactions of block {requested} are no longer available as they have been pruned, the \
+         earliest available block is {earliest}"
+    )]
+    TransactionsPruned { requested: BlockNumber, earliest: BlockNumber },
+}
+
+/// Determines how much history of a segment is kept in the database.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum PruneMode {
+    /// Keep the full history.
+    #[default]
+    Archive,
+    /// Only keep the history of the last `n` blocks relative to the chain tip.
+    Distance(u64),
+}
+
+impl PruneMode {
+    /// Returns the highest block number that should be pruned given the current chain `tip`, or
+    /// `None` if nothing should be pruned.
+    pub fn prune_target(&self, tip: BlockNumber) -> Option<BlockNumber> {
+        match self {
+            PruneMode::Archive => None,
+            PruneMode::Distance(distance) => tip.checked_sub(*distance),
+        }
+    }
+}
+
+/// Configuration of the [`Pruner`].
+///
+/// The default configuration runs the node in archive mode.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct PruningConfig {
+    /// Pruning mode of the nonce, class hash and storage change sets.
+    pub state_history: PruneMode,
+    /// Pruning mode of the transaction receipts.
+    pub receipts: PruneMode,
+    /// Pruning mode of the transactions.
+    pub transactions: PruneMode,
+}
+
+impl PruningConfig {
+    /// Keeps the full history of every segment.
+    pub fn archive() -> Self {
+        Self::default()
+    }
+
+    /// Only keeps the history of the last `distance` blocks for every segment.
+    pub fn distance(distance: u64) -> Self {
+        Self {
+            state_history: PruneMode::Distance(distance),
+            receipts: PruneMode::Distance(distance),
+            transactions: PruneMode::Distance(distance),
+        }
+    }
+}
+
+/// Prunes the history of a database according to a [`PruningConfig`].
+#[derive(Debug)]
+pub struct Pruner {
+    db: Arc<DbEnv>,
+    config: PruningConfig,
+}
+
+impl Pruner {
+    pub fn new(db: Arc<DbEnv>, config: PruningConfig) -> Self {
+        Self { db, config }
+    }
+
+    /// Prunes every segment relative to the given chain `tip`.
+    pub fn run(&self, tip: BlockNumber) -> Result<()> {
+        self.db.update(|db_tx| -> Result<()> {
+            if let Some(target) = self.config.state_history.prune_target(tip) {
+                prune_segment(db_tx, PruneSegment::StateHistory, target, |db_tx, block| {
+                    prune_contract_info_changes(db_tx, block)?;
+                    prune_storage_changes(db_tx, block)
+                })?;
+            }
+
+            if let Some(target) = self.config.receipts.prune_target(tip) {
+                prune_segment(db_tx, PruneSegment::Receipts, target, prune_receipts)?;
+            }
+
+            if let Some(target) = self.config.transactions.prune_target(tip) {
+                prune_segment(db_tx, PruneSegment::Transactions, target, prune_transactions)?;
+            }
+
+            Ok(())
+        })?
+    }
+
+    /// Spawns a thread that runs the pruner against the latest block every `interval`.
+    ///
+    /// The thread is stopped once the returned [`PrunerHandle`] is stopped or dropped.
+    pub fn spawn(self, interval: Duration) -> PrunerHandle {
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+
+        let handle = thread::Builder::new()
+            .name("pruner".into())
+            .spawn(move || {
+                loop {
+                    match stop_rx.recv_timeout(interval) {
+                        Err(RecvTimeoutError::Timeout) => {
+                            if let Err(error) = self.latest_number().and_then(|tip| self.run(tip)) {
+                                error!(target: "pruner", %error, "Pruning database history.");
+                            }
+                        }
+                        _ => break,
+                    }
+                }
+            })
+            .expect("failed to spawn pruner thread");
+
+        PrunerHandle { stop_tx, handle }
+    }
+
+    fn latest_number(&self) -> Result<BlockNumber> {
+        let db_tx = self.db.tx()?;
+        let total_blocks = db_tx.entries::<BlockNumbers>()? as u64;
+        db_tx.commit()?;
+        Ok(total_blocks.saturating_sub(1))
+    }
+}
+
+/// A handle to a running [`Pruner`] thread.
+#[derive(Debug)]
+pub struct PrunerHandle {
+    stop_tx: mpsc::Sender<()>,
+    handle: JoinHandle<()>,
+}
+
+impl PrunerHandle {
+    /// Stops the pruner and waits for its thread to finish.
+    pub fn stop(self) {
+        let _ = self.stop_tx.send(());
+        let _ = self.handle.join();
+    }
+}
+
+/// Returns an error if the state at `block` is no longer available because its history has been
+/// pruned.
+pub(super) fn ensure_state_not_pruned(db_tx: &TxRO, block: BlockNumber) -> Result<()> {
+    match db_tx.get::<PruneCheckpoints>(PruneSegment::StateHistory)? {
+        Some(earliest) if block < earliest => {
+            Err(PruningError::StateHistoryPruned { requested: block, earliest }.into())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Returns an error if the receipts of `block` have been pruned.
+pub(super) fn ensure_receipts_not_pruned(db_tx: &TxRO, block: BlockNumber) -> Result<()> {
+    match db_tx.get::<PruneCheckpoints>(PruneSegment::Receipts)? {
+        Some(pruned) if block <= pruned => {
+            Err(PruningError::ReceiptsPruned { requested: block, earliest: pruned + 1 }.into())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Returns an error if the transactions of `block` have been pruned.
+pub(super) fn ensure_transactions_not_pruned(db_tx: &TxRO, block: BlockNumber) -> Result<()> {
+    match db_tx.get::<PruneCheckpoints>(PruneSegment::Transactions)? {
+        Some(pruned) if block <= pruned => {
+            Err(PruningError::TransactionsPruned { requested: block, earliest: pruned + 1 }.into())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Prunes all the blocks of `segment` that haven't been pruned yet, up to and including `target`,
+/// and then updates the checkpoint of the segment.
+fn prune_segment(
+    db_tx: &TxRW,
+    segment: PruneSegment,
+    target: BlockNumber,
+    mut prune_block: impl FnMut(&TxRW, BlockNumber) -> Result<(), DatabaseError>,
+) -> Result<(), DatabaseError> {
+    let from = db_tx.get::<PruneCheckpoints>(segment)?.map_or(0, |num| num + 1);
+
+    if from > target {
+        return Ok(());
+    }
+
+    trace!(target: "pruner", ?segment, from, to = target, "Pruning history.");
+
+    for block in from..=target {
+        prune_block(db_tx, block)?;
+    }
+
+    db_tx.put::<PruneCheckpoints>(segment, target)
+}
+
+/// Removes the nonce and class hash changes that are superseded by the changes made at `block`.
+///
+/// The changes at `block` itself are kept, as they are still needed to serve the state of the
+/// blocks after it.
+fn prune_contract_info_changes(db_tx: &TxRW, block: BlockNumber) -> Result<(), DatabaseError> {
+    let nonce_changes =
+        dup_values::<NonceChanges, _>(db_tx, block, |change| change.contract_address)?;
+
+    for address in nonce_changes {
+        let mut change_list = db_tx.get::<ContractInfoChangeSet>(address)?.unwrap_or_default();
+
+        for num in take_superseded(&mut change_list.nonce_change_list, block) {
+            delete_dup_entry::<NonceChanges>(db_tx, num, address, |change| {
+                change.contract_address == address
+            })?;
+        }
+
+        db_tx.put::<ContractInfoChangeSet>(address, change_list)?;
+    }
+
+    let class_changes =
+        dup_values::<ContractClassChanges, _>(db_tx, block, |change| change.contract_address)?;
+
+    for address in class_changes {
+        let mut change_list = db_tx.get::<ContractInfoChangeSet>(address)?.unwrap_or_default();
+
+        for num in take_superseded(&mut change_list.class_change_list, block) {
+            delete_dup_entry::<ContractClassChanges>(db_tx, num, address, |change| {
+                change.contract_address == address
+            })?;
+        }
+
+        db_tx.put::<ContractInfoChangeSet>(address, change_list)?;
+    }
+
+    Ok(())
+}
+
+/// Removes the storage changes that are superseded by the changes made at `block`.
+fn prune_storage_changes(db_tx: &TxRW, block: BlockNumber) -> Result<(), DatabaseError> {
+    let storage_changes = dup_values::<StorageChanges, _>(db_tx, block, |entry| entry.key)?;
+
+    for ContractStorageKey { contract_address, key } in storage_changes {
+        let mut cursor = db_tx.cursor::<StorageChangeSet>()?;
+
+        let mut block_list = match cursor.seek_by_key_subkey(contract_address, key)? {
+            Some(change_list) if change_list.key == key => change_list.block_list,
+            _ => continue,
+        };
+
+        let superseded = take_superseded(&mut block_list, block);
+        if superseded.is_empty() {
+            continue;
+        }
+
+        cursor.delete_current()?;
+        cursor.upsert(contract_address, StorageEntryChangeList { key, block_list })?;
+
+        for num in superseded {
+            let storage_key = ContractStorageKey { contract_address, key };
+            delete_dup_entry::<StorageChanges>(db_tx, num, storage_key, |entry| {
+                entry.key.contract_address == contract_address && entry.key.key == key
+            })?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Removes the receipts of all the transactions in `block`, and the index of the events they
+/// emitted.
+fn prune_receipts(db_tx: &TxRW, block: BlockNumber) -> Result<(), DatabaseError> {
+    let mut contracts = BTreeSet::new();
+    let mut keys = BTreeSet::new();
+
+    if let Some(indices) = db_tx.get::<BlockBodyIndices>(block)? {
+        for tx_number in Range::from(indices) {
+            if let Some(receipt) = db_tx.get::<Receipts>(tx_number)? {
+                for event in receipt.events() {
+                    contracts.insert(event.from_address);
+                    keys.extend(event.keys.first().copied());
+                }
+            }
+            db_tx.delete::<Receipts>(tx_number, None)?;
+        }
+    }
+
+    // the block is one of the duplicate entries of each contract and key it's indexed under
+    db_tx.delete::<BlockEventBlooms>(block, None)?;
+    for address in contracts {
+        db_tx.delete::<ContractEventBlocks>(address, Some(block))?;
+    }
+    for key in keys {
+        db_tx.delete::<EventKeyBlocks>(key, Some(block))?;
+    }
+
+    Ok(())
+}
+
+/// Removes the transactions in `block`, and their hash and block number indices. The body
+/// indices of the block are kept, so the numbering of the next transactions is unaffected.
+fn prune_transactions(db_tx: &TxRW, block: BlockNumber) -> Result<(), DatabaseError> {
+    if let Some(indices) = db_tx.get::<BlockBodyIndices>(block)? {
+        for tx_number in Range::from(indices) {
+            if let Some(hash) = db_tx.get::<TxHashes>(tx_number)? {
+                db_tx.delete::<TxNumbers>(hash, None)?;
+            }
+            db_tx.delete::<TxHashes>(tx_number, None)?;
+            db_tx.delete::<TxBlocks>(tx_number, None)?;
+            db_tx.delete::<Transactions>(tx_number, None)?;
+        }
+    }
+    Ok(())
+}
+
+/// Removes and returns the block numbers in the sorted `block_list` that are lower than `block`.
+fn take_superseded(block_list: &mut BlockList, block: BlockNumber) -> BlockList {
+    let pos = block_list.partition_point(|num| *num < block);
+    block_list.drain(..pos).collect()
+}
+
+/// Collects the values of all the duplicate entries of `key` in the dupsort table `T`.
+fn dup_values<T, V>(
+    db_tx: &TxRW,
+    key: <T as Table>::Key,
+    mut f: impl FnMut(<T as Table>::Value) -> V,
+) -> Result<Vec<V>, DatabaseError>
+where
+    T: DupSort,
+{
+    Ok(db_tx
+        .cursor::<T>()?
+        .walk_dup(Some(key), None)?
+        .map(|walker| walker.map(|entry| entry.map(|(_, value)| f(value))).collect())
+        .transpose()?
+        .unwrap_or_default())
+}
+
+/// Deletes the entry of `key` in the dupsort table `T` whose value is positioned at `subkey`, if
+/// `matches` returns true for it.
+fn delete_dup_entry<T: DupSort>(
+    db_tx: &TxRW,
+    key: <T as Table>::Key,
+    subkey: <T as DupSort>::SubKey,
+    matches: impl FnOnce(&<T as Table>::Value) -> bool,
+) -> Result<(), DatabaseError> {
+    let mut cursor = db_tx.cursor::<T>()?;
+    match cursor.seek_by_key_subkey(key, subkey)? {
+        Some(value) if matches(&value) => cursor.delete_current(),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use katana_db::mdbx::DbEnvKind;
+    use katana_db::models::prune::PruneSegment;
+    use katana_db::tables::{BlockEventBlooms, ContractInfoChangeSet, PruneCheckpoints};
+    use katana_primitives::block::{
+        Block, BlockHashOrNumber, FinalityStatus, Header, SealedBlockWithStatus,
+    };
+    use katana_primitives::contract::{ContractAddress, Nonce, StorageValue};
+    use katana_primitives::event::EventFilter;
+    use katana_primitives::receipt::{Event, InvokeTxReceipt, Receipt};
+    use katana_primitives::state::{StateUpdates, StateUpdatesWithDeclaredClasses};
+    use katana_primitives::transaction::{Tx, TxWithHash};
+    use katana_primitives::FieldElement;
+    use starknet::macros::felt;
+
+    use super::{PruneMode, PruningConfig, PruningError};
+    use crate::providers::db::DbProvider;
+    use crate::traits::block::BlockWriter;
+    use crate::traits::event::EventProvider;
+    use crate::traits::state::StateFactoryProvider;
+    use crate::traits::transaction::{ReceiptProvider, TransactionProvider};
+
+    const ADDRESS: FieldElement = felt!("0x1");
+    const STORAGE_KEY: FieldElement = felt!("0x2");
+
+    fn insert_block(provider: &DbProvider, number: u64, nonce: Nonce, value: StorageValue) {
+        let header = Header { number, ..Default::default() };
+        let tx = TxWithHash { hash: number.into(), transaction: Tx::Invoke(Default::default()) };
+        let block = Block { header, body: vec![tx] }.seal();
+        let block = SealedBlockWithStatus { block, status: FinalityStatus::AcceptedOnL2 };
+
+        let states = StateUpdatesWithDeclaredClasses {
+            state_updates: StateUpdates {
+                nonce_updates: HashMap::from([(ContractAddress::from(ADDRESS), nonce)]),
+                storage_updates: HashMap::from([(
+                    ContractAddress::from(ADDRESS),
+                    HashMap::from([(STORAGE_KEY, value)]),
+                )]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // every transaction emits an event from the same contract, keyed by the block number
+        let event = Event { from_address: ADDRESS.into(), keys: vec![number.into()], data: vec![] };
+        let receipt =
+            Receipt::Invoke(InvokeTxReceipt { events: vec![event], ..Default::default() });
+
+        provider
+            .insert_block_with_states_and_receipts(block, states, vec![receipt])
+            .expect("failed to insert block");
+    }
+
+    #[test]
+    fn prune_mode_target() {
+        assert_eq!(PruneMode::Archive.prune_target(10), None);
+        assert_eq!(PruneMode::Distance(3).prune_target(10), Some(7));
+        assert_eq!(PruneMode::Distance(11).prune_target(10), None);
+    }
+
+    #[test]
+    fn prune_history_older_than_distance() {
+        let provider = DbProvider::new(katana_db::mdbx::test_utils::create_test_db(DbEnvKind::RW));
+
+        insert_block(&provider, 0, felt!("1"), felt!("10"));
+        insert_block(&provider, 1, felt!("2"), felt!("20"));
+        insert_block(&provider, 2, felt!("3"), felt!("30"));
+
+        provider.pruner(PruningConfig::distance(1)).run(2).expect("failed to prune");
+
+        let err = match provider.historical(BlockHashOrNumber::Num(0)) {
+            Err(err) => err,
+            Ok(_) => panic!("state at block 0 should have been pruned"),
+        };
+        assert!(matches!(
+            err.downcast_ref::<PruningError>(),
+            Some(PruningError::StateHistoryPruned { requested: 0, earliest: 1 })
+        ));
+
+        let state = provider.historical(BlockHashOrNumber::Num(1)).unwrap().unwrap();
+        assert_eq!(state.nonce(ADDRESS.into()).unwrap(), Some(felt!("2")));
+        assert_eq!(state.storage(ADDRESS.into(), STORAGE_KEY).unwrap(), Some(felt!("20")));
+
+        let state = provider.latest().unwrap();
+        assert_eq!(state.nonce(ADDRESS.into()).unwrap(), Some(felt!("3")));
+        assert_eq!(state.storage(ADDRESS.into(), STORAGE_KEY).unwrap(), Some(felt!("30")));
+
+        let err = provider.receipts_by_block(BlockHashOrNumber::Num(1)).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<PruningError>(),
+            Some(PruningError::ReceiptsPruned { requested: 1, earliest: 2 })
+        ));
+        assert_eq!(
+            provider.receipts_by_block(BlockHashOrNumber::Num(2)).unwrap().unwrap().len(),
+            1
+        );
+
+        // the hashes of the pruned transactions are pruned with them
+        assert_eq!(provider.transaction_by_hash(felt!("0x0")).unwrap(), None);
+        assert!(provider.transaction_by_hash(felt!("0x2")).unwrap().is_some());
+        for err in [
+            provider.transactions_by_block(BlockHashOrNumber::Num(1)).map(|_| ()).unwrap_err(),
+            provider
+                .transaction_by_block_and_idx(BlockHashOrNumber::Num(1), 0)
+                .map(|_| ())
+                .unwrap_err(),
+        ] {
+            assert!(matches!(
+                err.downcast_ref::<PruningError>(),
+                Some(PruningError::TransactionsPruned { requested: 1, earliest: 2 })
+            ));
+        }
+
+        // the events of the pruned receipts are no longer indexed
+        let filter = EventFilter { address: Some(ADDRESS.into()), keys: None };
+        assert_eq!(provider.blocks_with_events(0..=2, &filter).unwrap(), vec![2]);
+        let filter = EventFilter { address: None, keys: Some(vec![vec![felt!("0x1")]]) };
+        assert_eq!(provider.blocks_with_events(0..=2, &filter).unwrap(), Vec::<u64>::new());
+
+        // the next transactions are numbered after the pruned ones
+        insert_block(&provider, 3, felt!("4"), felt!("40"));
+        let txs = provider.transactions_by_block(BlockHashOrNumber::Num(3)).unwrap().unwrap();
+        assert_eq!(txs.iter().map(|tx| tx.hash).collect::<Vec<_>>(), vec![felt!("0x3")]);
+
+        let db_tx = provider.0.tx().unwrap();
+        let change_list = db_tx.get::<ContractInfoChangeSet>(ADDRESS.into()).unwrap().unwrap();
+        let checkpoint = db_tx.get::<PruneCheckpoints>(PruneSegment::StateHistory).unwrap();
+        assert_eq!(change_list.nonce_change_list, vec![1, 2]);
+        assert_eq!(checkpoint, Some(1));
+        assert_eq!(db_tx.get::<BlockEventBlooms>(1).unwrap(), None);
+        assert!(db_tx.get::<BlockEventBlooms>(2).unwrap().is_some());
+    }
+
+    #[test]
+    fn archive_mode_keeps_full_history() {
+        let provider = DbProvider::new(katana_db::mdbx::test_utils::create_test_db(DbEnvKind::RW));
+
+        insert_block(&provider, 0, felt!("1"), felt!("10"));
+        insert_block(&provider, 1, felt!("2"), felt!("20"));
+
+        provider.pruner(PruningConfig::archive()).run(1).expect("failed to prune");
+
+        let state = provider.historical(BlockHashOrNumber::Num(0)).unwrap().unwrap();
+        assert_eq!(state.nonce(ADDRESS.into()).unwrap(), Some(felt!("1")));
+        assert_eq!(
+            provider.receipts_by_block(BlockHashOrNumber::Num(0)).unwrap().unwrap().len(),
+            1
+        );
+    }
+}
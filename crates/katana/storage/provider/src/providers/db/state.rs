@@ -1,7 +1,8 @@
 use std::cmp::Ordering;
+use std::sync::Arc;
 
-use anyhow::Result;
-use katana_db::mdbx::{self};
+use anyhow::{anyhow, Result};
+use katana_db::mdbx::{self, DbEnv};
 use katana_db::models::contract::{
     ContractClassChange, ContractInfoChangeList, ContractNonceChange,
 };
@@ -19,7 +20,7 @@ use katana_primitives::contract::{
 
 use super::DbProvider;
 use crate::traits::contract::{ContractClassProvider, ContractClassWriter};
-use crate::traits::state::{StateProvider, StateWriter};
+use crate::traits::state::{StateFactoryProvider, StateProvider, StateWriter};
 
 impl StateWriter for DbProvider {
     fn set_nonce(&self, address: ContractAddress, nonce: Nonce) -> Result<()> {
@@ -100,6 +101,64 @@ impl ContractClassWriter for DbProvider {
     }
 }
 
+/// A state provider that provides the state at a pinned block from the database.
+///
+/// Every read opens its own short-lived read-only transaction, so that the provider can be held
+/// for a long time without preventing the database from reclaiming its pages.
+#[derive(Debug)]
+pub struct PinnedStateProvider {
+    db: Arc<DbEnv>,
+    /// The block number of the state.
+    block_number: BlockNumber,
+}
+
+impl PinnedStateProvider {
+    pub(super) fn new(db: Arc<DbEnv>, block_number: BlockNumber) -> Self {
+        Self { db, block_number }
+    }
+
+    fn state(&self) -> Result<Box<dyn StateProvider>> {
+        DbProvider(Arc::clone(&self.db))
+            .historical(self.block_number.into())?
+            .ok_or_else(|| anyhow!("state of block {} is not available", self.block_number))
+    }
+}
+
+impl ContractClassProvider for PinnedStateProvider {
+    fn class(&self, hash: ClassHash) -> Result<Option<CompiledContractClass>> {
+        self.state()?.class(hash)
+    }
+
+    fn compiled_class_hash_of_class_hash(
+        &self,
+        hash: ClassHash,
+    ) -> Result<Option<CompiledClassHash>> {
+        self.state()?.compiled_class_hash_of_class_hash(hash)
+    }
+
+    fn sierra_class(&self, hash: ClassHash) -> Result<Option<FlattenedSierraClass>> {
+        self.state()?.sierra_class(hash)
+    }
+}
+
+impl StateProvider for PinnedStateProvider {
+    fn nonce(&self, address: ContractAddress) -> Result<Option<Nonce>> {
+        self.state()?.nonce(address)
+    }
+
+    fn class_hash_of_contract(&self, address: ContractAddress) -> Result<Option<ClassHash>> {
+        self.state()?.class_hash_of_contract(address)
+    }
+
+    fn storage(
+        &self,
+        address: ContractAddress,
+        storage_key: StorageKey,
+    ) -> Result<Option<StorageValue>> {
+        self.state()?.storage(address, storage_key)
+    }
+}
+
 /// A state provider that provides the latest states from the database.
 pub(super) struct LatestStateProvider(mdbx::tx::TxRO);
 
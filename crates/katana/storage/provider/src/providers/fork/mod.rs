@@ -13,6 +13,7 @@ use katana_primitives::block::{
 use katana_primitives::contract::{
     ClassHash, CompiledClassHash, CompiledContractClass, ContractAddress, FlattenedSierraClass,
 };
+use katana_primitives::event::EventFilter;
 use katana_primitives::receipt::Receipt;
 use katana_primitives::state::{StateUpdates, StateUpdatesWithDeclaredClasses};
 use katana_primitives::transaction::{Tx, TxHash, TxNumber, TxWithHash};
@@ -21,14 +22,17 @@ use starknet::providers::jsonrpc::HttpTransport;
 use starknet::providers::JsonRpcClient;
 
 use self::backend::{ForkedBackend, SharedStateProvider};
-use self::state::ForkedStateDb;
+use self::state::{ForkedStateDb, ForkedStateSource};
 use super::in_memory::cache::{CacheDb, CacheStateDb};
-use super::in_memory::state::HistoricalStates;
+use super::in_memory::state::{
+    HistoricalStates, InMemoryStateDb, LatestStateProvider as InMemoryLatestStateProvider,
+};
 use crate::traits::block::{
     BlockHashProvider, BlockNumberProvider, BlockProvider, BlockStatusProvider, BlockWriter,
     HeaderProvider,
 };
 use crate::traits::contract::ContractClassWriter;
+use crate::traits::event::EventProvider;
 use crate::traits::state::{StateFactoryProvider, StateProvider, StateRootProvider, StateWriter};
 use crate::traits::state_update::StateUpdateProvider;
 use crate::traits::transaction::{
@@ -43,12 +47,28 @@ pub struct ForkedProvider {
 }
 
 impl ForkedProvider {
+    /// Forks the remote network served by `provider` at the given block.
     pub fn new(provider: Arc<JsonRpcClient<HttpTransport>>, block_id: BlockHashOrNumber) -> Self {
         let backend = ForkedBackend::new_with_backend_thread(provider, block_id);
         let shared_provider = SharedStateProvider::new_with_backend(backend);
+        Self::new_with_source(ForkedStateSource::Remote(shared_provider))
+    }
+
+    /// Forks a local state, eg. the historical state of another Katana's database.
+    pub fn new_with_state(state: Box<dyn StateProvider>) -> Self {
+        Self::new_with_source(ForkedStateSource::Local(Arc::from(state)))
+    }
+
+    /// Forks the state made of the given state updates, eg. loaded from a state dump.
+    pub fn new_with_state_updates(states: StateUpdatesWithDeclaredClasses) -> Self {
+        let state = InMemoryStateDb::default();
+        state.insert_updates(states);
+        Self::new_with_state(Box::new(InMemoryLatestStateProvider(Arc::new(state))))
+    }
 
+    fn new_with_source(source: ForkedStateSource) -> Self {
         let storage = RwLock::new(CacheDb::new(()));
-        let state = Arc::new(CacheStateDb::new(shared_provider));
+        let state = Arc::new(CacheStateDb::new(source));
         let historical_states = RwLock::new(HistoricalStates::default());
 
         Self { storage, state, historical_states }
@@ -347,6 +367,16 @@ impl StateRootProvider for ForkedProvider {
     }
 }
 
+impl EventProvider for ForkedProvider {
+    fn blocks_with_events(
+        &self,
+        range: RangeInclusive<BlockNumber>,
+        filter: &EventFilter,
+    ) -> Result<Vec<BlockNumber>> {
+        Ok(self.storage.read().blocks_with_events(range, filter))
+    }
+}
+
 impl StateUpdateProvider for ForkedProvider {
     fn state_update(&self, block_id: BlockHashOrNumber) -> Result<Option<StateUpdates>> {
         let block_num = match block_id {
@@ -427,6 +457,7 @@ impl BlockWriter for ForkedProvider {
         storage.transaction_hashes.extend(txs_id);
         storage.transaction_numbers.extend(txs_num);
         storage.transaction_block.extend(txs_block);
+        storage.index_events(block_number, &receipts);
         storage.receipts.extend(receipts);
 
         storage.state_update.insert(block_number, states.state_updates.clone());
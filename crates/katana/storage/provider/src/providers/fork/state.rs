@@ -12,8 +12,87 @@ use crate::providers::in_memory::state::StateSnapshot;
 use crate::traits::contract::{ContractClassProvider, ContractInfoProvider};
 use crate::traits::state::StateProvider;
 
-pub type ForkedStateDb = CacheStateDb<SharedStateProvider>;
-pub type ForkedSnapshot = StateSnapshot<SharedStateProvider>;
+pub type ForkedStateDb = CacheStateDb<ForkedStateSource>;
+pub type ForkedSnapshot = StateSnapshot<ForkedStateSource>;
+
+/// The state on top of which a [ForkedProvider](super::ForkedProvider) is built.
+#[derive(Clone)]
+pub enum ForkedStateSource {
+    /// The state of a remote network, fetched through its JSON-RPC endpoint.
+    Remote(SharedStateProvider),
+    /// A local state, eg. the database of another Katana instance or a loaded state dump.
+    Local(Arc<dyn StateProvider>),
+}
+
+impl ContractInfoProvider for ForkedStateSource {
+    fn contract(&self, address: ContractAddress) -> Result<Option<GenericContractInfo>> {
+        match self {
+            Self::Remote(provider) => ContractInfoProvider::contract(provider, address),
+            Self::Local(state) => {
+                let Some(class_hash) = state.class_hash_of_contract(address)? else {
+                    return Ok(None);
+                };
+                let nonce = state.nonce(address)?.unwrap_or_default();
+                Ok(Some(GenericContractInfo { nonce, class_hash }))
+            }
+        }
+    }
+}
+
+impl StateProvider for ForkedStateSource {
+    fn nonce(&self, address: ContractAddress) -> Result<Option<Nonce>> {
+        match self {
+            Self::Remote(provider) => StateProvider::nonce(provider, address),
+            Self::Local(state) => state.nonce(address),
+        }
+    }
+
+    fn storage(
+        &self,
+        address: ContractAddress,
+        storage_key: StorageKey,
+    ) -> Result<Option<StorageValue>> {
+        match self {
+            Self::Remote(provider) => StateProvider::storage(provider, address, storage_key),
+            Self::Local(state) => state.storage(address, storage_key),
+        }
+    }
+
+    fn class_hash_of_contract(&self, address: ContractAddress) -> Result<Option<ClassHash>> {
+        match self {
+            Self::Remote(provider) => StateProvider::class_hash_of_contract(provider, address),
+            Self::Local(state) => state.class_hash_of_contract(address),
+        }
+    }
+}
+
+impl ContractClassProvider for ForkedStateSource {
+    fn sierra_class(&self, hash: ClassHash) -> Result<Option<FlattenedSierraClass>> {
+        match self {
+            Self::Remote(provider) => ContractClassProvider::sierra_class(provider, hash),
+            Self::Local(state) => state.sierra_class(hash),
+        }
+    }
+
+    fn compiled_class_hash_of_class_hash(
+        &self,
+        hash: ClassHash,
+    ) -> Result<Option<CompiledClassHash>> {
+        match self {
+            Self::Remote(provider) => {
+                ContractClassProvider::compiled_class_hash_of_class_hash(provider, hash)
+            }
+            Self::Local(state) => state.compiled_class_hash_of_class_hash(hash),
+        }
+    }
+
+    fn class(&self, hash: ClassHash) -> Result<Option<CompiledContractClass>> {
+        match self {
+            Self::Remote(provider) => ContractClassProvider::class(provider, hash),
+            Self::Local(state) => state.class(hash),
+        }
+    }
+}
 
 impl ForkedStateDb {
     pub(crate) fn create_snapshot(&self) -> ForkedSnapshot {
@@ -62,7 +141,7 @@ impl StateProvider for ForkedStateDb {
     }
 }
 
-impl ContractClassProvider for CacheStateDb<SharedStateProvider> {
+impl ContractClassProvider for ForkedStateDb {
     fn sierra_class(&self, hash: ClassHash) -> Result<Option<FlattenedSierraClass>> {
         if let class @ Some(_) = self.shared_contract_classes.sierra_classes.read().get(&hash) {
             return Ok(class.cloned());
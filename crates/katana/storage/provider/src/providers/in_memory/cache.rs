@@ -1,4 +1,6 @@
 use std::collections::HashMap;
+use std::convert::Infallible;
+use std::ops::RangeInclusive;
 use std::sync::Arc;
 
 use katana_db::models::block::StoredBlockBodyIndices;
@@ -7,9 +9,11 @@ use katana_primitives::contract::{
     ClassHash, CompiledClassHash, CompiledContractClass, ContractAddress, FlattenedSierraClass,
     GenericContractInfo, StorageKey, StorageValue,
 };
+use katana_primitives::event::{EventBloom, EventFilter};
 use katana_primitives::receipt::Receipt;
 use katana_primitives::state::{StateUpdates, StateUpdatesWithDeclaredClasses};
 use katana_primitives::transaction::{Tx, TxHash, TxNumber};
+use katana_primitives::FieldElement;
 use parking_lot::RwLock;
 
 type ContractStorageMap = HashMap<ContractAddress, HashMap<StorageKey, StorageValue>>;
@@ -85,6 +89,9 @@ pub struct CacheDb<Db> {
     pub(crate) transaction_hashes: HashMap<TxNumber, TxHash>,
     pub(crate) transaction_numbers: HashMap<TxHash, TxNumber>,
     pub(crate) transaction_block: HashMap<TxNumber, BlockNumber>,
+    pub(crate) event_blooms: HashMap<BlockNumber, EventBloom>,
+    pub(crate) contract_event_blocks: HashMap<ContractAddress, Vec<BlockNumber>>,
+    pub(crate) event_key_blocks: HashMap<FieldElement, Vec<BlockNumber>>,
 }
 
 impl<Db> CacheStateDb<Db> {
@@ -114,12 +121,53 @@ impl<Db> CacheDb<Db> {
             transaction_hashes: HashMap::new(),
             block_body_indices: HashMap::new(),
             transaction_numbers: HashMap::new(),
+            event_blooms: HashMap::new(),
+            event_key_blocks: HashMap::new(),
+            contract_event_blocks: HashMap::new(),
             latest_block_hash: Default::default(),
             latest_block_number: Default::default(),
         }
     }
 }
 
+impl<Db> CacheDb<Db> {
+    /// Indexes the events emitted in the block by their emitting contract and first key.
+    pub(crate) fn index_events(&mut self, block_number: BlockNumber, receipts: &[Receipt]) {
+        let events = receipts.iter().flat_map(|receipt| receipt.events());
+        self.event_blooms.insert(block_number, EventBloom::from_events(events.clone()));
+
+        for event in events {
+            let blocks = self.contract_event_blocks.entry(event.from_address).or_default();
+            if blocks.last() != Some(&block_number) {
+                blocks.push(block_number);
+            }
+
+            if let Some(key) = event.keys.first() {
+                let blocks = self.event_key_blocks.entry(*key).or_default();
+                if blocks.last() != Some(&block_number) {
+                    blocks.push(block_number);
+                }
+            }
+        }
+    }
+
+    /// Returns the blocks in the range that may contain events matching the filter.
+    pub(crate) fn blocks_with_events(
+        &self,
+        range: RangeInclusive<BlockNumber>,
+        filter: &EventFilter,
+    ) -> Vec<BlockNumber> {
+        let result: Result<_, Infallible> = crate::providers::blocks_with_events(
+            range,
+            filter,
+            |address| Ok(self.contract_event_blocks.get(&address).cloned().unwrap_or_default()),
+            |key| Ok(self.event_key_blocks.get(&key).cloned().unwrap_or_default()),
+            |block| Ok(self.event_blooms.get(&block).copied()),
+        );
+        result.unwrap_or_else(|e| match e {})
+    }
+}
+
 impl<Db> std::ops::Deref for CacheStateDb<Db> {
     type Target = Db;
     fn deref(&self) -> &Self::Target {
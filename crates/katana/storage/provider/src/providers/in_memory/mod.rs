@@ -13,6 +13,7 @@ use katana_primitives::block::{
 use katana_primitives::contract::{
     ClassHash, CompiledClassHash, CompiledContractClass, ContractAddress, FlattenedSierraClass,
 };
+use katana_primitives::event::EventFilter;
 use katana_primitives::receipt::Receipt;
 use katana_primitives::state::{StateUpdates, StateUpdatesWithDeclaredClasses};
 use katana_primitives::transaction::{Tx, TxHash, TxNumber, TxWithHash};
@@ -25,6 +26,7 @@ use crate::traits::block::{
     HeaderProvider,
 };
 use crate::traits::contract::ContractClassWriter;
+use crate::traits::event::EventProvider;
 use crate::traits::state::{StateFactoryProvider, StateProvider, StateRootProvider, StateWriter};
 use crate::traits::state_update::StateUpdateProvider;
 use crate::traits::transaction::{
@@ -332,6 +334,16 @@ impl ReceiptProvider for InMemoryProvider {
     }
 }
 
+impl EventProvider for InMemoryProvider {
+    fn blocks_with_events(
+        &self,
+        range: RangeInclusive<BlockNumber>,
+        filter: &EventFilter,
+    ) -> Result<Vec<BlockNumber>> {
+        Ok(self.storage.read().blocks_with_events(range, filter))
+    }
+}
+
 impl StateUpdateProvider for InMemoryProvider {
     fn state_update(&self, block_id: BlockHashOrNumber) -> Result<Option<StateUpdates>> {
         let block_num = match block_id {
@@ -424,6 +436,7 @@ impl BlockWriter for InMemoryProvider {
         storage.transaction_hashes.extend(txs_id);
         storage.transaction_numbers.extend(txs_num);
         storage.transaction_block.extend(txs_block);
+        storage.index_events(block_number, &receipts);
         storage.receipts.extend(receipts);
 
         storage.state_update.insert(block_number, states.state_updates.clone());
@@ -90,7 +90,7 @@ impl Default for HistoricalStates {
     }
 }
 
-pub(super) type InMemoryStateDb = CacheStateDb<()>;
+pub(crate) type InMemoryStateDb = CacheStateDb<()>;
 pub(super) type InMemorySnapshot = StateSnapshot<()>;
 
 impl Default for InMemoryStateDb {
@@ -171,7 +171,7 @@ impl ContractClassProvider for InMemorySnapshot {
     }
 }
 
-pub(super) struct LatestStateProvider(pub(super) Arc<InMemoryStateDb>);
+pub(crate) struct LatestStateProvider(pub(crate) Arc<InMemoryStateDb>);
 
 impl ContractInfoProvider for LatestStateProvider {
     fn contract(&self, address: ContractAddress) -> Result<Option<GenericContractInfo>> {
@@ -3,3 +3,60 @@ pub mod db;
 pub mod fork;
 #[cfg(feature = "in-memory")]
 pub mod in_memory;
+
+use std::collections::BTreeSet;
+use std::ops::RangeInclusive;
+
+use katana_primitives::block::BlockNumber;
+use katana_primitives::contract::ContractAddress;
+use katana_primitives::event::{EventBloom, EventFilter};
+use katana_primitives::FieldElement;
+
+/// Returns the blocks in `range` that may contain events matching `filter`, using the event
+/// indexes of a provider.
+///
+/// * `contract_blocks` - returns the blocks in which a contract has emitted events.
+/// * `key_blocks` - returns the blocks in which events have been emitted with a given first key.
+/// * `bloom` - returns the event bloom of a block.
+pub(crate) fn blocks_with_events<E>(
+    range: RangeInclusive<BlockNumber>,
+    filter: &EventFilter,
+    contract_blocks: impl Fn(ContractAddress) -> Result<Vec<BlockNumber>, E>,
+    key_blocks: impl Fn(FieldElement) -> Result<Vec<BlockNumber>, E>,
+    bloom: impl Fn(BlockNumber) -> Result<Option<EventBloom>, E>,
+) -> Result<Vec<BlockNumber>, E> {
+    let mut candidates: Option<BTreeSet<BlockNumber>> = None;
+
+    if let Some(address) = filter.address {
+        let blocks = contract_blocks(address)?.into_iter().filter(|b| range.contains(b)).collect();
+        candidates = Some(blocks);
+    }
+
+    let first_keys = filter.keys.as_ref().and_then(|keys| keys.first()).filter(|k| !k.is_empty());
+    if let Some(first_keys) = first_keys {
+        let mut blocks = BTreeSet::new();
+        for key in first_keys {
+            blocks.extend(key_blocks(*key)?.into_iter().filter(|b| range.contains(b)));
+        }
+
+        candidates = Some(match candidates {
+            Some(candidates) => candidates.intersection(&blocks).copied().collect(),
+            None => blocks,
+        });
+    }
+
+    let candidates: Vec<BlockNumber> = match candidates {
+        Some(candidates) => candidates.into_iter().collect(),
+        None => range.collect(),
+    };
+
+    let mut blocks = Vec::with_capacity(candidates.len());
+    for block in candidates {
+        // blocks without a bloom are kept, as they can't be ruled out
+        if bloom(block)?.map_or(true, |bloom| bloom.may_match(filter)) {
+            blocks.push(block);
+        }
+    }
+
+    Ok(blocks)
+}
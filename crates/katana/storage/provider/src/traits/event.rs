@@ -0,0 +1,17 @@
+use std::ops::RangeInclusive;
+
+use anyhow::Result;
+use katana_primitives::block::BlockNumber;
+use katana_primitives::event::EventFilter;
+
+#[auto_impl::auto_impl(&, Box, Arc)]
+pub trait EventProvider: Send + Sync {
+    /// Returns the numbers of the blocks in the given range that may contain events matching the
+    /// filter, in ascending order. The blocks that are not returned are guaranteed to not contain
+    /// any matching event.
+    fn blocks_with_events(
+        &self,
+        range: RangeInclusive<BlockNumber>,
+        filter: &EventFilter,
+    ) -> Result<Vec<BlockNumber>>;
+}
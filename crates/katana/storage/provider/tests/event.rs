@@ -0,0 +1,105 @@
+use anyhow::Result;
+use katana_primitives::block::{Block, FinalityStatus, Header, SealedBlockWithStatus};
+use katana_primitives::contract::ContractAddress;
+use katana_primitives::event::EventFilter;
+use katana_primitives::receipt::{Event, InvokeTxReceipt, Receipt};
+use katana_primitives::transaction::{Tx, TxWithHash};
+use katana_primitives::FieldElement;
+use katana_provider::providers::db::DbProvider;
+use katana_provider::providers::fork::ForkedProvider;
+use katana_provider::providers::in_memory::InMemoryProvider;
+use katana_provider::traits::block::BlockWriter;
+use katana_provider::traits::event::EventProvider;
+use katana_provider::BlockchainProvider;
+use rstest_reuse::{self, *};
+use starknet::macros::felt;
+
+mod fixtures;
+
+use fixtures::{db_provider, fork_provider, in_memory_provider};
+
+fn filter(address: Option<FieldElement>, keys: Option<Vec<Vec<FieldElement>>>) -> EventFilter {
+    EventFilter { address: address.map(ContractAddress::from), keys }
+}
+
+#[template]
+#[rstest::rstest]
+#[case::all_blocks(filter(None, None), vec![0, 1, 2])]
+#[case::by_address(filter(Some(felt!("0xa")), None), vec![0])]
+#[case::by_first_key(filter(None, Some(vec![vec![felt!("0x2")]])), vec![2])]
+#[case::by_any_first_key(filter(None, Some(vec![vec![felt!("0x1"), felt!("0x2")]])), vec![0, 2])]
+#[case::by_second_key(filter(None, Some(vec![vec![], vec![felt!("0x3")]])), vec![2])]
+#[case::by_address_and_key(filter(Some(felt!("0xa")), Some(vec![vec![felt!("0x2")]])), vec![])]
+#[case::unknown_address(filter(Some(felt!("0xc")), None), vec![])]
+fn blocks_with_events_cases(#[case] filter: EventFilter, #[case] expected: Vec<u64>) {}
+
+#[apply(blocks_with_events_cases)]
+fn blocks_with_events_with_in_memory_provider(
+    #[from(in_memory_provider)] provider: BlockchainProvider<InMemoryProvider>,
+    #[case] filter: EventFilter,
+    #[case] expected: Vec<u64>,
+) -> Result<()> {
+    blocks_with_events_test_impl(provider, filter, expected)
+}
+
+#[apply(blocks_with_events_cases)]
+fn blocks_with_events_with_fork_provider(
+    #[from(fork_provider)] provider: BlockchainProvider<ForkedProvider>,
+    #[case] filter: EventFilter,
+    #[case] expected: Vec<u64>,
+) -> Result<()> {
+    blocks_with_events_test_impl(provider, filter, expected)
+}
+
+#[apply(blocks_with_events_cases)]
+fn blocks_with_events_with_db_provider(
+    #[from(db_provider)] provider: BlockchainProvider<DbProvider>,
+    #[case] filter: EventFilter,
+    #[case] expected: Vec<u64>,
+) -> Result<()> {
+    blocks_with_events_test_impl(provider, filter, expected)
+}
+
+fn blocks_with_events_test_impl<Db>(
+    provider: BlockchainProvider<Db>,
+    filter: EventFilter,
+    expected: Vec<u64>,
+) -> Result<()>
+where
+    Db: BlockWriter + EventProvider,
+{
+    // block 0: an event from `0xa` with key `0x1`
+    // block 1: no events
+    // block 2: an event from `0xb` with keys `0x2` and `0x3`
+    let events = [
+        vec![Event { from_address: felt!("0xa").into(), keys: vec![felt!("0x1")], data: vec![] }],
+        vec![],
+        vec![Event {
+            from_address: felt!("0xb").into(),
+            keys: vec![felt!("0x2"), felt!("0x3")],
+            data: vec![],
+        }],
+    ];
+
+    for (i, events) in events.into_iter().enumerate() {
+        let tx = TxWithHash {
+            hash: FieldElement::from(i as u64),
+            transaction: Tx::Invoke(Default::default()),
+        };
+        let receipt = Receipt::Invoke(InvokeTxReceipt { events, ..Default::default() });
+
+        let header = Header { number: i as u64, ..Default::default() };
+        let block = Block { header, body: vec![tx] }.seal_with_hash(FieldElement::from(i as u64));
+        let block = SealedBlockWithStatus { block, status: FinalityStatus::AcceptedOnL2 };
+
+        provider.insert_block_with_states_and_receipts(block, Default::default(), vec![receipt])?;
+    }
+
+    assert_eq!(provider.blocks_with_events(0..=2, &filter)?, expected);
+    assert_eq!(
+        provider.blocks_with_events(1..=2, &filter)?,
+        expected.into_iter().filter(|num| *num >= 1).collect::<Vec<_>>()
+    );
+
+    Ok(())
+}
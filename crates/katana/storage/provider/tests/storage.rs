@@ -26,7 +26,7 @@ mod latest {
     use katana_provider::providers::db::DbProvider;
 
     use super::*;
-    use crate::fixtures::db_provider;
+    use crate::fixtures::{db_provider, mock_state_updates};
 
     fn assert_latest_storage_value<Db: StateFactoryProvider>(
         provider: BlockchainProvider<Db>,
@@ -78,6 +78,22 @@ mod latest {
     ) -> Result<()> {
         assert_latest_storage_value(provider, expected_storage_entry)
     }
+
+    #[test]
+    fn read_storage_from_fork_provider_with_local_state() -> Result<()> {
+        let db = provider_with_states(db_provider(), mock_state_updates());
+        let provider = BlockchainProvider::new(ForkedProvider::new_with_state(db.latest()?));
+
+        assert_latest_storage_value(
+            provider,
+            vec![
+                (ContractAddress::from(felt!("1")), felt!("1"), Some(felt!("111"))),
+                (ContractAddress::from(felt!("1")), felt!("3"), Some(felt!("77"))),
+                (ContractAddress::from(felt!("2")), felt!("2"), Some(felt!("13"))),
+                (ContractAddress::from(felt!("3")), felt!("1"), None),
+            ],
+        )
+    }
 }
 
 mod historical {
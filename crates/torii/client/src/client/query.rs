@@ -0,0 +1,186 @@
+//! Builder of the queries of [`Client::entities`](super::Client::entities).
+
+use starknet_crypto::FieldElement;
+use torii_grpc::types::{
+    Clause, ComparisonOperator, CompositeClause, KeysClause, LogicalOperator, MemberClause, Query,
+    Value,
+};
+
+/// Builds a [`Query`], whose clauses are combined with [`QueryBuilder::and`] and
+/// [`QueryBuilder::or`].
+///
+/// ```
+/// # use starknet_crypto::FieldElement;
+/// # use torii_client::client::query::{keys, member, QueryBuilder};
+/// # use torii_grpc::types::{ComparisonOperator, Value, ValueType};
+/// # use dojo_types::primitive::Primitive;
+/// let query = QueryBuilder::new()
+///     .clause(keys("Position", vec![FieldElement::ONE]))
+///     .and(member(
+///         "Moves",
+///         "remaining",
+///         ComparisonOperator::Gt,
+///         Value { primitive_type: Primitive::U8(None), value_type: ValueType::UInt(0) },
+///     ))
+///     .limit(10)
+///     .build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct QueryBuilder {
+    clause: Option<Clause>,
+    limit: u32,
+    offset: u32,
+}
+
+impl Default for QueryBuilder {
+    fn default() -> Self {
+        Self { clause: None, limit: 100, offset: 0 }
+    }
+}
+
+impl QueryBuilder {
+    /// Creates a builder of a query without clause, which returns up to 100 entities.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the clause of the query, replacing the current one.
+    pub fn clause(mut self, clause: Clause) -> Self {
+        self.clause = Some(clause);
+        self
+    }
+
+    /// Only returns the entities matching both the current clause and `clause`.
+    pub fn and(self, clause: Clause) -> Self {
+        self.combine(LogicalOperator::And, clause)
+    }
+
+    /// Returns the entities matching either the current clause or `clause`.
+    pub fn or(self, clause: Clause) -> Self {
+        self.combine(LogicalOperator::Or, clause)
+    }
+
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    pub fn offset(mut self, offset: u32) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn build(self) -> Query {
+        Query { clause: self.clause, limit: self.limit, offset: self.offset }
+    }
+
+    fn combine(mut self, operator: LogicalOperator, clause: Clause) -> Self {
+        self.clause = Some(match self.clause {
+            None => clause,
+            // Chaining the same operator extends the composite clause instead of nesting it.
+            Some(Clause::Composite(mut composite))
+                if composite.operator == operator && composite.model.is_empty() =>
+            {
+                composite.clauses.push(clause);
+                Clause::Composite(composite)
+            }
+            Some(current) => composite_clause(operator, vec![current, clause]),
+        });
+        self
+    }
+}
+
+/// Matches the entity with the given hashed keys.
+pub fn hashed_keys(hashed_keys: Vec<FieldElement>) -> Clause {
+    Clause::HashedKeys(hashed_keys)
+}
+
+/// Matches the entities of `model` whose keys start with `keys`.
+pub fn keys(model: impl Into<String>, keys: Vec<FieldElement>) -> Clause {
+    Clause::Keys(KeysClause { model: model.into(), keys })
+}
+
+/// Matches the entities of `model` whose `member` compares to `value`. The members of nested
+/// structs are separated by dots (ex: `vec.x`).
+pub fn member(
+    model: impl Into<String>,
+    member: impl Into<String>,
+    operator: ComparisonOperator,
+    value: Value,
+) -> Clause {
+    Clause::Member(MemberClause { model: model.into(), member: member.into(), operator, value })
+}
+
+/// Matches the entities matching all of `clauses`.
+pub fn and(clauses: Vec<Clause>) -> Clause {
+    composite_clause(LogicalOperator::And, clauses)
+}
+
+/// Matches the entities matching any of `clauses`.
+pub fn or(clauses: Vec<Clause>) -> Clause {
+    composite_clause(LogicalOperator::Or, clauses)
+}
+
+fn composite_clause(operator: LogicalOperator, clauses: Vec<Clause>) -> Clause {
+    Clause::Composite(CompositeClause { model: String::new(), operator, clauses })
+}
+
+#[cfg(test)]
+mod tests {
+    use dojo_types::primitive::Primitive;
+    use starknet::macros::felt;
+    use torii_grpc::types::{Clause, ComparisonOperator, LogicalOperator, Value, ValueType};
+
+    use super::{and, keys, member, or, QueryBuilder};
+
+    fn remaining_moves(operator: ComparisonOperator, value: u64) -> Clause {
+        let value =
+            Value { primitive_type: Primitive::U8(None), value_type: ValueType::UInt(value) };
+        member("Moves", "remaining", operator, value)
+    }
+
+    #[test]
+    fn chained_clauses_are_flattened() {
+        let query = QueryBuilder::new()
+            .clause(keys("Position", vec![felt!("0x1")]))
+            .and(remaining_moves(ComparisonOperator::Gt, 0))
+            .and(remaining_moves(ComparisonOperator::Lt, 10))
+            .offset(5)
+            .build();
+
+        assert_eq!(
+            query.clause,
+            Some(and(vec![
+                keys("Position", vec![felt!("0x1")]),
+                remaining_moves(ComparisonOperator::Gt, 0),
+                remaining_moves(ComparisonOperator::Lt, 10),
+            ]))
+        );
+        assert_eq!(query.limit, 100);
+        assert_eq!(query.offset, 5);
+    }
+
+    #[test]
+    fn mixed_operators_are_nested() {
+        let query = QueryBuilder::new()
+            .clause(keys("Position", vec![felt!("0x1")]))
+            .or(keys("Position", vec![felt!("0x2")]))
+            .and(remaining_moves(ComparisonOperator::Eq, 0))
+            .build();
+
+        let expected = and(vec![
+            or(vec![keys("Position", vec![felt!("0x1")]), keys("Position", vec![felt!("0x2")])]),
+            remaining_moves(ComparisonOperator::Eq, 0),
+        ]);
+        assert_eq!(query.clause, Some(expected));
+
+        let proto: torii_grpc::proto::types::Query = query.into();
+        let Some(torii_grpc::proto::types::clause::ClauseType::Composite(composite)) =
+            proto.clause.and_then(|clause| clause.clause_type)
+        else {
+            panic!("expected a composite clause");
+        };
+        assert_eq!(composite.operator, LogicalOperator::And as i32);
+        assert_eq!(composite.clauses.len(), 2);
+    }
+}
@@ -0,0 +1,43 @@
+//! The database Torii stores the indexed worlds in.
+//!
+//! Only SQLite is supported: the store, the model cache and the GraphQL and gRPC servers all query
+//! it directly. PostgreSQL URLs are rejected, rather than opened as the path of a SQLite file.
+
+use std::str::FromStr;
+
+use anyhow::{bail, Result};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+
+/// Opens the SQLite database at `path`, creating it if it doesn't exist, and runs the migrations.
+pub async fn connect_sqlite(path: &str) -> Result<SqlitePool> {
+    if path.starts_with("postgres://") || path.starts_with("postgresql://") {
+        bail!("PostgreSQL databases aren't supported, expected the path of a SQLite database");
+    }
+
+    let options = SqliteConnectOptions::from_str(&format!("sqlite:{path}"))?
+        .create_if_missing(true)
+        .with_regexp();
+    let pool = SqlitePoolOptions::new()
+        .min_connections(1)
+        .max_connections(5)
+        .connect_with(options)
+        .await?;
+
+    sqlx::migrate!("../migrations").run(&pool).await?;
+
+    Ok(pool)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::connect_sqlite;
+
+    #[tokio::test]
+    async fn postgres_urls_are_rejected() {
+        let err = connect_sqlite("postgres://torii@localhost/torii").await.unwrap_err();
+        assert!(err.to_string().contains("PostgreSQL databases aren't supported"));
+        assert!(connect_sqlite("postgresql://localhost").await.is_err());
+
+        assert!(connect_sqlite(":memory:").await.is_ok());
+    }
+}
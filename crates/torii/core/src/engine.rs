@@ -1,10 +1,18 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
 use std::time::Duration;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use dojo_world::contracts::world::WorldContractReader;
+use futures_util::future::try_join;
+use futures_util::stream::{self, StreamExt, TryStreamExt};
 use starknet::core::types::{
-    BlockId, BlockWithTxs, Event, InvokeTransaction, InvokeTransactionReceipt, InvokeTransactionV1,
-    MaybePendingBlockWithTxs, MaybePendingTransactionReceipt, Transaction, TransactionReceipt,
+    BlockId, BlockStatus, BlockTag, BlockWithTxs, Event, EventFilter, FieldElement,
+    InvokeTransaction, InvokeTransactionReceipt, InvokeTransactionV1,
+    MaybePendingBlockWithTxHashes, MaybePendingBlockWithTxs, MaybePendingTransactionReceipt,
+    PendingBlockWithTxs, PendingInvokeTransactionReceipt, PendingTransactionReceipt, Transaction,
+    TransactionFinalityStatus, TransactionReceipt,
 };
 use starknet::core::utils::get_selector_from_name;
 use starknet::providers::Provider;
@@ -16,6 +24,10 @@ use tracing::{error, info, warn};
 use crate::processors::{BlockProcessor, EventProcessor, TransactionProcessor};
 use crate::sql::Sql;
 
+/// The number of blocks behind the head that can still be reverted if they get reorged out of
+/// the chain. The undo log of older blocks is pruned.
+pub const MAX_REORG_DEPTH: u64 = 64;
+
 pub struct Processors<P: Provider + Sync> {
     pub block: Vec<Box<dyn BlockProcessor<P>>>,
     pub transaction: Vec<Box<dyn TransactionProcessor<P>>>,
@@ -28,15 +40,65 @@ impl<P: Provider + Sync> Default for Processors<P> {
     }
 }
 
+/// How the engine finds the world's events of a range of blocks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SyncMode {
+    /// Fetches every block and the receipts of all of their invoke transactions.
+    #[default]
+    Blocks,
+    /// Pages through the events emitted by the world, and only fetches the blocks and receipts
+    /// of the transactions that emitted them. Block processors only run on those blocks.
+    Events,
+}
+
+impl FromStr for SyncMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "blocks" => Ok(SyncMode::Blocks),
+            "events" => Ok(SyncMode::Events),
+            _ => Err(anyhow!("invalid sync mode `{s}`, expected `blocks` or `events`")),
+        }
+    }
+}
+
+impl fmt::Display for SyncMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyncMode::Blocks => write!(f, "blocks"),
+            SyncMode::Events => write!(f, "events"),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct EngineConfig {
     pub block_time: Duration,
     pub start_block: u64,
+    pub sync_mode: SyncMode,
+    /// The number of events requested per page in [`SyncMode::Events`].
+    pub events_chunk_size: u64,
+    /// The maximum number of blocks and receipts requested at once in [`SyncMode::Events`].
+    pub max_concurrent_requests: usize,
+    /// The number of blocks a block must be buried under before it is indexed.
+    pub confirmations: u64,
+    /// Whether the transactions of the pending block are indexed, until the block is accepted.
+    /// Only applies without confirmations.
+    pub index_pending: bool,
 }
 
 impl Default for EngineConfig {
     fn default() -> Self {
-        Self { block_time: Duration::from_secs(1), start_block: 0 }
+        Self {
+            block_time: Duration::from_secs(1),
+            start_block: 0,
+            sync_mode: SyncMode::default(),
+            events_chunk_size: 1024,
+            max_concurrent_requests: 32,
+            confirmations: 0,
+            index_pending: false,
+        }
     }
 }
 
@@ -48,6 +110,8 @@ pub struct Engine<'db, P: Provider + Sync> {
     config: EngineConfig,
     shutdown_tx: Sender<()>,
     block_tx: Option<BoundedSender<u64>>,
+    /// The transactions of the pending block that have been indexed.
+    pending_transactions: HashSet<FieldElement>,
 }
 
 impl<'db, P: Provider + Sync> Engine<'db, P> {
@@ -60,11 +124,26 @@ impl<'db, P: Provider + Sync> Engine<'db, P> {
         shutdown_tx: Sender<()>,
         block_tx: Option<BoundedSender<u64>>,
     ) -> Self {
-        Self { world, db, provider: Box::new(provider), processors, config, shutdown_tx, block_tx }
+        Self {
+            world,
+            db,
+            provider: Box::new(provider),
+            processors,
+            config,
+            shutdown_tx,
+            block_tx,
+            pending_transactions: HashSet::new(),
+        }
     }
 
     pub async fn start(&mut self) -> Result<()> {
         let mut head = self.db.head().await?;
+
+        // Discards what was indexed past the head before stopping, like the pending block.
+        if self.db.indexed_past(head).await? {
+            self.db.revert_to(head).await?;
+        }
+
         if head == 0 {
             head = self.config.start_block;
         } else if self.config.start_block != 0 {
@@ -102,15 +181,89 @@ impl<'db, P: Provider + Sync> Engine<'db, P> {
     }
 
     pub async fn sync_to_head(&mut self, from: u64) -> Result<u64> {
-        let latest_block_number = self.provider.block_hash_and_number().await?.block_number;
+        let chain_head = self.provider.block_hash_and_number().await?.block_number;
+        let latest_block_number = chain_head.saturating_sub(self.config.confirmations);
+        self.db.set_reorgable_from(chain_head.saturating_sub(MAX_REORG_DEPTH));
+
+        // The indexed head may have been reorged out of the chain since the last sync.
+        let from = match self.find_reorg(from, chain_head).await? {
+            Some(fork) => {
+                self.revert_to(fork).await?;
+                fork
+            }
+            None => from,
+        };
 
         if from < latest_block_number {
+            // The pending block has been accepted, it is indexed again as part of the range.
+            self.discard_pending(from).await?;
+
             // if `from` == 0, then the block may or may not be processed yet.
             let from = if from == 0 { from } else { from + 1 };
-            self.sync_range(from, latest_block_number).await?;
+            match self.config.sync_mode {
+                SyncMode::Blocks => self.sync_range(from, latest_block_number).await?,
+                SyncMode::Events => self.sync_range_with_events(from, latest_block_number).await?,
+            }
         };
 
-        Ok(latest_block_number)
+        let head = latest_block_number.max(from);
+        if self.config.index_pending && self.config.confirmations == 0 {
+            self.sync_pending(head).await?;
+        }
+
+        Ok(head)
+    }
+
+    /// Indexes the transactions of the pending block that haven't been indexed yet, as the block
+    /// following `head`.
+    async fn sync_pending(&mut self, head: u64) -> Result<()> {
+        let pending_block =
+            match self.provider.get_block_with_txs(BlockId::Tag(BlockTag::Pending)).await? {
+                MaybePendingBlockWithTxs::PendingBlock(block) => block,
+                // The pending block has just been accepted, it is indexed on the next sync.
+                MaybePendingBlockWithTxs::Block(_) => return Ok(()),
+            };
+
+        // The head isn't the parent of the pending block until the sync catches up.
+        if self.db.block_hash(head).await?.is_some_and(|hash| hash != pending_block.parent_hash) {
+            return Ok(());
+        }
+
+        let block = pending_block_with_number(pending_block, head + 1);
+        self.db.set_pending_block(block.block_number);
+
+        let mut indexed = Vec::new();
+        for (tx_idx, transaction) in block.transactions.iter().enumerate() {
+            let invoke_transaction = match transaction {
+                Transaction::Invoke(InvokeTransaction::V1(invoke_transaction)) => {
+                    invoke_transaction
+                }
+                _ => continue,
+            };
+
+            let transaction_hash = invoke_transaction.transaction_hash;
+            if self.pending_transactions.contains(&transaction_hash) {
+                continue;
+            }
+
+            let receipt = match self.provider.get_transaction_receipt(transaction_hash).await? {
+                MaybePendingTransactionReceipt::PendingReceipt(
+                    PendingTransactionReceipt::Invoke(receipt),
+                ) => pending_receipt_with_number(receipt, block.block_number),
+                MaybePendingTransactionReceipt::Receipt(TransactionReceipt::Invoke(receipt)) => {
+                    receipt
+                }
+                _ => continue,
+            };
+
+            self.process_invoke(&block, tx_idx, invoke_transaction, &receipt).await?;
+            indexed.push(transaction_hash);
+        }
+
+        self.db.execute().await?;
+        self.pending_transactions.extend(indexed);
+
+        Ok(())
     }
 
     pub async fn sync_range(&mut self, mut from: u64, to: u64) -> Result<()> {
@@ -125,6 +278,20 @@ impl<'db, P: Provider + Sync> Engine<'db, P> {
                 }
             };
 
+            if let MaybePendingBlockWithTxs::Block(block) = &block_with_txs {
+                if block.block_number > 0 {
+                    let parent = block.block_number - 1;
+                    if self.db.block_hash(parent).await?.is_some_and(|h| h != block.parent_hash) {
+                        let fork = self.find_fork_point(parent, to).await?;
+                        self.revert_to(fork).await?;
+                        from = if fork == 0 { fork } else { fork + 1 };
+                        continue;
+                    }
+                }
+
+                self.set_block(block.block_number, block.block_hash, block.parent_hash);
+            }
+
             // send the current block number
             if let Some(ref block_tx) = self.block_tx {
                 block_tx.send(from).await.expect("failed to send block number to gRPC server");
@@ -140,6 +307,186 @@ impl<'db, P: Provider + Sync> Engine<'db, P> {
         Ok(())
     }
 
+    /// Syncs the blocks `from..=to` by paging through the events emitted by the world, instead
+    /// of fetching every block of the range.
+    pub async fn sync_range_with_events(&mut self, from: u64, to: u64) -> Result<()> {
+        let filter = EventFilter {
+            from_block: Some(BlockId::Number(from)),
+            to_block: Some(BlockId::Number(to)),
+            address: Some(self.world.address),
+            keys: None,
+        };
+
+        // The transactions that emitted world events, in the order they were emitted. A block is
+        // only processed once the pages have moved past it, as the events of a transaction may
+        // be split over two pages.
+        let mut transactions: Vec<(u64, FieldElement)> = Vec::new();
+        let mut continuation_token = None;
+
+        loop {
+            let page = self
+                .provider
+                .get_events(filter.clone(), continuation_token, self.config.events_chunk_size)
+                .await?;
+
+            for event in page.events {
+                if transactions.last() != Some(&(event.block_number, event.transaction_hash)) {
+                    transactions.push((event.block_number, event.transaction_hash));
+                }
+            }
+
+            continuation_token = page.continuation_token;
+            if continuation_token.is_none() {
+                break;
+            }
+
+            if let Some(&(last_block, _)) = transactions.last() {
+                let complete = transactions.partition_point(|(block, _)| *block < last_block);
+                let complete_transactions: Vec<_> = transactions.drain(..complete).collect();
+                self.process_transactions(complete_transactions).await?;
+            }
+        }
+
+        self.process_transactions(transactions).await?;
+
+        // Blocks without any world event are done as well. The hash of the last one is kept, so
+        // the next sync can tell whether it got reorged.
+        match self.provider.get_block_with_tx_hashes(BlockId::Number(to)).await? {
+            MaybePendingBlockWithTxHashes::Block(block) => {
+                self.set_block(block.block_number, block.block_hash, block.parent_hash)
+            }
+            MaybePendingBlockWithTxHashes::PendingBlock(_) => {
+                return Err(anyhow!("block {to} is still pending"));
+            }
+        }
+        self.db.set_head(to);
+        self.db.execute().await?;
+
+        Ok(())
+    }
+
+    /// Processes the given transactions, which are ordered by block, fetching their blocks and
+    /// receipts concurrently, at most [`EngineConfig::max_concurrent_requests`] of each at once.
+    async fn process_transactions(&mut self, transactions: Vec<(u64, FieldElement)>) -> Result<()> {
+        let mut block_numbers: Vec<u64> = transactions.iter().map(|(block, _)| *block).collect();
+        block_numbers.dedup();
+
+        let provider = self.provider.as_ref();
+        let concurrency = self.config.max_concurrent_requests.max(1);
+        let blocks = stream::iter(&block_numbers)
+            .map(|block| provider.get_block_with_txs(BlockId::Number(*block)))
+            .buffered(concurrency)
+            .try_collect::<Vec<_>>();
+        let receipts = stream::iter(&transactions)
+            .map(|(_, hash)| provider.get_transaction_receipt(*hash))
+            .buffer_unordered(concurrency)
+            .try_collect::<Vec<_>>();
+        let (blocks, receipts) = try_join(blocks, receipts).await?;
+
+        let mut receipts: HashMap<FieldElement, InvokeTransactionReceipt> = receipts
+            .into_iter()
+            .filter_map(|receipt| match receipt {
+                MaybePendingTransactionReceipt::Receipt(TransactionReceipt::Invoke(receipt)) => {
+                    Some((receipt.transaction_hash, receipt))
+                }
+                _ => None,
+            })
+            .collect();
+
+        for block in blocks {
+            let block = match block {
+                MaybePendingBlockWithTxs::Block(block) => block,
+                _ => continue,
+            };
+
+            if let Some(ref block_tx) = self.block_tx {
+                block_tx
+                    .send(block.block_number)
+                    .await
+                    .expect("failed to send block number to gRPC server");
+            }
+
+            self.set_block(block.block_number, block.block_hash, block.parent_hash);
+            Self::process_block(self, &block).await?;
+
+            for (tx_idx, transaction) in block.transactions.iter().enumerate() {
+                let invoke_transaction = match transaction {
+                    Transaction::Invoke(InvokeTransaction::V1(invoke_transaction)) => {
+                        invoke_transaction
+                    }
+                    _ => continue,
+                };
+
+                if let Some(receipt) = receipts.remove(&invoke_transaction.transaction_hash) {
+                    self.process_invoke(&block, tx_idx, invoke_transaction, &receipt).await?;
+                }
+            }
+
+            info!("processed block: {}", block.block_number);
+
+            self.db.set_head(block.block_number);
+            self.db.execute().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Records the hash of the block being indexed, and prunes the blocks that can no longer be
+    /// reorged.
+    fn set_block(&mut self, number: u64, hash: FieldElement, parent_hash: FieldElement) {
+        self.db.set_block(number, hash, parent_hash);
+        self.db.prune_blocks(number.saturating_sub(MAX_REORG_DEPTH));
+    }
+
+    /// Returns the block to revert to if the indexed block `head` is no longer part of the chain.
+    async fn find_reorg(&self, head: u64, chain_head: u64) -> Result<Option<u64>> {
+        let Some(hash) = self.db.block_hash(head).await? else {
+            return Ok(None);
+        };
+
+        if head <= chain_head && self.chain_block_hash(head).await? == hash {
+            return Ok(None);
+        }
+
+        Ok(Some(self.find_fork_point(head, chain_head).await?))
+    }
+
+    /// Returns the most recent indexed block, up to `head`, that is still part of the chain.
+    async fn find_fork_point(&self, head: u64, chain_head: u64) -> Result<u64> {
+        for (number, hash) in self.db.indexed_blocks(head).await? {
+            if number <= chain_head && self.chain_block_hash(number).await? == hash {
+                return Ok(number);
+            }
+        }
+
+        Err(anyhow!("reorg deeper than the {MAX_REORG_DEPTH} blocks that can be reverted"))
+    }
+
+    async fn chain_block_hash(&self, number: u64) -> Result<FieldElement> {
+        match self.provider.get_block_with_tx_hashes(BlockId::Number(number)).await? {
+            MaybePendingBlockWithTxHashes::Block(block) => Ok(block.block_hash),
+            MaybePendingBlockWithTxHashes::PendingBlock(_) => {
+                Err(anyhow!("block {number} is still pending"))
+            }
+        }
+    }
+
+    async fn revert_to(&mut self, fork: u64) -> Result<()> {
+        warn!("reorg detected, reverting to block {fork}");
+        self.pending_transactions.clear();
+        self.db.revert_to(fork).await
+    }
+
+    /// Reverts the indexed transactions of the pending block, which is on top of `head`.
+    async fn discard_pending(&mut self, head: u64) -> Result<()> {
+        if self.pending_transactions.is_empty() {
+            return Ok(());
+        }
+
+        self.pending_transactions.clear();
+        self.db.revert_to(head).await
+    }
+
     async fn process(&mut self, block: MaybePendingBlockWithTxs) -> Result<()> {
         let block: BlockWithTxs = match block {
             MaybePendingBlockWithTxs::Block(block) => block,
@@ -176,34 +523,48 @@ impl<'db, P: Provider + Sync> Engine<'db, P> {
                 _ => continue,
             };
 
-            let mut world_event = false;
-            for (event_idx, event) in invoke_receipt.events.iter().enumerate() {
-                if event.from_address != self.world.address {
-                    continue;
-                }
+            self.process_invoke(&block, tx_idx, invoke_transaction, &invoke_receipt).await?;
+        }
+
+        info!("processed block: {}", block.block_number);
 
-                world_event = true;
-                let event_id =
-                    format!("0x{:064x}:0x{:04x}:0x{:04x}", block.block_number, tx_idx, event_idx);
+        Ok(())
+    }
 
-                Self::process_event(self, &block, &invoke_receipt, &event_id, event).await?;
+    /// Processes the world events emitted by an invoke transaction, and the transaction itself if
+    /// it emitted any.
+    async fn process_invoke(
+        &mut self,
+        block: &BlockWithTxs,
+        tx_idx: usize,
+        invoke_transaction: &InvokeTransactionV1,
+        invoke_receipt: &InvokeTransactionReceipt,
+    ) -> Result<()> {
+        let mut world_event = false;
+        for (event_idx, event) in invoke_receipt.events.iter().enumerate() {
+            if event.from_address != self.world.address {
+                continue;
             }
 
-            if world_event {
-                let transaction_id = format!("0x{:064x}:0x{:04x}", block.block_number, tx_idx);
+            world_event = true;
+            let event_id =
+                format!("0x{:064x}:0x{:04x}:0x{:04x}", block.block_number, tx_idx, event_idx);
 
-                Self::process_transaction(
-                    self,
-                    &block,
-                    &invoke_receipt,
-                    &transaction_id,
-                    invoke_transaction,
-                )
-                .await?;
-            }
+            Self::process_event(self, block, invoke_receipt, &event_id, event).await?;
         }
 
-        info!("processed block: {}", block.block_number);
+        if world_event {
+            let transaction_id = format!("0x{:064x}:0x{:04x}", block.block_number, tx_idx);
+
+            Self::process_transaction(
+                self,
+                block,
+                invoke_receipt,
+                &transaction_id,
+                invoke_transaction,
+            )
+            .await?;
+        }
 
         Ok(())
     }
@@ -258,3 +619,37 @@ impl<'db, P: Provider + Sync> Engine<'db, P> {
         Ok(())
     }
 }
+
+/// Gives the pending block the number it will have once accepted, so its events and transactions
+/// get the same ids they will have then. Its hash isn't known yet.
+fn pending_block_with_number(block: PendingBlockWithTxs, block_number: u64) -> BlockWithTxs {
+    BlockWithTxs {
+        status: BlockStatus::Pending,
+        block_hash: FieldElement::ZERO,
+        parent_hash: block.parent_hash,
+        block_number,
+        new_root: FieldElement::ZERO,
+        timestamp: block.timestamp,
+        sequencer_address: block.sequencer_address,
+        l1_gas_price: block.l1_gas_price,
+        starknet_version: block.starknet_version,
+        transactions: block.transactions,
+    }
+}
+
+fn pending_receipt_with_number(
+    receipt: PendingInvokeTransactionReceipt,
+    block_number: u64,
+) -> InvokeTransactionReceipt {
+    InvokeTransactionReceipt {
+        transaction_hash: receipt.transaction_hash,
+        actual_fee: receipt.actual_fee,
+        finality_status: TransactionFinalityStatus::AcceptedOnL2,
+        block_hash: FieldElement::ZERO,
+        block_number,
+        messages_sent: receipt.messages_sent,
+        events: receipt.events,
+        execution_resources: receipt.execution_resources,
+        execution_result: receipt.execution_result,
+    }
+}
@@ -4,6 +4,7 @@ use sqlx::FromRow;
 use crate::types::SQLFieldElement;
 
 pub mod cache;
+pub mod database;
 pub mod engine;
 pub mod error;
 pub mod model;
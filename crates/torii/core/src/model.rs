@@ -208,7 +208,10 @@ pub fn build_sql_query(model_schemas: &Vec<Ty>) -> Result<String, Error> {
         .collect::<Vec<_>>()
         .join(" ");
 
-    Ok(format!("SELECT entities.id, entities.keys, {selections_clause} FROM entities{join_clause}"))
+    Ok(format!(
+        "SELECT entities.id, entities.keys, entities.pending, {selections_clause} FROM \
+         entities{join_clause}"
+    ))
 }
 
 /// Populate the values of a Ty (schema) from SQLite row.
@@ -509,7 +512,7 @@ mod tests {
         let query = build_sql_query(&vec![ty]).unwrap();
         assert_eq!(
             query,
-            r#"SELECT entities.id, entities.keys, Position.external_name AS "Position.name", Position.external_age AS "Position.age", Position$vec.external_x AS "Position$vec.x", Position$vec.external_y AS "Position$vec.y" FROM entities JOIN Position ON entities.id = Position.entity_id  JOIN Position$vec ON entities.id = Position$vec.entity_id"#
+            r#"SELECT entities.id, entities.keys, entities.pending, Position.external_name AS "Position.name", Position.external_age AS "Position.age", Position$vec.external_x AS "Position$vec.x", Position$vec.external_y AS "Position$vec.y" FROM entities JOIN Position ON entities.id = Position.entity_id  JOIN Position$vec ON entities.id = Position$vec.entity_id"#
         );
     }
 }
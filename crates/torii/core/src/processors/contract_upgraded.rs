@@ -0,0 +1,60 @@
+use anyhow::{Error, Ok, Result};
+use async_trait::async_trait;
+use dojo_world::contracts::world::WorldContractReader;
+use starknet::core::types::{BlockWithTxs, Event, InvokeTransactionReceipt};
+use starknet::providers::Provider;
+use tracing::info;
+
+use super::EventProcessor;
+use crate::sql::Sql;
+
+#[derive(Default)]
+pub struct ContractUpgradedProcessor;
+
+const CLASS_HASH_INDEX: usize = 0;
+const ADDRESS_INDEX: usize = 1;
+
+#[async_trait]
+impl<P> EventProcessor<P> for ContractUpgradedProcessor
+where
+    P: Provider + Send + Sync,
+{
+    fn event_key(&self) -> String {
+        "ContractUpgraded".to_string()
+    }
+
+    fn validate(&self, event: &Event) -> bool {
+        if event.keys.len() > 1 {
+            info!(
+                "invalid keys for event {}: {}",
+                <ContractUpgradedProcessor as EventProcessor<P>>::event_key(self),
+                <ContractUpgradedProcessor as EventProcessor<P>>::event_keys_as_string(self, event),
+            );
+            return false;
+        }
+        true
+    }
+
+    async fn process(
+        &self,
+        _world: &WorldContractReader<P>,
+        db: &mut Sql,
+        block: &BlockWithTxs,
+        transaction_receipt: &InvokeTransactionReceipt,
+        _event_id: &str,
+        event: &Event,
+    ) -> Result<(), Error> {
+        let class_hash = event.data[CLASS_HASH_INDEX];
+        let address = event.data[ADDRESS_INDEX];
+        info!("contract upgraded: {:#x} (class hash {:#x})", address, class_hash);
+
+        db.set_contract_upgraded(
+            address,
+            class_hash,
+            block.timestamp,
+            transaction_receipt.transaction_hash,
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Error, Result};
+use async_trait::async_trait;
+use dojo_types::primitive::Primitive;
+use dojo_types::schema::{Enum, EnumOption, Member, Struct, Ty};
+use dojo_world::contracts::world::WorldContractReader;
+use dojo_world::manifest::Manifest;
+use serde_json::Value;
+use starknet::core::types::{BlockWithTxs, Event, InvokeTransactionReceipt};
+use starknet::providers::Provider;
+use tracing::{info, warn};
+
+use super::EventProcessor;
+use crate::sql::Sql;
+
+/// Stores a custom event emitted by the world's systems with `emit!`. The keys of the event are
+/// its selector, its `#[key]` members and the address of the system, appended by the world.
+pub struct CustomEventProcessor {
+    event: Ty,
+}
+
+impl CustomEventProcessor {
+    /// Creates the processor of a custom event, as returned by [`custom_events`].
+    pub fn new(event: Ty) -> Self {
+        Self { event }
+    }
+}
+
+#[async_trait]
+impl<P> EventProcessor<P> for CustomEventProcessor
+where
+    P: Provider + Send + Sync,
+{
+    fn event_key(&self) -> String {
+        self.event.name()
+    }
+
+    fn validate(&self, event: &Event) -> bool {
+        if event.keys.len() < 2 {
+            info!(
+                "invalid keys for event {}: {}",
+                <CustomEventProcessor as EventProcessor<P>>::event_key(self),
+                <CustomEventProcessor as EventProcessor<P>>::event_keys_as_string(self, event),
+            );
+            return false;
+        }
+        true
+    }
+
+    async fn process(
+        &self,
+        _world: &WorldContractReader<P>,
+        db: &mut Sql,
+        _block: &BlockWithTxs,
+        _transaction_receipt: &InvokeTransactionReceipt,
+        event_id: &str,
+        event: &Event,
+    ) -> Result<(), Error> {
+        info!("custom event: {}", self.event.name());
+
+        let mut keys = event.keys[1..event.keys.len() - 1].to_vec();
+        let mut data = event.data.clone();
+
+        let mut custom_event = self.event.clone();
+        if let Ty::Struct(s) = &mut custom_event {
+            for member in s.children.iter_mut() {
+                let felts = if member.key { &mut keys } else { &mut data };
+                if felts.is_empty() {
+                    return Err(anyhow!("Missing value of custom event member `{}`", member.name));
+                }
+                member.ty.deserialize(felts)?;
+            }
+        }
+
+        db.set_custom_event(&custom_event, event_id).await?;
+        Ok(())
+    }
+}
+
+/// Returns the custom events of the manifest's contracts, which are the struct events of their
+/// `Event` enum, named after their variant. Events with members that can't be stored in a column,
+/// like arrays or structs, are skipped.
+pub fn custom_events(manifest: &Manifest) -> Result<Vec<Ty>> {
+    let mut events: Vec<Ty> = Vec::new();
+
+    for contract in &manifest.contracts {
+        let Some(abi) = &contract.abi else {
+            continue;
+        };
+        let abi = serde_json::to_value(abi)?;
+        let items = abi.as_array().ok_or_else(|| anyhow!("Invalid ABI of {}", contract.name))?;
+
+        let mut event_items = HashMap::new();
+        let mut type_items = HashMap::new();
+        for item in items {
+            let Some(name) = item["name"].as_str() else {
+                continue;
+            };
+            match item["type"].as_str() {
+                Some("event") => event_items.insert(name, item),
+                Some("struct" | "enum") => type_items.insert(name, item),
+                _ => None,
+            };
+        }
+
+        let contract_event = format!("{}::Event", contract.name);
+        let Some(variants) =
+            event_items.get(contract_event.as_str()).and_then(|e| e["variants"].as_array())
+        else {
+            continue;
+        };
+
+        for variant in variants {
+            let (Some(name), Some(ty)) = (variant["name"].as_str(), variant["type"].as_str())
+            else {
+                continue;
+            };
+            // Component events are enums of events, emitted by the contract itself.
+            let Some(event) = event_items.get(ty).filter(|e| e["kind"] == "struct") else {
+                continue;
+            };
+
+            let schema = match event_schema(name, event, &type_items) {
+                Ok(schema) => schema,
+                Err(e) => {
+                    warn!("custom event {name} of {} ignored: {e}", contract.name);
+                    continue;
+                }
+            };
+
+            match events.iter().find(|e| e.name() == name) {
+                Some(registered) if *registered != schema => {
+                    warn!("custom event {name} of {} ignored: already declared", contract.name)
+                }
+                Some(_) => {}
+                None => events.push(schema),
+            }
+        }
+    }
+
+    Ok(events)
+}
+
+fn event_schema(name: &str, event: &Value, types: &HashMap<&str, &Value>) -> Result<Ty> {
+    let members = event["members"].as_array().ok_or_else(|| anyhow!("missing members"))?;
+
+    let children = members
+        .iter()
+        .map(|member| {
+            let name = member["name"].as_str().ok_or_else(|| anyhow!("missing member name"))?;
+            let ty = member["type"].as_str().ok_or_else(|| anyhow!("missing type of {name}"))?;
+
+            Ok(Member {
+                name: name.to_string(),
+                ty: member_type(ty, types)?,
+                key: member["kind"] == "key",
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Ty::Struct(Struct { name: name.to_string(), children }))
+}
+
+/// Returns the type of an event member, which is either a core primitive or an enum without
+/// values.
+fn member_type(path: &str, types: &HashMap<&str, &Value>) -> Result<Ty> {
+    let name = path.rsplit("::").next().unwrap_or(path);
+
+    if path.starts_with("core::") {
+        if let Ok(primitive) = Primitive::from_str(name) {
+            return Ok(Ty::Primitive(primitive));
+        }
+    }
+
+    let options = types
+        .get(path)
+        .filter(|item| item["type"] == "enum")
+        .and_then(|item| item["variants"].as_array())
+        .ok_or_else(|| anyhow!("unsupported type `{path}`"))?
+        .iter()
+        .map(|variant| match (variant["name"].as_str(), variant["type"].as_str()) {
+            (Some(name), Some("()")) => {
+                Ok(EnumOption { name: name.to_string(), ty: Ty::Tuple(vec![]) })
+            }
+            _ => Err(anyhow!("unsupported enum `{path}` with values")),
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Ty::Enum(Enum { name: name.to_string(), option: None, options }))
+}
@@ -0,0 +1,53 @@
+use anyhow::{Error, Ok, Result};
+use async_trait::async_trait;
+use dojo_world::contracts::world::WorldContractReader;
+use starknet::core::types::{BlockWithTxs, Event, InvokeTransactionReceipt};
+use starknet::providers::Provider;
+use tracing::info;
+
+use super::EventProcessor;
+use crate::sql::Sql;
+
+#[derive(Default)]
+pub struct ExecutorUpdatedProcessor;
+
+const ADDRESS_INDEX: usize = 0;
+
+#[async_trait]
+impl<P> EventProcessor<P> for ExecutorUpdatedProcessor
+where
+    P: Provider + Send + Sync,
+{
+    fn event_key(&self) -> String {
+        "ExecutorUpdated".to_string()
+    }
+
+    fn validate(&self, event: &Event) -> bool {
+        if event.keys.len() > 1 {
+            info!(
+                "invalid keys for event {}: {}",
+                <ExecutorUpdatedProcessor as EventProcessor<P>>::event_key(self),
+                <ExecutorUpdatedProcessor as EventProcessor<P>>::event_keys_as_string(self, event),
+            );
+            return false;
+        }
+        true
+    }
+
+    async fn process(
+        &self,
+        _world: &WorldContractReader<P>,
+        db: &mut Sql,
+        _block: &BlockWithTxs,
+        _transaction_receipt: &InvokeTransactionReceipt,
+        _event_id: &str,
+        event: &Event,
+    ) -> Result<(), Error> {
+        let address = event.data[ADDRESS_INDEX];
+        info!("executor updated: {:#x}", address);
+
+        db.set_executor(address);
+
+        Ok(())
+    }
+}
@@ -6,10 +6,18 @@ use starknet::providers::Provider;
 
 use crate::sql::Sql;
 
+pub mod contract_deployed;
+pub mod contract_upgraded;
+pub mod custom_event;
+pub mod executor_updated;
 pub mod metadata_update;
+pub mod owner_updated;
 pub mod register_model;
+pub mod store_del_record;
 pub mod store_set_record;
 pub mod store_transaction;
+pub mod world_upgraded;
+pub mod writer_updated;
 
 #[async_trait]
 pub trait EventProcessor<P>
@@ -0,0 +1,65 @@
+use anyhow::{Error, Ok, Result};
+use async_trait::async_trait;
+use dojo_world::contracts::world::WorldContractReader;
+use starknet::core::types::{BlockWithTxs, Event, InvokeTransactionReceipt};
+use starknet::providers::Provider;
+use starknet_crypto::FieldElement;
+use tracing::info;
+
+use super::EventProcessor;
+use crate::sql::Sql;
+use crate::types::PermissionKind;
+
+#[derive(Default)]
+pub struct OwnerUpdatedProcessor;
+
+const ADDRESS_INDEX: usize = 0;
+const RESOURCE_INDEX: usize = 1;
+const VALUE_INDEX: usize = 2;
+
+#[async_trait]
+impl<P> EventProcessor<P> for OwnerUpdatedProcessor
+where
+    P: Provider + Send + Sync,
+{
+    fn event_key(&self) -> String {
+        "OwnerUpdated".to_string()
+    }
+
+    fn validate(&self, event: &Event) -> bool {
+        if event.keys.len() > 1 {
+            info!(
+                "invalid keys for event {}: {}",
+                <OwnerUpdatedProcessor as EventProcessor<P>>::event_key(self),
+                <OwnerUpdatedProcessor as EventProcessor<P>>::event_keys_as_string(self, event),
+            );
+            return false;
+        }
+        true
+    }
+
+    async fn process(
+        &self,
+        _world: &WorldContractReader<P>,
+        db: &mut Sql,
+        _block: &BlockWithTxs,
+        transaction_receipt: &InvokeTransactionReceipt,
+        _event_id: &str,
+        event: &Event,
+    ) -> Result<(), Error> {
+        let address = event.data[ADDRESS_INDEX];
+        let resource = event.data[RESOURCE_INDEX];
+        let granted = event.data[VALUE_INDEX] != FieldElement::ZERO;
+        info!("owner of resource {:#x} updated: {:#x} ({})", resource, address, granted);
+
+        db.set_permission(
+            PermissionKind::Owner,
+            resource,
+            address,
+            granted,
+            transaction_receipt.transaction_hash,
+        );
+
+        Ok(())
+    }
+}
@@ -1,5 +1,6 @@
 use anyhow::{Error, Ok, Result};
 use async_trait::async_trait;
+use dojo_types::schema::Ty;
 use dojo_world::contracts::model::ModelReader;
 use dojo_world::contracts::world::WorldContractReader;
 use starknet::core::types::{BlockWithTxs, Event, InvokeTransactionReceipt};
@@ -63,6 +64,10 @@ where
 
         let mut entity = model.schema().await?;
         entity.deserialize(&mut keys_and_unpacked)?;
+        // The schema is named after the namespaced model, which the store namespaces itself.
+        if let Ty::Struct(s) = &mut entity {
+            s.name = name;
+        }
 
         db.set_entity(entity, event_id).await?;
         Ok(())
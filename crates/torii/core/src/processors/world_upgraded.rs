@@ -0,0 +1,53 @@
+use anyhow::{Error, Ok, Result};
+use async_trait::async_trait;
+use dojo_world::contracts::world::WorldContractReader;
+use starknet::core::types::{BlockWithTxs, Event, InvokeTransactionReceipt};
+use starknet::providers::Provider;
+use tracing::info;
+
+use super::EventProcessor;
+use crate::sql::Sql;
+
+#[derive(Default)]
+pub struct WorldUpgradedProcessor;
+
+const CLASS_HASH_INDEX: usize = 0;
+
+#[async_trait]
+impl<P> EventProcessor<P> for WorldUpgradedProcessor
+where
+    P: Provider + Send + Sync,
+{
+    fn event_key(&self) -> String {
+        "WorldUpgraded".to_string()
+    }
+
+    fn validate(&self, event: &Event) -> bool {
+        if event.keys.len() > 1 {
+            info!(
+                "invalid keys for event {}: {}",
+                <WorldUpgradedProcessor as EventProcessor<P>>::event_key(self),
+                <WorldUpgradedProcessor as EventProcessor<P>>::event_keys_as_string(self, event),
+            );
+            return false;
+        }
+        true
+    }
+
+    async fn process(
+        &self,
+        _world: &WorldContractReader<P>,
+        db: &mut Sql,
+        block: &BlockWithTxs,
+        transaction_receipt: &InvokeTransactionReceipt,
+        _event_id: &str,
+        event: &Event,
+    ) -> Result<(), Error> {
+        let class_hash = event.data[CLASS_HASH_INDEX];
+        info!("world upgraded: class hash {:#x}", class_hash);
+
+        db.set_world_upgraded(class_hash, block.timestamp, transaction_receipt.transaction_hash);
+
+        Ok(())
+    }
+}
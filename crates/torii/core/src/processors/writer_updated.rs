@@ -0,0 +1,65 @@
+use anyhow::{Error, Ok, Result};
+use async_trait::async_trait;
+use dojo_world::contracts::world::WorldContractReader;
+use starknet::core::types::{BlockWithTxs, Event, InvokeTransactionReceipt};
+use starknet::providers::Provider;
+use starknet_crypto::FieldElement;
+use tracing::info;
+
+use super::EventProcessor;
+use crate::sql::Sql;
+use crate::types::PermissionKind;
+
+#[derive(Default)]
+pub struct WriterUpdatedProcessor;
+
+const MODEL_INDEX: usize = 0;
+const SYSTEM_INDEX: usize = 1;
+const VALUE_INDEX: usize = 2;
+
+#[async_trait]
+impl<P> EventProcessor<P> for WriterUpdatedProcessor
+where
+    P: Provider + Send + Sync,
+{
+    fn event_key(&self) -> String {
+        "WriterUpdated".to_string()
+    }
+
+    fn validate(&self, event: &Event) -> bool {
+        if event.keys.len() > 1 {
+            info!(
+                "invalid keys for event {}: {}",
+                <WriterUpdatedProcessor as EventProcessor<P>>::event_key(self),
+                <WriterUpdatedProcessor as EventProcessor<P>>::event_keys_as_string(self, event),
+            );
+            return false;
+        }
+        true
+    }
+
+    async fn process(
+        &self,
+        _world: &WorldContractReader<P>,
+        db: &mut Sql,
+        _block: &BlockWithTxs,
+        transaction_receipt: &InvokeTransactionReceipt,
+        _event_id: &str,
+        event: &Event,
+    ) -> Result<(), Error> {
+        let model = event.data[MODEL_INDEX];
+        let system = event.data[SYSTEM_INDEX];
+        let granted = event.data[VALUE_INDEX] != FieldElement::ZERO;
+        info!("writer of model {:#x} updated: {:#x} ({})", model, system, granted);
+
+        db.set_permission(
+            PermissionKind::Writer,
+            model,
+            system,
+            granted,
+            transaction_receipt.transaction_hash,
+        );
+
+        Ok(())
+    }
+}
@@ -1,21 +1,27 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::convert::TryInto;
 use std::str::FromStr;
 
 use anyhow::{anyhow, Result};
 use chrono::Utc;
 use dojo_types::primitive::Primitive;
-use dojo_types::schema::Ty;
+use dojo_types::schema::{Enum, Ty};
+use dojo_world::contracts::model::ModelReader;
 use dojo_world::metadata::WorldMetadata;
 use sqlx::pool::PoolConnection;
 use sqlx::{Pool, Sqlite};
 use starknet::core::types::{Event, FieldElement, InvokeTransactionV1};
+use starknet::core::utils::get_selector_from_name;
 use starknet_crypto::poseidon_hash_many;
 
 use super::World;
 use crate::model::ModelSQLReader;
 use crate::query_queue::{Argument, QueryQueue};
 use crate::simple_broker::SimpleBroker;
-use crate::types::{Entity as EntityUpdated, Event as EventEmitted, Model as ModelRegistered};
+use crate::types::{
+    CustomEventEmitted, Entity as EntityUpdated, EntityDeleted, Event as EventEmitted,
+    Model as ModelRegistered, PermissionKind,
+};
 
 pub const FELT_DELIMITER: &str = "/";
 
@@ -26,12 +32,43 @@ mod test;
 #[derive(Debug, Clone)]
 pub struct Sql {
     world_address: FieldElement,
+    /// The prefix of the ids of the world's models, metadata and permissions, empty if they
+    /// aren't prefixed.
+    namespace: String,
     pool: Pool<Sqlite>,
     query_queue: QueryQueue,
+    /// The block being indexed, writes to entities and models are recorded in its undo log.
+    block_number: Option<u64>,
+    /// Whether the block being indexed is the pending block.
+    pending: bool,
+    /// The first block that can still be reorged, the writes of older blocks aren't recorded in
+    /// the undo log.
+    reorgable_from: u64,
+    /// The columns of the tables whose rows are recorded in the undo log.
+    table_columns: HashMap<String, Vec<String>>,
+    /// The models whose changes are recorded in their history table.
+    historical: HashSet<String>,
 }
 
 impl Sql {
     pub async fn new(pool: Pool<Sqlite>, world_address: FieldElement) -> Result<Self> {
+        Self::with_namespace(pool, world_address, "").await
+    }
+
+    /// Creates the store of a world whose models, metadata and permissions are prefixed with
+    /// `namespace` (ex: `season1_Position`), so that worlds registering the same models can be
+    /// indexed into the same database. A world keeps the namespace it was first indexed with.
+    pub async fn with_namespace(
+        pool: Pool<Sqlite>,
+        world_address: FieldElement,
+        namespace: &str,
+    ) -> Result<Self> {
+        if !namespace.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(anyhow!(
+                "Invalid namespace `{namespace}`, expected alphanumeric characters"
+            ));
+        }
+
         let mut query_queue = QueryQueue::new(pool.clone());
 
         query_queue.enqueue(
@@ -39,13 +76,52 @@ impl Sql {
             vec![Argument::FieldElement(world_address), Argument::Int(0)],
         );
         query_queue.enqueue(
-            "INSERT OR IGNORE INTO worlds (id, world_address) VALUES (?, ?)",
-            vec![Argument::FieldElement(world_address), Argument::FieldElement(world_address)],
+            "INSERT OR IGNORE INTO worlds (id, world_address, namespace) VALUES (?, ?, ?)",
+            vec![
+                Argument::FieldElement(world_address),
+                Argument::FieldElement(world_address),
+                Argument::String(namespace.to_string()),
+            ],
         );
 
         query_queue.execute_all().await?;
 
-        Ok(Self { pool, world_address, query_queue })
+        let (indexed_namespace,): (String,) =
+            sqlx::query_as("SELECT namespace FROM worlds WHERE id = ?")
+                .bind(format!("{world_address:#x}"))
+                .fetch_one(&pool)
+                .await?;
+        if indexed_namespace != namespace {
+            return Err(anyhow!(
+                "World {world_address:#x} is already indexed with the namespace \
+                 `{indexed_namespace}`"
+            ));
+        }
+
+        let historical =
+            sqlx::query_scalar("SELECT id FROM models WHERE world_address = ? AND historical")
+                .bind(format!("{world_address:#x}"))
+                .fetch_all(&pool)
+                .await?
+                .into_iter()
+                .collect();
+
+        Ok(Self {
+            pool,
+            world_address,
+            namespace: namespace.to_string(),
+            query_queue,
+            block_number: None,
+            pending: false,
+            reorgable_from: 0,
+            table_columns: HashMap::new(),
+            historical,
+        })
+    }
+
+    /// Returns the id of a resource of the world, prefixed with its namespace.
+    fn namespaced(&self, id: &str) -> String {
+        if self.namespace.is_empty() { id.to_string() } else { format!("{}_{id}", self.namespace) }
     }
 
     pub async fn head(&self) -> Result<u64> {
@@ -76,7 +152,7 @@ impl Sql {
 
     pub async fn register_model(
         &mut self,
-        model: Ty,
+        mut model: Ty,
         layout: Vec<FieldElement>,
         class_hash: FieldElement,
         packed_size: u32,
@@ -87,32 +163,73 @@ impl Sql {
             .map(|x| <FieldElement as TryInto<u8>>::try_into(*x).unwrap())
             .collect::<Vec<u8>>();
 
+        if let Ty::Struct(s) = &mut model {
+            s.name = self.namespaced(&s.name);
+        }
+
+        let world_address = format!("{:#x}", self.world_address);
+        let registered_by: Option<(Option<String>,)> =
+            sqlx::query_as("SELECT world_address FROM models WHERE id = ?")
+                .bind(model.name())
+                .fetch_optional(&self.pool)
+                .await?;
+        if let Some((Some(registered_by),)) = registered_by {
+            if registered_by != world_address {
+                return Err(anyhow!(
+                    "Model `{}` is already registered by the world {registered_by}, index this \
+                     world with a namespace",
+                    model.name()
+                ));
+            }
+        }
+
+        self.record_undo("models", &[("id", &model.name())], None).await?;
+        self.record_undo("model_members", &[("model_id", &model.name())], None).await?;
+
         let insert_models = "INSERT INTO models (id, name, class_hash, layout, packed_size, \
-                             unpacked_size) VALUES (?, ?, ?, ?, ?, ?) ON CONFLICT(id) DO UPDATE \
-                             SET class_hash=EXCLUDED.class_hash, layout=EXCLUDED.layout, \
-                             packed_size=EXCLUDED.packed_size, \
-                             unpacked_size=EXCLUDED.unpacked_size RETURNING *";
-        let model_registered: ModelRegistered = sqlx::query_as(insert_models)
-            .bind(model.name())
-            .bind(model.name())
-            .bind(format!("{class_hash:#x}"))
-            .bind(hex::encode(&layout_blob))
-            .bind(packed_size)
-            .bind(unpacked_size)
-            .fetch_one(&self.pool)
-            .await?;
+                             unpacked_size, world_address) VALUES (?, ?, ?, ?, ?, ?, ?) ON \
+                             CONFLICT(id) DO UPDATE SET class_hash=EXCLUDED.class_hash, \
+                             layout=EXCLUDED.layout, packed_size=EXCLUDED.packed_size, \
+                             unpacked_size=EXCLUDED.unpacked_size";
+        self.query_queue.enqueue(
+            insert_models,
+            vec![
+                Argument::String(model.name()),
+                Argument::String(model.name()),
+                Argument::FieldElement(class_hash),
+                Argument::String(hex::encode(&layout_blob)),
+                Argument::Int(packed_size.into()),
+                Argument::Int(unpacked_size.into()),
+                Argument::String(world_address),
+            ],
+        );
 
         let mut model_idx = 0_i64;
         self.build_register_queries_recursive(&model, vec![model.name()], &mut model_idx);
+        if self.historical.contains(&model.name()) {
+            self.build_history_query(&model);
+        }
         self.query_queue.execute_all().await?;
 
+        // The tables of the model may have changed.
+        let prefix = format!("{}$", model.name());
+        self.table_columns.retain(|table, _| table != &model.name() && !table.starts_with(&prefix));
+
+        let model_registered: ModelRegistered = sqlx::query_as("SELECT * FROM models WHERE id = ?")
+            .bind(model.name())
+            .fetch_one(&self.pool)
+            .await?;
         SimpleBroker::publish(model_registered);
 
         Ok(())
     }
 
-    pub async fn set_entity(&mut self, entity: Ty, event_id: &str) -> Result<()> {
-        let keys = if let Ty::Struct(s) = &entity {
+    /// Sets the values of an entity's model, where `entity` is the model's schema with the
+    /// entity's values, named after the model as registered by the world.
+    pub async fn set_entity(&mut self, mut entity: Ty, event_id: &str) -> Result<()> {
+        let keys = if let Ty::Struct(s) = &mut entity {
+            s.name = self.namespaced(&s.name);
+
             let mut keys = Vec::new();
             for m in s.keys() {
                 keys.extend(m.serialize()?);
@@ -123,47 +240,774 @@ impl Sql {
         };
 
         let entity_id = format!("{:#x}", poseidon_hash_many(&keys));
+        self.record_entity_undo(&entity.name(), &entity_id).await?;
+
+        let keys_str = felts_sql_string(&keys);
+        let insert_entities = "INSERT INTO entities (id, keys, event_id, pending) VALUES (?, ?, \
+                               ?, ?) ON CONFLICT(id) DO UPDATE SET updated_at=CURRENT_TIMESTAMP, \
+                               event_id=EXCLUDED.event_id, pending=EXCLUDED.pending";
+        self.query_queue.enqueue(
+            insert_entities,
+            vec![
+                Argument::String(entity_id.clone()),
+                Argument::String(keys_str.clone()),
+                Argument::String(event_id.to_string()),
+                Argument::Bool(self.pending),
+            ],
+        );
         self.query_queue.enqueue(
             "INSERT INTO entity_model (entity_id, model_id) VALUES (?, ?) ON CONFLICT(entity_id, \
              model_id) DO NOTHING",
             vec![Argument::String(entity_id.clone()), Argument::String(entity.name())],
         );
 
-        let keys_str = felts_sql_string(&keys);
-        let insert_entities = "INSERT INTO entities (id, keys, event_id) VALUES (?, ?, ?) ON \
-                               CONFLICT(id) DO UPDATE SET updated_at=CURRENT_TIMESTAMP, \
-                               event_id=EXCLUDED.event_id RETURNING *";
-        let entity_updated: EntityUpdated = sqlx::query_as(insert_entities)
+        let path = vec![entity.name()];
+        self.build_set_entity_queries_recursive(path, event_id, &entity_id, &entity);
+        if self.historical.contains(&entity.name()) {
+            self.record_history(&entity.name(), Some(&entity), &entity_id, &keys_str, event_id)?;
+        }
+        self.query_queue.execute_all().await?;
+
+        let entity_updated: EntityUpdated = sqlx::query_as("SELECT * FROM entities WHERE id = ?")
             .bind(&entity_id)
-            .bind(&keys_str)
+            .fetch_one(&self.pool)
+            .await?;
+        SimpleBroker::publish(entity_updated);
+
+        Ok(())
+    }
+
+    pub async fn delete_entity(
+        &mut self,
+        model: &str,
+        keys: &[FieldElement],
+        event_id: &str,
+    ) -> Result<()> {
+        let model = &self.namespaced(model);
+        let entity_id = format!("{:#x}", poseidon_hash_many(keys));
+        self.record_entity_undo(model, &entity_id).await?;
+
+        // Nested tables reference their parent, so they have to be emptied first.
+        let mut table_ids = self.model_tables(model).await?;
+        table_ids.sort_by_key(|id| std::cmp::Reverse(id.matches('$').count()));
+
+        for table_id in table_ids {
+            self.query_queue.enqueue(
+                format!("DELETE FROM [{table_id}] WHERE entity_id = ?"),
+                vec![Argument::String(entity_id.clone())],
+            );
+        }
+
+        self.query_queue.enqueue(
+            "DELETE FROM entity_model WHERE entity_id = ? AND model_id = ?",
+            vec![Argument::String(entity_id.clone()), Argument::String(model.to_string())],
+        );
+        if self.historical.contains(model) {
+            self.record_history(model, None, &entity_id, &felts_sql_string(keys), event_id)?;
+        }
+        self.query_queue.execute_all().await?;
+
+        let (remaining_models,): (i64,) =
+            sqlx::query_as("SELECT count(*) FROM entity_model WHERE entity_id = ?")
+                .bind(&entity_id)
+                .fetch_one(&self.pool)
+                .await?;
+
+        // The entity only exists as long as it has at least one model.
+        if remaining_models == 0 {
+            let entity: Option<EntityUpdated> =
+                sqlx::query_as("DELETE FROM entities WHERE id = ? RETURNING *")
+                    .bind(&entity_id)
+                    .fetch_optional(&self.pool)
+                    .await?;
+
+            if let Some(entity) = entity {
+                SimpleBroker::publish(EntityDeleted(entity));
+            }
+        } else {
+            let entity_updated: EntityUpdated = sqlx::query_as(
+                "UPDATE entities SET updated_at=CURRENT_TIMESTAMP, event_id=?, pending=? WHERE id \
+                 = ? RETURNING *",
+            )
             .bind(event_id)
+            .bind(self.pending)
+            .bind(&entity_id)
             .fetch_one(&self.pool)
             .await?;
 
-        let path = vec![entity.name()];
-        self.build_set_entity_queries_recursive(path, event_id, &entity_id, &entity);
+            SimpleBroker::publish(entity_updated);
+        }
+
+        Ok(())
+    }
+
+    /// Records the changes of the given models in their history table, from now on. The models
+    /// stay historical once they are, even if the indexer is restarted without them.
+    pub async fn set_historical(&mut self, models: &[String]) -> Result<()> {
+        for model in models {
+            let model_id = self.namespaced(model);
+            if !self.historical.insert(model_id.clone()) {
+                continue;
+            }
+
+            let registered: Option<(String,)> =
+                sqlx::query_as("SELECT id FROM models WHERE id = ? AND world_address = ?")
+                    .bind(&model_id)
+                    .bind(format!("{:#x}", self.world_address))
+                    .fetch_optional(&self.pool)
+                    .await?;
+            if registered.is_some() {
+                let schema = self.model(model).await?.schema().await?;
+                self.build_history_query(&schema);
+            }
+        }
+
         self.query_queue.execute_all().await?;
 
-        SimpleBroker::publish(entity_updated);
+        Ok(())
+    }
+
+    /// Appends the values of an entity's model, or its deletion if `entity` is `None`, to the
+    /// history of the model.
+    fn record_history(
+        &mut self,
+        model: &str,
+        entity: Option<&Ty>,
+        entity_id: &str,
+        keys: &str,
+        event_id: &str,
+    ) -> Result<()> {
+        // The transaction hash is the one of the event, stored before the entity.
+        let mut columns =
+            vec!["entity_id", "event_id", "block_number", "transaction_hash", "deleted", "keys"]
+                .into_iter()
+                .map(String::from)
+                .collect::<Vec<_>>();
+        let mut placeholders =
+            vec!["?", "?", "?", "(SELECT transaction_hash FROM events WHERE id = ?)", "?", "?"];
+        let mut arguments = vec![
+            Argument::String(entity_id.to_string()),
+            Argument::String(event_id.to_string()),
+            self.block_number
+                .map(|number| Argument::Int(number.try_into().expect("doesn't fit in i64")))
+                .unwrap_or(Argument::Null),
+            Argument::String(event_id.to_string()),
+            Argument::Bool(entity.is_none()),
+            Argument::String(keys.to_string()),
+        ];
+
+        if let Some(entity) = entity {
+            columns.push("data".to_string());
+            placeholders.push("?");
+            arguments.push(Argument::String(felts_sql_string(&entity.serialize()?)));
+
+            if let Ty::Struct(s) = entity {
+                for member in s.children.iter() {
+                    let value = match &member.ty {
+                        Ty::Primitive(ty) => ty.to_sql_value()?,
+                        Ty::Enum(e) => e.to_sql_value()?,
+                        _ => continue,
+                    };
+                    columns.push(format!("external_{}", member.name));
+                    placeholders.push("?");
+                    arguments.push(Argument::String(value));
+                }
+            }
+        }
+
+        self.query_queue.enqueue(
+            format!(
+                "INSERT INTO [{model}@history] ({}) VALUES ({})",
+                columns.join(","),
+                placeholders.join(",")
+            ),
+            arguments,
+        );
+
+        Ok(())
+    }
+
+    /// Returns the ids of the tables storing the members of a model, including its nested types.
+    async fn model_tables(&self, model: &str) -> Result<Vec<String>> {
+        let table_ids =
+            sqlx::query_scalar("SELECT DISTINCT id FROM model_members WHERE model_id = ?")
+                .bind(model)
+                .fetch_all(&self.pool)
+                .await?;
+
+        Ok(table_ids)
+    }
+
+    /// Registers a custom event that the world's systems emit with `emit!`, where `event` is a
+    /// struct of primitives and enums named after the event. The events are stored in the
+    /// `{name}Event` table, prefixed with the world's namespace.
+    pub async fn register_custom_event(&mut self, event: &Ty) -> Result<()> {
+        let Ty::Struct(s) = event else {
+            return Err(anyhow!("Custom event is not a struct"));
+        };
+        let table_id = self.custom_event_table(&s.name);
+        let world_address = format!("{:#x}", self.world_address);
+
+        let registered_by: Option<(String,)> =
+            sqlx::query_as("SELECT world_address FROM custom_events WHERE id = ?")
+                .bind(&table_id)
+                .fetch_optional(&self.pool)
+                .await?;
+        if let Some((registered_by,)) = registered_by {
+            if registered_by != world_address {
+                return Err(anyhow!(
+                    "Custom event `{table_id}` is already registered by the world \
+                     {registered_by}, index this world with a namespace"
+                ));
+            }
+
+            let members: Vec<(String, String)> = sqlx::query_as(
+                "SELECT name, type FROM custom_event_members WHERE custom_event_id = ? ORDER BY \
+                 member_idx",
+            )
+            .bind(&table_id)
+            .fetch_all(&self.pool)
+            .await?;
+            if members
+                != s.children.iter().map(|m| (m.name.clone(), m.ty.name())).collect::<Vec<_>>()
+            {
+                return Err(anyhow!(
+                    "Custom event `{table_id}` doesn't match the members it was registered with"
+                ));
+            }
+
+            return Ok(());
+        }
+
+        self.query_queue.enqueue(
+            "INSERT INTO custom_events (id, name, selector, world_address) VALUES (?, ?, ?, ?)",
+            vec![
+                Argument::String(table_id.clone()),
+                Argument::String(s.name.clone()),
+                Argument::FieldElement(get_selector_from_name(&s.name)?),
+                Argument::String(world_address),
+            ],
+        );
+
+        let mut create_table_query =
+            format!("CREATE TABLE [{table_id}] (event_id TEXT NOT NULL PRIMARY KEY, ");
+        let mut indices = Vec::new();
+
+        for (member_idx, member) in s.children.iter().enumerate() {
+            let name = &member.name;
+            let column = external_column(name, &member.ty).ok_or_else(|| {
+                anyhow!("Unsupported type `{}` of custom event member `{name}`", member.ty.name())
+            })?;
+            create_table_query.push_str(&column);
+            indices.push(format!(
+                "CREATE INDEX idx_{table_id}_{name} ON [{table_id}] (external_{name});"
+            ));
+
+            let options = match &member.ty {
+                Ty::Enum(e) => Argument::String(enum_options(e)),
+                _ => Argument::Null,
+            };
+            self.query_queue.enqueue(
+                "INSERT INTO custom_event_members (custom_event_id, member_idx, name, type, \
+                 type_enum, enum_options, key) VALUES (?, ?, ?, ?, ?, ?, ?)",
+                vec![
+                    Argument::String(table_id.clone()),
+                    Argument::Int(member_idx as i64),
+                    Argument::String(name.clone()),
+                    Argument::String(member.ty.name()),
+                    Argument::String(member.ty.as_ref().into()),
+                    options,
+                    Argument::Bool(member.key),
+                ],
+            );
+        }
+
+        create_table_query.push_str(
+            "created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP, FOREIGN KEY (event_id) \
+             REFERENCES events(id));",
+        );
+        self.query_queue.enqueue(create_table_query, vec![]);
+        indices.into_iter().for_each(|s| self.query_queue.enqueue(s, vec![]));
+
+        self.query_queue.execute_all().await?;
+
+        Ok(())
+    }
+
+    /// Stores a custom event emitted by the world, where `event` is the schema registered with
+    /// [`Sql::register_custom_event`] with the event's values.
+    pub async fn set_custom_event(&mut self, event: &Ty, event_id: &str) -> Result<()> {
+        let Ty::Struct(s) = event else {
+            return Err(anyhow!("Custom event is not a struct"));
+        };
+        let table_id = self.custom_event_table(&s.name);
+        self.record_undo(&table_id, &[("event_id", event_id)], None).await?;
+
+        let mut columns = vec!["event_id".to_string()];
+        let mut arguments = vec![Argument::String(event_id.to_string())];
+        for member in s.children.iter() {
+            let value = match &member.ty {
+                Ty::Primitive(ty) => ty.to_sql_value()?,
+                Ty::Enum(e) => e.to_sql_value()?,
+                ty => return Err(anyhow!("Unsupported type `{}` of custom event", ty.name())),
+            };
+            columns.push(format!("external_{}", member.name));
+            arguments.push(Argument::String(value));
+        }
+
+        let placeholders: Vec<&str> = arguments.iter().map(|_| "?").collect();
+        self.query_queue.enqueue(
+            format!(
+                "INSERT OR REPLACE INTO [{table_id}] ({}) VALUES ({})",
+                columns.join(","),
+                placeholders.join(",")
+            ),
+            arguments,
+        );
+        self.query_queue.execute_all().await?;
+
+        SimpleBroker::publish(CustomEventEmitted { id: table_id, event_id: event_id.to_string() });
+
+        Ok(())
+    }
+
+    /// Returns the id of the table storing a custom event, which is also its GraphQL type name.
+    fn custom_event_table(&self, name: &str) -> String {
+        self.namespaced(&format!("{name}Event"))
+    }
+
+    /// Sets the block whose events are being indexed. Until the next block is set, the writes to
+    /// entities and models are recorded in the undo log of this block, so they can be reverted
+    /// if the block gets reorged out of the chain.
+    pub fn set_block(&mut self, number: u64, hash: FieldElement, parent_hash: FieldElement) {
+        self.block_number = Some(number);
+        self.pending = false;
+
+        self.query_queue.enqueue(
+            "INSERT OR REPLACE INTO blocks (world_address, number, hash, parent_hash) VALUES (?, \
+             ?, ?, ?)",
+            vec![
+                Argument::FieldElement(self.world_address),
+                Argument::Int(number.try_into().expect("doesn't fit in i64")),
+                Argument::FieldElement(hash),
+                Argument::FieldElement(parent_hash),
+            ],
+        );
+    }
+
+    /// Sets the first block that can still be reorged out of the chain. The writes of older blocks
+    /// aren't recorded in the undo log, as it is pruned of them right away.
+    pub fn set_reorgable_from(&mut self, number: u64) {
+        self.reorgable_from = number;
+    }
+
+    /// Returns the block whose undo log records the writes, if the block being indexed can still
+    /// be reorged.
+    fn undo_block(&self) -> Option<i64> {
+        let block_number = self.block_number.filter(|number| *number >= self.reorgable_from)?;
+        Some(block_number.try_into().expect("doesn't fit in i64"))
+    }
+
+    /// Sets the pending block, on top of the block `number - 1`, as the block being indexed. The
+    /// entities it writes are flagged as pending, and its writes are discarded by reverting to
+    /// the head once it is accepted.
+    pub fn set_pending_block(&mut self, number: u64) {
+        self.block_number = Some(number);
+        self.pending = true;
+    }
+
+    /// Returns the hash indexed for the given block, if it is still kept.
+    pub async fn block_hash(&self, number: u64) -> Result<Option<FieldElement>> {
+        let number: i64 = number.try_into().expect("doesn't fit in i64");
+        let hash: Option<(String,)> =
+            sqlx::query_as("SELECT hash FROM blocks WHERE world_address = ? AND number = ?")
+                .bind(format!("{:#x}", self.world_address))
+                .bind(number)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(hash.map(|(hash,)| FieldElement::from_str(&hash)).transpose()?)
+    }
+
+    /// Returns whether anything was indexed past the given block, like the transactions of the
+    /// pending block.
+    pub async fn indexed_past(&self, number: u64) -> Result<bool> {
+        let block_number: i64 = number.try_into().expect("doesn't fit in i64");
+        let (indexed,): (bool,) = sqlx::query_as(
+            "SELECT EXISTS (SELECT 1 FROM undo_log WHERE world_address = ? AND block_number > ?) \
+             OR EXISTS (SELECT 1 FROM events WHERE world_address = ? AND id >= ?)",
+        )
+        .bind(format!("{:#x}", self.world_address))
+        .bind(block_number)
+        .bind(format!("{:#x}", self.world_address))
+        .bind(format!("0x{:064x}", number + 1))
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(indexed)
+    }
+
+    /// Returns the indexed blocks up to `to`, from the most recent one.
+    pub async fn indexed_blocks(&self, to: u64) -> Result<Vec<(u64, FieldElement)>> {
+        let to: i64 = to.try_into().expect("doesn't fit in i64");
+        let blocks: Vec<(i64, String)> = sqlx::query_as(
+            "SELECT number, hash FROM blocks WHERE world_address = ? AND number <= ? ORDER BY \
+             number DESC",
+        )
+        .bind(format!("{:#x}", self.world_address))
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?;
+
+        blocks
+            .into_iter()
+            .map(|(number, hash)| -> Result<_> {
+                Ok((number.try_into()?, FieldElement::from_str(&hash)?))
+            })
+            .collect()
+    }
+
+    /// Forgets the hashes and the undo log of the blocks before `number`, which can then no
+    /// longer be reverted.
+    pub fn prune_blocks(&mut self, number: u64) {
+        let world_address = Argument::FieldElement(self.world_address);
+        let number = Argument::Int(number.try_into().expect("doesn't fit in i64"));
+
+        self.query_queue.enqueue(
+            "DELETE FROM undo_log WHERE world_address = ? AND block_number < ?",
+            vec![world_address.clone(), number.clone()],
+        );
+        self.query_queue.enqueue(
+            "DELETE FROM blocks WHERE world_address = ? AND number < ?",
+            vec![world_address, number],
+        );
+    }
+
+    /// Reverts the entities, models, events and transactions indexed after the given block, and
+    /// sets it as the head. Subscribers are notified of the entities that got reverted.
+    ///
+    /// Metadata, permissions and contracts are not reverted, they are overwritten once the events
+    /// setting them are indexed again.
+    pub async fn revert_to(&mut self, number: u64) -> Result<()> {
+        let block_number: i64 = number.try_into().expect("doesn't fit in i64");
+        let world_address = format!("{:#x}", self.world_address);
+        let mut tx = self.pool.begin().await?;
+
+        // Rows are restored one table at a time, so references are only checked on commit.
+        sqlx::query("PRAGMA defer_foreign_keys = ON").execute(&mut *tx).await?;
+
+        let undo_log: Vec<(String, Option<String>)> = sqlx::query_as(
+            "SELECT statement, entity_id FROM undo_log WHERE world_address = ? AND block_number > \
+             ? ORDER BY id DESC",
+        )
+        .bind(&world_address)
+        .bind(block_number)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let entity_ids: BTreeSet<String> =
+            undo_log.iter().filter_map(|(_, entity_id)| entity_id.clone()).collect();
+        let mut reverted_entities = Vec::with_capacity(entity_ids.len());
+        for entity_id in entity_ids {
+            let entity: Option<EntityUpdated> =
+                sqlx::query_as("SELECT * FROM entities WHERE id = ?")
+                    .bind(&entity_id)
+                    .fetch_optional(&mut *tx)
+                    .await?;
+            reverted_entities.push((entity_id, entity));
+        }
+
+        // Listed before the models registered in the reverted blocks are removed.
+        let historical: Vec<String> =
+            sqlx::query_scalar("SELECT id FROM models WHERE world_address = ? AND historical")
+                .bind(&world_address)
+                .fetch_all(&mut *tx)
+                .await?;
+
+        for (statement, _) in &undo_log {
+            sqlx::query(statement).execute(&mut *tx).await?;
+        }
+
+        // The history of the reverted blocks is dropped.
+        for model in historical {
+            sqlx::query(&format!("DELETE FROM [{model}@history] WHERE block_number > ?"))
+                .bind(block_number)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        // Entities are shared by the worlds setting models on them, so the ones that still have
+        // models of another world are kept.
+        for entity in reverted_entities.iter().filter_map(|(_, entity)| entity.as_ref()) {
+            sqlx::query(
+                "INSERT OR IGNORE INTO entities (id, keys, event_id, pending, created_at, \
+                 updated_at) SELECT ?, ?, ?, ?, ?, ? WHERE EXISTS (SELECT 1 FROM entity_model \
+                 WHERE entity_id = ?)",
+            )
+            .bind(&entity.id)
+            .bind(&entity.keys)
+            .bind(&entity.event_id)
+            .bind(entity.pending)
+            .bind(entity.created_at)
+            .bind(entity.updated_at)
+            .bind(&entity.id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        // Events and transactions are identified by the block they are in. Transactions are kept
+        // as long as they have events of another world.
+        let first_reverted = format!("0x{:064x}", number + 1);
+        sqlx::query("DELETE FROM events WHERE world_address = ? AND id >= ?")
+            .bind(&world_address)
+            .bind(&first_reverted)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
+            "DELETE FROM transactions WHERE id >= ? AND transaction_hash NOT IN (SELECT \
+             transaction_hash FROM events WHERE id >= ?)",
+        )
+        .bind(&first_reverted)
+        .bind(&first_reverted)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("DELETE FROM undo_log WHERE world_address = ? AND block_number > ?")
+            .bind(&world_address)
+            .bind(block_number)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM blocks WHERE world_address = ? AND number > ?")
+            .bind(&world_address)
+            .bind(block_number)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("UPDATE indexers SET head = ? WHERE id = ?")
+            .bind(block_number)
+            .bind(&world_address)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        self.block_number = None;
+        self.pending = false;
+
+        for (entity_id, entity) in reverted_entities {
+            let reverted: Option<EntityUpdated> =
+                sqlx::query_as("SELECT * FROM entities WHERE id = ?")
+                    .bind(&entity_id)
+                    .fetch_optional(&self.pool)
+                    .await?;
+
+            match (reverted, entity) {
+                (Some(reverted), _) => SimpleBroker::publish(reverted),
+                (None, Some(entity)) => SimpleBroker::publish(EntityDeleted(entity)),
+                (None, None) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Records the writes to an entity's model in the undo log of the current block.
+    async fn record_entity_undo(&mut self, model: &str, entity_id: &str) -> Result<()> {
+        if self.undo_block().is_none() {
+            return Ok(());
+        }
+
+        for table_id in self.model_tables(model).await? {
+            self.record_undo(&table_id, &[("entity_id", entity_id)], Some(entity_id)).await?;
+        }
+        let entity_model = [("entity_id", entity_id), ("model_id", model)];
+        self.record_undo("entity_model", &entity_model, Some(entity_id)).await?;
+        self.record_undo("entities", &[("id", entity_id)], Some(entity_id)).await?;
+
+        Ok(())
+    }
+
+    /// Records, in the undo log of the current block, the statements restoring the rows of `table`
+    /// whose columns have the given values to their current state. The log is replayed from its
+    /// last entry, so the rows are deleted before their current state is inserted back.
+    ///
+    /// The statements are queued, so the rows are recorded in the transaction writing them.
+    async fn record_undo(
+        &mut self,
+        table: &str,
+        filter: &[(&str, &str)],
+        entity_id: Option<&str>,
+    ) -> Result<()> {
+        let Some(block_number) = self.undo_block() else {
+            return Ok(());
+        };
+
+        let columns = self.table_columns(table).await?;
+        let names = columns.iter().map(|c| format!("[{c}]")).collect::<Vec<_>>().join(", ");
+        let values = columns
+            .iter()
+            .map(|c| format!("quote([{c}])"))
+            .collect::<Vec<_>>()
+            .join(" || ', ' || ");
+        let condition =
+            filter.iter().map(|(c, _)| format!("[{c}] = ?")).collect::<Vec<_>>().join(" AND ");
+        let delete_condition = filter
+            .iter()
+            .map(|(c, v)| format!("[{c}] = '{}'", v.replace('\'', "''")))
+            .collect::<Vec<_>>()
+            .join(" AND ");
+
+        let entity_id =
+            entity_id.map(|id| Argument::String(id.to_string())).unwrap_or(Argument::Null);
+        let log_arguments = vec![
+            Argument::FieldElement(self.world_address),
+            Argument::Int(block_number),
+            entity_id,
+        ];
+
+        let mut arguments = log_arguments.clone();
+        arguments.extend(filter.iter().map(|(_, value)| Argument::String(value.to_string())));
+        self.query_queue.enqueue(
+            format!(
+                "INSERT INTO undo_log (world_address, block_number, entity_id, statement) SELECT \
+                 ?, ?, ?, 'INSERT OR REPLACE INTO [{table}] ({names}) VALUES (' || {values} || \
+                 ')' FROM [{table}] WHERE {condition}"
+            ),
+            arguments,
+        );
+
+        let mut arguments = log_arguments;
+        arguments.push(Argument::String(format!("DELETE FROM [{table}] WHERE {delete_condition}")));
+        self.query_queue.enqueue(
+            "INSERT INTO undo_log (world_address, block_number, entity_id, statement) VALUES (?, \
+             ?, ?, ?)",
+            arguments,
+        );
 
         Ok(())
     }
 
-    pub fn delete_entity(&mut self, model: String, key: FieldElement) {
-        let model = Argument::String(model);
-        let id = Argument::FieldElement(key);
+    /// Returns the columns of a table, which are only queried the first time.
+    async fn table_columns(&mut self, table: &str) -> Result<Vec<String>> {
+        if let Some(columns) = self.table_columns.get(table) {
+            return Ok(columns.clone());
+        }
+
+        let columns: Vec<String> = sqlx::query_scalar("SELECT name FROM pragma_table_info(?)")
+            .bind(table)
+            .fetch_all(&self.pool)
+            .await?;
+        if columns.is_empty() {
+            return Err(anyhow!("Table `{table}` doesn't exist"));
+        }
+
+        self.table_columns.insert(table.to_string(), columns.clone());
+        Ok(columns)
+    }
+
+    pub fn set_permission(
+        &mut self,
+        kind: PermissionKind,
+        resource: FieldElement,
+        address: FieldElement,
+        granted: bool,
+        transaction_hash: FieldElement,
+    ) {
+        let id = self.namespaced(&format!("{kind}:{resource:#x}:{address:#x}"));
+
+        self.query_queue.enqueue(
+            "INSERT INTO permissions (id, kind, resource, address, granted, transaction_hash, \
+             world_address) VALUES (?, ?, ?, ?, ?, ?, ?) ON CONFLICT(id) DO UPDATE SET \
+             granted=EXCLUDED.granted, transaction_hash=EXCLUDED.transaction_hash, \
+             updated_at=CURRENT_TIMESTAMP",
+            vec![
+                Argument::String(id),
+                Argument::String(kind.to_string()),
+                Argument::FieldElement(resource),
+                Argument::FieldElement(address),
+                Argument::Bool(granted),
+                Argument::FieldElement(transaction_hash),
+                Argument::FieldElement(self.world_address),
+            ],
+        );
+    }
 
-        self.query_queue.enqueue("DELETE FROM ? WHERE id = ?", vec![model, id]);
+    /// Records a contract deployed by the world. `timestamp` is the one of the block the contract
+    /// was deployed in.
+    pub fn set_contract_deployed(
+        &mut self,
+        address: FieldElement,
+        class_hash: FieldElement,
+        salt: FieldElement,
+        timestamp: u64,
+        transaction_hash: FieldElement,
+    ) {
+        self.query_queue.enqueue(
+            "INSERT INTO contracts (id, class_hash, salt, transaction_hash, deployed_at) VALUES \
+             (?, ?, ?, ?, datetime(?, 'unixepoch')) ON CONFLICT(id) DO UPDATE SET \
+             class_hash=EXCLUDED.class_hash, salt=EXCLUDED.salt, \
+             transaction_hash=EXCLUDED.transaction_hash, deployed_at=EXCLUDED.deployed_at, \
+             updated_at=CURRENT_TIMESTAMP",
+            vec![
+                Argument::FieldElement(address),
+                Argument::FieldElement(class_hash),
+                Argument::FieldElement(salt),
+                Argument::FieldElement(transaction_hash),
+                Argument::Int(timestamp.try_into().expect("doesn't fit in i64")),
+            ],
+        );
+    }
+
+    /// Records the upgrade of a contract to a new class. `timestamp` is the one of the block the
+    /// contract was upgraded in.
+    pub fn set_contract_upgraded(
+        &mut self,
+        address: FieldElement,
+        class_hash: FieldElement,
+        timestamp: u64,
+        transaction_hash: FieldElement,
+    ) {
+        self.query_queue.enqueue(
+            "INSERT INTO contracts (id, class_hash, transaction_hash, upgraded_at) VALUES (?, ?, \
+             ?, datetime(?, 'unixepoch')) ON CONFLICT(id) DO UPDATE SET \
+             class_hash=EXCLUDED.class_hash, transaction_hash=EXCLUDED.transaction_hash, \
+             upgraded_at=EXCLUDED.upgraded_at, updated_at=CURRENT_TIMESTAMP",
+            vec![
+                Argument::FieldElement(address),
+                Argument::FieldElement(class_hash),
+                Argument::FieldElement(transaction_hash),
+                Argument::Int(timestamp.try_into().expect("doesn't fit in i64")),
+            ],
+        );
+    }
+
+    pub fn set_world_upgraded(
+        &mut self,
+        class_hash: FieldElement,
+        timestamp: u64,
+        transaction_hash: FieldElement,
+    ) {
+        self.query_queue.enqueue(
+            "UPDATE worlds SET world_class_hash = ? WHERE id = ?",
+            vec![Argument::FieldElement(class_hash), Argument::FieldElement(self.world_address)],
+        );
+        self.set_contract_upgraded(self.world_address, class_hash, timestamp, transaction_hash);
+    }
+
+    pub fn set_executor(&mut self, address: FieldElement) {
+        self.query_queue.enqueue(
+            "UPDATE worlds SET executor_address = ? WHERE id = ?",
+            vec![Argument::FieldElement(address), Argument::FieldElement(self.world_address)],
+        );
     }
 
     pub fn set_metadata(&mut self, resource: &FieldElement, uri: &str) {
-        let resource = Argument::FieldElement(*resource);
+        let id = Argument::String(self.namespaced(&format!("{resource:#x}")));
         let uri = Argument::String(uri.to_string());
+        let world_address = Argument::FieldElement(self.world_address);
 
         self.query_queue.enqueue(
-            "INSERT INTO metadata (id, uri) VALUES (?, ?) ON CONFLICT(id) DO UPDATE SET \
-             id=excluded.id, updated_at=CURRENT_TIMESTAMP",
-            vec![resource, uri],
+            "INSERT INTO metadata (id, uri, world_address) VALUES (?, ?, ?) ON CONFLICT(id) DO \
+             UPDATE SET id=excluded.id, updated_at=CURRENT_TIMESTAMP",
+            vec![id, uri, world_address],
         );
     }
 
@@ -177,13 +1021,14 @@ impl Sql {
     ) -> Result<()> {
         let json = serde_json::to_string(metadata).unwrap(); // safe unwrap
 
-        let mut columns = vec!["id", "uri", "json"];
+        let mut columns = vec!["id", "uri", "json", "world_address"];
         let mut update =
             vec!["id=excluded.id", "json=excluded.json", "updated_at=CURRENT_TIMESTAMP"];
         let mut arguments = vec![
-            Argument::FieldElement(*resource),
+            Argument::String(self.namespaced(&format!("{resource:#x}"))),
             Argument::String(uri.to_string()),
             Argument::String(json),
+            Argument::FieldElement(self.world_address),
         ];
 
         if let Some(icon) = icon_img {
@@ -212,8 +1057,9 @@ impl Sql {
         Ok(())
     }
 
+    /// Returns the reader of a model registered by the world.
     pub async fn model(&self, model: &str) -> Result<ModelSQLReader> {
-        let reader = ModelSQLReader::new(model, self.pool.clone()).await?;
+        let reader = ModelSQLReader::new(&self.namespaced(model), self.pool.clone()).await?;
         Ok(reader)
     }
 
@@ -255,10 +1101,12 @@ impl Sql {
         let keys = Argument::String(felts_sql_string(&event.keys));
         let data = Argument::String(felts_sql_string(&event.data));
         let hash = Argument::FieldElement(transaction_hash);
+        let world_address = Argument::FieldElement(self.world_address);
 
         self.query_queue.enqueue(
-            "INSERT OR IGNORE INTO events (id, keys, data, transaction_hash) VALUES (?, ?, ?, ?)",
-            vec![id, keys, data, hash],
+            "INSERT OR IGNORE INTO events (id, keys, data, transaction_hash, world_address) \
+             VALUES (?, ?, ?, ?, ?)",
+            vec![id, keys, data, hash, world_address],
         );
 
         SimpleBroker::publish(EventEmitted {
@@ -378,38 +1226,16 @@ impl Sql {
                 let name = member.name.clone();
                 let mut options = None; // TEMP: doesnt support complex enums yet
 
-                if let Ok(cairo_type) = Primitive::from_str(&member.ty.name()) {
-                    create_table_query
-                        .push_str(&format!("external_{name} {}, ", cairo_type.to_sql_type()));
-                    indices.push(format!(
-                        "CREATE INDEX IF NOT EXISTS idx_{table_id}_{name} ON [{table_id}] \
-                         (external_{name});"
-                    ));
-                } else if let Ty::Enum(e) = &member.ty {
-                    let all_options = e
-                        .options
-                        .iter()
-                        .map(|c| format!("'{}'", c.name))
-                        .collect::<Vec<_>>()
-                        .join(", ");
-
-                    create_table_query.push_str(&format!(
-                        "external_{name} TEXT CHECK(external_{name} IN ({all_options})) NOT NULL, ",
-                    ));
-
+                if let Some(column) = external_column(&name, &member.ty) {
+                    create_table_query.push_str(&column);
                     indices.push(format!(
                         "CREATE INDEX IF NOT EXISTS idx_{table_id}_{name} ON [{table_id}] \
                          (external_{name});"
                     ));
+                }
 
-                    options = Some(Argument::String(
-                        e.options
-                            .iter()
-                            .map(|c| c.name.clone())
-                            .collect::<Vec<_>>()
-                            .join(",")
-                            .to_string(),
-                    ));
+                if let Ty::Enum(e) = &member.ty {
+                    options = Some(Argument::String(enum_options(e)));
                 }
 
                 let statement = "INSERT OR IGNORE INTO model_members (id, model_id, model_idx, \
@@ -449,6 +1275,44 @@ impl Sql {
         });
     }
 
+    /// Creates the history table of a model, whose rows are the successive values of its
+    /// entities. The primitives and enums of the model are stored in their own columns, and all
+    /// of its values serialized in `data`. The `@` keeps it apart from the tables of nested types.
+    fn build_history_query(&mut self, model: &Ty) {
+        let table_id = format!("{}@history", model.name());
+        let mut create_table_query = format!(
+            "CREATE TABLE IF NOT EXISTS [{table_id}] (id INTEGER PRIMARY KEY AUTOINCREMENT, \
+             entity_id TEXT NOT NULL, event_id TEXT NOT NULL, block_number INTEGER, \
+             transaction_hash TEXT, deleted BOOLEAN NOT NULL DEFAULT FALSE, keys TEXT, data TEXT, "
+        );
+
+        if let Ty::Struct(s) = model {
+            for member in s.children.iter() {
+                let name = &member.name;
+                if let Ok(cairo_type) = Primitive::from_str(&member.ty.name()) {
+                    create_table_query
+                        .push_str(&format!("external_{name} {}, ", cairo_type.to_sql_type()));
+                } else if let Ty::Enum(_) = &member.ty {
+                    create_table_query.push_str(&format!("external_{name} TEXT, "));
+                }
+            }
+        }
+
+        create_table_query.push_str("created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP);");
+        self.query_queue.enqueue(create_table_query, vec![]);
+        self.query_queue.enqueue(
+            format!(
+                "CREATE INDEX IF NOT EXISTS [idx_{table_id}_entity_id] ON [{table_id}] \
+                 (entity_id, block_number);"
+            ),
+            vec![],
+        );
+        self.query_queue.enqueue(
+            "UPDATE models SET historical = TRUE WHERE id = ?",
+            vec![Argument::String(model.name())],
+        );
+    }
+
     pub async fn execute(&mut self) -> Result<()> {
         self.query_queue.execute_all().await?;
 
@@ -456,6 +1320,25 @@ impl Sql {
     }
 }
 
+/// Returns the definition of the column storing a primitive or enum member, `None` for the
+/// other types.
+fn external_column(name: &str, ty: &Ty) -> Option<String> {
+    if let Ok(cairo_type) = Primitive::from_str(&ty.name()) {
+        Some(format!("external_{name} {}, ", cairo_type.to_sql_type()))
+    } else if let Ty::Enum(e) = ty {
+        let all_options =
+            e.options.iter().map(|c| format!("'{}'", c.name)).collect::<Vec<_>>().join(", ");
+
+        Some(format!("external_{name} TEXT CHECK(external_{name} IN ({all_options})) NOT NULL, "))
+    } else {
+        None
+    }
+}
+
+fn enum_options(e: &Enum) -> String {
+    e.options.iter().map(|c| c.name.clone()).collect::<Vec<_>>().join(",")
+}
+
 fn felts_sql_string(felts: &[FieldElement]) -> String {
     felts.iter().map(|k| format!("{:#x}", k)).collect::<Vec<String>>().join(FELT_DELIMITER)
         + FELT_DELIMITER
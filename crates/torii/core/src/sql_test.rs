@@ -5,20 +5,26 @@ use dojo_test_utils::migration::prepare_migration;
 use dojo_test_utils::sequencer::{
     get_default_test_starknet_config, SequencerConfig, TestSequencer,
 };
+use dojo_types::primitive::Primitive;
+use dojo_types::schema::{Enum, EnumOption, Member, Struct, Ty};
+use dojo_world::contracts::model::ModelReader;
 use dojo_world::contracts::world::WorldContractReader;
 use dojo_world::migration::strategy::MigrationStrategy;
 use scarb::ops;
 use sozo::ops::migration::execute_strategy;
 use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
 use starknet::core::types::{BlockId, BlockTag, Event, FieldElement};
+use starknet::core::utils::get_selector_from_name;
 use starknet::providers::jsonrpc::HttpTransport;
 use starknet::providers::{JsonRpcClient, Provider};
 use tokio::sync::broadcast;
 
-use crate::engine::{Engine, EngineConfig, Processors};
+use crate::engine::{Engine, EngineConfig, Processors, SyncMode};
 use crate::processors::register_model::RegisterModelProcessor;
+use crate::processors::store_del_record::StoreDelRecordProcessor;
 use crate::processors::store_set_record::StoreSetRecordProcessor;
 use crate::sql::Sql;
+use crate::types::PermissionKind;
 
 pub async fn bootstrap_engine<P>(
     world: WorldContractReader<P>,
@@ -26,6 +32,7 @@ pub async fn bootstrap_engine<P>(
     provider: P,
     migration: MigrationStrategy,
     sequencer: TestSequencer,
+    config: EngineConfig,
 ) -> Result<Engine<'_, P>, Box<dyn std::error::Error>>
 where
     P: Provider + Send + Sync,
@@ -44,10 +51,14 @@ where
         db,
         provider,
         Processors {
-            event: vec![Box::new(RegisterModelProcessor), Box::new(StoreSetRecordProcessor)],
+            event: vec![
+                Box::new(RegisterModelProcessor),
+                Box::new(StoreSetRecordProcessor),
+                Box::new(StoreDelRecordProcessor),
+            ],
             ..Processors::default()
         },
-        EngineConfig::default(),
+        config,
         shutdown_tx,
         None,
     );
@@ -71,7 +82,9 @@ async fn test_load_from_remote() {
     let world = WorldContractReader::new(migration.world_address().unwrap(), &provider);
 
     let mut db = Sql::new(pool.clone(), migration.world_address().unwrap()).await.unwrap();
-    let _ = bootstrap_engine(world, &mut db, &provider, migration, sequencer).await;
+    let _ =
+        bootstrap_engine(world, &mut db, &provider, migration, sequencer, EngineConfig::default())
+            .await;
 
     let models = sqlx::query("SELECT * FROM models").fetch_all(&pool).await.unwrap();
     assert_eq!(models.len(), 2);
@@ -122,3 +135,516 @@ async fn test_load_from_remote() {
     assert_eq!(data, format!("{:#x}/{:#x}/", FieldElement::TWO, FieldElement::THREE));
     assert_eq!(tx_hash, format!("{:#x}", FieldElement::THREE))
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_load_from_remote_with_events() {
+    let options =
+        SqliteConnectOptions::from_str("sqlite::memory:").unwrap().create_if_missing(true);
+    let pool = SqlitePoolOptions::new().max_connections(5).connect_with(options).await.unwrap();
+    sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+    let migration =
+        prepare_migration("../../../examples/spawn-and-move/target/dev".into()).unwrap();
+    let sequencer =
+        TestSequencer::start(SequencerConfig::default(), get_default_test_starknet_config()).await;
+    let provider = JsonRpcClient::new(HttpTransport::new(sequencer.url()));
+    let world = WorldContractReader::new(migration.world_address().unwrap(), &provider);
+
+    let mut db = Sql::new(pool.clone(), migration.world_address().unwrap()).await.unwrap();
+    // A small page size makes the events of the migration span several pages.
+    let config = EngineConfig {
+        sync_mode: SyncMode::Events,
+        events_chunk_size: 2,
+        max_concurrent_requests: 2,
+        ..Default::default()
+    };
+    let _ = bootstrap_engine(world, &mut db, &provider, migration, sequencer, config).await;
+
+    let models: Vec<(String, u8, u8)> =
+        sqlx::query_as("SELECT name, packed_size, unpacked_size FROM models ORDER BY name")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+    assert_eq!(models, vec![("Moves".to_string(), 1, 2), ("Position".to_string(), 1, 2)]);
+
+    // every model registration is indexed once, in a block up to the indexed head
+    let selector = get_selector_from_name("ModelRegistered").unwrap();
+    let registrations: Vec<(String, String)> =
+        sqlx::query_as("SELECT id, transaction_hash FROM events WHERE keys LIKE ?")
+            .bind(format!("{selector:#x}/%"))
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+    assert_eq!(registrations.len(), 2);
+
+    let head = db.head().await.unwrap();
+    assert!(head > 0);
+    for (id, transaction_hash) in registrations {
+        let block = id.split(':').next().unwrap();
+        assert!(u64::from_str_radix(block.trim_start_matches("0x"), 16).unwrap() <= head);
+        assert!(FieldElement::from_hex_be(&transaction_hash).is_ok());
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_permissions_and_contracts() {
+    let options =
+        SqliteConnectOptions::from_str("sqlite::memory:").unwrap().create_if_missing(true);
+    let pool = SqlitePoolOptions::new().max_connections(5).connect_with(options).await.unwrap();
+    sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+    let world_address = FieldElement::from(0x1234_u32);
+    let system = FieldElement::from(0x5678_u32);
+    let model = FieldElement::from(0x9abc_u32);
+    let mut db = Sql::new(pool.clone(), world_address).await.unwrap();
+
+    db.set_permission(PermissionKind::Writer, model, system, true, FieldElement::ONE);
+    db.set_permission(PermissionKind::Owner, model, system, true, FieldElement::ONE);
+    db.set_permission(PermissionKind::Owner, model, system, false, FieldElement::TWO);
+    db.set_contract_deployed(system, FieldElement::ONE, FieldElement::TWO, 0, FieldElement::ONE);
+    db.set_contract_upgraded(system, FieldElement::THREE, 60, FieldElement::TWO);
+    db.set_world_upgraded(FieldElement::THREE, 120, FieldElement::THREE);
+    db.execute().await.unwrap();
+
+    let permissions: Vec<(String, bool, String)> = sqlx::query_as(
+        "SELECT kind, granted, transaction_hash FROM permissions WHERE resource = ? AND address = \
+         ? ORDER BY kind",
+    )
+    .bind(format!("{model:#x}"))
+    .bind(format!("{system:#x}"))
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+
+    assert_eq!(
+        permissions,
+        vec![
+            ("Owner".to_string(), false, format!("{:#x}", FieldElement::TWO)),
+            ("Writer".to_string(), true, format!("{:#x}", FieldElement::ONE)),
+        ]
+    );
+
+    let (class_hash, salt, deployed_at, upgraded_at): (String, String, String, String) =
+        sqlx::query_as(
+            "SELECT class_hash, salt, deployed_at, upgraded_at FROM contracts WHERE id = ?",
+        )
+        .bind(format!("{system:#x}"))
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+    assert_eq!(class_hash, format!("{:#x}", FieldElement::THREE));
+    assert_eq!(salt, format!("{:#x}", FieldElement::TWO));
+    assert_eq!(deployed_at, "1970-01-01 00:00:00");
+    assert_eq!(upgraded_at, "1970-01-01 00:01:00");
+
+    let (world_class_hash,): (String,) =
+        sqlx::query_as("SELECT world_class_hash FROM worlds WHERE id = ?")
+            .bind(format!("{world_address:#x}"))
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    let (upgraded_at,): (String,) =
+        sqlx::query_as("SELECT upgraded_at FROM contracts WHERE id = ?")
+            .bind(format!("{world_address:#x}"))
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+    assert_eq!(world_class_hash, format!("{:#x}", FieldElement::THREE));
+    assert_eq!(upgraded_at, "1970-01-01 00:02:00");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_revert_to() {
+    let options =
+        SqliteConnectOptions::from_str("sqlite::memory:").unwrap().create_if_missing(true);
+    let pool = SqlitePoolOptions::new().max_connections(5).connect_with(options).await.unwrap();
+    sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+    let mut db = Sql::new(pool.clone(), FieldElement::from(0x1234_u32)).await.unwrap();
+
+    let player = FieldElement::from(0x5678_u32);
+    let position = |x: u32| {
+        Ty::Struct(Struct {
+            name: "Position".into(),
+            children: vec![
+                Member {
+                    name: "player".into(),
+                    key: true,
+                    ty: Ty::Primitive(Primitive::ContractAddress(Some(player))),
+                },
+                Member { name: "x".into(), key: false, ty: Ty::Primitive(Primitive::U32(Some(x))) },
+            ],
+        })
+    };
+
+    db.set_block(1, FieldElement::ONE, FieldElement::ZERO);
+    db.register_model(position(0), vec![], FieldElement::ONE, 0, 0).await.unwrap();
+    db.set_entity(position(1), "0x1").await.unwrap();
+    db.set_head(1);
+    db.execute().await.unwrap();
+
+    db.set_block(2, FieldElement::TWO, FieldElement::ONE);
+    db.set_entity(position(2), "0x2").await.unwrap();
+    let event = Event { from_address: FieldElement::ONE, keys: vec![], data: vec![] };
+    db.store_event(&format!("0x{:064x}:0x0000:0x0000", 2), &event, FieldElement::TWO);
+    db.set_head(2);
+    db.execute().await.unwrap();
+
+    db.revert_to(1).await.unwrap();
+
+    let (x,): (i64,) =
+        sqlx::query_as("SELECT external_x FROM [Position]").fetch_one(&pool).await.unwrap();
+    let (event_id,): (String,) =
+        sqlx::query_as("SELECT event_id FROM entities").fetch_one(&pool).await.unwrap();
+    let events = sqlx::query("SELECT * FROM events").fetch_all(&pool).await.unwrap();
+
+    assert_eq!(x, 1);
+    assert_eq!(event_id, "0x1");
+    assert!(events.is_empty());
+    assert_eq!(db.head().await.unwrap(), 1);
+    assert_eq!(db.block_hash(1).await.unwrap(), Some(FieldElement::ONE));
+    assert_eq!(db.block_hash(2).await.unwrap(), None);
+
+    db.revert_to(0).await.unwrap();
+
+    let entities = sqlx::query("SELECT * FROM entities").fetch_all(&pool).await.unwrap();
+    let models = sqlx::query("SELECT * FROM models").fetch_all(&pool).await.unwrap();
+    let rows = sqlx::query("SELECT * FROM [Position]").fetch_all(&pool).await.unwrap();
+
+    assert!(entities.is_empty());
+    assert!(models.is_empty());
+    assert!(rows.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_undo_log_of_reorgable_blocks() {
+    let options =
+        SqliteConnectOptions::from_str("sqlite::memory:").unwrap().create_if_missing(true);
+    let pool = SqlitePoolOptions::new().max_connections(5).connect_with(options).await.unwrap();
+    sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+    let mut db = Sql::new(pool.clone(), FieldElement::from(0x1234_u32)).await.unwrap();
+    db.set_reorgable_from(2);
+
+    let player = FieldElement::from(0x5678_u32);
+    let position = |x: u32| {
+        Ty::Struct(Struct {
+            name: "Position".into(),
+            children: vec![
+                Member {
+                    name: "player".into(),
+                    key: true,
+                    ty: Ty::Primitive(Primitive::ContractAddress(Some(player))),
+                },
+                Member { name: "x".into(), key: false, ty: Ty::Primitive(Primitive::U32(Some(x))) },
+            ],
+        })
+    };
+    let undo_log = |block: i64| {
+        sqlx::query_scalar::<_, i64>("SELECT count(*) FROM undo_log WHERE block_number = ?")
+            .bind(block)
+            .fetch_one(&pool)
+    };
+
+    // the block can no longer be reorged, so its writes aren't recorded
+    db.set_block(1, FieldElement::ONE, FieldElement::ZERO);
+    db.register_model(position(0), vec![], FieldElement::ONE, 0, 0).await.unwrap();
+    db.set_entity(position(1), "0x1").await.unwrap();
+    db.set_head(1);
+    db.execute().await.unwrap();
+    assert_eq!(undo_log(1).await.unwrap(), 0);
+
+    db.set_block(2, FieldElement::TWO, FieldElement::ONE);
+    db.set_entity(position(2), "0x2").await.unwrap();
+    db.set_entity(position(3), "0x3").await.unwrap();
+    db.set_head(2);
+    db.execute().await.unwrap();
+    assert!(undo_log(2).await.unwrap() > 0);
+
+    // the entity is restored to its state before the first write of the reverted block
+    db.revert_to(1).await.unwrap();
+
+    let (x,): (i64,) =
+        sqlx::query_as("SELECT external_x FROM [Position]").fetch_one(&pool).await.unwrap();
+    let (event_id,): (String,) =
+        sqlx::query_as("SELECT event_id FROM entities").fetch_one(&pool).await.unwrap();
+    assert_eq!(x, 1);
+    assert_eq!(event_id, "0x1");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_revert_pending_block() {
+    let options =
+        SqliteConnectOptions::from_str("sqlite::memory:").unwrap().create_if_missing(true);
+    let pool = SqlitePoolOptions::new().max_connections(5).connect_with(options).await.unwrap();
+    sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+    let mut db = Sql::new(pool.clone(), FieldElement::from(0x1234_u32)).await.unwrap();
+
+    let player = FieldElement::from(0x5678_u32);
+    let position = |x: u32| {
+        Ty::Struct(Struct {
+            name: "Position".into(),
+            children: vec![
+                Member {
+                    name: "player".into(),
+                    key: true,
+                    ty: Ty::Primitive(Primitive::ContractAddress(Some(player))),
+                },
+                Member { name: "x".into(), key: false, ty: Ty::Primitive(Primitive::U32(Some(x))) },
+            ],
+        })
+    };
+
+    db.set_block(1, FieldElement::ONE, FieldElement::ZERO);
+    db.register_model(position(0), vec![], FieldElement::ONE, 0, 0).await.unwrap();
+    db.set_entity(position(1), "0x1").await.unwrap();
+    db.set_head(1);
+    db.execute().await.unwrap();
+
+    assert!(!db.indexed_past(1).await.unwrap());
+
+    db.set_pending_block(2);
+    db.set_entity(position(2), "0x2").await.unwrap();
+
+    let (x, pending): (i64, bool) = sqlx::query_as(
+        "SELECT external_x, pending FROM [Position] JOIN entities ON entities.id = entity_id",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    assert_eq!(x, 2);
+    assert!(pending);
+
+    assert!(db.indexed_past(1).await.unwrap());
+
+    // The pending block is discarded once accepted.
+    db.revert_to(1).await.unwrap();
+    assert!(!db.indexed_past(1).await.unwrap());
+
+    let (x, pending): (i64, bool) = sqlx::query_as(
+        "SELECT external_x, pending FROM [Position] JOIN entities ON entities.id = entity_id",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    assert_eq!(x, 1);
+    assert!(!pending);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_multiple_worlds() {
+    let options =
+        SqliteConnectOptions::from_str("sqlite::memory:").unwrap().create_if_missing(true);
+    let pool = SqlitePoolOptions::new().max_connections(5).connect_with(options).await.unwrap();
+    sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+    let mut db = Sql::new(pool.clone(), FieldElement::ONE).await.unwrap();
+    let mut season = Sql::with_namespace(pool.clone(), FieldElement::TWO, "season2").await.unwrap();
+
+    let player = FieldElement::from(0x5678_u32);
+    let position = Ty::Struct(Struct {
+        name: "Position".into(),
+        children: vec![
+            Member {
+                name: "player".into(),
+                key: true,
+                ty: Ty::Primitive(Primitive::ContractAddress(None)),
+            },
+            Member { name: "x".into(), key: false, ty: Ty::Primitive(Primitive::U32(None)) },
+        ],
+    });
+    let entity = move |db: &Sql, x: u32| {
+        let db = db.clone();
+        async move {
+            let mut entity = db.model("Position").await.unwrap().schema().await.unwrap();
+            entity.deserialize(&mut vec![player, FieldElement::from(x)]).unwrap();
+            if let Ty::Struct(s) = &mut entity {
+                s.name = "Position".into();
+            }
+            entity
+        }
+    };
+
+    for db in [&mut db, &mut season] {
+        db.set_block(1, FieldElement::ONE, FieldElement::ZERO);
+        db.register_model(position.clone(), vec![], FieldElement::ONE, 0, 0).await.unwrap();
+        db.set_head(1);
+        db.execute().await.unwrap();
+    }
+
+    // The entity is created by the second world, then also set by the first one.
+    let (season_entity, entity) = (entity(&season, 2).await, entity(&db, 1).await);
+    season.set_block(2, FieldElement::TWO, FieldElement::ONE);
+    season.set_entity(season_entity, "0x2").await.unwrap();
+    db.set_block(2, FieldElement::TWO, FieldElement::ONE);
+    db.set_entity(entity, "0x1").await.unwrap();
+
+    let models: Vec<(String, String)> =
+        sqlx::query_as("SELECT id, world_address FROM models ORDER BY id")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+    assert_eq!(
+        models,
+        vec![("Position".into(), "0x1".into()), ("season2_Position".into(), "0x2".into())]
+    );
+
+    // A world can't register a model registered by another world under the same name.
+    let mut other = Sql::new(pool.clone(), FieldElement::THREE).await.unwrap();
+    assert!(other.register_model(position.clone(), vec![], FieldElement::ONE, 0, 0).await.is_err());
+    assert!(Sql::with_namespace(pool.clone(), FieldElement::TWO, "season3").await.is_err());
+
+    // Reverting the second world keeps the entity, which still has a model of the first one.
+    season.revert_to(1).await.unwrap();
+
+    let (x,): (i64,) =
+        sqlx::query_as("SELECT external_x FROM [Position]").fetch_one(&pool).await.unwrap();
+    let rows = sqlx::query("SELECT * FROM [season2_Position]").fetch_all(&pool).await.unwrap();
+    let entities = sqlx::query("SELECT * FROM entities").fetch_all(&pool).await.unwrap();
+
+    assert_eq!(x, 1);
+    assert!(rows.is_empty());
+    assert_eq!(entities.len(), 1);
+    assert_eq!(db.block_hash(2).await.unwrap(), Some(FieldElement::TWO));
+    assert_eq!(season.block_hash(2).await.unwrap(), None);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_custom_events() {
+    let options =
+        SqliteConnectOptions::from_str("sqlite::memory:").unwrap().create_if_missing(true);
+    let pool = SqlitePoolOptions::new().max_connections(5).connect_with(options).await.unwrap();
+    sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+    let mut db = Sql::new(pool.clone(), FieldElement::ONE).await.unwrap();
+
+    let player = FieldElement::from(0x5678_u32);
+    let moved = Ty::Struct(Struct {
+        name: "Moved".into(),
+        children: vec![
+            Member {
+                name: "player".into(),
+                key: true,
+                ty: Ty::Primitive(Primitive::ContractAddress(None)),
+            },
+            Member {
+                name: "direction".into(),
+                key: false,
+                ty: Ty::Enum(Enum {
+                    name: "Direction".into(),
+                    option: None,
+                    options: vec![
+                        EnumOption { name: "Left".into(), ty: Ty::Tuple(vec![]) },
+                        EnumOption { name: "Right".into(), ty: Ty::Tuple(vec![]) },
+                    ],
+                }),
+            },
+        ],
+    });
+    let event = |direction: u8| {
+        let mut event = moved.clone();
+        event.deserialize(&mut vec![player, FieldElement::from(direction)]).unwrap();
+        event
+    };
+
+    db.register_custom_event(&moved).await.unwrap();
+    db.register_custom_event(&moved).await.unwrap();
+
+    for block in 1..=2_u8 {
+        let event_id = format!("0x{:064x}:0x0000:0x0000", block);
+        db.set_block(block.into(), FieldElement::from(block), FieldElement::ZERO);
+        let raw = Event { from_address: FieldElement::ONE, keys: vec![], data: vec![] };
+        db.store_event(&event_id, &raw, FieldElement::from(block));
+        db.set_custom_event(&event(block - 1), &event_id).await.unwrap();
+        db.set_head(block.into());
+        db.execute().await.unwrap();
+    }
+
+    let rows: Vec<(String, String)> =
+        sqlx::query_as("SELECT external_player, external_direction FROM [MovedEvent]")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+    let player = format!("0x{:064x}", player);
+    assert_eq!(rows, vec![(player.clone(), "Left".into()), (player, "Right".into())]);
+
+    // An event registered again must keep its members.
+    let Ty::Struct(mut changed) = moved.clone() else { unreachable!() };
+    changed.children.pop();
+    assert!(db.register_custom_event(&Ty::Struct(changed)).await.is_err());
+
+    db.revert_to(1).await.unwrap();
+
+    let rows = sqlx::query("SELECT * FROM [MovedEvent]").fetch_all(&pool).await.unwrap();
+    assert_eq!(rows.len(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_entity_history() {
+    let options =
+        SqliteConnectOptions::from_str("sqlite::memory:").unwrap().create_if_missing(true);
+    let pool = SqlitePoolOptions::new().max_connections(5).connect_with(options).await.unwrap();
+    sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+    let mut db = Sql::new(pool.clone(), FieldElement::from(0x1234_u32)).await.unwrap();
+    db.set_historical(&["Position".to_string()]).await.unwrap();
+
+    let player = FieldElement::from(0x5678_u32);
+    let position = |x: u32| {
+        Ty::Struct(Struct {
+            name: "Position".into(),
+            children: vec![
+                Member {
+                    name: "player".into(),
+                    key: true,
+                    ty: Ty::Primitive(Primitive::ContractAddress(Some(player))),
+                },
+                Member { name: "x".into(), key: false, ty: Ty::Primitive(Primitive::U32(Some(x))) },
+            ],
+        })
+    };
+    let event = Event { from_address: FieldElement::ONE, keys: vec![], data: vec![] };
+
+    db.set_block(1, FieldElement::ONE, FieldElement::ZERO);
+    db.register_model(position(0), vec![], FieldElement::ONE, 0, 0).await.unwrap();
+    db.store_event("0x1", &event, FieldElement::ONE);
+    db.set_entity(position(1), "0x1").await.unwrap();
+    db.set_head(1);
+    db.execute().await.unwrap();
+
+    db.set_block(2, FieldElement::TWO, FieldElement::ONE);
+    db.store_event("0x2", &event, FieldElement::TWO);
+    db.set_entity(position(2), "0x2").await.unwrap();
+    db.store_event("0x3", &event, FieldElement::TWO);
+    db.delete_entity("Position", &[player], "0x3").await.unwrap();
+    db.set_head(2);
+    db.execute().await.unwrap();
+
+    let history: Vec<(String, i64, String, bool, Option<i64>)> = sqlx::query_as(
+        "SELECT event_id, block_number, transaction_hash, deleted, external_x FROM \
+         [Position@history] ORDER BY id",
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+
+    assert_eq!(
+        history,
+        vec![
+            ("0x1".to_string(), 1, "0x1".to_string(), false, Some(1)),
+            ("0x2".to_string(), 2, "0x2".to_string(), false, Some(2)),
+            ("0x3".to_string(), 2, "0x2".to_string(), true, None),
+        ]
+    );
+
+    // The history of the reverted blocks is dropped with them.
+    db.revert_to(1).await.unwrap();
+
+    let (count,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM [Position@history]").fetch_one(&pool).await.unwrap();
+    assert_eq!(count, 1);
+}
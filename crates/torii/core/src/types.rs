@@ -34,10 +34,16 @@ pub struct Entity {
     pub id: String,
     pub keys: String,
     pub event_id: String,
+    /// Whether the entity was last written by a transaction of the pending block.
+    pub pending: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// An entity that was removed from the world, as it was before its last model got deleted.
+#[derive(Debug, Clone)]
+pub struct EntityDeleted(pub Entity);
+
 #[derive(FromRow, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Model {
@@ -57,3 +63,26 @@ pub struct Event {
     pub transaction_hash: String,
     pub created_at: DateTime<Utc>,
 }
+
+/// A custom event stored into the table `id`, under the id of the event that emitted it.
+#[derive(Debug, Clone)]
+pub struct CustomEventEmitted {
+    pub id: String,
+    pub event_id: String,
+}
+
+/// The kind of permission an address holds on a world's resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PermissionKind {
+    Owner,
+    Writer,
+}
+
+impl fmt::Display for PermissionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PermissionKind::Owner => write!(f, "Owner"),
+            PermissionKind::Writer => write!(f, "Writer"),
+        }
+    }
+}
@@ -12,6 +12,7 @@ lazy_static! {
         (Name::new("id"), TypeData::Simple(TypeRef::named(TypeRef::ID))),
         (Name::new("keys"), TypeData::Simple(TypeRef::named_list(TypeRef::STRING))),
         (Name::new("eventId"), TypeData::Simple(TypeRef::named(TypeRef::STRING))),
+        (Name::new("pending"), TypeData::Simple(TypeRef::named(Primitive::Bool(None).to_string()))),
         (
             Name::new("createdAt"),
             TypeData::Simple(TypeRef::named(GraphqlType::DateTime.to_string())),
@@ -21,6 +22,26 @@ lazy_static! {
             TypeData::Simple(TypeRef::named(GraphqlType::DateTime.to_string())),
         ),
     ]);
+    pub static ref ENTITY_CHANGE_TYPE_MAPPING: TypeMapping = IndexMap::from([
+        (Name::new("entityId"), TypeData::Simple(TypeRef::named(TypeRef::ID))),
+        (Name::new("modelName"), TypeData::Simple(TypeRef::named(TypeRef::STRING))),
+        (Name::new("eventId"), TypeData::Simple(TypeRef::named(TypeRef::STRING))),
+        (
+            Name::new("blockNumber"),
+            TypeData::Simple(TypeRef::named(Primitive::U64(None).to_string())),
+        ),
+        (
+            Name::new("transactionHash"),
+            TypeData::Simple(TypeRef::named(Primitive::Felt252(None).to_string())),
+        ),
+        (Name::new("deleted"), TypeData::Simple(TypeRef::named(Primitive::Bool(None).to_string()))),
+        (Name::new("keys"), TypeData::Simple(TypeRef::named_list(TypeRef::STRING))),
+        (Name::new("data"), TypeData::Simple(TypeRef::named_list(TypeRef::STRING))),
+        (
+            Name::new("createdAt"),
+            TypeData::Simple(TypeRef::named(GraphqlType::DateTime.to_string())),
+        ),
+    ]);
     pub static ref EVENT_TYPE_MAPPING: TypeMapping = IndexMap::from([
         (Name::new("id"), TypeData::Simple(TypeRef::named(TypeRef::ID))),
         (Name::new("keys"), TypeData::Simple(TypeRef::named_list(TypeRef::STRING))),
@@ -78,6 +99,86 @@ lazy_static! {
             TypeData::Simple(TypeRef::named(GraphqlType::DateTime.to_string())),
         ),
     ]);
+    pub static ref CONTRACT_TYPE_MAPPING: TypeMapping = IndexMap::from([
+        (Name::new("id"), TypeData::Simple(TypeRef::named(TypeRef::ID))),
+        (
+            Name::new("classHash"),
+            TypeData::Simple(TypeRef::named(Primitive::Felt252(None).to_string())),
+        ),
+        (
+            Name::new("salt"),
+            TypeData::Simple(TypeRef::named(Primitive::Felt252(None).to_string())),
+        ),
+        (
+            Name::new("transactionHash"),
+            TypeData::Simple(TypeRef::named(Primitive::Felt252(None).to_string())),
+        ),
+        (
+            Name::new("deployedAt"),
+            TypeData::Simple(TypeRef::named(GraphqlType::DateTime.to_string())),
+        ),
+        (
+            Name::new("upgradedAt"),
+            TypeData::Simple(TypeRef::named(GraphqlType::DateTime.to_string())),
+        ),
+        (
+            Name::new("createdAt"),
+            TypeData::Simple(TypeRef::named(GraphqlType::DateTime.to_string())),
+        ),
+        (
+            Name::new("updatedAt"),
+            TypeData::Simple(TypeRef::named(GraphqlType::DateTime.to_string())),
+        ),
+    ]);
+    pub static ref PERMISSION_TYPE_MAPPING: TypeMapping = IndexMap::from([
+        (Name::new("id"), TypeData::Simple(TypeRef::named(TypeRef::ID))),
+        (Name::new("kind"), TypeData::Simple(TypeRef::named(TypeRef::STRING))),
+        (
+            Name::new("resource"),
+            TypeData::Simple(TypeRef::named(Primitive::Felt252(None).to_string())),
+        ),
+        (
+            Name::new("address"),
+            TypeData::Simple(TypeRef::named(Primitive::ContractAddress(None).to_string())),
+        ),
+        (Name::new("granted"), TypeData::Simple(TypeRef::named(Primitive::Bool(None).to_string()))),
+        (
+            Name::new("transactionHash"),
+            TypeData::Simple(TypeRef::named(Primitive::Felt252(None).to_string())),
+        ),
+        (
+            Name::new("createdAt"),
+            TypeData::Simple(TypeRef::named(GraphqlType::DateTime.to_string())),
+        ),
+        (
+            Name::new("updatedAt"),
+            TypeData::Simple(TypeRef::named(GraphqlType::DateTime.to_string())),
+        ),
+    ]);
+    pub static ref WORLD_TYPE_MAPPING: TypeMapping = IndexMap::from([
+        (Name::new("id"), TypeData::Simple(TypeRef::named(TypeRef::ID))),
+        (
+            Name::new("worldAddress"),
+            TypeData::Simple(TypeRef::named(Primitive::ContractAddress(None).to_string())),
+        ),
+        (Name::new("namespace"), TypeData::Simple(TypeRef::named(TypeRef::STRING))),
+        (
+            Name::new("worldClassHash"),
+            TypeData::Simple(TypeRef::named(Primitive::Felt252(None).to_string())),
+        ),
+        (
+            Name::new("executorAddress"),
+            TypeData::Simple(TypeRef::named(Primitive::ContractAddress(None).to_string())),
+        ),
+        (
+            Name::new("executorClassHash"),
+            TypeData::Simple(TypeRef::named(Primitive::Felt252(None).to_string())),
+        ),
+        (
+            Name::new("createdAt"),
+            TypeData::Simple(TypeRef::named(GraphqlType::DateTime.to_string())),
+        ),
+    ]);
     pub static ref PAGE_INFO_TYPE_MAPPING: TypeMapping = TypeMapping::from([
         (Name::new("hasPreviousPage"), TypeData::Simple(TypeRef::named(TypeRef::BOOLEAN))),
         (Name::new("hasNextPage"), TypeData::Simple(TypeRef::named(TypeRef::BOOLEAN))),
@@ -0,0 +1,23 @@
+use super::{ObjectTrait, TypeMapping};
+use crate::constants::{CONTRACT_NAMES, CONTRACT_TABLE, CONTRACT_TYPE_NAME};
+use crate::mapping::CONTRACT_TYPE_MAPPING;
+
+pub struct ContractObject;
+
+impl ObjectTrait for ContractObject {
+    fn name(&self) -> (&str, &str) {
+        CONTRACT_NAMES
+    }
+
+    fn type_name(&self) -> &str {
+        CONTRACT_TYPE_NAME
+    }
+
+    fn type_mapping(&self) -> &TypeMapping {
+        &CONTRACT_TYPE_MAPPING
+    }
+
+    fn table_name(&self) -> Option<&str> {
+        Some(CONTRACT_TABLE)
+    }
+}
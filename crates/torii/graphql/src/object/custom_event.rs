@@ -0,0 +1,180 @@
+use async_graphql::dynamic::{
+    Enum, Field, FieldFuture, InputObject, Object, SubscriptionField, SubscriptionFieldFuture,
+    TypeRef,
+};
+use async_graphql::{Error, Value};
+use sqlx::{Pool, Sqlite};
+use tokio_stream::StreamExt;
+use torii_core::simple_broker::SimpleBroker;
+use torii_core::types::CustomEventEmitted;
+
+use super::connection::{connection_arguments, connection_output, parse_connection_arguments};
+use super::inputs::order_input::{order_argument, parse_order_argument, OrderInputObject};
+use super::inputs::where_input::{parse_where_argument, where_argument, WhereInputObject};
+use super::inputs::InputObjectTrait;
+use super::model_data::object;
+use super::{ObjectTrait, TypeMapping};
+use crate::constants::{
+    EVENT_ID_COLUMN, EVENT_TABLE, EVENT_TYPE_NAME, ID_COLUMN, INTERNAL_EVENT_ID_KEY,
+};
+use crate::mapping::EVENT_TYPE_MAPPING;
+use crate::query::data::{count_rows, fetch_multiple_rows, fetch_single_row};
+use crate::query::value_mapping_from_row;
+use crate::utils::extract;
+
+/// The values of a custom event emitted with `emit!`, stored in the table named after its type.
+pub struct CustomEventObject {
+    pub name: String,
+    pub plural_name: String,
+    pub type_name: String,
+    pub type_mapping: TypeMapping,
+    pub where_input: WhereInputObject,
+    pub order_input: OrderInputObject,
+}
+
+impl CustomEventObject {
+    pub fn new(name: String, type_name: String, type_mapping: TypeMapping) -> Self {
+        let where_input = WhereInputObject::new(type_name.as_str(), &type_mapping);
+        let order_input = OrderInputObject::new(type_name.as_str(), &type_mapping);
+        let plural_name = format!("{}s", name);
+        Self { name, plural_name, type_name, type_mapping, where_input, order_input }
+    }
+}
+
+impl ObjectTrait for CustomEventObject {
+    fn name(&self) -> (&str, &str) {
+        (&self.name, &self.plural_name)
+    }
+
+    fn type_name(&self) -> &str {
+        &self.type_name
+    }
+
+    fn type_mapping(&self) -> &TypeMapping {
+        &self.type_mapping
+    }
+
+    fn input_objects(&self) -> Option<Vec<InputObject>> {
+        Some(vec![self.where_input.input_object(), self.order_input.input_object()])
+    }
+
+    fn enum_objects(&self) -> Option<Vec<Enum>> {
+        self.order_input.enum_objects()
+    }
+
+    fn resolve_one(&self) -> Option<Field> {
+        None
+    }
+
+    fn resolve_many(&self) -> Option<Field> {
+        let type_name = self.type_name.clone();
+        let type_mapping = self.type_mapping.clone();
+        let where_mapping = self.where_input.type_mapping.clone();
+        let field_type = format!("{}Connection", self.type_name());
+
+        let mut field = Field::new(self.name().1, TypeRef::named(field_type), move |ctx| {
+            let type_mapping = type_mapping.clone();
+            let where_mapping = where_mapping.clone();
+            let type_name = type_name.clone();
+
+            FieldFuture::new(async move {
+                let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
+                let order = parse_order_argument(&ctx);
+                let filters = parse_where_argument(&ctx, &where_mapping)?;
+                let connection = parse_connection_arguments(&ctx)?;
+
+                let total_count = count_rows(&mut conn, &type_name, &None, &filters).await?;
+                let (data, page_info) = fetch_multiple_rows(
+                    &mut conn,
+                    &type_name,
+                    EVENT_ID_COLUMN,
+                    &None,
+                    &order,
+                    &filters,
+                    &connection,
+                    total_count,
+                )
+                .await?;
+                let connection = connection_output(
+                    &data,
+                    &type_mapping,
+                    &order,
+                    EVENT_ID_COLUMN,
+                    total_count,
+                    true,
+                    page_info,
+                )?;
+
+                Ok(Some(Value::Object(connection)))
+            })
+        });
+
+        field = connection_arguments(field);
+        field = where_argument(field, self.type_name());
+        field = order_argument(field, self.type_name());
+
+        Some(field)
+    }
+
+    fn subscriptions(&self) -> Option<Vec<SubscriptionField>> {
+        let type_name = self.type_name.clone();
+        let type_mapping = self.type_mapping.clone();
+        let name = format!("{}Emitted", self.name);
+
+        Some(vec![SubscriptionField::new(name, TypeRef::named_nn(self.type_name()), move |ctx| {
+            let type_name = type_name.clone();
+            let type_mapping = type_mapping.clone();
+
+            SubscriptionFieldFuture::new(async move {
+                let pool = ctx.data::<Pool<Sqlite>>()?.clone();
+
+                Ok(SimpleBroker::<CustomEventEmitted>::subscribe()
+                    .filter(move |event| event.id == type_name)
+                    .then(move |event| {
+                        let pool = pool.clone();
+                        let type_mapping = type_mapping.clone();
+
+                        async move {
+                            let mut conn = pool.acquire().await?;
+                            let data = fetch_single_row(
+                                &mut conn,
+                                &event.id,
+                                EVENT_ID_COLUMN,
+                                &event.event_id,
+                            )
+                            .await?;
+                            let values = value_mapping_from_row(&data, &type_mapping, true)?;
+
+                            Ok::<_, Error>(Value::Object(values))
+                        }
+                    }))
+            })
+        })])
+    }
+
+    fn objects(&self) -> Vec<Object> {
+        let root =
+            object(self.type_name(), self.type_mapping(), vec![self.type_name().to_string()]);
+
+        vec![root.field(event_field())]
+    }
+}
+
+fn event_field() -> Field {
+    Field::new("event", TypeRef::named(EVENT_TYPE_NAME), |ctx| {
+        FieldFuture::new(async move {
+            match ctx.parent_value.try_to_value()? {
+                Value::Object(indexmap) => {
+                    let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
+                    let event_id = extract::<String>(indexmap, INTERNAL_EVENT_ID_KEY)?;
+                    let data =
+                        fetch_single_row(&mut conn, EVENT_TABLE, ID_COLUMN, &event_id).await?;
+                    let event = value_mapping_from_row(&data, &EVENT_TYPE_MAPPING, false)?;
+
+                    Ok(Some(Value::Object(event)))
+                }
+                _ => Err("incorrect value, requires Value::Object".into()),
+            }
+        })
+    })
+}
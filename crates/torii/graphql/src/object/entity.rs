@@ -1,17 +1,19 @@
 use async_graphql::dynamic::indexmap::IndexMap;
 use async_graphql::dynamic::{
-    Field, FieldFuture, FieldValue, InputValue, SubscriptionField, SubscriptionFieldFuture, TypeRef,
+    Field, FieldFuture, FieldValue, InputValue, ResolverContext, SubscriptionField,
+    SubscriptionFieldFuture, TypeRef,
 };
-use async_graphql::{Name, Value};
+use async_graphql::{Error, Name, Value};
 use async_recursion::async_recursion;
 use sqlx::pool::PoolConnection;
 use sqlx::{Pool, Sqlite};
 use tokio_stream::StreamExt;
 use torii_core::simple_broker::SimpleBroker;
-use torii_core::types::Entity;
+use torii_core::types::{Entity, EntityDeleted};
 
 use super::connection::{connection_arguments, connection_output, parse_connection_arguments};
 use super::inputs::keys_input::{keys_argument, parse_keys_argument};
+use super::inputs::world_input::{parse_world_address, world_argument};
 use super::{ObjectTrait, TypeMapping, ValueMapping};
 use crate::constants::{ENTITY_NAMES, ENTITY_TABLE, ENTITY_TYPE_NAME, EVENT_ID_COLUMN};
 use crate::mapping::ENTITY_TYPE_MAPPING;
@@ -51,10 +53,11 @@ impl ObjectTrait for EntityObject {
                     let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
                     let connection = parse_connection_arguments(&ctx)?;
                     let keys = parse_keys_argument(&ctx)?;
-                    let total_count = count_rows(&mut conn, ENTITY_TABLE, &keys, &None).await?;
+                    let table = entities_table(&ctx)?;
+                    let total_count = count_rows(&mut conn, &table, &keys, &None).await?;
                     let (data, page_info) = fetch_multiple_rows(
                         &mut conn,
-                        ENTITY_TABLE,
+                        &table,
                         EVENT_ID_COLUMN,
                         &keys,
                         &None,
@@ -80,6 +83,7 @@ impl ObjectTrait for EntityObject {
 
         field = connection_arguments(field);
         field = keys_argument(field);
+        field = world_argument(field);
 
         Some(field)
     }
@@ -105,6 +109,24 @@ impl ObjectTrait for EntityObject {
                 })
             })
             .argument(InputValue::new("id", TypeRef::named(TypeRef::ID))),
+            SubscriptionField::new("entityDeleted", TypeRef::named_nn(self.type_name()), |ctx| {
+                SubscriptionFieldFuture::new(async move {
+                    let id = match ctx.args.get("id") {
+                        Some(id) => Some(id.string()?.to_string()),
+                        None => None,
+                    };
+                    Ok(SimpleBroker::<EntityDeleted>::subscribe().filter_map(
+                        move |EntityDeleted(entity)| {
+                            if id.is_none() || id == Some(entity.id.clone()) {
+                                Some(Ok(Value::Object(EntityObject::value_mapping(entity))))
+                            } else {
+                                None
+                            }
+                        },
+                    ))
+                })
+            })
+            .argument(InputValue::new("id", TypeRef::named(TypeRef::ID))),
         ])
     }
 }
@@ -116,6 +138,7 @@ impl EntityObject {
             (Name::new("id"), Value::from(entity.id)),
             (Name::new("keys"), Value::from(keys)),
             (Name::new("eventId"), Value::from(entity.event_id)),
+            (Name::new("pending"), Value::from(entity.pending)),
             (
                 Name::new("createdAt"),
                 Value::from(entity.created_at.format("%Y-%m-%d %H:%M:%S").to_string()),
@@ -128,6 +151,20 @@ impl EntityObject {
     }
 }
 
+// The entities don't belong to a world, but their models do: an entity is part of a world as
+// long as it has at least one of its models.
+fn entities_table(ctx: &ResolverContext<'_>) -> Result<String, Error> {
+    let Some(world) = parse_world_address(ctx)? else {
+        return Ok(ENTITY_TABLE.to_string());
+    };
+
+    Ok(format!(
+        "(SELECT * FROM {ENTITY_TABLE} WHERE id IN (SELECT entity_model.entity_id FROM \
+         entity_model JOIN models ON entity_model.model_id = models.id WHERE models.world_address \
+         = '{world}')) AS {ENTITY_TABLE}"
+    ))
+}
+
 fn model_union_field() -> Field {
     Field::new("models", TypeRef::named_list("ModelUnion"), move |ctx| {
         FieldFuture::new(async move {
@@ -0,0 +1,159 @@
+use async_graphql::dynamic::{Field, FieldFuture, FieldValue, InputValue, TypeRef};
+use async_graphql::{Name, Value};
+use sqlx::{Pool, Sqlite};
+
+use super::connection::{connection_arguments, connection_output, parse_connection_arguments};
+use super::inputs::block_input::{at_block_argument, parse_at_block_argument};
+use super::{ObjectTrait, TypeMapping};
+use crate::constants::{ENTITY_CHANGE_NAMES, ENTITY_CHANGE_TYPE_NAME, EVENT_ID_COLUMN};
+use crate::mapping::ENTITY_CHANGE_TYPE_MAPPING;
+use crate::query::data::{count_rows, fetch_multiple_rows};
+use crate::query::filter::{Comparator, Filter, FilterValue};
+use crate::query::{type_mapping_query, value_mapping_from_row};
+use crate::utils::extract;
+
+/// The changes of an entity's historical models, read from their history tables.
+pub struct EntityHistoryObject {
+    table_name: String,
+}
+
+impl EntityHistoryObject {
+    pub fn new(models: &[String]) -> Self {
+        let histories = models
+            .iter()
+            .map(|model| {
+                format!(
+                    "SELECT '{model}' AS model_name, entity_id, event_id, block_number, \
+                     transaction_hash, deleted, keys, data, created_at FROM [{model}@history]"
+                )
+            })
+            .collect::<Vec<_>>();
+
+        Self { table_name: format!("({})", histories.join(" UNION ALL ")) }
+    }
+}
+
+impl ObjectTrait for EntityHistoryObject {
+    fn name(&self) -> (&str, &str) {
+        ENTITY_CHANGE_NAMES
+    }
+
+    fn type_name(&self) -> &str {
+        ENTITY_CHANGE_TYPE_NAME
+    }
+
+    fn type_mapping(&self) -> &TypeMapping {
+        &ENTITY_CHANGE_TYPE_MAPPING
+    }
+
+    fn related_fields(&self) -> Option<Vec<Field>> {
+        Some(vec![model_union_field()])
+    }
+
+    fn resolve_one(&self) -> Option<Field> {
+        None
+    }
+
+    fn resolve_many(&self) -> Option<Field> {
+        let table_name = self.table_name.clone();
+
+        let mut field = Field::new(
+            self.name().1,
+            TypeRef::named(format!("{}Connection", self.type_name())),
+            move |ctx| {
+                let table_name = table_name.clone();
+
+                FieldFuture::new(async move {
+                    let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
+                    let connection = parse_connection_arguments(&ctx)?;
+                    let entity_id = extract::<String>(ctx.args.as_index_map(), "id")?;
+
+                    let mut filters = vec![Filter {
+                        field: "entity_id".to_string(),
+                        comparator: Comparator::Eq,
+                        value: FilterValue::String(entity_id),
+                    }];
+                    if let Some(block_number) = parse_at_block_argument(&ctx)? {
+                        filters.push(Filter {
+                            field: "block_number".to_string(),
+                            comparator: Comparator::Lte,
+                            value: FilterValue::Int(block_number.try_into()?),
+                        });
+                    }
+                    let filters = Some(filters);
+
+                    let total_count = count_rows(&mut conn, &table_name, &None, &filters).await?;
+                    let (data, page_info) = fetch_multiple_rows(
+                        &mut conn,
+                        &table_name,
+                        EVENT_ID_COLUMN,
+                        &None,
+                        &None,
+                        &filters,
+                        &connection,
+                        total_count,
+                    )
+                    .await?;
+                    let results = connection_output(
+                        &data,
+                        &ENTITY_CHANGE_TYPE_MAPPING,
+                        &None,
+                        EVENT_ID_COLUMN,
+                        total_count,
+                        false,
+                        page_info,
+                    )?;
+
+                    Ok(Some(Value::Object(results)))
+                })
+            },
+        );
+
+        field = connection_arguments(field);
+        field = field.argument(InputValue::new("id", TypeRef::named_nn(TypeRef::ID)));
+        field = at_block_argument(field);
+
+        Some(field)
+    }
+}
+
+// The values of the model after the change, null if the change deleted it. Nested types are not
+// recorded in the history, so they resolve to null.
+fn model_union_field() -> Field {
+    Field::new("model", TypeRef::named("ModelUnion"), move |ctx| {
+        FieldFuture::new(async move {
+            match ctx.parent_value.try_to_value()? {
+                Value::Object(indexmap) => {
+                    if indexmap.get("deleted") == Some(&Value::Boolean(true)) {
+                        return Ok(None);
+                    }
+
+                    let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
+                    let model = extract::<String>(indexmap, "modelName")?;
+                    let entity_id = extract::<String>(indexmap, "entityId")?;
+                    let event_id = extract::<String>(indexmap, "eventId")?;
+
+                    let query = format!(
+                        "SELECT * FROM [{model}@history] WHERE entity_id = ? AND event_id = ?"
+                    );
+                    let row = sqlx::query(&query)
+                        .bind(&entity_id)
+                        .bind(&event_id)
+                        .fetch_one(&mut *conn)
+                        .await?;
+
+                    let type_mapping = type_mapping_query(&mut conn, &model).await?;
+                    let mut data = value_mapping_from_row(&row, &type_mapping, true)?;
+                    for (field_name, type_data) in &type_mapping {
+                        if !type_data.is_simple() {
+                            data.insert(Name::new(field_name), Value::Null);
+                        }
+                    }
+
+                    Ok(Some(FieldValue::with_type(FieldValue::owned_any(data), model)))
+                }
+                _ => Err("incorrect value, requires Value::Object".into()),
+            }
+        })
+    })
+}
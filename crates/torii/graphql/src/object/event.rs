@@ -10,6 +10,7 @@ use torii_core::types::Event;
 
 use super::connection::{connection_arguments, connection_output, parse_connection_arguments};
 use super::inputs::keys_input::{keys_argument, parse_keys_argument};
+use super::inputs::world_input::{parse_world_argument, world_argument};
 use super::{ObjectTrait, TypeMapping};
 use crate::constants::{EVENT_NAMES, EVENT_TABLE, EVENT_TYPE_NAME, ID_COLUMN};
 use crate::mapping::EVENT_TYPE_MAPPING;
@@ -48,14 +49,15 @@ impl ObjectTrait for EventObject {
                     let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
                     let connection = parse_connection_arguments(&ctx)?;
                     let keys = parse_keys_argument(&ctx)?;
-                    let total_count = count_rows(&mut conn, EVENT_TABLE, &keys, &None).await?;
+                    let filters = parse_world_argument(&ctx)?;
+                    let total_count = count_rows(&mut conn, EVENT_TABLE, &keys, &filters).await?;
                     let (data, page_info) = fetch_multiple_rows(
                         &mut conn,
                         EVENT_TABLE,
                         ID_COLUMN,
                         &keys,
                         &None,
-                        &None,
+                        &filters,
                         &connection,
                         total_count,
                     )
@@ -77,6 +79,7 @@ impl ObjectTrait for EventObject {
 
         field = connection_arguments(field);
         field = keys_argument(field);
+        field = world_argument(field);
 
         Some(field)
     }
@@ -0,0 +1,17 @@
+use async_graphql::dynamic::{Field, InputValue, ResolverContext, TypeRef};
+use async_graphql::Error;
+
+use crate::error::ExtractError;
+use crate::utils::extract;
+
+pub fn at_block_argument(field: Field) -> Field {
+    field.argument(InputValue::new("atBlock", TypeRef::named(TypeRef::INT)))
+}
+
+pub fn parse_at_block_argument(ctx: &ResolverContext<'_>) -> Result<Option<u64>, Error> {
+    match extract::<u64>(ctx.args.as_index_map(), "atBlock") {
+        Ok(block_number) => Ok(Some(block_number)),
+        Err(ExtractError::NotFound(_)) => Ok(None),
+        Err(_) => Err("`atBlock` must be a non-negative block number".into()),
+    }
+}
@@ -0,0 +1,28 @@
+use async_graphql::dynamic::{Field, InputValue, ResolverContext, TypeRef};
+use async_graphql::Error;
+
+use crate::query::filter::{Comparator, Filter, FilterValue};
+use crate::utils::{extract, normalize_felt};
+
+pub fn world_argument(field: Field) -> Field {
+    field.argument(InputValue::new("world", TypeRef::named(TypeRef::STRING)))
+}
+
+// Restricts the rows to the ones of a world, when several worlds are indexed into the database.
+pub fn parse_world_argument(ctx: &ResolverContext<'_>) -> Result<Option<Vec<Filter>>, Error> {
+    Ok(parse_world_address(ctx)?.map(|world| {
+        vec![Filter {
+            field: "world_address".into(),
+            comparator: Comparator::Eq,
+            value: FilterValue::String(world),
+        }]
+    }))
+}
+
+pub fn parse_world_address(ctx: &ResolverContext<'_>) -> Result<Option<String>, Error> {
+    let Ok(world) = extract::<String>(ctx.args.as_index_map(), "world") else {
+        return Ok(None);
+    };
+
+    Ok(Some(normalize_felt(&world)?))
+}
@@ -113,6 +113,9 @@ fn metadata_connection_output(
             let order = row.try_get::<String, &str>(ID_COLUMN)?;
             let cursor = cursor::encode(&order, &order);
             let mut value_mapping = value_mapping_from_row(row, row_types, false)?;
+            let world_address = row
+                .try_get::<Option<String>, &str>("world_address")?
+                .unwrap_or_else(|| world_address.clone());
             value_mapping.insert(Name::new("worldAddress"), Value::from(world_address));
 
             let json_str = row.try_get::<String, &str>(JSON_COLUMN)?;
@@ -1,11 +1,16 @@
 pub mod connection;
+pub mod contract;
+pub mod custom_event;
 pub mod entity;
+pub mod entity_history;
 pub mod event;
 pub mod inputs;
 pub mod metadata;
 pub mod model;
 pub mod model_data;
+pub mod permission;
 pub mod transaction;
+pub mod world;
 
 use async_graphql::dynamic::{
     Enum, Field, FieldFuture, InputObject, InputValue, Object, SubscriptionField, TypeRef,
@@ -11,6 +11,7 @@ use torii_core::types::Model;
 
 use super::connection::{connection_arguments, connection_output, parse_connection_arguments};
 use super::inputs::order_input::parse_order_argument;
+use super::inputs::world_input::{parse_world_argument, world_argument};
 use super::{ObjectTrait, TypeMapping, ValueMapping};
 use crate::constants::{
     ID_COLUMN, MODEL_NAMES, MODEL_ORDER_FIELD_TYPE_NAME, MODEL_ORDER_TYPE_NAME, MODEL_TABLE,
@@ -72,14 +73,15 @@ impl ObjectTrait for ModelObject {
                     let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
                     let order = parse_order_argument(&ctx);
                     let connection = parse_connection_arguments(&ctx)?;
-                    let total_count = count_rows(&mut conn, &table_name, &None, &None).await?;
+                    let filters = parse_world_argument(&ctx)?;
+                    let total_count = count_rows(&mut conn, &table_name, &None, &filters).await?;
                     let (data, page_info) = fetch_multiple_rows(
                         &mut conn,
                         &table_name,
                         ID_COLUMN,
                         &None,
                         &order,
-                        &None,
+                        &filters,
                         &connection,
                         total_count,
                     )
@@ -101,6 +103,7 @@ impl ObjectTrait for ModelObject {
 
         field = connection_arguments(field);
         field = field.argument(InputValue::new("order", TypeRef::named(MODEL_ORDER_TYPE_NAME)));
+        field = world_argument(field);
 
         Some(field)
     }
@@ -5,6 +5,7 @@ use serde::Deserialize;
 use sqlx::{FromRow, Pool, Sqlite};
 
 use super::connection::{connection_arguments, connection_output, parse_connection_arguments};
+use super::inputs::block_input::{at_block_argument, parse_at_block_argument};
 use super::inputs::order_input::{order_argument, parse_order_argument, OrderInputObject};
 use super::inputs::where_input::{parse_where_argument, where_argument, WhereInputObject};
 use super::inputs::InputObjectTrait;
@@ -36,14 +37,21 @@ pub struct ModelDataObject {
     pub type_mapping: TypeMapping,
     pub where_input: WhereInputObject,
     pub order_input: OrderInputObject,
+    /// Whether the changes of the model are recorded, which allows querying it `atBlock`.
+    pub historical: bool,
 }
 
 impl ModelDataObject {
-    pub fn new(name: String, type_name: String, type_mapping: TypeMapping) -> Self {
+    pub fn new(
+        name: String,
+        type_name: String,
+        type_mapping: TypeMapping,
+        historical: bool,
+    ) -> Self {
         let where_input = WhereInputObject::new(type_name.as_str(), &type_mapping);
         let order_input = OrderInputObject::new(type_name.as_str(), &type_mapping);
         let plural_name = format!("{}Models", name);
-        Self { name, plural_name, type_name, type_mapping, where_input, order_input }
+        Self { name, plural_name, type_name, type_mapping, where_input, order_input, historical }
     }
 }
 
@@ -90,10 +98,29 @@ impl ObjectTrait for ModelDataObject {
                 let connection = parse_connection_arguments(&ctx)?;
                 let id_column = "event_id";
 
-                let total_count = count_rows(&mut conn, &type_name, &None, &filters).await?;
+                // The latest value of each entity up to the block, from the model's history
+                let table_name = match parse_at_block_argument(&ctx)? {
+                    Some(block_number) => {
+                        if type_mapping.values().any(|type_data| !type_data.is_simple()) {
+                            return Err("`atBlock` is not supported on models with nested types, \
+                                        whose history is not recorded"
+                                .into());
+                        }
+
+                        let history = format!("[{type_name}@history]");
+                        format!(
+                            "(SELECT * FROM {history} WHERE id IN (SELECT MAX(id) FROM {history} \
+                             WHERE block_number <= {block_number} GROUP BY entity_id) AND NOT \
+                             deleted)"
+                        )
+                    }
+                    None => type_name,
+                };
+
+                let total_count = count_rows(&mut conn, &table_name, &None, &filters).await?;
                 let (data, page_info) = fetch_multiple_rows(
                     &mut conn,
-                    &type_name,
+                    &table_name,
                     id_column,
                     &None,
                     &order,
@@ -121,6 +148,10 @@ impl ObjectTrait for ModelDataObject {
         field = where_argument(field, self.type_name());
         field = order_argument(field, self.type_name());
 
+        if self.historical {
+            field = at_block_argument(field);
+        }
+
         Some(field)
     }
 
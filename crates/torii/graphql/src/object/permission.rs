@@ -0,0 +1,126 @@
+use async_graphql::dynamic::{Enum, Field, FieldFuture, InputValue, ResolverContext, TypeRef};
+use async_graphql::{Error, Value};
+use sqlx::{Pool, Sqlite};
+
+use super::connection::{connection_arguments, connection_output, parse_connection_arguments};
+use super::inputs::world_input::{parse_world_argument, world_argument};
+use super::{ObjectTrait, TypeMapping};
+use crate::constants::{
+    BOOLEAN_TRUE, ID_COLUMN, PERMISSION_KIND_TYPE_NAME, PERMISSION_NAMES, PERMISSION_TABLE,
+    PERMISSION_TYPE_NAME,
+};
+use crate::mapping::PERMISSION_TYPE_MAPPING;
+use crate::query::data::{count_rows, fetch_multiple_rows};
+use crate::query::filter::{Comparator, Filter, FilterValue};
+use crate::utils::{extract, normalize_felt};
+
+const KIND_OWNER: &str = "OWNER";
+const KIND_WRITER: &str = "WRITER";
+
+pub struct PermissionObject;
+
+impl ObjectTrait for PermissionObject {
+    fn name(&self) -> (&str, &str) {
+        PERMISSION_NAMES
+    }
+
+    fn type_name(&self) -> &str {
+        PERMISSION_TYPE_NAME
+    }
+
+    fn type_mapping(&self) -> &TypeMapping {
+        &PERMISSION_TYPE_MAPPING
+    }
+
+    fn table_name(&self) -> Option<&str> {
+        Some(PERMISSION_TABLE)
+    }
+
+    fn enum_objects(&self) -> Option<Vec<Enum>> {
+        Some(vec![Enum::new(PERMISSION_KIND_TYPE_NAME).item(KIND_OWNER).item(KIND_WRITER)])
+    }
+
+    fn resolve_many(&self) -> Option<Field> {
+        let mut field = Field::new(
+            self.name().1,
+            TypeRef::named(format!("{}Connection", self.type_name())),
+            |ctx| {
+                FieldFuture::new(async move {
+                    let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
+                    let connection = parse_connection_arguments(&ctx)?;
+                    let filters = parse_permission_arguments(&ctx)?;
+                    let total_count =
+                        count_rows(&mut conn, PERMISSION_TABLE, &None, &filters).await?;
+                    let (data, page_info) = fetch_multiple_rows(
+                        &mut conn,
+                        PERMISSION_TABLE,
+                        ID_COLUMN,
+                        &None,
+                        &None,
+                        &filters,
+                        &connection,
+                        total_count,
+                    )
+                    .await?;
+                    let results = connection_output(
+                        &data,
+                        &PERMISSION_TYPE_MAPPING,
+                        &None,
+                        ID_COLUMN,
+                        total_count,
+                        false,
+                        page_info,
+                    )?;
+
+                    Ok(Some(Value::Object(results)))
+                })
+            },
+        );
+
+        field = connection_arguments(field);
+        field = field
+            .argument(InputValue::new("kind", TypeRef::named(PERMISSION_KIND_TYPE_NAME)))
+            .argument(InputValue::new("resource", TypeRef::named(TypeRef::STRING)))
+            .argument(InputValue::new("address", TypeRef::named(TypeRef::STRING)))
+            .argument(InputValue::new("granted", TypeRef::named(TypeRef::BOOLEAN)));
+        field = world_argument(field);
+
+        Some(field)
+    }
+}
+
+// Permissions can be filtered by kind, resource, address, world and whether they are currently
+// granted, eg. the systems that can write a model are the granted writers of that model.
+fn parse_permission_arguments(ctx: &ResolverContext<'_>) -> Result<Option<Vec<Filter>>, Error> {
+    let mut filters = Vec::new();
+
+    if let Some(kind) = ctx.args.get("kind") {
+        let kind = match kind.enum_name()? {
+            KIND_OWNER => "Owner",
+            KIND_WRITER => "Writer",
+            kind => return Err(format!("Invalid permission kind `{kind}`").into()),
+        };
+        filters.push(equals("kind", FilterValue::String(kind.into())));
+    }
+
+    for column in ["resource", "address"] {
+        if let Ok(felt) = extract::<String>(ctx.args.as_index_map(), column) {
+            filters.push(equals(column, FilterValue::String(normalize_felt(&felt)?)));
+        }
+    }
+
+    if let Some(granted) = ctx.args.get("granted") {
+        let granted = if granted.boolean()? { BOOLEAN_TRUE } else { 0 };
+        filters.push(equals("granted", FilterValue::Int(granted)));
+    }
+
+    if let Some(world) = parse_world_argument(ctx)? {
+        filters.extend(world);
+    }
+
+    Ok(if filters.is_empty() { None } else { Some(filters) })
+}
+
+fn equals(field: &str, value: FilterValue) -> Filter {
+    Filter { field: field.into(), comparator: Comparator::Eq, value }
+}
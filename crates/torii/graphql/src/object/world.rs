@@ -0,0 +1,23 @@
+use super::{ObjectTrait, TypeMapping};
+use crate::constants::{WORLD_NAMES, WORLD_TABLE, WORLD_TYPE_NAME};
+use crate::mapping::WORLD_TYPE_MAPPING;
+
+pub struct WorldObject;
+
+impl ObjectTrait for WorldObject {
+    fn name(&self) -> (&str, &str) {
+        WORLD_NAMES
+    }
+
+    fn type_name(&self) -> &str {
+        WORLD_TYPE_NAME
+    }
+
+    fn type_mapping(&self) -> &TypeMapping {
+        &WORLD_TYPE_MAPPING
+    }
+
+    fn table_name(&self) -> Option<&str> {
+        Some(WORLD_TABLE)
+    }
+}
@@ -8,7 +8,9 @@ use sqlx::sqlite::SqliteRow;
 use sqlx::{Row, SqliteConnection};
 use torii_core::sql::FELT_DELIMITER;
 
-use crate::constants::{BOOLEAN_TRUE, ENTITY_ID_COLUMN, INTERNAL_ENTITY_ID_KEY};
+use crate::constants::{
+    BOOLEAN_TRUE, ENTITY_ID_COLUMN, EVENT_ID_COLUMN, INTERNAL_ENTITY_ID_KEY, INTERNAL_EVENT_ID_KEY,
+};
 use crate::object::model_data::ModelMember;
 use crate::types::{TypeData, TypeMapping, ValueMapping};
 
@@ -27,6 +29,29 @@ pub async fn type_mapping_query(
     build_type_mapping(&root_members, &nested_members)
 }
 
+/// Returns the type mapping of a custom event, whose members are primitives and enums.
+pub async fn custom_event_type_mapping(
+    conn: &mut SqliteConnection,
+    custom_event_id: &str,
+) -> sqlx::Result<TypeMapping> {
+    let members: Vec<(String, String, String)> = sqlx::query_as(
+        "SELECT name, type, type_enum FROM custom_event_members WHERE custom_event_id = ? ORDER \
+         BY member_idx",
+    )
+    .bind(custom_event_id)
+    .fetch_all(conn)
+    .await?;
+
+    Ok(members
+        .into_iter()
+        .map(|(name, ty, type_enum)| {
+            let type_ref =
+                if type_enum == "Enum" { TypeRef::named("Enum") } else { TypeRef::named(ty) };
+            (Name::new(name), TypeData::Simple(type_ref))
+        })
+        .collect())
+}
+
 async fn fetch_model_members(
     conn: &mut SqliteConnection,
     model_id: &str,
@@ -136,6 +161,11 @@ pub fn value_mapping_from_row(
         value_mapping.insert(Name::new(INTERNAL_ENTITY_ID_KEY), Value::from(entity_id));
     }
 
+    // event_id relates the values of a custom event to the event that emitted them
+    if let Ok(event_id) = row.try_get::<String, &str>(EVENT_ID_COLUMN) {
+        value_mapping.insert(Name::new(INTERNAL_EVENT_ID_KEY), Value::from(event_id));
+    }
+
     Ok(value_mapping)
 }
 
@@ -160,10 +190,10 @@ fn fetch_value(
         Ok(ty) => match ty.to_sql_type() {
             SqlType::Integer => row.try_get::<i64, &str>(&column_name).map(Value::from),
             SqlType::Text => Ok(remove_hex_leading_zeros(
-                row.try_get::<String, &str>(&column_name).map(Value::from)?,
+                row.try_get::<Option<String>, &str>(&column_name).map(Value::from)?,
             )),
         },
-        // fetch everything else as non-formated string
-        _ => Ok(row.try_get::<String, &str>(&column_name).map(Value::from)?),
+        // fetch everything else as non-formated string, nullable columns resolve to null
+        _ => Ok(row.try_get::<Option<String>, &str>(&column_name).map(Value::from)?),
     }
 }
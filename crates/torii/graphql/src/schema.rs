@@ -7,18 +7,23 @@ use sqlx::SqlitePool;
 use torii_core::types::Model;
 
 use super::object::connection::page_info::PageInfoObject;
+use super::object::custom_event::CustomEventObject;
 use super::object::entity::EntityObject;
+use super::object::entity_history::EntityHistoryObject;
 use super::object::event::EventObject;
 use super::object::model_data::ModelDataObject;
 use super::object::ObjectTrait;
 use super::types::ScalarType;
 use crate::constants::{QUERY_TYPE_NAME, SUBSCRIPTION_TYPE_NAME};
+use crate::object::contract::ContractObject;
 use crate::object::metadata::content::ContentObject;
 use crate::object::metadata::social::SocialObject;
 use crate::object::metadata::MetadataObject;
 use crate::object::model::ModelObject;
+use crate::object::permission::PermissionObject;
 use crate::object::transaction::TransactionObject;
-use crate::query::type_mapping_query;
+use crate::object::world::WorldObject;
+use crate::query::{custom_event_type_mapping, type_mapping_query};
 
 // The graphql schema is built dynamically at runtime, this is because we won't know the schema of
 // the models until runtime. There are however, predefined objects such as entities and
@@ -117,8 +122,18 @@ async fn build_objects(pool: &SqlitePool) -> Result<(Vec<Box<dyn ObjectTrait>>,
         Box::new(ModelObject),
         Box::new(PageInfoObject),
         Box::new(TransactionObject),
+        Box::new(ContractObject),
+        Box::new(PermissionObject),
+        Box::new(WorldObject),
     ];
 
+    // models whose changes are recorded in a history table
+    let historical: Vec<String> =
+        sqlx::query_scalar("SELECT id FROM models WHERE historical").fetch_all(&mut *conn).await?;
+    if !historical.is_empty() {
+        objects.push(Box::new(EntityHistoryObject::new(&historical)));
+    }
+
     // model union object
     let mut union = Union::new("ModelUnion");
 
@@ -132,7 +147,25 @@ async fn build_objects(pool: &SqlitePool) -> Result<(Vec<Box<dyn ObjectTrait>>,
 
             union = union.possible_type(&type_name);
 
-            objects.push(Box::new(ModelDataObject::new(field_name, type_name, type_mapping)));
+            objects.push(Box::new(ModelDataObject::new(
+                field_name,
+                type_name,
+                type_mapping,
+                historical.contains(&model.id),
+            )));
+        }
+    }
+
+    // custom event objects, named after their table
+    let custom_events: Vec<String> =
+        sqlx::query_scalar("SELECT id FROM custom_events").fetch_all(&mut *conn).await?;
+    for custom_event in custom_events {
+        let type_mapping = custom_event_type_mapping(&mut conn, &custom_event).await?;
+
+        if !type_mapping.is_empty() {
+            let field_name = custom_event.to_case(Case::Camel);
+
+            objects.push(Box::new(CustomEventObject::new(field_name, custom_event, type_mapping)));
         }
     }
 
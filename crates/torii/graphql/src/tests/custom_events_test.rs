@@ -0,0 +1,93 @@
+#[cfg(test)]
+mod tests {
+    use dojo_types::primitive::Primitive;
+    use dojo_types::schema::{Member, Struct, Ty};
+    use serde_json::json;
+    use sqlx::SqlitePool;
+    use starknet::core::types::Event;
+    use starknet_crypto::FieldElement;
+    use torii_core::sql::Sql;
+
+    use crate::schema::build_schema;
+    use crate::tests::run_graphql_query;
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_custom_events(pool: SqlitePool) {
+        let mut db = Sql::new(pool.clone(), FieldElement::ZERO).await.unwrap();
+
+        let moved = Ty::Struct(Struct {
+            name: "Moved".into(),
+            children: vec![
+                Member {
+                    name: "player".into(),
+                    key: true,
+                    ty: Ty::Primitive(Primitive::ContractAddress(None)),
+                },
+                Member {
+                    name: "steps".into(),
+                    key: false,
+                    ty: Ty::Primitive(Primitive::U32(None)),
+                },
+            ],
+        });
+        db.register_custom_event(&moved).await.unwrap();
+
+        for steps in 1..=3_u8 {
+            let event_id = format!("0x{:064x}:0x0000:0x0000", steps);
+            let player = FieldElement::from(0xa_u8 + steps);
+            let raw = Event { from_address: FieldElement::ZERO, keys: vec![player], data: vec![] };
+            db.store_event(&event_id, &raw, FieldElement::from(steps));
+
+            let mut event = moved.clone();
+            event.deserialize(&mut vec![player, FieldElement::from(steps)]).unwrap();
+            db.set_custom_event(&event, &event_id).await.unwrap();
+        }
+
+        let schema = build_schema(&pool).await.unwrap();
+        let result = run_graphql_query(
+            &schema,
+            r#"
+              {
+                movedEvents(where: { stepsGTE: 2 }, order: { field: STEPS, direction: DESC }) {
+                  totalCount
+                  edges {
+                    node {
+                      player
+                      steps
+                      event {
+                        transactionHash
+                      }
+                    }
+                  }
+                }
+              }
+            "#,
+        )
+        .await;
+
+        assert_eq!(
+            result,
+            json!({
+                "movedEvents": {
+                    "totalCount": 2,
+                    "edges": [
+                        {
+                            "node": {
+                                "player": "0xd",
+                                "steps": 3,
+                                "event": { "transactionHash": "0x3" }
+                            }
+                        },
+                        {
+                            "node": {
+                                "player": "0xc",
+                                "steps": 2,
+                                "event": { "transactionHash": "0x2" }
+                            }
+                        }
+                    ]
+                }
+            })
+        );
+    }
+}
@@ -3,8 +3,12 @@ mod tests {
 
     use anyhow::Result;
     use async_graphql::dynamic::Schema;
-    use serde_json::Value;
+    use dojo_types::primitive::Primitive;
+    use dojo_types::schema::{Member, Struct, Ty};
+    use serde_json::{json, Value};
+    use sqlx::SqlitePool;
     use starknet_crypto::{poseidon_hash_many, FieldElement};
+    use torii_core::sql::Sql;
 
     use crate::schema::build_schema;
     use crate::tests::{
@@ -238,4 +242,57 @@ mod tests {
 
         Ok(())
     }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_entities_world_filter(pool: SqlitePool) {
+        let position = |player: u32| {
+            Ty::Struct(Struct {
+                name: "Position".into(),
+                children: vec![
+                    Member {
+                        name: "player".into(),
+                        key: true,
+                        ty: Ty::Primitive(Primitive::ContractAddress(Some(player.into()))),
+                    },
+                    Member {
+                        name: "x".into(),
+                        key: false,
+                        ty: Ty::Primitive(Primitive::U32(Some(player))),
+                    },
+                ],
+            })
+        };
+
+        let mut db = Sql::new(pool.clone(), FieldElement::ONE).await.unwrap();
+        let mut season =
+            Sql::with_namespace(pool.clone(), FieldElement::TWO, "season2").await.unwrap();
+        for db in [&mut db, &mut season] {
+            db.register_model(position(0), vec![], FieldElement::ONE, 0, 0).await.unwrap();
+        }
+
+        // Player `1` is in both worlds, player `2` only in the second one.
+        db.set_entity(position(1), "0x1").await.unwrap();
+        season.set_entity(position(1), "0x2").await.unwrap();
+        season.set_entity(position(2), "0x3").await.unwrap();
+        db.execute().await.unwrap();
+        season.execute().await.unwrap();
+
+        let schema = build_schema(&pool).await.unwrap();
+        let keys = |player: u32| vec![format!("{:#x}", FieldElement::from(player))];
+
+        let entities = entities_query(&schema, r#"(world: "0x1")"#).await;
+        assert_eq!(entities["totalCount"], json!(1));
+        assert_eq!(entities["edges"][0]["node"]["keys"], json!(keys(1)));
+
+        let entities = entities_query(&schema, r#"(world: "0x2")"#).await;
+        assert_eq!(entities["totalCount"], json!(2));
+        assert_eq!(entities["edges"][0]["node"]["keys"], json!(keys(2)));
+        assert_eq!(entities["edges"][1]["node"]["keys"], json!(keys(1)));
+
+        let entities = entities_query(&schema, r#"(world: "0x3")"#).await;
+        assert_eq!(entities["totalCount"], json!(0));
+
+        let entities = entities_query(&schema, "").await;
+        assert_eq!(entities["totalCount"], json!(2));
+    }
 }
@@ -0,0 +1,127 @@
+#[cfg(test)]
+mod tests {
+    use dojo_types::primitive::Primitive;
+    use dojo_types::schema::{Member, Struct, Ty};
+    use serde_json::json;
+    use sqlx::SqlitePool;
+    use starknet::core::types::Event;
+    use starknet_crypto::{poseidon_hash_many, FieldElement};
+    use torii_core::sql::Sql;
+
+    use crate::schema::build_schema;
+    use crate::tests::run_graphql_query;
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_entity_history(pool: SqlitePool) {
+        let mut db = Sql::new(pool.clone(), FieldElement::ZERO).await.unwrap();
+        db.set_historical(&["Position".to_string()]).await.unwrap();
+
+        let player = FieldElement::from(0x5678_u32);
+        let position = |x: u32| {
+            Ty::Struct(Struct {
+                name: "Position".into(),
+                children: vec![
+                    Member {
+                        name: "player".into(),
+                        key: true,
+                        ty: Ty::Primitive(Primitive::ContractAddress(Some(player))),
+                    },
+                    Member {
+                        name: "x".into(),
+                        key: false,
+                        ty: Ty::Primitive(Primitive::U32(Some(x))),
+                    },
+                ],
+            })
+        };
+
+        db.set_block(1, FieldElement::ONE, FieldElement::ZERO);
+        db.register_model(position(0), vec![], FieldElement::ONE, 0, 0).await.unwrap();
+        for block in 1..=2_u32 {
+            if block > 1 {
+                db.set_block(block.into(), block.into(), (block - 1).into());
+            }
+
+            let event_id = format!("0x{:064x}:0x0000:0x0000", block);
+            let raw = Event { from_address: FieldElement::ZERO, keys: vec![], data: vec![] };
+            db.store_event(&event_id, &raw, block.into());
+            db.set_entity(position(block), &event_id).await.unwrap();
+        }
+        db.execute().await.unwrap();
+
+        let entity_id = format!("{:#x}", poseidon_hash_many(&[player]));
+        let schema = build_schema(&pool).await.unwrap();
+        let result = run_graphql_query(
+            &schema,
+            &format!(
+                r#"
+                  {{
+                    positionModels(atBlock: 1) {{
+                      edges {{
+                        node {{
+                          x
+                        }}
+                      }}
+                    }}
+                    entityHistory(id: "{entity_id}") {{
+                      totalCount
+                      edges {{
+                        node {{
+                          blockNumber
+                          transactionHash
+                          deleted
+                          model {{
+                            ... on Position {{
+                              x
+                            }}
+                          }}
+                        }}
+                      }}
+                    }}
+                  }}
+                "#
+            ),
+        )
+        .await;
+
+        assert_eq!(
+            result,
+            json!({
+                "positionModels": {
+                    "edges": [{ "node": { "x": 1 } }]
+                },
+                "entityHistory": {
+                    "totalCount": 2,
+                    "edges": [
+                        {
+                            "node": {
+                                "blockNumber": 2,
+                                "transactionHash": "0x2",
+                                "deleted": false,
+                                "model": { "x": 2 }
+                            }
+                        },
+                        {
+                            "node": {
+                                "blockNumber": 1,
+                                "transactionHash": "0x1",
+                                "deleted": false,
+                                "model": { "x": 1 }
+                            }
+                        }
+                    ]
+                }
+            })
+        );
+
+        // A block number that can't be parsed is rejected, rather than ignored.
+        for query in [
+            "{ positionModels(atBlock: -1) { totalCount } }".to_string(),
+            format!(r#"{{ entityHistory(id: "{entity_id}", atBlock: -1) {{ totalCount }} }}"#),
+        ] {
+            let res = schema.execute(query.as_str()).await;
+            assert_eq!(res.errors.len(), 1, "{query}");
+            assert_eq!(res.errors[0].message, "`atBlock` must be a non-negative block number");
+        }
+    }
+}
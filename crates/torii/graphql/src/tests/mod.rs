@@ -27,13 +27,17 @@ use tokio::sync::broadcast;
 use tokio_stream::StreamExt;
 use torii_core::engine::{Engine, EngineConfig, Processors};
 use torii_core::processors::register_model::RegisterModelProcessor;
+use torii_core::processors::store_del_record::StoreDelRecordProcessor;
 use torii_core::processors::store_set_record::StoreSetRecordProcessor;
 use torii_core::sql::Sql;
 
+mod custom_events_test;
 mod entities_test;
+mod entity_history_test;
 mod metadata_test;
 mod models_ordering_test;
 mod models_test;
+mod permissions_test;
 mod subscription_test;
 
 use crate::schema::build_schema;
@@ -291,7 +295,11 @@ pub async fn spinup_types_test() -> Result<SqlitePool> {
         &mut db,
         &provider,
         Processors {
-            event: vec![Box::new(RegisterModelProcessor), Box::new(StoreSetRecordProcessor)],
+            event: vec![
+                Box::new(RegisterModelProcessor),
+                Box::new(StoreSetRecordProcessor),
+                Box::new(StoreDelRecordProcessor),
+            ],
             ..Processors::default()
         },
         EngineConfig::default(),
@@ -0,0 +1,144 @@
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use sqlx::SqlitePool;
+    use starknet_crypto::FieldElement;
+    use torii_core::sql::Sql;
+    use torii_core::types::PermissionKind;
+
+    use crate::schema::build_schema;
+    use crate::tests::run_graphql_query;
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_permissions(pool: SqlitePool) {
+        let mut db = Sql::new(pool.clone(), FieldElement::ZERO).await.unwrap();
+        let schema = build_schema(&pool).await.unwrap();
+
+        let model = FieldElement::from(0x1_u8);
+        let (system_a, system_b) = (FieldElement::from(0xa_u8), FieldElement::from(0xb_u8));
+        db.set_permission(PermissionKind::Writer, model, system_a, true, FieldElement::ONE);
+        db.set_permission(PermissionKind::Writer, model, system_b, true, FieldElement::ONE);
+        db.set_permission(PermissionKind::Writer, model, system_b, false, FieldElement::TWO);
+        db.set_permission(PermissionKind::Owner, model, system_b, true, FieldElement::TWO);
+        db.execute().await.unwrap();
+
+        let result = run_graphql_query(
+            &schema,
+            r#"
+              {
+                permissions(kind: WRITER, resource: "0x0001", granted: true) {
+                  totalCount
+                  edges {
+                    node {
+                      kind
+                      resource
+                      address
+                      granted
+                    }
+                  }
+                }
+              }
+            "#,
+        )
+        .await;
+
+        assert_eq!(
+            result,
+            json!({
+                "permissions": {
+                    "totalCount": 1,
+                    "edges": [{
+                        "node": {
+                            "kind": "Writer",
+                            "resource": "0x1",
+                            "address": "0xa",
+                            "granted": true
+                        }
+                    }]
+                }
+            })
+        );
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_contracts(pool: SqlitePool) {
+        let mut db = Sql::new(pool.clone(), FieldElement::ZERO).await.unwrap();
+        let schema = build_schema(&pool).await.unwrap();
+
+        let address = FieldElement::from(0xc_u8);
+        db.set_contract_deployed(
+            address,
+            FieldElement::ONE,
+            FieldElement::TWO,
+            0,
+            FieldElement::ONE,
+        );
+        db.set_contract_upgraded(address, FieldElement::THREE, 60, FieldElement::TWO);
+        db.execute().await.unwrap();
+
+        let result = run_graphql_query(
+            &schema,
+            r#"
+              {
+                contract(id: "0xc") {
+                  classHash
+                  salt
+                  deployedAt
+                  upgradedAt
+                }
+              }
+            "#,
+        )
+        .await;
+
+        assert_eq!(
+            result,
+            json!({
+                "contract": {
+                    "classHash": "0x3",
+                    "salt": "0x2",
+                    "deployedAt": "1970-01-01 00:00:00",
+                    "upgradedAt": "1970-01-01 00:01:00"
+                }
+            })
+        );
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_worlds(pool: SqlitePool) {
+        Sql::new(pool.clone(), FieldElement::ZERO).await.unwrap();
+        Sql::with_namespace(pool.clone(), FieldElement::ONE, "season2").await.unwrap();
+        let schema = build_schema(&pool).await.unwrap();
+
+        let result = run_graphql_query(
+            &schema,
+            r#"
+              {
+                worlds {
+                  totalCount
+                  edges {
+                    node {
+                      worldAddress
+                      namespace
+                    }
+                  }
+                }
+              }
+            "#,
+        )
+        .await;
+
+        assert_eq!(
+            result,
+            json!({
+                "worlds": {
+                    "totalCount": 2,
+                    "edges": [
+                        { "node": { "worldAddress": "0x1", "namespace": "season2" } },
+                        { "node": { "worldAddress": "0x0", "namespace": "" } }
+                    ]
+                }
+            })
+        );
+    }
+}
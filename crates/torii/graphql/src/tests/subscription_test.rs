@@ -236,6 +236,88 @@ mod tests {
         rx.recv().await.unwrap();
     }
 
+    #[sqlx::test(migrations = "../migrations")]
+    #[serial]
+    async fn test_entity_deleted_subscription(pool: SqlitePool) {
+        let mut db = Sql::new(pool.clone(), FieldElement::ZERO).await.unwrap();
+
+        model_fixtures(&mut db).await;
+        // 0. Preprocess expected entity value
+        let model_name = "Record".to_string();
+        let key = vec![FieldElement::ONE];
+        let entity_id = format!("{:#x}", poseidon_hash_many(&key));
+        let keys_str = key.iter().map(|k| format!("{:#x}", k)).collect::<Vec<String>>().join(",");
+        let expected_value: async_graphql::Value = value!({
+            "entityDeleted": {
+                "id": entity_id,
+                "keys": vec![keys_str],
+                "models": []
+            }
+        });
+        let (tx, mut rx) = mpsc::channel(10);
+
+        tokio::spawn(async move {
+            // 1. Open process and sleep.Go to execute subscription
+            tokio::time::sleep(Duration::from_secs(1)).await;
+
+            // Set entity with one Record model, then delete it
+            db.set_entity(
+                Ty::Struct(Struct {
+                    name: model_name.clone(),
+                    children: vec![
+                        Member {
+                            name: "type_felt".to_string(),
+                            key: false,
+                            ty: Ty::Primitive(Primitive::Felt252(Some(FieldElement::from(1u128)))),
+                        },
+                        Member {
+                            name: "typeContractAddress".to_string(),
+                            key: true,
+                            ty: Ty::Primitive(Primitive::ContractAddress(Some(FieldElement::ONE))),
+                        },
+                    ],
+                }),
+                &format!("0x{:064x}:0x{:04x}:0x{:04x}", 0, 0, 0),
+            )
+            .await
+            .unwrap();
+
+            db.delete_entity(
+                &model_name,
+                &[FieldElement::ONE],
+                &format!("0x{:064x}:0x{:04x}:0x{:04x}", 0, 0, 1),
+            )
+            .await
+            .unwrap();
+
+            tx.send(()).await.unwrap();
+        });
+
+        // 2. The subscription is executed and it is listening, waiting for publish() to be executed
+        let response_value = run_graphql_subscription(
+            &pool,
+            r#"subscription {
+                entityDeleted {
+                    id
+                    keys
+                    models {
+                        __typename
+                    }
+                }
+            }"#,
+        )
+        .await;
+        // 3. The subscription has received the message from publish()
+        assert_eq!(expected_value, response_value);
+        rx.recv().await.unwrap();
+
+        // 4. The entity and its model row are gone
+        let entities = sqlx::query("SELECT * FROM entities").fetch_all(&pool).await.unwrap();
+        assert!(entities.is_empty());
+        let records = sqlx::query("SELECT * FROM [Record]").fetch_all(&pool).await.unwrap();
+        assert!(records.is_empty());
+    }
+
     #[sqlx::test(migrations = "../migrations")]
     #[serial]
     async fn test_model_subscription(pool: SqlitePool) {
@@ -11,7 +11,9 @@ impl ExtractFromIndexMap for u64 {
     fn extract(indexmap: &ValueMapping, input: &str) -> Result<Self, ExtractError> {
         let value = indexmap.get(input).ok_or_else(|| ExtractError::NotFound(input.to_string()))?;
         match value {
-            Value::Number(n) => Ok(n.as_u64().unwrap()),
+            Value::Number(n) => {
+                n.as_u64().ok_or_else(|| ExtractError::NotNumber(input.to_string()))
+            }
             _ => Err(ExtractError::NotNumber(input.to_string())),
         }
     }
@@ -45,3 +47,13 @@ pub fn extract<T: ExtractFromIndexMap>(
 ) -> Result<T, ExtractError> {
     T::extract(values, key)
 }
+
+// Felts are stored as hex strings without leading zeros.
+pub fn normalize_felt(felt: &str) -> Result<String> {
+    let hex = felt.strip_prefix("0x").unwrap_or(felt);
+    if hex.is_empty() || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("Invalid felt `{felt}`, expected a hex string").into());
+    }
+
+    Ok(format!("0x{:0>1}", hex.trim_start_matches('0').to_lowercase()))
+}
@@ -7,9 +7,11 @@ use starknet::core::types::{FromByteSliceError, FromStrError, StateUpdate};
 use starknet_crypto::FieldElement;
 
 use crate::proto::world::{
-    world_client, MetadataRequest, RetrieveEntitiesRequest, RetrieveEntitiesResponse,
-    SubscribeEntitiesRequest, SubscribeEntityResponse, SubscribeModelsRequest,
-    SubscribeModelsResponse,
+    world_client, MetadataRequest, RetrieveContractsRequest, RetrieveContractsResponse,
+    RetrieveEntitiesRequest, RetrieveEntitiesResponse, RetrieveEntityHistoryRequest,
+    RetrieveEntityHistoryResponse, RetrievePermissionsRequest, RetrievePermissionsResponse,
+    RetrieveWorldsRequest, RetrieveWorldsResponse, SubscribeEntitiesRequest,
+    SubscribeEntityResponse, SubscribeModelsRequest, SubscribeModelsResponse,
 };
 use crate::types::schema::Entity;
 use crate::types::{KeysClause, Query};
@@ -36,7 +38,7 @@ pub enum Error {
 
 /// A lightweight wrapper around the grpc client.
 pub struct WorldClient {
-    _world_address: FieldElement,
+    world_address: FieldElement,
     #[cfg(not(target_arch = "wasm32"))]
     inner: world_client::WorldClient<tonic::transport::Channel>,
     #[cfg(target_arch = "wasm32")]
@@ -45,22 +47,22 @@ pub struct WorldClient {
 
 impl WorldClient {
     #[cfg(not(target_arch = "wasm32"))]
-    pub async fn new<D>(dst: D, _world_address: FieldElement) -> Result<Self, Error>
+    pub async fn new<D>(dst: D, world_address: FieldElement) -> Result<Self, Error>
     where
         D: TryInto<tonic::transport::Endpoint>,
         D::Error: Into<Box<(dyn std::error::Error + Send + Sync + 'static)>>,
     {
         Ok(Self {
-            _world_address,
+            world_address,
             inner: world_client::WorldClient::connect(dst).await.map_err(Error::Transport)?,
         })
     }
 
     // we make this function async so that we can keep the function signature similar
     #[cfg(target_arch = "wasm32")]
-    pub async fn new(endpoint: String, _world_address: FieldElement) -> Result<Self, Error> {
+    pub async fn new(endpoint: String, world_address: FieldElement) -> Result<Self, Error> {
         Ok(Self {
-            _world_address,
+            world_address,
             inner: world_client::WorldClient::new(tonic_web_wasm_client::Client::new(endpoint)),
         })
     }
@@ -68,7 +70,7 @@ impl WorldClient {
     /// Retrieve the metadata of the World.
     pub async fn metadata(&mut self) -> Result<dojo_types::WorldMetadata, Error> {
         self.inner
-            .world_metadata(MetadataRequest {})
+            .world_metadata(MetadataRequest { world_address: format!("{:#x}", self.world_address) })
             .await
             .map_err(Error::Grpc)
             .and_then(|res| res.into_inner().metadata.ok_or(Error::MissingExpectedData))
@@ -79,10 +81,94 @@ impl WorldClient {
         &mut self,
         query: Query,
     ) -> Result<RetrieveEntitiesResponse, Error> {
-        let request = RetrieveEntitiesRequest { query: Some(query.into()) };
+        let request = RetrieveEntitiesRequest {
+            query: Some(query.into()),
+            world_address: format!("{:#x}", self.world_address),
+            at_block: 0,
+        };
         self.inner.retrieve_entities(request).await.map_err(Error::Grpc).map(|res| res.into_inner())
     }
 
+    /// Retrieve the entities as they were at the block `at_block`, from the history of the
+    /// World's historical models. Only hashed keys clauses are supported.
+    pub async fn retrieve_entities_at_block(
+        &mut self,
+        query: Query,
+        at_block: u64,
+    ) -> Result<RetrieveEntitiesResponse, Error> {
+        let request = RetrieveEntitiesRequest {
+            query: Some(query.into()),
+            world_address: format!("{:#x}", self.world_address),
+            at_block,
+        };
+        self.inner.retrieve_entities(request).await.map_err(Error::Grpc).map(|res| res.into_inner())
+    }
+
+    /// Retrieve the contracts deployed and upgraded through the World, all of them if
+    /// `addresses` is empty.
+    pub async fn retrieve_contracts(
+        &mut self,
+        addresses: Vec<FieldElement>,
+    ) -> Result<RetrieveContractsResponse, Error> {
+        let addresses = addresses.iter().map(|address| format!("{address:#x}")).collect();
+        let request = RetrieveContractsRequest { addresses };
+        self.inner
+            .retrieve_contracts(request)
+            .await
+            .map_err(Error::Grpc)
+            .map(|res| res.into_inner())
+    }
+
+    /// Retrieve the permissions currently granted on the resources of the World, optionally
+    /// restricted to a resource and an address.
+    pub async fn retrieve_permissions(
+        &mut self,
+        resource: Option<FieldElement>,
+        address: Option<FieldElement>,
+    ) -> Result<RetrievePermissionsResponse, Error> {
+        let request = RetrievePermissionsRequest {
+            resource: resource.map(|resource| format!("{resource:#x}")).unwrap_or_default(),
+            address: address.map(|address| format!("{address:#x}")).unwrap_or_default(),
+        };
+        self.inner
+            .retrieve_permissions(request)
+            .await
+            .map_err(Error::Grpc)
+            .map(|res| res.into_inner())
+    }
+
+    /// Retrieve the changes of an entity's historical models, from the most recent one, up to
+    /// the block `at_block` if given.
+    pub async fn retrieve_entity_history(
+        &mut self,
+        hashed_keys: FieldElement,
+        at_block: Option<u64>,
+        limit: u32,
+        offset: u32,
+    ) -> Result<RetrieveEntityHistoryResponse, Error> {
+        let request = RetrieveEntityHistoryRequest {
+            hashed_keys: hashed_keys.to_bytes_be().to_vec(),
+            world_address: format!("{:#x}", self.world_address),
+            at_block: at_block.unwrap_or_default(),
+            limit,
+            offset,
+        };
+        self.inner
+            .retrieve_entity_history(request)
+            .await
+            .map_err(Error::Grpc)
+            .map(|res| res.into_inner())
+    }
+
+    /// Retrieve the metadata of all the worlds indexed by the server, the World included.
+    pub async fn retrieve_worlds(&mut self) -> Result<RetrieveWorldsResponse, Error> {
+        self.inner
+            .retrieve_worlds(RetrieveWorldsRequest {})
+            .await
+            .map_err(Error::Grpc)
+            .map(|res| res.into_inner())
+    }
+
     /// Subscribe to entities updates of a World.
     pub async fn subscribe_entities(
         &mut self,
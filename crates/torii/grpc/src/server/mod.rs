@@ -1,6 +1,7 @@
 pub mod logger;
 pub mod subscriptions;
 
+use std::collections::HashMap;
 use std::future::Future;
 use std::net::SocketAddr;
 use std::pin::Pin;
@@ -10,8 +11,10 @@ use std::sync::Arc;
 use dojo_types::schema::Ty;
 use futures::Stream;
 use proto::world::{
-    MetadataRequest, MetadataResponse, RetrieveEntitiesRequest, RetrieveEntitiesResponse,
-    SubscribeModelsRequest, SubscribeModelsResponse,
+    MetadataRequest, MetadataResponse, RetrieveContractsRequest, RetrieveContractsResponse,
+    RetrieveEntitiesRequest, RetrieveEntitiesResponse, RetrieveEntityHistoryRequest,
+    RetrieveEntityHistoryResponse, RetrievePermissionsRequest, RetrievePermissionsResponse,
+    RetrieveWorldsRequest, RetrieveWorldsResponse, SubscribeModelsRequest, SubscribeModelsResponse,
 };
 use sqlx::sqlite::SqliteRow;
 use sqlx::{Pool, Row, Sqlite};
@@ -27,6 +30,7 @@ use tonic::{Request, Response, Status};
 use torii_core::cache::ModelCache;
 use torii_core::error::{Error, ParseError, QueryError};
 use torii_core::model::{build_sql_query, map_row_to_ty};
+use torii_core::sql::FELT_DELIMITER;
 
 use self::subscriptions::entity::EntityManager;
 use self::subscriptions::model_diff::{ModelDiffRequest, StateDiffManager};
@@ -34,11 +38,16 @@ use crate::proto::types::clause::ClauseType;
 use crate::proto::world::world_server::WorldServer;
 use crate::proto::world::{SubscribeEntitiesRequest, SubscribeEntityResponse};
 use crate::proto::{self};
-use crate::types::ComparisonOperator;
+use crate::types::{ComparisonOperator, LogicalOperator};
+
+#[cfg(test)]
+#[path = "server_test.rs"]
+mod test;
 
 #[derive(Clone)]
 pub struct DojoWorld {
     pool: Pool<Sqlite>,
+    /// The world served when requests don't specify one.
     world_address: FieldElement,
     model_cache: Arc<ModelCache>,
     entity_manager: Arc<EntityManager>,
@@ -46,9 +55,11 @@ pub struct DojoWorld {
 }
 
 impl DojoWorld {
+    /// Creates the service of the worlds indexed into `pool`, where `block_rxs` receives the
+    /// blocks indexed for each world, to publish their model diffs to the subscribers.
     pub fn new(
         pool: Pool<Sqlite>,
-        block_rx: Receiver<u64>,
+        block_rxs: Vec<(FieldElement, Receiver<u64>)>,
         world_address: FieldElement,
         provider: Arc<JsonRpcClient<HttpTransport>>,
     ) -> Self {
@@ -56,12 +67,14 @@ impl DojoWorld {
         let entity_manager = Arc::new(EntityManager::default());
         let state_diff_manager = Arc::new(StateDiffManager::default());
 
-        tokio::task::spawn(subscriptions::model_diff::Service::new_with_block_rcv(
-            block_rx,
-            world_address,
-            provider,
-            Arc::clone(&state_diff_manager),
-        ));
+        for (world_address, block_rx) in block_rxs {
+            tokio::task::spawn(subscriptions::model_diff::Service::new_with_block_rcv(
+                block_rx,
+                world_address,
+                Arc::clone(&provider),
+                Arc::clone(&state_diff_manager),
+            ));
+        }
 
         tokio::task::spawn(subscriptions::entity::Service::new(
             pool.clone(),
@@ -74,23 +87,38 @@ impl DojoWorld {
 }
 
 impl DojoWorld {
-    pub async fn metadata(&self) -> Result<proto::types::WorldMetadata, Error> {
-        let (world_address, world_class_hash, executor_address, executor_class_hash): (
+    /// Returns the world a request is made to, the default world if its address is empty.
+    fn world(&self, world_address: &str) -> Result<FieldElement, Error> {
+        if world_address.is_empty() {
+            return Ok(self.world_address);
+        }
+
+        Ok(FieldElement::from_str(world_address).map_err(ParseError::FromStr)?)
+    }
+
+    pub async fn metadata(
+        &self,
+        world_address: FieldElement,
+    ) -> Result<proto::types::WorldMetadata, Error> {
+        let (world_address, world_class_hash, executor_address, executor_class_hash, namespace): (
             String,
             String,
             String,
             String,
-        ) = sqlx::query_as(&format!(
-            "SELECT world_address, world_class_hash, executor_address, executor_class_hash FROM \
-             worlds WHERE id = '{:#x}'",
-            self.world_address
-        ))
+            String,
+        ) = sqlx::query_as(
+            "SELECT world_address, world_class_hash, executor_address, executor_class_hash, \
+             namespace FROM worlds WHERE id = ?",
+        )
+        .bind(format!("{world_address:#x}"))
         .fetch_one(&self.pool)
         .await?;
 
         let models: Vec<(String, String, u32, u32, String)> = sqlx::query_as(
-            "SELECT name, class_hash, packed_size, unpacked_size, layout FROM models",
+            "SELECT name, class_hash, packed_size, unpacked_size, layout FROM models WHERE \
+             world_address = ?",
         )
+        .bind(&world_address)
         .fetch_all(&self.pool)
         .await?;
 
@@ -113,55 +141,82 @@ impl DojoWorld {
             executor_address,
             executor_class_hash,
             models: models_metadata,
+            namespace,
         })
     }
 
+    /// Returns the metadata of the worlds indexed into the database, from the first indexed.
+    pub async fn worlds(&self) -> Result<Vec<proto::types::WorldMetadata>, Error> {
+        let world_addresses: Vec<String> =
+            sqlx::query_scalar("SELECT id FROM worlds ORDER BY created_at, id")
+                .fetch_all(&self.pool)
+                .await?;
+
+        let mut worlds = Vec::with_capacity(world_addresses.len());
+        for world_address in world_addresses {
+            let world_address =
+                FieldElement::from_str(&world_address).map_err(ParseError::FromStr)?;
+            worlds.push(self.metadata(world_address).await?);
+        }
+
+        Ok(worlds)
+    }
+
     async fn entities_all(
         &self,
+        world_address: FieldElement,
         limit: u32,
         offset: u32,
     ) -> Result<Vec<proto::types::Entity>, Error> {
-        self.entities_by_hashed_keys(None, limit, offset).await
+        self.entities_by_hashed_keys(world_address, None, limit, offset).await
     }
 
     async fn entities_by_hashed_keys(
         &self,
+        world_address: FieldElement,
         hashed_keys: Option<proto::types::HashedKeysClause>,
         limit: u32,
         offset: u32,
     ) -> Result<Vec<proto::types::Entity>, Error> {
-        // TODO: use prepared statement for where clause
-        let filter_ids = match hashed_keys {
-            Some(hashed_keys) => {
-                let ids = hashed_keys
-                    .hashed_keys
-                    .iter()
-                    .map(|id| {
-                        Ok(FieldElement::from_byte_slice_be(id)
-                            .map(|id| format!("entities.id = '{id:#x}'"))
-                            .map_err(ParseError::FromByteSliceError)?)
-                    })
-                    .collect::<Result<Vec<_>, Error>>()?;
-
-                format!("WHERE {}", ids.join(" OR "))
-            }
-            None => String::new(),
+        let mut bind_values = Vec::new();
+        let condition = match hashed_keys {
+            Some(hashed_keys) => hashed_keys_condition(&hashed_keys, &mut bind_values)?,
+            None => "TRUE".to_string(),
         };
 
+        self.entities_where(world_address, &condition, bind_values, limit, offset).await
+    }
+
+    /// Returns the entities matching `condition`, a condition on the `entities` table whose
+    /// parameters are bound to `bind_values`, from the most recently updated. Only the entities
+    /// having models of the world are returned, with the models of the world.
+    async fn entities_where(
+        &self,
+        world_address: FieldElement,
+        condition: &str,
+        bind_values: Vec<String>,
+        limit: u32,
+        offset: u32,
+    ) -> Result<Vec<proto::types::Entity>, Error> {
         let query = format!(
             r#"
             SELECT entities.id, group_concat(entity_model.model_id) as model_names
             FROM entities
             JOIN entity_model ON entities.id = entity_model.entity_id
-            {filter_ids}
+            JOIN models ON entity_model.model_id = models.id
+            WHERE models.world_address = ? AND {condition}
             GROUP BY entities.id
             ORDER BY entities.event_id DESC
             LIMIT ? OFFSET ?
          "#
         );
 
-        let db_entities: Vec<(String, String)> =
-            sqlx::query_as(&query).bind(limit).bind(offset).fetch_all(&self.pool).await?;
+        let mut db_entities =
+            sqlx::query_as::<_, (String, String)>(&query).bind(format!("{world_address:#x}"));
+        for value in bind_values {
+            db_entities = db_entities.bind(value);
+        }
+        let db_entities = db_entities.bind(limit).bind(offset).fetch_all(&self.pool).await?;
 
         let mut entities = Vec::with_capacity(db_entities.len());
         for (entity_id, models_str) in db_entities {
@@ -171,144 +226,38 @@ impl DojoWorld {
             let entity_query = format!("{} WHERE entities.id = ?", build_sql_query(&schemas)?);
             let row = sqlx::query(&entity_query).bind(&entity_id).fetch_one(&self.pool).await?;
 
-            let models = schemas
-                .iter()
-                .map(|s| {
-                    let mut struct_ty = s.as_struct().expect("schema should be struct").to_owned();
-                    map_row_to_ty(&s.name(), &mut struct_ty, &row)?;
-
-                    Ok(struct_ty.try_into().unwrap())
-                })
-                .collect::<Result<Vec<_>, Error>>()?;
-
-            let hashed_keys = FieldElement::from_str(&entity_id).map_err(ParseError::FromStr)?;
-            entities.push(proto::types::Entity {
-                hashed_keys: hashed_keys.to_bytes_be().to_vec(),
-                models,
-            })
+            entities.push(Self::map_row_to_entity(&row, &schemas)?);
         }
 
         Ok(entities)
     }
 
-    async fn entities_by_keys(
+    /// Returns the entities matching a keys, member or composite clause. The members compared by
+    /// the clause are checked against the schemas of their models.
+    async fn entities_by_clause(
         &self,
-        keys_clause: proto::types::KeysClause,
+        world_address: FieldElement,
+        clause: &proto::types::Clause,
         limit: u32,
         offset: u32,
     ) -> Result<Vec<proto::types::Entity>, Error> {
-        let keys = keys_clause
-            .keys
-            .iter()
-            .map(|bytes| {
-                if bytes.is_empty() {
-                    return Ok("%".to_string());
-                }
-                Ok(FieldElement::from_byte_slice_be(bytes)
-                    .map(|felt| format!("{:#x}", felt))
-                    .map_err(ParseError::FromByteSliceError)?)
-            })
-            .collect::<Result<Vec<_>, Error>>()?;
-        let keys_pattern = keys.join("/") + "/%";
-
-        let models_query = format!(
-            r#"
-            SELECT group_concat(entity_model.model_id) as model_names
-            FROM entities
-            JOIN entity_model ON entities.id = entity_model.entity_id
-            WHERE entities.keys LIKE ?
-            GROUP BY entities.id
-            HAVING model_names REGEXP '(^|,){}(,|$)'
-            LIMIT 1
-        "#,
-            keys_clause.model
-        );
-        let (models_str,): (String,) =
-            sqlx::query_as(&models_query).bind(&keys_pattern).fetch_one(&self.pool).await?;
-
-        let model_names = models_str.split(',').collect::<Vec<&str>>();
-        let schemas = self.model_cache.schemas(model_names).await?;
-
-        let entities_query = format!(
-            "{} WHERE entities.keys LIKE ? ORDER BY entities.event_id DESC LIMIT ? OFFSET ?",
-            build_sql_query(&schemas)?
-        );
-        let db_entities = sqlx::query(&entities_query)
-            .bind(&keys_pattern)
-            .bind(limit)
-            .bind(offset)
-            .fetch_all(&self.pool)
-            .await?;
-
-        db_entities.iter().map(|row| Self::map_row_to_entity(row, &schemas)).collect()
-    }
-
-    async fn entities_by_member(
-        &self,
-        member_clause: proto::types::MemberClause,
-        _limit: u32,
-        _offset: u32,
-    ) -> Result<Vec<proto::types::Entity>, Error> {
-        let comparison_operator = ComparisonOperator::from_repr(member_clause.operator as usize)
-            .expect("invalid comparison operator");
-
-        let value_type = member_clause
-            .value
-            .ok_or(QueryError::MissingParam("value".into()))?
-            .value_type
-            .ok_or(QueryError::MissingParam("value_type".into()))?;
-
-        let comparison_value = match value_type {
-            proto::types::value::ValueType::StringValue(string) => string,
-            proto::types::value::ValueType::IntValue(int) => int.to_string(),
-            proto::types::value::ValueType::UintValue(uint) => uint.to_string(),
-            proto::types::value::ValueType::BoolValue(bool) => {
-                if bool {
-                    "1".to_string()
-                } else {
-                    "0".to_string()
+        let mut schemas = HashMap::new();
+        let mut clauses = vec![clause];
+        while let Some(clause) = clauses.pop() {
+            match &clause.clause_type {
+                Some(ClauseType::Member(member)) if !schemas.contains_key(&member.model) => {
+                    let schema = self.model_cache.schema(&member.model).await?;
+                    schemas.insert(member.model.clone(), schema);
                 }
+                Some(ClauseType::Composite(composite)) => clauses.extend(&composite.clauses),
+                _ => {}
             }
-            _ => return Err(QueryError::UnsupportedQuery.into()),
-        };
-
-        let models_query = format!(
-            r#"
-            SELECT group_concat(entity_model.model_id) as model_names
-            FROM entities
-            JOIN entity_model ON entities.id = entity_model.entity_id
-            GROUP BY entities.id
-            HAVING model_names REGEXP '(^|,){}(,|$)'
-            LIMIT 1
-        "#,
-            member_clause.model
-        );
-        let (models_str,): (String,) = sqlx::query_as(&models_query).fetch_one(&self.pool).await?;
-
-        let model_names = models_str.split(',').collect::<Vec<&str>>();
-        let schemas = self.model_cache.schemas(model_names).await?;
-
-        let table_name = member_clause.model;
-        let column_name = format!("external_{}", member_clause.member);
-        let member_query = format!(
-            "{} WHERE {table_name}.{column_name} {comparison_operator} ?",
-            build_sql_query(&schemas)?
-        );
-
-        let db_entities =
-            sqlx::query(&member_query).bind(comparison_value).fetch_all(&self.pool).await?;
+        }
 
-        db_entities.iter().map(|row| Self::map_row_to_entity(row, &schemas)).collect()
-    }
+        let mut bind_values = Vec::new();
+        let condition = clause_condition(clause, &schemas, &mut bind_values)?;
 
-    async fn entities_by_composite(
-        &self,
-        _composite: proto::types::CompositeClause,
-        _limit: u32,
-        _offset: u32,
-    ) -> Result<Vec<proto::types::Entity>, Error> {
-        // TODO: Implement
-        Err(QueryError::UnsupportedQuery.into())
+        self.entities_where(world_address, &condition, bind_values, limit, offset).await
     }
 
     pub async fn model_metadata(&self, model: &str) -> Result<proto::types::ModelMetadata, Error> {
@@ -371,13 +320,16 @@ impl DojoWorld {
 
     async fn retrieve_entities(
         &self,
+        world_address: FieldElement,
         query: proto::types::Query,
     ) -> Result<proto::world::RetrieveEntitiesResponse, Error> {
         let entities = match query.clause {
-            None => self.entities_all(query.limit, query.offset).await?,
+            None => self.entities_all(world_address, query.limit, query.offset).await?,
             Some(clause) => {
-                let clause_type =
-                    clause.clause_type.ok_or(QueryError::MissingParam("clause_type".into()))?;
+                let clause_type = clause
+                    .clause_type
+                    .as_ref()
+                    .ok_or(QueryError::MissingParam("clause_type".into()))?;
 
                 match clause_type {
                     ClauseType::HashedKeys(hashed_keys) => {
@@ -385,25 +337,25 @@ impl DojoWorld {
                             return Err(QueryError::MissingParam("ids".into()).into());
                         }
 
-                        self.entities_by_hashed_keys(Some(hashed_keys), query.limit, query.offset)
-                            .await?
+                        self.entities_by_hashed_keys(
+                            world_address,
+                            Some(hashed_keys.clone()),
+                            query.limit,
+                            query.offset,
+                        )
+                        .await?
                     }
                     ClauseType::Keys(keys) => {
                         if keys.keys.is_empty() {
                             return Err(QueryError::MissingParam("keys".into()).into());
                         }
 
-                        if keys.model.is_empty() {
-                            return Err(QueryError::MissingParam("model".into()).into());
-                        }
-
-                        self.entities_by_keys(keys, query.limit, query.offset).await?
-                    }
-                    ClauseType::Member(member) => {
-                        self.entities_by_member(member, query.limit, query.offset).await?
+                        self.entities_by_clause(world_address, &clause, query.limit, query.offset)
+                            .await?
                     }
-                    ClauseType::Composite(composite) => {
-                        self.entities_by_composite(composite, query.limit, query.offset).await?
+                    ClauseType::Member(_) | ClauseType::Composite(_) => {
+                        self.entities_by_clause(world_address, &clause, query.limit, query.offset)
+                            .await?
                     }
                 }
             }
@@ -412,6 +364,258 @@ impl DojoWorld {
         Ok(RetrieveEntitiesResponse { entities })
     }
 
+    async fn retrieve_contracts(
+        &self,
+        addresses: Vec<String>,
+    ) -> Result<proto::world::RetrieveContractsResponse, Error> {
+        let addresses = addresses
+            .iter()
+            .map(|address| {
+                FieldElement::from_str(address)
+                    .map(|address| format!("{address:#x}"))
+                    .map_err(ParseError::FromStr)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut query = "SELECT id, class_hash, salt, CAST(strftime('%s', deployed_at) AS \
+                         INTEGER), CAST(strftime('%s', upgraded_at) AS INTEGER) FROM contracts"
+            .to_string();
+        if !addresses.is_empty() {
+            let placeholders = vec!["?"; addresses.len()].join(", ");
+            query.push_str(&format!(" WHERE id IN ({placeholders})"));
+        }
+
+        let mut db_contracts =
+            sqlx::query_as::<_, (String, String, Option<String>, Option<i64>, Option<i64>)>(&query);
+        for address in &addresses {
+            db_contracts = db_contracts.bind(address);
+        }
+
+        let contracts = db_contracts
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|(address, class_hash, salt, deployed_at, upgraded_at)| proto::types::Contract {
+                address,
+                class_hash,
+                salt: salt.unwrap_or_default(),
+                deployed_at: deployed_at.unwrap_or_default() as u64,
+                upgraded_at: upgraded_at.unwrap_or_default() as u64,
+            })
+            .collect();
+
+        Ok(RetrieveContractsResponse { contracts })
+    }
+
+    async fn retrieve_permissions(
+        &self,
+        resource: String,
+        address: String,
+    ) -> Result<proto::world::RetrievePermissionsResponse, Error> {
+        let mut query =
+            "SELECT kind, resource, address FROM permissions WHERE granted = 1".to_string();
+        let mut filters = Vec::new();
+        for (column, value) in [("resource", resource), ("address", address)] {
+            if !value.is_empty() {
+                let felt = FieldElement::from_str(&value).map_err(ParseError::FromStr)?;
+                query.push_str(&format!(" AND {column} = ?"));
+                filters.push(format!("{felt:#x}"));
+            }
+        }
+
+        let mut db_permissions = sqlx::query_as::<_, (String, String, String)>(&query);
+        for filter in &filters {
+            db_permissions = db_permissions.bind(filter);
+        }
+
+        let permissions = db_permissions
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|(kind, resource, address)| {
+                let kind = match kind.as_str() {
+                    "Owner" => proto::types::PermissionKind::Owner,
+                    _ => proto::types::PermissionKind::Writer,
+                };
+
+                proto::types::Permission { kind: kind as i32, resource, address }
+            })
+            .collect();
+
+        Ok(RetrievePermissionsResponse { permissions })
+    }
+
+    /// Returns the changes recorded for an entity in the history tables of the world's historical
+    /// models, up to the block `at_block` unless it's 0, from the most recent one.
+    async fn retrieve_entity_history(
+        &self,
+        world_address: FieldElement,
+        hashed_keys: FieldElement,
+        at_block: u64,
+        limit: u32,
+        offset: u32,
+    ) -> Result<RetrieveEntityHistoryResponse, Error> {
+        let models: Vec<String> =
+            sqlx::query_scalar("SELECT id FROM models WHERE world_address = ? AND historical")
+                .bind(format!("{world_address:#x}"))
+                .fetch_all(&self.pool)
+                .await?;
+        if models.is_empty() {
+            return Ok(RetrieveEntityHistoryResponse { changes: vec![] });
+        }
+
+        let block_condition = if at_block == 0 { "" } else { " AND block_number <= ?" };
+        let histories = models
+            .iter()
+            .map(|model| {
+                format!(
+                    "SELECT '{model}' AS model_name, event_id, block_number, transaction_hash, \
+                     deleted, data FROM [{model}@history] WHERE entity_id = ?{block_condition}"
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" UNION ALL ");
+        let query = format!("{histories} ORDER BY event_id DESC LIMIT ? OFFSET ?");
+
+        let entity_id = format!("{hashed_keys:#x}");
+        let mut db_changes = sqlx::query_as::<
+            _,
+            (String, String, Option<i64>, Option<String>, bool, Option<String>),
+        >(&query);
+        for _ in &models {
+            db_changes = db_changes.bind(&entity_id);
+            if at_block != 0 {
+                db_changes = db_changes.bind(at_block as i64);
+            }
+        }
+        let db_changes = db_changes.bind(limit).bind(offset).fetch_all(&self.pool).await?;
+
+        let mut changes = Vec::with_capacity(db_changes.len());
+        for (model, event_id, block_number, transaction_hash, deleted, data) in db_changes {
+            // The values of the model are serialized in `data`, unless the change deleted it.
+            let model = match data {
+                Some(data) => self.history_model(&model, &data).await?,
+                None => proto::types::Model { name: model, members: vec![] },
+            };
+
+            changes.push(proto::types::EntityChange {
+                model: Some(model),
+                block_number: block_number.unwrap_or_default() as u64,
+                transaction_hash: transaction_hash.unwrap_or_default(),
+                event_id,
+                deleted,
+            });
+        }
+
+        Ok(RetrieveEntityHistoryResponse { changes })
+    }
+
+    /// Returns the entities as they were at the block `at_block`, with the values their
+    /// historical models had then, from the most recently updated. Only the entities matching a
+    /// hashed keys clause, or all of them, can be retrieved at a block.
+    async fn entities_at_block(
+        &self,
+        world_address: FieldElement,
+        query: proto::types::Query,
+        at_block: u64,
+    ) -> Result<Vec<proto::types::Entity>, Error> {
+        let mut bind_values = Vec::new();
+        let condition = match query.clause {
+            None => "TRUE".to_string(),
+            Some(clause) => match clause.clause_type {
+                Some(ClauseType::HashedKeys(hashed_keys)) => {
+                    hashed_keys_condition(&hashed_keys, &mut bind_values)?
+                }
+                Some(_) => return Err(QueryError::UnsupportedQuery.into()),
+                None => return Err(QueryError::MissingParam("clause_type".into()).into()),
+            },
+        };
+
+        let models: Vec<String> =
+            sqlx::query_scalar("SELECT id FROM models WHERE world_address = ? AND historical")
+                .bind(format!("{world_address:#x}"))
+                .fetch_all(&self.pool)
+                .await?;
+        if models.is_empty() {
+            return Ok(vec![]);
+        }
+
+        // The last change of each entity's models up to the block, unless it deleted them.
+        let changes = models
+            .iter()
+            .map(|model| {
+                format!(
+                    "SELECT entity_id AS id, '{model}' AS model_name, event_id, data FROM \
+                     [{model}@history] WHERE id IN (SELECT MAX(id) FROM [{model}@history] WHERE \
+                     block_number <= ? GROUP BY entity_id) AND NOT deleted"
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" UNION ALL ");
+        let entities_query = format!(
+            r#"
+            WITH entities AS ({changes}),
+            ids AS (
+                SELECT id, MAX(event_id) AS last_event_id
+                FROM entities
+                WHERE {condition}
+                GROUP BY id
+                ORDER BY last_event_id DESC
+                LIMIT ? OFFSET ?
+            )
+            SELECT entities.id, entities.model_name, entities.data
+            FROM entities
+            JOIN ids ON entities.id = ids.id
+            ORDER BY ids.last_event_id DESC, entities.id, entities.model_name
+        "#
+        );
+
+        let mut db_models = sqlx::query_as::<_, (String, String, String)>(&entities_query);
+        for _ in &models {
+            db_models = db_models.bind(at_block as i64);
+        }
+        for value in bind_values {
+            db_models = db_models.bind(value);
+        }
+        let db_models =
+            db_models.bind(query.limit).bind(query.offset).fetch_all(&self.pool).await?;
+
+        let mut entities: Vec<(String, Vec<proto::types::Model>)> = Vec::new();
+        for (entity_id, model, data) in db_models {
+            let model = self.history_model(&model, &data).await?;
+            match entities.last_mut() {
+                Some((id, models)) if *id == entity_id => models.push(model),
+                _ => entities.push((entity_id, vec![model])),
+            }
+        }
+
+        entities
+            .into_iter()
+            .map(|(entity_id, models)| {
+                let hashed_keys =
+                    FieldElement::from_str(&entity_id).map_err(ParseError::FromStr)?;
+                Ok(proto::types::Entity {
+                    hashed_keys: hashed_keys.to_bytes_be().to_vec(),
+                    models,
+                    pending: false,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the model whose values are serialized in `data`, as recorded in its history table.
+    async fn history_model(&self, model: &str, data: &str) -> Result<proto::types::Model, Error> {
+        let mut felts = data
+            .split(FELT_DELIMITER)
+            .filter(|felt| !felt.is_empty())
+            .map(|felt| FieldElement::from_str(felt).map_err(ParseError::FromStr))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut schema = self.model_cache.schema(model).await?;
+        schema.deserialize(&mut felts)?;
+
+        Ok(schema.as_struct().expect("schema should be struct").to_owned().try_into().unwrap())
+    }
+
     fn map_row_to_entity(row: &SqliteRow, schemas: &[Ty]) -> Result<proto::types::Entity, Error> {
         let hashed_keys =
             FieldElement::from_str(&row.get::<String, _>("id")).map_err(ParseError::FromStr)?;
@@ -425,10 +629,164 @@ impl DojoWorld {
             })
             .collect::<Result<Vec<_>, Error>>()?;
 
-        Ok(proto::types::Entity { hashed_keys: hashed_keys.to_bytes_be().to_vec(), models })
+        Ok(proto::types::Entity {
+            hashed_keys: hashed_keys.to_bytes_be().to_vec(),
+            models,
+            pending: row.get("pending"),
+        })
+    }
+}
+
+/// Translates a clause into a condition on the `entities` table, pushing the values it compares
+/// with to `bind_values` in the order of its parameters.
+fn clause_condition(
+    clause: &proto::types::Clause,
+    schemas: &HashMap<String, Ty>,
+    bind_values: &mut Vec<String>,
+) -> Result<String, Error> {
+    let clause_type =
+        clause.clause_type.as_ref().ok_or(QueryError::MissingParam("clause_type".into()))?;
+
+    match clause_type {
+        ClauseType::HashedKeys(hashed_keys) => hashed_keys_condition(hashed_keys, bind_values),
+        ClauseType::Keys(keys) => {
+            if keys.model.is_empty() {
+                return Err(QueryError::MissingParam("model".into()).into());
+            }
+
+            bind_values.push(keys_pattern(&keys.keys)?);
+            bind_values.push(keys.model.clone());
+
+            Ok("(entities.keys LIKE ? AND entities.id IN (SELECT entity_id FROM entity_model \
+                WHERE model_id = ?))"
+                .to_string())
+        }
+        ClauseType::Member(member) => {
+            let schema = schemas
+                .get(&member.model)
+                .ok_or_else(|| QueryError::ModelNotFound(member.model.clone()))?;
+            let (table, column) = member_column(schema, &member.member)?;
+            let operator = ComparisonOperator::from_repr(member.operator as usize)
+                .ok_or(QueryError::UnsupportedQuery)?;
+
+            bind_values.push(comparison_value(member.value.clone())?);
+
+            Ok(format!(
+                "entities.id IN (SELECT entity_id FROM [{table}] WHERE [{column}] {operator} ?)"
+            ))
+        }
+        ClauseType::Composite(composite) => composite_condition(composite, schemas, bind_values),
+    }
+}
+
+fn composite_condition(
+    composite: &proto::types::CompositeClause,
+    schemas: &HashMap<String, Ty>,
+    bind_values: &mut Vec<String>,
+) -> Result<String, Error> {
+    if composite.clauses.is_empty() {
+        return Err(QueryError::MissingParam("clauses".into()).into());
+    }
+
+    let operator = LogicalOperator::from_repr(composite.operator as usize)
+        .ok_or(QueryError::UnsupportedQuery)?;
+    let conditions = composite
+        .clauses
+        .iter()
+        .map(|clause| clause_condition(clause, schemas, bind_values))
+        .collect::<Result<Vec<_>, Error>>()?;
+    let condition = format!("({})", conditions.join(&format!(" {} ", operator.as_ref())));
+
+    // The model of a composite clause restricts it to the entities of that model.
+    if composite.model.is_empty() {
+        return Ok(condition);
+    }
+
+    bind_values.push(composite.model.clone());
+    Ok(format!(
+        "({condition} AND entities.id IN (SELECT entity_id FROM entity_model WHERE model_id = ?))"
+    ))
+}
+
+fn hashed_keys_condition(
+    hashed_keys: &proto::types::HashedKeysClause,
+    bind_values: &mut Vec<String>,
+) -> Result<String, Error> {
+    if hashed_keys.hashed_keys.is_empty() {
+        return Err(QueryError::MissingParam("hashed_keys".into()).into());
+    }
+
+    for id in &hashed_keys.hashed_keys {
+        let id = FieldElement::from_byte_slice_be(id).map_err(ParseError::FromByteSliceError)?;
+        bind_values.push(format!("{id:#x}"));
+    }
+
+    let placeholders = vec!["?"; hashed_keys.hashed_keys.len()].join(", ");
+    Ok(format!("entities.id IN ({placeholders})"))
+}
+
+/// Returns the `LIKE` pattern of the keys of the entities starting with `keys`, where empty keys
+/// match any key.
+fn keys_pattern(keys: &[Vec<u8>]) -> Result<String, Error> {
+    let keys = keys
+        .iter()
+        .map(|bytes| {
+            if bytes.is_empty() {
+                return Ok("%".to_string());
+            }
+            Ok(FieldElement::from_byte_slice_be(bytes)
+                .map(|felt| format!("{:#x}", felt))
+                .map_err(ParseError::FromByteSliceError)?)
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    Ok(keys.join("/") + "/%")
+}
+
+fn comparison_value(value: Option<proto::types::Value>) -> Result<String, Error> {
+    let value_type = value
+        .ok_or(QueryError::MissingParam("value".into()))?
+        .value_type
+        .ok_or(QueryError::MissingParam("value_type".into()))?;
+
+    match value_type {
+        proto::types::value::ValueType::StringValue(string) => Ok(string),
+        proto::types::value::ValueType::IntValue(int) => Ok(int.to_string()),
+        proto::types::value::ValueType::UintValue(uint) => Ok(uint.to_string()),
+        proto::types::value::ValueType::BoolValue(bool) => {
+            Ok(if bool { "1".to_string() } else { "0".to_string() })
+        }
+        _ => Err(QueryError::UnsupportedQuery.into()),
     }
 }
 
+/// Returns the table and the column storing a member of a model, where the members of nested
+/// structs are separated by dots (ex: `position.vec.x`).
+fn member_column(schema: &Ty, member: &str) -> Result<(String, String), Error> {
+    let path: Vec<&str> = member.split('.').collect();
+    let mut table = schema.name();
+    let mut ty = schema;
+
+    for (idx, name) in path.iter().enumerate() {
+        let child = ty
+            .as_struct()
+            .and_then(|s| s.children.iter().find(|child| child.name == *name))
+            .ok_or_else(|| QueryError::MemberNotFound(member.into()))?;
+
+        match &child.ty {
+            Ty::Struct(_) if idx + 1 < path.len() => {
+                table = format!("{table}${name}");
+                ty = &child.ty;
+            }
+            Ty::Struct(_) => break,
+            _ if idx + 1 == path.len() => return Ok((table, format!("external_{name}"))),
+            _ => break,
+        }
+    }
+
+    Err(QueryError::MemberNotFound(member.into()).into())
+}
+
 type ServiceResult<T> = Result<Response<T>, Status>;
 type SubscribeModelsResponseStream =
     Pin<Box<dyn Stream<Item = Result<SubscribeModelsResponse, Status>> + Send>>;
@@ -442,9 +800,12 @@ impl proto::world::world_server::World for DojoWorld {
 
     async fn world_metadata(
         &self,
-        _request: Request<MetadataRequest>,
+        request: Request<MetadataRequest>,
     ) -> Result<Response<MetadataResponse>, Status> {
-        let metadata = self.metadata().await.map_err(|e| match e {
+        let MetadataRequest { world_address } = request.into_inner();
+        let world_address =
+            self.world(&world_address).map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let metadata = self.metadata(world_address).await.map_err(|e| match e {
             Error::Sql(sqlx::Error::RowNotFound) => Status::not_found("World not found"),
             e => Status::internal(e.to_string()),
         })?;
@@ -488,22 +849,82 @@ impl proto::world::world_server::World for DojoWorld {
         &self,
         request: Request<RetrieveEntitiesRequest>,
     ) -> Result<Response<RetrieveEntitiesResponse>, Status> {
-        let query = request
-            .into_inner()
-            .query
-            .ok_or_else(|| Status::invalid_argument("Missing query argument"))?;
-
-        let entities =
-            self.retrieve_entities(query).await.map_err(|e| Status::internal(e.to_string()))?;
+        let RetrieveEntitiesRequest { query, world_address, at_block } = request.into_inner();
+        let query = query.ok_or_else(|| Status::invalid_argument("Missing query argument"))?;
+        let world_address =
+            self.world(&world_address).map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+        let entities = if at_block == 0 {
+            self.retrieve_entities(world_address, query).await
+        } else {
+            self.entities_at_block(world_address, query, at_block)
+                .await
+                .map(|entities| RetrieveEntitiesResponse { entities })
+        };
+        let entities = entities.map_err(|e| Status::internal(e.to_string()))?;
 
         Ok(Response::new(entities))
     }
+
+    async fn retrieve_contracts(
+        &self,
+        request: Request<RetrieveContractsRequest>,
+    ) -> Result<Response<RetrieveContractsResponse>, Status> {
+        let RetrieveContractsRequest { addresses } = request.into_inner();
+        let contracts = self
+            .retrieve_contracts(addresses)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(contracts))
+    }
+
+    async fn retrieve_permissions(
+        &self,
+        request: Request<RetrievePermissionsRequest>,
+    ) -> Result<Response<RetrievePermissionsResponse>, Status> {
+        let RetrievePermissionsRequest { resource, address } = request.into_inner();
+        let permissions = self
+            .retrieve_permissions(resource, address)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(permissions))
+    }
+
+    async fn retrieve_entity_history(
+        &self,
+        request: Request<RetrieveEntityHistoryRequest>,
+    ) -> Result<Response<RetrieveEntityHistoryResponse>, Status> {
+        let RetrieveEntityHistoryRequest { hashed_keys, world_address, at_block, limit, offset } =
+            request.into_inner();
+        let hashed_keys = FieldElement::from_byte_slice_be(&hashed_keys)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let world_address =
+            self.world(&world_address).map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+        let history = self
+            .retrieve_entity_history(world_address, hashed_keys, at_block, limit, offset)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(history))
+    }
+
+    async fn retrieve_worlds(
+        &self,
+        _request: Request<RetrieveWorldsRequest>,
+    ) -> Result<Response<RetrieveWorldsResponse>, Status> {
+        let worlds = self.worlds().await.map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(RetrieveWorldsResponse { worlds }))
+    }
 }
 
 pub async fn new(
     mut shutdown_rx: tokio::sync::broadcast::Receiver<()>,
     pool: &Pool<Sqlite>,
-    block_rx: Receiver<u64>,
+    block_rxs: Vec<(FieldElement, Receiver<u64>)>,
     world_address: FieldElement,
     provider: Arc<JsonRpcClient<HttpTransport>>,
 ) -> Result<
@@ -518,7 +939,7 @@ pub async fn new(
         .build()
         .unwrap();
 
-    let world = DojoWorld::new(pool.clone(), block_rx, world_address, provider);
+    let world = DojoWorld::new(pool.clone(), block_rxs, world_address, provider);
     let server = WorldServer::new(world);
 
     let server_future = Server::builder()
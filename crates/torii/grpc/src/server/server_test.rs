@@ -0,0 +1,273 @@
+use std::sync::Arc;
+
+use dojo_types::primitive::Primitive;
+use dojo_types::schema::{Member, Struct, Ty};
+use sqlx::{Pool, Sqlite};
+use starknet_crypto::{poseidon_hash_many, FieldElement};
+use torii_core::cache::ModelCache;
+use torii_core::database::connect_sqlite;
+use torii_core::sql::Sql;
+
+use super::subscriptions::entity::EntityManager;
+use super::subscriptions::model_diff::StateDiffManager;
+use super::DojoWorld;
+use crate::proto::types::clause::ClauseType;
+use crate::proto::types::value::ValueType;
+use crate::proto::types::{
+    Clause, CompositeClause, Entity, HashedKeysClause, KeysClause, MemberClause, Model, Query,
+    Value,
+};
+use crate::types::{ComparisonOperator, LogicalOperator};
+
+fn model(name: &str, player: u32, members: Vec<(&str, Primitive)>) -> Ty {
+    let mut children = vec![Member {
+        name: "player".into(),
+        key: true,
+        ty: Ty::Primitive(Primitive::ContractAddress(Some(player.into()))),
+    }];
+    children.extend(members.into_iter().map(|(name, value)| Member {
+        name: name.into(),
+        key: false,
+        ty: Ty::Primitive(value),
+    }));
+
+    Ty::Struct(Struct { name: name.into(), children })
+}
+
+fn position(player: u32, x: u32, y: u32) -> Ty {
+    model("Position", player, vec![("x", Primitive::U32(Some(x))), ("y", Primitive::U32(Some(y)))])
+}
+
+fn moves(player: u32, remaining: u8) -> Ty {
+    model("Moves", player, vec![("remaining", Primitive::U8(Some(remaining)))])
+}
+
+/// Seeds a database with the positions and the moves of three players, from the least recently
+/// updated:
+/// - player `1` at (1, 5), with 3 moves remaining
+/// - player `2` at (2, 5), with no moves remaining
+/// - player `3` at (3, 1), without moves
+async fn seeded_world() -> DojoWorld {
+    let pool = connect_sqlite(":memory:").await.unwrap();
+    let mut db = Sql::new(pool.clone(), FieldElement::ONE).await.unwrap();
+
+    db.register_model(position(0, 0, 0), vec![], FieldElement::ONE, 0, 0).await.unwrap();
+    db.register_model(moves(0, 0), vec![], FieldElement::TWO, 0, 0).await.unwrap();
+
+    db.set_entity(position(1, 1, 5), "0x1").await.unwrap();
+    db.set_entity(moves(1, 3), "0x1").await.unwrap();
+    db.set_entity(position(2, 2, 5), "0x2").await.unwrap();
+    db.set_entity(moves(2, 0), "0x2").await.unwrap();
+    db.set_entity(position(3, 3, 1), "0x3").await.unwrap();
+    db.execute().await.unwrap();
+
+    dojo_world(pool)
+}
+
+fn dojo_world(pool: Pool<Sqlite>) -> DojoWorld {
+    DojoWorld {
+        model_cache: Arc::new(ModelCache::new(pool.clone())),
+        entity_manager: Arc::new(EntityManager::default()),
+        state_diff_manager: Arc::new(StateDiffManager::default()),
+        world_address: FieldElement::ONE,
+        pool,
+    }
+}
+
+fn member(model: &str, member: &str, operator: ComparisonOperator, value: u64) -> Clause {
+    Clause {
+        clause_type: Some(ClauseType::Member(MemberClause {
+            model: model.into(),
+            member: member.into(),
+            operator: operator as i32,
+            value: Some(Value { value_type: Some(ValueType::UintValue(value)) }),
+        })),
+    }
+}
+
+fn composite(operator: LogicalOperator, clauses: Vec<Clause>) -> Clause {
+    Clause {
+        clause_type: Some(ClauseType::Composite(CompositeClause {
+            model: String::new(),
+            operator: operator as i32,
+            clauses,
+        })),
+    }
+}
+
+/// Returns the hashed keys of the entities matching `clause`.
+async fn entities(
+    world: &DojoWorld,
+    clause: Option<Clause>,
+    limit: u32,
+    offset: u32,
+) -> Result<Vec<FieldElement>, torii_core::error::Error> {
+    let response =
+        world.retrieve_entities(FieldElement::ONE, Query { clause, limit, offset }).await?;
+    Ok(response
+        .entities
+        .iter()
+        .map(|entity| FieldElement::from_byte_slice_be(&entity.hashed_keys).unwrap())
+        .collect())
+}
+
+fn players(players: &[u32]) -> Vec<FieldElement> {
+    players.iter().map(|player| poseidon_hash_many(&[FieldElement::from(*player)])).collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_nested_composite_across_models() {
+    let world = seeded_world().await;
+
+    // (Position.x > 1 AND Moves.remaining == 0) OR Position.y == 1
+    let clause = composite(
+        LogicalOperator::Or,
+        vec![
+            composite(
+                LogicalOperator::And,
+                vec![
+                    member("Position", "x", ComparisonOperator::Gt, 1),
+                    member("Moves", "remaining", ComparisonOperator::Eq, 0),
+                ],
+            ),
+            member("Position", "y", ComparisonOperator::Eq, 1),
+        ],
+    );
+    assert_eq!(entities(&world, Some(clause), 10, 0).await.unwrap(), players(&[3, 2]));
+
+    // (Position.y == 5 OR Position.x == 3) AND Moves.remaining > 0
+    let clause = composite(
+        LogicalOperator::And,
+        vec![
+            composite(
+                LogicalOperator::Or,
+                vec![
+                    member("Position", "y", ComparisonOperator::Eq, 5),
+                    member("Position", "x", ComparisonOperator::Eq, 3),
+                ],
+            ),
+            member("Moves", "remaining", ComparisonOperator::Gt, 0),
+        ],
+    );
+    assert_eq!(entities(&world, Some(clause), 10, 0).await.unwrap(), players(&[1]));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_member_comparisons() {
+    let world = seeded_world().await;
+
+    let cases = [
+        (ComparisonOperator::Eq, vec![2]),
+        (ComparisonOperator::Neq, vec![3, 1]),
+        (ComparisonOperator::Gt, vec![3]),
+        (ComparisonOperator::Gte, vec![3, 2]),
+        (ComparisonOperator::Lt, vec![1]),
+        (ComparisonOperator::Lte, vec![2, 1]),
+    ];
+
+    for (operator, expected) in cases {
+        let clause =
+            composite(LogicalOperator::And, vec![member("Position", "x", operator.clone(), 2)]);
+        assert_eq!(
+            entities(&world, Some(clause), 10, 0).await.unwrap(),
+            players(&expected),
+            "Position.x {operator} 2"
+        );
+    }
+
+    let clause =
+        composite(LogicalOperator::And, vec![member("Position", "z", ComparisonOperator::Eq, 0)]);
+    assert!(entities(&world, Some(clause), 10, 0).await.is_err(), "unknown member");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_limit_and_offset() {
+    let world = seeded_world().await;
+
+    assert_eq!(entities(&world, None, 2, 0).await.unwrap(), players(&[3, 2]));
+    assert_eq!(entities(&world, None, 2, 2).await.unwrap(), players(&[1]));
+    assert_eq!(entities(&world, None, 2, 3).await.unwrap(), players(&[]));
+
+    let clause =
+        composite(LogicalOperator::And, vec![member("Position", "y", ComparisonOperator::Eq, 5)]);
+    assert_eq!(entities(&world, Some(clause.clone()), 1, 0).await.unwrap(), players(&[2]));
+    assert_eq!(entities(&world, Some(clause), 1, 1).await.unwrap(), players(&[1]));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_member_and_keys_clauses() {
+    let world = seeded_world().await;
+
+    let clause = member("Position", "y", ComparisonOperator::Eq, 5);
+    assert_eq!(entities(&world, Some(clause.clone()), 10, 0).await.unwrap(), players(&[2, 1]));
+    assert_eq!(entities(&world, Some(clause.clone()), 1, 0).await.unwrap(), players(&[2]));
+    assert_eq!(entities(&world, Some(clause), 1, 1).await.unwrap(), players(&[1]));
+
+    let keys = |model: &str, keys: Vec<Vec<u8>>| Clause {
+        clause_type: Some(ClauseType::Keys(KeysClause { model: model.into(), keys })),
+    };
+    // empty keys match any key
+    let clause = keys("Moves", vec![vec![]]);
+    assert_eq!(entities(&world, Some(clause.clone()), 10, 0).await.unwrap(), players(&[2, 1]));
+    assert_eq!(entities(&world, Some(clause), 1, 1).await.unwrap(), players(&[1]));
+    let clause = keys("Position", vec![FieldElement::THREE.to_bytes_be().to_vec()]);
+    assert_eq!(entities(&world, Some(clause), 10, 0).await.unwrap(), players(&[3]));
+
+    let mut clause = member("Position", "x", ComparisonOperator::Eq, 1);
+    if let Some(ClauseType::Member(member)) = &mut clause.clause_type {
+        member.operator = 42;
+    }
+    assert!(entities(&world, Some(clause), 10, 0).await.is_err(), "unknown operator");
+
+    let clause = member("Position] OR 1=1 --", "x", ComparisonOperator::Eq, 1);
+    assert!(entities(&world, Some(clause), 10, 0).await.is_err(), "unknown model");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_entities_at_block() {
+    let pool = connect_sqlite(":memory:").await.unwrap();
+    let mut db = Sql::new(pool.clone(), FieldElement::ONE).await.unwrap();
+    db.set_historical(&["Position".to_string()]).await.unwrap();
+
+    db.set_block(1, FieldElement::ONE, FieldElement::ZERO);
+    db.register_model(position(0, 0, 0), vec![], FieldElement::ONE, 0, 0).await.unwrap();
+    db.set_entity(position(1, 1, 5), "0x1").await.unwrap();
+    db.set_entity(position(2, 2, 5), "0x2").await.unwrap();
+    db.set_head(1);
+    db.execute().await.unwrap();
+
+    // Player `1` moves and player `2` is deleted at the second block.
+    db.set_block(2, FieldElement::TWO, FieldElement::ONE);
+    db.set_entity(position(1, 4, 5), "0x3").await.unwrap();
+    db.delete_entity("Position", &[FieldElement::TWO], "0x4").await.unwrap();
+    db.set_head(2);
+    db.execute().await.unwrap();
+
+    let world = dojo_world(pool);
+    let entity = |player: u32, x: u32| {
+        let model: Model = position(player, x, 5).as_struct().unwrap().clone().try_into().unwrap();
+        Entity {
+            hashed_keys: players(&[player])[0].to_bytes_be().to_vec(),
+            models: vec![model],
+            pending: false,
+        }
+    };
+    let entities_at = |clause: Option<Clause>, at_block: u64| {
+        world.entities_at_block(FieldElement::ONE, Query { clause, limit: 10, offset: 0 }, at_block)
+    };
+
+    assert_eq!(entities_at(None, 1).await.unwrap(), vec![entity(2, 2), entity(1, 1)]);
+    assert_eq!(entities_at(None, 2).await.unwrap(), vec![entity(1, 4)]);
+
+    let hashed_keys = |ids: &[u32]| Clause {
+        clause_type: Some(ClauseType::HashedKeys(HashedKeysClause {
+            hashed_keys: players(ids).iter().map(|id| id.to_bytes_be().to_vec()).collect(),
+        })),
+    };
+    assert_eq!(entities_at(Some(hashed_keys(&[1])), 1).await.unwrap(), vec![entity(1, 1)]);
+    assert_eq!(entities_at(Some(hashed_keys(&[2])), 2).await.unwrap(), vec![]);
+
+    let clause =
+        composite(LogicalOperator::And, vec![member("Position", "x", ComparisonOperator::Eq, 1)]);
+    assert!(entities_at(Some(clause), 1).await.is_err(), "only hashed keys are supported");
+}
@@ -8,7 +8,7 @@ use std::task::{Context, Poll};
 use futures::Stream;
 use futures_util::StreamExt;
 use rand::Rng;
-use sqlx::{Pool, Sqlite};
+use sqlx::{Pool, Row, Sqlite};
 use starknet_crypto::FieldElement;
 use tokio::sync::mpsc::{channel, Receiver, Sender};
 use tokio::sync::RwLock;
@@ -16,7 +16,7 @@ use torii_core::cache::ModelCache;
 use torii_core::error::{Error, ParseError};
 use torii_core::model::{build_sql_query, map_row_to_ty};
 use torii_core::simple_broker::SimpleBroker;
-use torii_core::types::Entity;
+use torii_core::types::{Entity, EntityDeleted};
 use tracing::{error, trace};
 
 use crate::proto;
@@ -60,6 +60,7 @@ pub struct Service {
     subs_manager: Arc<EntityManager>,
     model_cache: Arc<ModelCache>,
     simple_broker: Pin<Box<dyn Stream<Item = Entity> + Send>>,
+    deleted_broker: Pin<Box<dyn Stream<Item = EntityDeleted> + Send>>,
 }
 
 impl Service {
@@ -73,6 +74,7 @@ impl Service {
             subs_manager,
             model_cache,
             simple_broker: Box::pin(SimpleBroker::<Entity>::subscribe()),
+            deleted_broker: Box::pin(SimpleBroker::<EntityDeleted>::subscribe()),
         }
     }
 
@@ -118,6 +120,36 @@ impl Service {
                     entity: Some(proto::types::Entity {
                         hashed_keys: hashed.to_bytes_be().to_vec(),
                         models,
+                        pending: row.get("pending"),
+                    }),
+                };
+
+                if sub.sender.send(Ok(resp)).await.is_err() {
+                    closed_stream.push(*idx);
+                }
+            }
+        }
+
+        for id in closed_stream {
+            trace!(target = "subscription", "closing entity stream idx: {id}");
+            subs.remove_subscriber(id).await
+        }
+
+        Ok(())
+    }
+
+    /// Notifies the subscribers of a deleted entity, which is sent without any models.
+    async fn publish_deletion(subs: Arc<EntityManager>, hashed_keys: &str) -> Result<(), Error> {
+        let mut closed_stream = Vec::new();
+        let hashed = FieldElement::from_str(hashed_keys).map_err(ParseError::FromStr)?;
+
+        for (idx, sub) in subs.subscribers.read().await.iter() {
+            if sub.hashed_keys.is_empty() || sub.hashed_keys.contains(&hashed) {
+                let resp = proto::world::SubscribeEntityResponse {
+                    entity: Some(proto::types::Entity {
+                        hashed_keys: hashed.to_bytes_be().to_vec(),
+                        models: vec![],
+                        pending: false,
                     }),
                 };
 
@@ -153,6 +185,16 @@ impl Future for Service {
             });
         }
 
+        while let Poll::Ready(Some(EntityDeleted(entity))) = pin.deleted_broker.poll_next_unpin(cx)
+        {
+            let subs = Arc::clone(&pin.subs_manager);
+            tokio::spawn(async move {
+                if let Err(e) = Service::publish_deletion(subs, &entity.id).await {
+                    error!(target = "subscription", "error when publishing entity deletion: {e}");
+                }
+            });
+        }
+
         Poll::Pending
     }
 }
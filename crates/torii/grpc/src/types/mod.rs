@@ -24,6 +24,7 @@ pub struct Query {
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Hash, Eq, Clone)]
 pub enum Clause {
+    HashedKeys(Vec<FieldElement>),
     Keys(KeysClause),
     Member(MemberClause),
     Composite(CompositeClause),
@@ -157,6 +158,13 @@ impl From<Query> for proto::types::Query {
 impl From<Clause> for proto::types::Clause {
     fn from(value: Clause) -> Self {
         match value {
+            Clause::HashedKeys(hashed_keys) => Self {
+                clause_type: Some(proto::types::clause::ClauseType::HashedKeys(
+                    proto::types::HashedKeysClause {
+                        hashed_keys: hashed_keys.iter().map(|k| k.to_bytes_be().into()).collect(),
+                    },
+                )),
+            },
             Clause::Keys(clause) => {
                 Self { clause_type: Some(proto::types::clause::ClauseType::Keys(clause.into())) }
             }
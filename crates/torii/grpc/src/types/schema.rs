@@ -11,6 +11,7 @@ use crate::proto::{self};
 pub struct Entity {
     pub hashed_keys: FieldElement,
     pub models: Vec<Model>,
+    pub pending: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Hash, Eq, Clone)]
@@ -30,6 +31,7 @@ impl TryFrom<proto::types::Entity> for Entity {
                 .into_iter()
                 .map(TryInto::try_into)
                 .collect::<Result<Vec<_>, _>>()?,
+            pending: entity.pending,
         })
     }
 }
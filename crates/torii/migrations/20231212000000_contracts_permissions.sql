@@ -0,0 +1,28 @@
+-- Contracts deployed and upgraded through the world, including the world itself
+CREATE TABLE contracts (
+    id TEXT NOT NULL PRIMARY KEY,
+    class_hash TEXT NOT NULL,
+    salt TEXT,
+    transaction_hash TEXT,
+    deployed_at DATETIME,
+    upgraded_at DATETIME,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_contracts_class_hash ON contracts (class_hash);
+
+-- Owners and writers of the world's resources
+CREATE TABLE permissions (
+    id TEXT NOT NULL PRIMARY KEY,
+    kind TEXT CHECK(kind IN ('Owner', 'Writer')) NOT NULL,
+    resource TEXT NOT NULL,
+    address TEXT NOT NULL,
+    granted BOOLEAN NOT NULL,
+    transaction_hash TEXT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_permissions_resource ON permissions (resource);
+CREATE INDEX idx_permissions_address ON permissions (address);
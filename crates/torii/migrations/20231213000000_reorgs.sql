@@ -0,0 +1,18 @@
+-- Hashes of the indexed blocks, used to detect reorgs
+CREATE TABLE blocks (
+    number INTEGER NOT NULL PRIMARY KEY,
+    hash TEXT NOT NULL,
+    parent_hash TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Statements restoring the rows written by a block to their previous state
+CREATE TABLE undo_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    block_number INTEGER NOT NULL,
+    entity_id TEXT,
+    statement TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_undo_log_block_number ON undo_log (block_number);
@@ -0,0 +1,2 @@
+-- Entities last written by a transaction of the pending block
+ALTER TABLE entities ADD COLUMN pending BOOLEAN NOT NULL DEFAULT FALSE;
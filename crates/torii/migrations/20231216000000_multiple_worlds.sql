@@ -0,0 +1,37 @@
+-- Several worlds can be indexed into the same database. The models, metadata and permission ids
+-- of a world are prefixed with its namespace, which is empty for the worlds indexed unprefixed.
+ALTER TABLE worlds ADD COLUMN namespace TEXT NOT NULL DEFAULT '';
+
+ALTER TABLE models ADD COLUMN world_address TEXT;
+ALTER TABLE events ADD COLUMN world_address TEXT;
+ALTER TABLE metadata ADD COLUMN world_address TEXT;
+ALTER TABLE permissions ADD COLUMN world_address TEXT;
+ALTER TABLE undo_log ADD COLUMN world_address TEXT;
+
+-- Databases indexed so far hold a single world.
+UPDATE models SET world_address = (SELECT world_address FROM worlds LIMIT 1);
+UPDATE events SET world_address = (SELECT world_address FROM worlds LIMIT 1);
+UPDATE metadata SET world_address = (SELECT world_address FROM worlds LIMIT 1);
+UPDATE permissions SET world_address = (SELECT world_address FROM worlds LIMIT 1);
+UPDATE undo_log SET world_address = (SELECT world_address FROM worlds LIMIT 1);
+
+CREATE INDEX idx_models_world_address ON models (world_address);
+CREATE INDEX idx_events_world_address ON events (world_address);
+CREATE INDEX idx_undo_log_world_address ON undo_log (world_address, block_number);
+
+-- Each world keeps the hashes of the blocks it indexed.
+CREATE TABLE blocks_new (
+    world_address TEXT NOT NULL,
+    number INTEGER NOT NULL,
+    hash TEXT NOT NULL,
+    parent_hash TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (world_address, number)
+);
+
+INSERT INTO blocks_new (world_address, number, hash, parent_hash, created_at)
+SELECT worlds.world_address, number, hash, parent_hash, blocks.created_at
+FROM blocks, (SELECT world_address FROM worlds LIMIT 1) AS worlds;
+
+DROP TABLE blocks;
+ALTER TABLE blocks_new RENAME TO blocks;
@@ -0,0 +1,24 @@
+-- Custom events emitted by the systems through the world, decoded with the event ABIs of the
+-- manifest. The values of each custom event are stored in its own table.
+CREATE TABLE custom_events (
+    id TEXT NOT NULL PRIMARY KEY,
+    name TEXT NOT NULL,
+    selector TEXT NOT NULL,
+    world_address TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_custom_events_world_address ON custom_events (world_address);
+
+CREATE TABLE custom_event_members (
+    custom_event_id TEXT NOT NULL,
+    member_idx INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    type TEXT NOT NULL,
+    type_enum TEXT DEFAULT 'Primitive' CHECK(type_enum IN ('Primitive', 'Enum')) NOT NULL,
+    enum_options TEXT NULL,
+    key BOOLEAN NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (custom_event_id, member_idx),
+    FOREIGN KEY (custom_event_id) REFERENCES custom_events(id)
+);
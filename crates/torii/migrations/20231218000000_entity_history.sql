@@ -0,0 +1,3 @@
+-- The changes of the entities of a historical model are recorded in its `{model}@history` table,
+-- created once the model is set as historical.
+ALTER TABLE models ADD COLUMN historical BOOLEAN NOT NULL DEFAULT FALSE;
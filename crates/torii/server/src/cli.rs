@@ -12,27 +12,41 @@
 
 mod proxy;
 
+use std::collections::HashSet;
 use std::net::SocketAddr;
 use std::str::FromStr;
 use std::sync::Arc;
 
+use camino::Utf8PathBuf;
 use clap::Parser;
+use dojo_types::schema::Ty;
 use dojo_world::contracts::world::WorldContractReader;
+use dojo_world::manifest::Manifest;
+use futures::future::try_join_all;
 use metrics::prometheus_exporter;
 use metrics::utils::parse_socket_address;
-use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
 use sqlx::SqlitePool;
 use starknet::core::types::FieldElement;
 use starknet::providers::jsonrpc::HttpTransport;
-use starknet::providers::JsonRpcClient;
+use starknet::providers::{JsonRpcClient, Provider};
 use tokio::sync::broadcast;
 use tokio::sync::broadcast::Sender;
 use tokio_stream::StreamExt;
-use torii_core::engine::{Engine, EngineConfig, Processors};
+use torii_core::database::connect_sqlite;
+use torii_core::engine::{Engine, EngineConfig, Processors, SyncMode};
+use torii_core::processors::contract_deployed::ContractDeployedProcessor;
+use torii_core::processors::contract_upgraded::ContractUpgradedProcessor;
+use torii_core::processors::custom_event::{custom_events, CustomEventProcessor};
+use torii_core::processors::executor_updated::ExecutorUpdatedProcessor;
 use torii_core::processors::metadata_update::MetadataUpdateProcessor;
+use torii_core::processors::owner_updated::OwnerUpdatedProcessor;
 use torii_core::processors::register_model::RegisterModelProcessor;
+use torii_core::processors::store_del_record::StoreDelRecordProcessor;
 use torii_core::processors::store_set_record::StoreSetRecordProcessor;
 use torii_core::processors::store_transaction::StoreTransactionProcessor;
+use torii_core::processors::world_upgraded::WorldUpgradedProcessor;
+use torii_core::processors::writer_updated::WriterUpdatedProcessor;
+use torii_core::processors::EventProcessor;
 use torii_core::simple_broker::SimpleBroker;
 use torii_core::sql::Sql;
 use torii_core::types::Model;
@@ -46,16 +60,26 @@ use crate::proxy::Proxy;
 #[derive(Parser, Debug)]
 #[command(name = "torii", author, version, about, long_about = None)]
 struct Args {
-    /// The world to index
-    #[arg(short, long = "world", env = "DOJO_WORLD_ADDRESS")]
-    world_address: FieldElement,
+    /// The worlds to index, as `ADDRESS` or `NAMESPACE:ADDRESS` (comma-separated list). The
+    /// models, metadata and permissions of a world with a namespace are prefixed with it (ex:
+    /// `season1_Position`), so that worlds registering the same models can be indexed together.
+    /// The first world is the one served by default
+    #[arg(short, long = "world", env = "DOJO_WORLD_ADDRESS", required = true)]
+    #[arg(value_delimiter = ',')]
+    worlds: Vec<WorldArg>,
+
+    /// The sozo manifests of the worlds, in the order of `--world` (comma-separated list). The
+    /// event ABIs of their contracts are used to store the custom events emitted with `emit!`
+    /// into typed tables
+    #[arg(long = "manifest", value_delimiter = ',')]
+    manifests: Vec<Utf8PathBuf>,
 
     /// The rpc endpoint to use
     #[arg(long, default_value = "http://localhost:5050")]
     rpc: String,
 
-    /// Database filepath (ex: indexer.db). If specified file doesn't exist, it will be
-    /// created. Defaults to in-memory database
+    /// SQLite database filepath (ex: indexer.db). If specified file doesn't exist, it will be
+    /// created. Defaults to in-memory database. PostgreSQL isn't supported
     #[arg(short, long, default_value = ":memory:")]
     database: String,
 
@@ -63,6 +87,34 @@ struct Args {
     #[arg(short, long, default_value = "0")]
     start_block: u64,
 
+    /// How blocks are synced: `blocks` fetches every block, `events` only fetches the blocks and
+    /// transactions that emitted events of the world
+    #[arg(long, default_value = "blocks")]
+    sync_mode: SyncMode,
+
+    /// The number of events requested per page when syncing with `--sync-mode events`
+    #[arg(long, default_value = "1024")]
+    events_chunk_size: u64,
+
+    /// The maximum number of blocks and receipts requested at once when syncing with
+    /// `--sync-mode events`
+    #[arg(long, default_value = "32")]
+    max_concurrent_requests: usize,
+
+    /// The number of blocks a block must be buried under before it is indexed
+    #[arg(long, default_value = "0")]
+    confirmations: u64,
+
+    /// Index the transactions of the pending block, flagging the entities they write as pending
+    /// until the block is accepted
+    #[arg(long)]
+    index_pending: bool,
+
+    /// The models whose changes are recorded in a history table, for time-travel queries
+    /// (comma-separated list of model names, without the namespace of their world)
+    #[arg(long, value_delimiter = ',')]
+    historical: Vec<String>,
+
     /// Host address for api endpoints
     #[arg(long, default_value = "0.0.0.0")]
     host: String,
@@ -89,6 +141,26 @@ struct Args {
     pub metrics: Option<SocketAddr>,
 }
 
+/// A world to index, with the namespace prefixing its resources.
+#[derive(Debug, Clone)]
+struct WorldArg {
+    namespace: String,
+    address: FieldElement,
+}
+
+impl FromStr for WorldArg {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (namespace, address) = s.split_once(':').unwrap_or(("", s));
+        if !namespace.chars().all(|c| c.is_ascii_alphanumeric()) {
+            anyhow::bail!("Invalid namespace `{namespace}`, expected alphanumeric characters");
+        }
+
+        Ok(Self { namespace: namespace.to_string(), address: FieldElement::from_str(address)? })
+    }
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let args = Args::parse();
@@ -110,44 +182,62 @@ async fn main() -> anyhow::Result<()> {
     })
     .expect("Error setting Ctrl-C handler");
 
-    let database_url = format!("sqlite:{}", &args.database);
-    let options =
-        SqliteConnectOptions::from_str(&database_url)?.create_if_missing(true).with_regexp();
-    let pool = SqlitePoolOptions::new()
-        .min_connections(1)
-        .max_connections(5)
-        .connect_with(options)
-        .await?;
-
-    sqlx::migrate!("../migrations").run(&pool).await?;
+    let pool = connect_sqlite(&args.database).await?;
 
     let provider: Arc<_> = JsonRpcClient::new(HttpTransport::new(Url::parse(&args.rpc)?)).into();
 
-    // Get world address
-    let world = WorldContractReader::new(args.world_address, &provider);
+    let mut namespaces = HashSet::new();
+    if !args.worlds.iter().all(|world| namespaces.insert(&world.namespace)) {
+        anyhow::bail!("the worlds indexed together must have different namespaces");
+    }
 
-    let mut db = Sql::new(pool.clone(), args.world_address).await?;
-    let processors = Processors {
-        event: vec![
-            Box::new(RegisterModelProcessor),
-            Box::new(StoreSetRecordProcessor),
-            Box::new(MetadataUpdateProcessor),
-        ],
-        transaction: vec![Box::new(StoreTransactionProcessor)],
-        ..Processors::default()
-    };
+    if args.manifests.len() > args.worlds.len() {
+        anyhow::bail!("there are more manifests than worlds to index");
+    }
 
-    let (block_tx, block_rx) = tokio::sync::mpsc::channel(100);
+    let mut dbs = Vec::with_capacity(args.worlds.len());
+    let mut events = Vec::with_capacity(args.worlds.len());
+    for (idx, world) in args.worlds.iter().enumerate() {
+        let mut db = Sql::with_namespace(pool.clone(), world.address, &world.namespace).await?;
+        db.set_historical(&args.historical).await?;
+
+        let world_events = match args.manifests.get(idx) {
+            Some(path) => custom_events(&Manifest::load_from_path(path)?)?,
+            None => vec![],
+        };
+        for event in &world_events {
+            db.register_custom_event(event).await?;
+        }
 
-    let mut engine = Engine::new(
-        world,
-        &mut db,
-        &provider,
-        processors,
-        EngineConfig { start_block: args.start_block, ..Default::default() },
-        shutdown_tx.clone(),
-        Some(block_tx),
-    );
+        dbs.push(db);
+        events.push(world_events);
+    }
+
+    // Each world is indexed by its own engine, which notifies the blocks it indexed to the model
+    // subscriptions of its world.
+    let mut block_rxs = Vec::with_capacity(dbs.len());
+    let mut engines = Vec::with_capacity(dbs.len());
+    for ((world, db), events) in args.worlds.iter().zip(dbs.iter_mut()).zip(events) {
+        let (block_tx, block_rx) = tokio::sync::mpsc::channel(100);
+        block_rxs.push((world.address, block_rx));
+        engines.push(Engine::new(
+            WorldContractReader::new(world.address, &provider),
+            db,
+            &provider,
+            processors(events),
+            EngineConfig {
+                start_block: args.start_block,
+                sync_mode: args.sync_mode,
+                events_chunk_size: args.events_chunk_size,
+                max_concurrent_requests: args.max_concurrent_requests,
+                confirmations: args.confirmations,
+                index_pending: args.index_pending,
+                ..Default::default()
+            },
+            shutdown_tx.clone(),
+            Some(block_tx),
+        ));
+    }
 
     let addr: SocketAddr = format!("{}:{}", args.host, args.port).parse()?;
 
@@ -155,8 +245,8 @@ async fn main() -> anyhow::Result<()> {
     let (grpc_addr, grpc_server) = torii_grpc::server::new(
         shutdown_rx,
         &pool,
-        block_rx,
-        args.world_address,
+        block_rxs,
+        args.worlds[0].address,
         Arc::clone(&provider),
     )
     .await?;
@@ -186,7 +276,7 @@ async fn main() -> anyhow::Result<()> {
     }
 
     tokio::select! {
-        _ = engine.start() => {},
+        _ = try_join_all(engines.iter_mut().map(|engine| engine.start())) => {},
         _ = proxy_server.start(shutdown_tx.subscribe()) => {},
         _ = graphql_server => {},
         _ = grpc_server => {},
@@ -195,6 +285,30 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
+fn processors<P: Provider + Sync + Send>(custom_events: Vec<Ty>) -> Processors<P> {
+    let mut event: Vec<Box<dyn EventProcessor<P>>> = vec![
+        Box::new(RegisterModelProcessor),
+        Box::new(StoreSetRecordProcessor),
+        Box::new(StoreDelRecordProcessor),
+        Box::new(MetadataUpdateProcessor),
+        Box::new(WriterUpdatedProcessor),
+        Box::new(OwnerUpdatedProcessor),
+        Box::new(ContractDeployedProcessor),
+        Box::new(ContractUpgradedProcessor),
+        Box::new(WorldUpgradedProcessor),
+        Box::new(ExecutorUpdatedProcessor),
+    ];
+    event.extend(custom_events.into_iter().map(|custom_event| {
+        Box::new(CustomEventProcessor::new(custom_event)) as Box<dyn EventProcessor<P>>
+    }));
+
+    Processors {
+        event,
+        transaction: vec![Box::new(StoreTransactionProcessor)],
+        ..Processors::default()
+    }
+}
+
 async fn spawn_rebuilding_graphql_server(
     shutdown_tx: Sender<()>,
     pool: Arc<SqlitePool>,